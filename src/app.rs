@@ -1,10 +1,39 @@
-use crate::database::db::{SavedCommand, SavedKey, DB};
-use crate::display::menuopts::OPTION_PADDING_MID;
+use crate::database::bundle::{Bundle, BundleFormat, BundleSummary};
+use crate::database::db::{
+    Collection, Environment, HistoryEntry, KeyKind, SavedCommand, SavedKey, DB,
+};
+use crate::database::tree::{build_tree, collection_commands, collection_path, is_within, TreeRow};
+use crate::display::diffview::DiffViewer;
+use crate::display::highlight::{Palette, Syntax};
+use crate::display::inputopt::InputOpt;
+use crate::display::menuopts::{
+    BUNDLE_ERROR, COLLECTION_MOVE_ERROR, COLLECTION_NAME_ERROR, DIFF_BASE_ERROR, ENV_NAME_ERROR,
+    ENV_VAR_ERROR, HISTORY_NOT_FOUND_ERROR, HTTP_FILE_ERROR, KEY_URL_ERROR, LOCKED_ERROR,
+    NOT_JSON_ERROR, NO_COMMAND_ERROR, OPTION_PADDING_MID, SNIPPET_ERROR,
+};
+use crate::display::viewer::{BodyViewer, ViewInput};
 use crate::display::AppOptions;
-use crate::request::command::{CmdOpts, CMD};
-use crate::request::curl::Curl;
-use crate::screens::screen::Screen;
+use crate::events::event::{Event, Executed};
+use crate::request::assertion::Assertion;
+use crate::request::command::{Cmd, CmdOpts, CMD};
+use crate::request::curl::{AuthKind, Curl};
+use crate::request::har::HarExport;
+use crate::request::httpfile::{HttpFile, HttpRequest};
+use crate::request::importer::ImportFormat;
+use crate::request::jsonpath;
+use crate::request::parser::parse_curl_command;
+use crate::request::response::Response;
+use crate::request::runner::{self, TestResult};
+use crate::request::snippet::{generate_snippet, SnippetLang};
+use crate::screens::screen::{MoveItem, Screen};
 use crate::Config;
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::Sender;
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
 use std::{error, mem};
 use arboard::Clipboard;
 use tui::widgets::{ListItem, ListState};
@@ -17,6 +46,18 @@ pub enum InputMode {
     Editing,
 }
 
+/// A command running on a worker thread.
+pub struct Executing {
+    /// url the command was sent to
+    pub url: String,
+    /// when the command was started
+    pub started: Instant,
+    /// time spent so far, updated on every tick
+    pub elapsed: Duration,
+    /// set to abort the transfer
+    cancel: Arc<AtomicBool>,
+}
+
 /// Application.
 pub struct App<'a> {
     /// toml config file
@@ -49,6 +90,32 @@ pub struct App<'a> {
     pub response: Option<String>,
     /// database connection
     pub db: Box<DB>,
+    /// command currently running on a worker thread
+    pub executing: Option<Executing>,
+    /// where worker threads deliver their results
+    event_sender: Option<Sender<Event>>,
+    /// collections opened on the saved commands screen
+    pub expanded_collections: HashSet<i32>,
+    /// results of the last test run
+    pub test_results: Vec<TestResult>,
+    /// .http file opened from the home screen
+    pub http_file: Option<HttpFile>,
+    /// bundle being previewed before it's imported
+    pub bundle: Option<Bundle>,
+    /// response body being viewed
+    pub viewer: Option<BodyViewer>,
+    /// jq style filter applied to the response body
+    pub body_filter: String,
+    /// show the response body as it came in instead of pretty printed
+    pub raw_body: bool,
+    /// why the filter couldn't be applied, the last result stays on screen
+    pub filter_error: Option<String>,
+    /// filter or search being typed on the body and headers screens
+    pub view_input: Option<ViewInput>,
+    /// response picked to be compared with another one, and what it is
+    pub diff_base: Option<(String, Response)>,
+    /// two responses being compared
+    pub diff: Option<DiffViewer>,
 }
 
 impl<'a> Default for App<'a> {
@@ -69,6 +136,19 @@ impl<'a> Default for App<'a> {
             current_screen: Screen::Home,
             response: None,
             db: Box::new(DB::new().unwrap()),
+            executing: None,
+            event_sender: None,
+            expanded_collections: HashSet::new(),
+            test_results: Vec::new(),
+            http_file: None,
+            bundle: None,
+            viewer: None,
+            body_filter: String::new(),
+            raw_body: false,
+            filter_error: None,
+            view_input: None,
+            diff_base: None,
+            diff: None,
         }
     }
 }
@@ -83,7 +163,14 @@ impl<'a> App<'a> {
     pub fn set_config(&mut self, config: Config) {
         self.config = config;
     }
-    pub fn tick(&self) {}
+    pub fn set_event_sender(&mut self, sender: Sender<Event>) {
+        self.event_sender = Some(sender);
+    }
+    pub fn tick(&mut self) {
+        if let Some(executing) = self.executing.as_mut() {
+            executing.elapsed = executing.started.elapsed();
+        }
+    }
 
     pub fn redraw(&mut self) {
         if self.selected.is_some() {
@@ -118,18 +205,111 @@ impl<'a> App<'a> {
                 self.selected = None;
                 return;
             }
-            Screen::SavedCommands => {
+            Screen::Environments => {
+                self.items = self
+                    .get_environments()
+                    .unwrap_or_default()
+                    .iter()
+                    .map(|env| ListItem::new(format!("{}{}", env, OPTION_PADDING_MID)))
+                    .collect();
+                self.selected = None;
+                return;
+            }
+            Screen::EnvVars(id) => {
                 self.items = self
-                    .get_saved_commands()
+                    .db
+                    .get_env_vars(id)
                     .unwrap_or_default()
                     .iter()
-                    .map(|cmd| {
-                        ListItem::new(format!("{}{}", cmd.get_command(), OPTION_PADDING_MID))
+                    .map(|(key, value)| {
+                        ListItem::new(format!("{} = {}{}", key, value, OPTION_PADDING_MID))
                     })
                     .collect();
                 self.selected = None;
                 return;
             }
+            Screen::History(ref query) => {
+                self.items = self
+                    .get_history(query)
+                    .unwrap_or_default()
+                    .iter()
+                    .map(|entry| ListItem::new(format!("{}{}", entry, OPTION_PADDING_MID)))
+                    .collect();
+                self.selected = None;
+                return;
+            }
+            Screen::DiffPicker => {
+                self.items = self
+                    .get_history("")
+                    .unwrap_or_default()
+                    .iter()
+                    .map(|entry| ListItem::new(format!("{}{}", entry, OPTION_PADDING_MID)))
+                    .collect();
+                self.selected = None;
+                return;
+            }
+            Screen::SavedCommands => {
+                self.items = self
+                    .get_command_tree()
+                    .iter()
+                    .map(|row| ListItem::new(format!("{}{}", row.get_label(), OPTION_PADDING_MID)))
+                    .collect();
+                self.selected = None;
+                return;
+            }
+            Screen::Assertions(id) => {
+                self.items = self
+                    .get_assertions(id)
+                    .iter()
+                    .map(|assertion| ListItem::new(format!("{}{}", assertion, OPTION_PADDING_MID)))
+                    .collect();
+                self.selected = None;
+                return;
+            }
+            Screen::TestResults => {
+                self.items = self
+                    .test_results
+                    .iter()
+                    .map(|result| ListItem::new(format!("{}{}", result, OPTION_PADDING_MID)))
+                    .collect();
+                self.selected = None;
+                return;
+            }
+            Screen::HttpFile => {
+                self.items = self
+                    .get_http_requests()
+                    .iter()
+                    .map(|request| ListItem::new(format!("{}{}", request, OPTION_PADDING_MID)))
+                    .collect();
+                self.selected = None;
+                return;
+            }
+            Screen::KeyPicker => {
+                self.items = self
+                    .get_key_choices()
+                    .iter()
+                    .map(|key| ListItem::new(format!("{}{}", key, OPTION_PADDING_MID)))
+                    .collect();
+                self.selected = None;
+                return;
+            }
+            Screen::ViewBody => {
+                self.viewer = Some(self.get_body_viewer());
+                self.items = screen.get_opts(None);
+            }
+            Screen::ViewHeaders => {
+                self.viewer = Some(self.get_headers_viewer());
+                self.items = screen.get_opts(None);
+            }
+            Screen::MoveTo(item) => {
+                self.items = self
+                    .get_move_targets(item)
+                    .iter()
+                    .map(|(_, path)| ListItem::new(format!("{}{}", path, OPTION_PADDING_MID)))
+                    .collect();
+                self.selected = None;
+                return;
+            }
             _ => {
                 self.items = screen.get_opts(None);
             }
@@ -140,9 +320,17 @@ impl<'a> App<'a> {
     pub fn go_back_screen(&mut self) {
         self.screen_stack.pop(); // current screen
         match self.screen_stack.last() {
-            Some(Screen::InputMenu(_)) | Some(Screen::CmdMenu(_)) | Some(Screen::KeysMenu(_)) => {
-                self.go_back_screen()
-            }
+            Some(Screen::InputMenu(_))
+            | Some(Screen::CmdMenu(_))
+            | Some(Screen::KeysMenu(_))
+            | Some(Screen::EnvMenu(_))
+            | Some(Screen::HistoryMenu(_))
+            | Some(Screen::CollectionMenu(_))
+            | Some(Screen::MoveTo(_))
+            | Some(Screen::HttpRequestMenu(_))
+            | Some(Screen::ImportBundle(_))
+            | Some(Screen::KeyPicker)
+            | Some(Screen::Executing) => self.go_back_screen(),
             // is that recursion in prod????? o_0
             Some(screen) if screen == &self.current_screen => self.go_back_screen(),
             Some(screen) => {
@@ -197,6 +385,265 @@ impl<'a> App<'a> {
         }
     }
 
+    // Executes the command on a worker thread, so the UI stays responsive. The command comes
+    // back with an Event::Executed. Without an event loop to deliver it, we execute in place
+    pub fn spawn_command(&mut self) {
+        if let Some(command) = self.command.as_mut() {
+            command.set_env_vars(self.db.get_request_vars());
+        }
+        let Some(sender) = self.event_sender.clone() else {
+            let started = Instant::now();
+            let result = self.execute_command();
+            self.record_history(started.elapsed(), &result);
+            self.show_result(result);
+            return;
+        };
+        let Some(mut command) = self.command.take() else {
+            self.goto_screen(Screen::Error(String::from(NO_COMMAND_ERROR)));
+            return;
+        };
+        let cancel = Arc::new(AtomicBool::new(false));
+        command.set_cancel_flag(cancel.clone());
+        self.executing = Some(Executing {
+            url: command.get_url(),
+            started: Instant::now(),
+            elapsed: Duration::ZERO,
+            cancel,
+        });
+        self.goto_screen(Screen::Executing);
+        let vault = self.db.get_vault();
+        thread::spawn(move || {
+            // the connection can't be shared across threads, so the worker opens its own
+            let mut db = DB::new().ok().map(|mut db| {
+                db.set_vault(vault);
+                Box::new(db)
+            });
+            let result = command.execute(db.as_mut());
+            let _ = sender.send(Event::Executed(Executed { command, result }));
+        });
+    }
+
+    pub fn finish_command(&mut self, executed: Executed) {
+        let duration = self
+            .executing
+            .take()
+            .map(|executing| executing.started.elapsed())
+            .unwrap_or_default();
+        self.command = Some(executed.command);
+        self.record_history(duration, &executed.result);
+        // leave the executing screen out of the way of the back button
+        if self.current_screen == Screen::Executing {
+            self.screen_stack.pop();
+        }
+        self.show_result(executed.result);
+    }
+
+    // Runs the saved commands in a collection (None for all of them) as tests
+    pub fn spawn_tests(&mut self, collection: Option<i32>) {
+        let commands = collection_commands(
+            &self.get_collections().unwrap_or_default(),
+            &self.get_saved_commands().unwrap_or_default(),
+            collection,
+        );
+        let env_vars = self.db.get_request_vars();
+        let cancel = Arc::new(AtomicBool::new(false));
+        let Some(sender) = self.event_sender.clone() else {
+            let results = runner::run_tests(&commands, &env_vars, &cancel);
+            self.finish_tests(results);
+            return;
+        };
+        self.executing = Some(Executing {
+            url: format!("Running {} saved commands as tests", commands.len()),
+            started: Instant::now(),
+            elapsed: Duration::ZERO,
+            cancel: cancel.clone(),
+        });
+        self.goto_screen(Screen::Executing);
+        thread::spawn(move || {
+            let results = runner::run_tests(&commands, &env_vars, &cancel);
+            let _ = sender.send(Event::TestsFinished(results));
+        });
+    }
+
+    pub fn finish_tests(&mut self, results: Vec<TestResult>) {
+        self.executing = None;
+        if self.current_screen == Screen::Executing {
+            self.screen_stack.pop();
+        }
+        self.test_results = results;
+        self.goto_screen(Screen::TestResults);
+    }
+
+    pub fn cancel_command(&mut self) {
+        if let Some(executing) = self.executing.as_ref() {
+            executing.cancel.store(true, Ordering::Relaxed);
+        }
+    }
+
+    pub fn is_executing(&self) -> bool {
+        self.executing.is_some()
+    }
+
+    // Every executed HTTP request goes into the history, whether it succeeded or not
+    fn record_history(&self, duration: Duration, result: &Result<(), String>) {
+        let Some(curl) = self.command.as_ref().and_then(|cmd| cmd.as_curl()) else {
+            return;
+        };
+        let method = curl
+            .get_method()
+            .map(|method| method.to_string())
+            .unwrap_or(String::from("GET"));
+        let mut entry = HistoryEntry::new(
+            &curl.get_url(),
+            &method,
+            serde_json::to_string(curl).unwrap_or_default(),
+        );
+        if let Err(e) = result {
+            entry.set_error(duration.as_millis() as u64, e);
+            let _ = self.db.add_history(&entry);
+            return;
+        }
+        let response = curl.get_http_response();
+        let body = response
+            .map(|response| response.body.clone())
+            .unwrap_or_else(|| curl.get_response());
+        entry.set_response(
+            response.map(|response| response.status),
+            duration.as_millis() as u64,
+            body.len(),
+            self.config.save_history_bodies().then_some(body),
+        );
+        if let Some(response) = response {
+            entry.set_http_response(response);
+        }
+        let _ = self.db.add_history(&entry);
+    }
+
+    // Highlighted by the Content-Type of the response, or by what the body looks like. Curl keeps
+    // the body as it came in next to the pretty printed one, so either can be shown
+    pub fn get_body_viewer(&self) -> BodyViewer {
+        let palette = Palette::from_config(&self.config);
+        if !self.body_filter.trim().is_empty() {
+            if let Ok(results) = self.filter_body(&self.body_filter) {
+                return BodyViewer::new(&results, Syntax::Json, palette);
+            }
+        }
+        let response = self
+            .command
+            .as_ref()
+            .and_then(|cmd| cmd.as_curl())
+            .and_then(|curl| curl.get_http_response());
+        let body = match response {
+            Some(response) => response.body.clone(),
+            None => self.response.clone().unwrap_or_default(),
+        };
+        let content_type = response.and_then(|response| response.get_header("Content-Type"));
+        let syntax = Syntax::detect(content_type, &body);
+        match self.raw_body {
+            true => BodyViewer::raw(&body, syntax, palette),
+            false => BodyViewer::new(&body, syntax, palette),
+        }
+    }
+
+    // `r` on the body screen, the lines don't match up between the two so it starts at the top
+    pub fn toggle_raw_body(&mut self) {
+        self.raw_body = !self.raw_body;
+        self.viewer = Some(self.get_body_viewer());
+    }
+
+    // Headers are captured separately when we execute the request, we only have to parse them out
+    // of the body when they were written there instead (e.g. wget)
+    pub fn get_http_response(&self) -> Result<Response, String> {
+        let captured = self
+            .command
+            .as_ref()
+            .and_then(|cmd| cmd.as_curl())
+            .and_then(|curl| curl.get_http_response())
+            .cloned();
+        match captured {
+            Some(response) => Ok(response),
+            None => Response::from_raw_string(self.response.as_deref().unwrap_or_default())
+                .map_err(String::from),
+        }
+    }
+
+    pub fn get_response_headers(&self) -> Result<String, String> {
+        self.get_http_response()
+            .map(|response| response.get_headers())
+    }
+
+    pub fn get_headers_viewer(&self) -> BodyViewer {
+        BodyViewer::new(
+            &self.get_response_headers().unwrap_or_default(),
+            Syntax::Headers,
+            Palette::from_config(&self.config),
+        )
+    }
+
+    // The results of the filter over the JSON curl parsed, one after the other
+    fn filter_body(&self, filter: &str) -> Result<String, String> {
+        let filters = jsonpath::parse_filter(filter)?;
+        let parsed = self
+            .command
+            .as_ref()
+            .and_then(|cmd| cmd.as_curl())
+            .and_then(|curl| curl.get_json())
+            .cloned();
+        let json = match parsed {
+            Some(json) => json,
+            None => serde_json::from_str(self.response.as_deref().unwrap_or_default())
+                .map_err(|_| String::from(NOT_JSON_ERROR))?,
+        };
+        jsonpath::apply_filter(&json, &filters).map(|results| jsonpath::format_results(&results))
+    }
+
+    // Re-renders the body viewer with each change to the filter, an empty filter shows the whole
+    // body again
+    pub fn set_body_filter(&mut self, filter: &str) {
+        self.body_filter = filter.to_string();
+        self.filter_error = None;
+        if !filter.trim().is_empty() {
+            if let Err(e) = self.filter_body(filter) {
+                self.filter_error = Some(e);
+                return;
+            }
+        }
+        self.viewer = Some(self.get_body_viewer());
+    }
+
+    fn show_result(&mut self, result: Result<(), String>) {
+        match result {
+            Ok(()) => {
+                let response = self.command.as_ref().unwrap().get_response();
+                self.response = Some(response.clone());
+                self.body_filter.clear();
+                self.raw_body = false;
+                self.goto_screen(Screen::Response(response));
+            }
+            Err(e) => self.goto_screen(Screen::Error(e)),
+        }
+    }
+
+    // Encrypted databases are unlocked before anything else
+    pub fn prompt_unlock(&mut self) {
+        if self.db.is_locked() {
+            self.goto_screen(Screen::InputMenu(InputOpt::Unlock));
+            self.input_mode = InputMode::Editing;
+        }
+    }
+
+    pub fn unlock(&mut self, passphrase: &str) -> Result<(), String> {
+        self.db.unlock(passphrase)
+    }
+
+    pub fn set_passphrase(&mut self, passphrase: &str) -> Result<(), String> {
+        self.db.set_passphrase(passphrase)
+    }
+
+    pub fn remove_passphrase(&mut self) -> Result<(), String> {
+        self.db.remove_passphrase()
+    }
+
     pub fn get_saved_keys(&self) -> Result<Vec<SavedKey>, rusqlite::Error> {
         self.db.as_ref().get_keys()
     }
@@ -208,20 +655,350 @@ impl<'a> App<'a> {
         }
     }
 
+    // The saved keys offered for the request, only those meant for its host once we know it
+    pub fn get_key_choices(&self) -> Vec<SavedKey> {
+        let host = self
+            .command
+            .as_ref()
+            .and_then(|command| command.as_curl())
+            .and_then(|curl| curl.get_host());
+        self.get_saved_keys()
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|key| host.as_deref().is_none_or(|host| key.matches_host(host)))
+            .collect()
+    }
+
+    // The request refers to the key instead of copying it, so saving the command never saves
+    // the secret. The reference is filled in like a variable when the request runs
+    pub fn use_saved_key(&mut self, key: &SavedKey) -> Result<(), String> {
+        if self.db.is_locked() {
+            return Err(String::from(LOCKED_ERROR));
+        }
+        let reference = key.get_reference();
+        let auth = match key.get_kind() {
+            KeyKind::Bearer => AuthKind::Bearer(reference),
+            KeyKind::Basic => AuthKind::Basic(reference),
+            KeyKind::Digest => AuthKind::Digest(reference),
+            KeyKind::Header(name) => {
+                self.add_app_option(AppOptions::Headers(format!("{}: {}", name, reference)));
+                return Ok(());
+            }
+            KeyKind::Query(param) => {
+                let url = self
+                    .command
+                    .as_ref()
+                    .map(|cmd| cmd.get_url())
+                    .unwrap_or_default();
+                if url.is_empty() {
+                    return Err(String::from(KEY_URL_ERROR));
+                }
+                let separator = if url.contains('?') { '&' } else { '?' };
+                self.remove_app_option(&AppOptions::URL(String::new()));
+                self.add_app_option(AppOptions::URL(format!(
+                    "{}{}{}={}",
+                    url, separator, param, reference
+                )));
+                return Ok(());
+            }
+        };
+        if self.has_app_option(&AppOptions::Auth(String::new())) {
+            self.remove_app_option(&AppOptions::Auth(String::new()));
+        }
+        // a loaded request can have auth without the option, its secret mustn't stay behind
+        let command = self.command.as_mut().unwrap();
+        command.set_auth(AuthKind::None);
+        command.set_auth(auth);
+        self.add_app_option(AppOptions::Auth(format!(
+            "{} ({})",
+            key.get_label(),
+            key.get_kind()
+        )));
+        Ok(())
+    }
+
+    pub fn set_key_label(&mut self, id: i32, label: &str) -> Result<(), String> {
+        let label = Some(label.trim()).filter(|label| !label.is_empty());
+        self.db.set_key_label(id, label).map_err(|e| e.to_string())
+    }
+
+    pub fn set_key_host(&mut self, id: i32, host: &str) -> Result<(), String> {
+        let host = Some(host.trim()).filter(|host| !host.is_empty());
+        self.db.set_key_host(id, host).map_err(|e| e.to_string())
+    }
+
+    pub fn set_key_kind(&mut self, id: i32, kind: &str) -> Result<(), String> {
+        let kind: KeyKind = kind.parse()?;
+        self.db.set_key_kind(id, &kind).map_err(|e| e.to_string())
+    }
+
+    pub fn set_key_expiry(&mut self, id: i32, date: &str) -> Result<(), String> {
+        let date = Some(date.trim()).filter(|date| !date.is_empty());
+        self.db.set_key_expiry(id, date)
+    }
+
+    pub fn get_environments(&self) -> Result<Vec<Environment>, rusqlite::Error> {
+        self.db.as_ref().get_environments()
+    }
+
+    pub fn add_environment(&mut self, name: &str) -> Result<(), String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(String::from(ENV_NAME_ERROR));
+        }
+        self.db.add_environment(name).map_err(|e| e.to_string())
+    }
+
+    pub fn switch_environment(&mut self, id: i32) -> Result<(), rusqlite::Error> {
+        self.db.set_active_environment(id)
+    }
+
+    pub fn delete_environment(&mut self, id: i32) -> Result<(), rusqlite::Error> {
+        self.db.delete_environment(id)
+    }
+
+    // Sets a "key=value" variable of the environment, an empty value removes it
+    pub fn set_env_var(&mut self, env_id: i32, var: &str) -> Result<(), String> {
+        let (key, value) = var
+            .split_once('=')
+            .map(|(key, value)| (key.trim(), value.trim()))
+            .filter(|(key, _)| !key.is_empty())
+            .ok_or(String::from(ENV_VAR_ERROR))?;
+        if value.is_empty() {
+            self.db.delete_env_var(env_id, key)
+        } else {
+            self.db.set_env_var(env_id, key, value)
+        }
+        .map_err(|e| e.to_string())
+    }
+
+    pub fn get_active_environment_name(&self) -> Option<String> {
+        self.db
+            .get_active_environment()
+            .ok()
+            .flatten()
+            .map(|env| env.get_name().to_string())
+    }
+
     pub fn get_saved_commands(&self) -> Result<Vec<SavedCommand>, rusqlite::Error> {
         self.db.as_ref().get_commands()
     }
 
+    pub fn get_collections(&self) -> Result<Vec<Collection>, rusqlite::Error> {
+        self.db.as_ref().get_collections()
+    }
+
+    // The rows of the saved commands screen, only showing inside expanded collections
+    pub fn get_command_tree(&self) -> Vec<TreeRow> {
+        build_tree(
+            &self.get_collections().unwrap_or_default(),
+            &self.get_saved_commands().unwrap_or_default(),
+            &self.expanded_collections,
+        )
+    }
+
+    // Opens or closes a collection, keeping the cursor on it
+    pub fn toggle_collection(&mut self, id: i32) {
+        if !self.expanded_collections.remove(&id) {
+            self.expanded_collections.insert(id);
+        }
+        let cursor = self.cursor;
+        self.goto_screen(Screen::SavedCommands);
+        self.cursor = cursor;
+    }
+
+    pub fn add_collection(&mut self, name: &str, parent: Option<i32>) -> Result<(), String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(String::from(COLLECTION_NAME_ERROR));
+        }
+        self.db
+            .add_collection(name, parent)
+            .map_err(|e| e.to_string())?;
+        // show the new collection straight away
+        if let Some(parent) = parent {
+            self.expanded_collections.insert(parent);
+        }
+        Ok(())
+    }
+
+    pub fn rename_collection(&mut self, id: i32, name: &str) -> Result<(), String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(String::from(COLLECTION_NAME_ERROR));
+        }
+        self.db
+            .rename_collection(id, name)
+            .map_err(|e| e.to_string())
+    }
+
+    pub fn delete_collection(&mut self, id: i32) -> Result<(), rusqlite::Error> {
+        self.expanded_collections.remove(&id);
+        self.db.delete_collection(id)
+    }
+
+    // An empty name goes back to showing the command string
+    pub fn rename_saved_command(&mut self, id: i32, name: &str) -> Result<(), String> {
+        let name = Some(name.trim()).filter(|name| !name.is_empty());
+        self.db.rename_command(id, name).map_err(|e| e.to_string())
+    }
+
+    pub fn set_saved_command_description(&mut self, id: i32, text: &str) -> Result<(), String> {
+        let text = Some(text.trim()).filter(|text| !text.is_empty());
+        self.db
+            .set_command_description(id, text)
+            .map_err(|e| e.to_string())
+    }
+
+    // Where an item can be moved to, a collection can't go inside itself
+    pub fn get_move_targets(&self, item: MoveItem) -> Vec<(Option<i32>, String)> {
+        let collections = self.get_collections().unwrap_or_default();
+        let mut targets = vec![(None, String::from("Top level"))];
+        let mut paths: Vec<(Option<i32>, String)> = collections
+            .iter()
+            .filter(|c| match item {
+                MoveItem::Collection(id) => !is_within(&collections, c.get_id(), id),
+                MoveItem::Command(_) => true,
+            })
+            .map(|c| (Some(c.get_id()), collection_path(&collections, c.get_id())))
+            .collect();
+        paths.sort_by(|a, b| a.1.cmp(&b.1));
+        targets.extend(paths);
+        targets
+    }
+
+    pub fn move_item(&mut self, item: MoveItem, target: Option<i32>) -> Result<(), String> {
+        match item {
+            MoveItem::Command(id) => self.db.move_command(id, target),
+            MoveItem::Collection(id) => {
+                let collections = self.get_collections().map_err(|e| e.to_string())?;
+                if target.is_some_and(|target| is_within(&collections, target, id)) {
+                    return Err(String::from(COLLECTION_MOVE_ERROR));
+                }
+                self.db.move_collection(id, target)
+            }
+        }
+        .map_err(|e| e.to_string())
+    }
+
+    pub fn get_assertions(&self, id: i32) -> Vec<Assertion> {
+        self.db
+            .get_command_by_id(id)
+            .ok()
+            .flatten()
+            .map(|cmd| cmd.get_assertions().to_vec())
+            .unwrap_or_default()
+    }
+
+    // Adds an assertion, or replaces the one at `index`. Empty text removes it
+    pub fn set_assertion(
+        &mut self,
+        id: i32,
+        index: Option<usize>,
+        text: &str,
+    ) -> Result<(), String> {
+        let mut assertions = self.get_assertions(id);
+        let assertion = if text.trim().is_empty() {
+            None
+        } else {
+            Some(text.parse::<Assertion>()?)
+        };
+        match (index.filter(|i| *i < assertions.len()), assertion) {
+            (Some(i), Some(assertion)) => assertions[i] = assertion,
+            (Some(i), None) => {
+                assertions.remove(i);
+            }
+            (None, Some(assertion)) => assertions.push(assertion),
+            (None, None) => return Ok(()),
+        }
+        self.db
+            .set_command_assertions(id, &assertions)
+            .map_err(|e| e.to_string())
+    }
+
     pub fn execute_saved_command(&mut self, index: usize) {
         let saved_commands = self.get_saved_commands().unwrap();
         let cmd = saved_commands.get(index).unwrap();
-        let mut command: Curl = serde_json::from_str(cmd.get_curl_json()).unwrap();
+        if cmd.is_locked() {
+            self.goto_screen(Screen::Error(String::from(LOCKED_ERROR)));
+            return;
+        }
+        if let Err(e) = self.execute_curl_json(cmd.get_curl_json()) {
+            self.goto_screen(Screen::Error(e));
+        }
+    }
+
+    // Makes the serialized command the current command and executes it
+    fn execute_curl_json(&mut self, json: &str) -> Result<(), String> {
+        let mut command: Curl = serde_json::from_str(json).map_err(|e| e.to_string())?;
         command.easy_from_opts();
-        match command.execute(None) {
-            Ok(_) => self.set_response(command.get_response().clone()),
-            Err(e) => self.set_response(e.to_string()),
-        };
-        self.goto_screen(Screen::Response(self.response.clone().unwrap()));
+        // keep the command around so the response screen can show its headers
+        self.command = Some(Box::new(Cmd::Curl(command.into_owned())));
+        self.spawn_command();
+        Ok(())
+    }
+
+    pub fn get_history(&self, query: &str) -> Result<Vec<HistoryEntry>, rusqlite::Error> {
+        self.db.as_ref().get_history(query)
+    }
+
+    pub fn get_history_entry(&self, id: i32) -> Result<HistoryEntry, String> {
+        self.db
+            .get_history_by_id(id)
+            .map_err(|e| e.to_string())?
+            .ok_or(String::from(HISTORY_NOT_FOUND_ERROR))
+    }
+
+    pub fn rerun_history_entry(&mut self, id: i32) -> Result<(), String> {
+        let entry = self.get_history_entry(id)?;
+        if entry.is_locked() {
+            return Err(String::from(LOCKED_ERROR));
+        }
+        self.execute_curl_json(entry.get_curl_json())
+    }
+
+    // Promotes a history entry to a saved command
+    pub fn save_history_entry(&mut self, id: i32) -> Result<(), String> {
+        let entry = self.get_history_entry(id)?;
+        let mut curl: Curl =
+            serde_json::from_str(entry.get_curl_json()).map_err(|e| e.to_string())?;
+        self.db
+            .add_command(
+                &curl.get_command_string(),
+                entry.get_curl_json().to_string(),
+            )
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+
+    // The response on the screen is the first of the two to compare
+    pub fn compare_current_response(&mut self) -> Result<(), String> {
+        let response = self.get_http_response()?;
+        self.diff_base = Some((String::from("This response"), response));
+        Ok(())
+    }
+
+    pub fn compare_history_entry(&mut self, id: i32) -> Result<(), String> {
+        let entry = self.get_history_entry(id)?;
+        self.diff_base = Some((entry.get_label(), entry.to_response()));
+        Ok(())
+    }
+
+    // Compares the response picked first with a history entry
+    pub fn diff_with_history_entry(&mut self, entry: &HistoryEntry) -> Result<(), String> {
+        let base = self
+            .diff_base
+            .clone()
+            .ok_or(String::from(DIFF_BASE_ERROR))?;
+        self.diff = Some(DiffViewer::new(
+            base,
+            (entry.get_label(), entry.to_response()),
+        ));
+        Ok(())
+    }
+
+    pub fn delete_history_entry(&mut self, id: i32) -> Result<(), rusqlite::Error> {
+        self.db.delete_history(id)
     }
 
     pub fn copy_to_clipboard(&self, opt: &str) -> Result<(), String> {
@@ -235,6 +1012,192 @@ impl<'a> App<'a> {
         }
     }
 
+    pub fn paste_from_clipboard(&self) -> Result<String, String> {
+        match Clipboard::new() {
+            Ok(mut clipboard) => clipboard.get_text().map_err(|e| e.to_string()),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+
+    // Replaces the current command with one parsed from a curl command line,
+    // returns the flags we were unable to import
+    pub fn import_curl_command(&mut self, cmd: &str) -> Result<Vec<String>, String> {
+        let import = parse_curl_command(cmd)?;
+        self.remove_all_app_options();
+        self.command = Some(Box::new(Cmd::Curl(import.curl)));
+        self.opts = import.opts;
+        Ok(import.ignored)
+    }
+
+    // Saves the requests in a Postman collection or OpenAPI spec under a new collection,
+    // returns how many were imported and the items we were unable to translate
+    pub fn import_collection(
+        &mut self,
+        format: ImportFormat,
+        path: &str,
+    ) -> Result<(usize, Vec<String>), String> {
+        let path = path.trim();
+        let input = std::fs::read_to_string(path)
+            .map_err(|e| format!("Error: unable to read {}: {}", path, e))?;
+        let mut import = format.parse(&input)?;
+        let count = import.save(&self.db)?;
+        Ok((count, import.ignored))
+    }
+
+    pub fn open_http_file(&mut self, path: &str) -> Result<(), String> {
+        self.http_file = Some(HttpFile::open(path)?);
+        Ok(())
+    }
+
+    // Reads the open .http file again, after it was edited somewhere else
+    pub fn reload_http_file(&mut self) -> Result<(), String> {
+        let path = self
+            .http_file
+            .as_ref()
+            .map(|file| file.get_path().display().to_string())
+            .ok_or(String::from(HTTP_FILE_ERROR))?;
+        self.open_http_file(&path)
+    }
+
+    pub fn get_http_requests(&self) -> Vec<HttpRequest> {
+        self.http_file
+            .as_ref()
+            .map(|file| file.get_requests().to_vec())
+            .unwrap_or_default()
+    }
+
+    pub fn get_http_request(&self, index: usize) -> Result<HttpRequest, String> {
+        self.http_file
+            .as_ref()
+            .ok_or(String::from(HTTP_FILE_ERROR))?
+            .get_request(index)
+            .cloned()
+    }
+
+    // Executes a request from the .http file with the file's variables filled in
+    pub fn execute_http_request(&mut self, index: usize) -> Result<(), String> {
+        let file = self
+            .http_file
+            .as_ref()
+            .ok_or(String::from(HTTP_FILE_ERROR))?;
+        let curl = file.to_curl(index)?;
+        self.command = Some(Box::new(Cmd::Curl(curl)));
+        self.spawn_command();
+        Ok(())
+    }
+
+    // Applies the edit to a request and writes it back to the .http file
+    pub fn edit_http_request<F>(&mut self, index: usize, edit: F) -> Result<(), String>
+    where
+        F: FnOnce(&mut HttpRequest) -> Result<(), String>,
+    {
+        let file = self
+            .http_file
+            .as_mut()
+            .ok_or(String::from(HTTP_FILE_ERROR))?;
+        let mut request = file.get_request(index)?.clone();
+        edit(&mut request)?;
+        file.update_request(index, request)
+    }
+
+    // Copies a request from the .http file into the database, with its variables filled in
+    pub fn save_http_request(&mut self, index: usize) -> Result<(), String> {
+        let file = self
+            .http_file
+            .as_ref()
+            .ok_or(String::from(HTTP_FILE_ERROR))?;
+        let request = file.get_request(index)?;
+        let mut curl = file.to_curl(index)?;
+        let json = serde_json::to_string(&curl).map_err(|e| e.to_string())?;
+        let id = self
+            .db
+            .add_command(&curl.get_command_string(), json)
+            .map_err(|e| e.to_string())?;
+        if !request.name.is_empty() {
+            self.db
+                .rename_command(id, Some(&request.name))
+                .map_err(|e| e.to_string())?;
+        }
+        Ok(())
+    }
+
+    // Makes a saved command the current command, so we can work with it like a new request
+    pub fn load_saved_command(&mut self, index: usize) -> Result<(), String> {
+        let saved_commands = self.get_saved_commands().map_err(|e| e.to_string())?;
+        let cmd = saved_commands
+            .get(index)
+            .ok_or(String::from("Error: Saved command not found"))?;
+        let curl: Curl = serde_json::from_str(cmd.get_curl_json()).map_err(|e| e.to_string())?;
+        self.command = Some(Box::new(Cmd::Curl(curl.into_owned())));
+        Ok(())
+    }
+
+    pub fn get_snippet(&self, lang: SnippetLang) -> Result<String, String> {
+        match self.command.as_ref().and_then(|cmd| cmd.as_curl()) {
+            Some(curl) => Ok(generate_snippet(curl, lang)),
+            None => Err(String::from(SNIPPET_ERROR)),
+        }
+    }
+
+    // Writes the HAR file, returns how many requests it has and where it was written
+    pub fn export_har(&self, export: &HarExport, path: &str) -> Result<(usize, String), String> {
+        let har = export.export(&self.db)?;
+        let mut path = PathBuf::from(path.trim());
+        if path.extension().is_none() {
+            path.set_extension("har");
+        }
+        std::fs::write(&path, har.to_json()?).map_err(|e| e.to_string())?;
+        Ok((har.log.entries.len(), path.display().to_string()))
+    }
+
+    // Reads the bundle and checks it against the database without saving anything, the bundle
+    // is kept until it's imported. Returns the preview of what would be imported
+    pub fn preview_bundle(&mut self, path: &str) -> Result<String, String> {
+        let bundle = Bundle::open(path)?;
+        let summary = bundle.import(&self.db, true)?;
+        self.bundle = Some(bundle);
+        let mut preview = format!("This bundle has {}", summary);
+        if !summary.duplicate_commands.is_empty() {
+            preview.push_str(&format!(
+                "\n\nAlready saved: {}",
+                summary.duplicate_commands.join(", ")
+            ));
+        }
+        Ok(preview)
+    }
+
+    pub fn import_bundle(&mut self) -> Result<BundleSummary, String> {
+        self.bundle
+            .take()
+            .ok_or(String::from(BUNDLE_ERROR))?
+            .import(&self.db, false)
+    }
+
+    // Writes every saved command, key and environment to a bundle, JSON unless the path ends
+    // in .toml. Returns the path that was written
+    pub fn export_bundle(&self, path: &str) -> Result<String, String> {
+        let mut path = PathBuf::from(path.trim());
+        if path.extension().is_none() {
+            path.set_extension("json");
+        }
+        let format = BundleFormat::from_path(&path.to_string_lossy());
+        let bundle = Bundle::from_db(&self.db)?.to_string(format)?;
+        std::fs::write(&path, bundle).map_err(|e| e.to_string())?;
+        Ok(path.display().to_string())
+    }
+
+    // Writes the snippet to a file, adding the language's extension if there is none.
+    // Returns the path that was written
+    pub fn write_snippet(&self, lang: SnippetLang, path: &str) -> Result<String, String> {
+        let snippet = self.get_snippet(lang)?;
+        let mut path = PathBuf::from(path.trim());
+        if path.extension().is_none() {
+            path.set_extension(lang.extension());
+        }
+        std::fs::write(&path, format!("{}\n", snippet)).map_err(|e| e.to_string())?;
+        Ok(path.display().to_string())
+    }
+
     pub fn get_response(&self) -> &str {
         self.response.as_ref().unwrap().as_str()
     }
@@ -329,8 +1292,8 @@ impl<'a> App<'a> {
 
     pub fn set_response(&mut self, response: String) {
         self.response = Some(response.clone());
-        if self.command.is_some() {
-            self.command.as_mut().unwrap().set_response(&response);
+        if let Some(ref mut command) = self.command {
+            command.set_response(&response);
         }
     }
 
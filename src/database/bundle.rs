@@ -0,0 +1,460 @@
+/*
+* Bundles - the saved commands, collections, keys and environments in the database,
+* written to a portable JSON or TOML file so they can be moved to another machine.
+ */
+use std::collections::{HashMap, HashSet};
+use std::fmt::Display;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use super::db::{Collection, Environment, SavedCommand, SavedKey, DB};
+use crate::display::menuopts::LOCKED_ERROR;
+use crate::request::environment::expand_vars;
+
+// Bumped whenever the layout of a bundle changes, so older versions of CuTE can refuse newer bundles
+pub const BUNDLE_VERSION: u32 = 1;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum BundleFormat {
+    #[default]
+    Json,
+    Toml,
+}
+
+impl BundleFormat {
+    // .toml files are TOML, everything else is JSON
+    pub fn from_path(path: &str) -> Self {
+        match Path::new(path.trim()).extension() {
+            Some(ext) if ext.eq_ignore_ascii_case("toml") => BundleFormat::Toml,
+            _ => BundleFormat::Json,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Bundle {
+    pub version: u32,
+    #[serde(default)]
+    pub collections: Vec<Collection>,
+    #[serde(default)]
+    pub commands: Vec<SavedCommand>,
+    #[serde(default)]
+    pub keys: Vec<SavedKey>,
+    #[serde(default)]
+    pub environments: Vec<Environment>,
+}
+
+/// What an import added, and what it skipped because the database already had it.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct BundleSummary {
+    pub collections: usize,
+    pub commands: usize,
+    pub keys: usize,
+    pub environments: usize,
+    // variables added to environments, new or existing
+    pub variables: usize,
+    // labels of the commands that were already saved
+    pub duplicate_commands: Vec<String>,
+    pub duplicate_keys: usize,
+}
+
+impl Display for BundleSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} commands, {} collections, {} keys, {} environments and {} variables",
+            self.commands, self.collections, self.keys, self.environments, self.variables
+        )?;
+        if !self.duplicate_commands.is_empty() || self.duplicate_keys > 0 {
+            write!(
+                f,
+                ", skipped {} commands and {} keys that are already saved",
+                self.duplicate_commands.len(),
+                self.duplicate_keys
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl Bundle {
+    pub fn from_db(db: &DB) -> Result<Bundle, String> {
+        // the keys and auth would be exported masked
+        if db.is_locked() {
+            return Err(String::from(LOCKED_ERROR));
+        }
+        Ok(Bundle {
+            version: BUNDLE_VERSION,
+            collections: db.get_collections().map_err(|e| e.to_string())?,
+            commands: db.get_commands().map_err(|e| e.to_string())?,
+            keys: db.get_keys().map_err(|e| e.to_string())?,
+            environments: db.get_environments().map_err(|e| e.to_string())?,
+        })
+    }
+
+    pub fn parse(input: &str, format: BundleFormat) -> Result<Bundle, String> {
+        let bundle: Bundle = match format {
+            BundleFormat::Json => serde_json::from_str(input).map_err(|e| e.to_string()),
+            BundleFormat::Toml => toml::from_str(input).map_err(|e| e.to_string()),
+        }
+        .map_err(|e| format!("Error: invalid bundle: {}", e))?;
+        if bundle.version > BUNDLE_VERSION {
+            return Err(format!(
+                "Error: bundle version {} is newer than this version of CuTE supports ({})",
+                bundle.version, BUNDLE_VERSION
+            ));
+        }
+        Ok(bundle)
+    }
+
+    pub fn open(path: &str) -> Result<Bundle, String> {
+        let path = path.trim();
+        let input = std::fs::read_to_string(path)
+            .map_err(|e| format!("Error: unable to read {}: {}", path, e))?;
+        Bundle::parse(&input, BundleFormat::from_path(path))
+    }
+
+    pub fn to_string(&self, format: BundleFormat) -> Result<String, String> {
+        match format {
+            BundleFormat::Json => serde_json::to_string_pretty(self).map_err(|e| e.to_string()),
+            BundleFormat::Toml => toml::to_string_pretty(self).map_err(|e| e.to_string()),
+        }
+    }
+
+    // Adds everything the database doesn't already have. With `dry_run` nothing is written,
+    // the summary shows what would be imported
+    pub fn import(&self, db: &DB, dry_run: bool) -> Result<BundleSummary, String> {
+        // a bundle that fails partway through is rolled back, not left half imported
+        let tx = db.conn.unchecked_transaction().map_err(|e| e.to_string())?;
+        let summary = self.import_all(db, dry_run)?;
+        tx.commit().map_err(|e| e.to_string())?;
+        Ok(summary)
+    }
+
+    fn import_all(&self, db: &DB, dry_run: bool) -> Result<BundleSummary, String> {
+        let mut summary = BundleSummary::default();
+        let collections = self.import_collections(db, dry_run, &mut summary)?;
+        // the keys get new IDs, so they go first and the requests using them are pointed at those
+        let keys = self.import_keys(db, dry_run, &mut summary)?;
+        let mut seen = HashSet::new();
+        for command in self.commands.iter() {
+            let command_str = expand_vars(command.get_command(), &keys);
+            let exists = db.command_exists(&command_str).map_err(|e| e.to_string())?;
+            if exists || !seen.insert(command_str.clone()) {
+                summary
+                    .duplicate_commands
+                    .push(command.get_label().to_string());
+                continue;
+            }
+            summary.commands += 1;
+            if dry_run {
+                continue;
+            }
+            let id = db
+                .add_command(&command_str, expand_vars(command.get_curl_json(), &keys))
+                .map_err(|e| e.to_string())?;
+            db.rename_command(id, command.get_name())
+                .map_err(|e| e.to_string())?;
+            db.set_command_description(id, command.get_description())
+                .map_err(|e| e.to_string())?;
+            db.set_command_assertions(id, command.get_assertions())
+                .map_err(|e| e.to_string())?;
+            let collection = command
+                .get_collection_id()
+                .and_then(|id| collections.get(&id).copied());
+            db.move_command(id, collection).map_err(|e| e.to_string())?;
+        }
+        self.import_environments(db, dry_run, &mut summary)?;
+        Ok(summary)
+    }
+
+    // Returns each key's `key:<id>` variable with the reference to the ID it has now, a key the
+    // database already has keeps the ID it was saved with
+    fn import_keys(
+        &self,
+        db: &DB,
+        dry_run: bool,
+        summary: &mut BundleSummary,
+    ) -> Result<Vec<(String, String)>, String> {
+        let mut ids: HashMap<&str, Option<i32>> = HashMap::new();
+        let mut references = Vec::new();
+        for key in self.keys.iter() {
+            let id = match ids.get(key.get_key()) {
+                Some(id) => {
+                    summary.duplicate_keys += 1;
+                    *id
+                }
+                None => {
+                    let id = match db.find_key(key.get_key()).map_err(|e| e.to_string())? {
+                        Some(id) => {
+                            summary.duplicate_keys += 1;
+                            Some(id)
+                        }
+                        None => {
+                            summary.keys += 1;
+                            match dry_run {
+                                // not saved, so there's no ID to point at yet
+                                true => None,
+                                false => Some(db.add_saved_key(key).map_err(|e| e.to_string())?),
+                            }
+                        }
+                    };
+                    ids.insert(key.get_key(), id);
+                    id
+                }
+            };
+            if let Some(id) = id {
+                references.push((
+                    format!("key:{}", key.get_id()),
+                    format!("{{{{key:{}}}}}", id),
+                ));
+            }
+        }
+        Ok(references)
+    }
+
+    // Returns the new ID of every collection in the bundle. A collection with the same name and
+    // parent as an existing one is merged into it, so importing the same bundle twice is harmless
+    fn import_collections(
+        &self,
+        db: &DB,
+        dry_run: bool,
+        summary: &mut BundleSummary,
+    ) -> Result<HashMap<i32, i32>, String> {
+        let existing = db.get_collections().map_err(|e| e.to_string())?;
+        let mut ids: HashMap<i32, i32> = HashMap::new();
+        let mut remaining: Vec<&Collection> = self.collections.iter().collect();
+        // parents have to be mapped before their children, whatever order the file is in
+        while !remaining.is_empty() {
+            let before = remaining.len();
+            let mut next = Vec::new();
+            for collection in remaining {
+                let parent = match collection.get_parent_id() {
+                    Some(parent) => match ids.get(&parent) {
+                        Some(id) => Some(*id),
+                        // the parent comes later in the file, try again on the next pass
+                        None if self.collections.iter().any(|c| c.get_id() == parent) => {
+                            next.push(collection);
+                            continue;
+                        }
+                        // a parent missing from the bundle puts the collection at the top level
+                        None => None,
+                    },
+                    None => None,
+                };
+                let found = existing
+                    .iter()
+                    .find(|c| c.get_name() == collection.get_name() && c.get_parent_id() == parent);
+                let id = match found {
+                    Some(found) => found.get_id(),
+                    None => {
+                        summary.collections += 1;
+                        if dry_run {
+                            // never matches an existing collection, so its children count as new
+                            -collection.get_id().abs() - 1
+                        } else {
+                            db.add_collection(collection.get_name(), parent)
+                                .map_err(|e| e.to_string())?
+                        }
+                    }
+                };
+                ids.insert(collection.get_id(), id);
+            }
+            if next.len() == before {
+                return Err(String::from(
+                    "Error: invalid bundle: the collections are nested in a loop",
+                ));
+            }
+            remaining = next;
+        }
+        Ok(ids)
+    }
+
+    // Variables the environment already has keep their values, like collection imports
+    fn import_environments(
+        &self,
+        db: &DB,
+        dry_run: bool,
+        summary: &mut BundleSummary,
+    ) -> Result<(), String> {
+        let find = |name: &str| {
+            db.get_environments()
+                .map(|envs| envs.into_iter().find(|env| env.get_name() == name))
+                .map_err(|e| e.to_string())
+        };
+        for env in self.environments.iter() {
+            let existing = match find(env.get_name())? {
+                Some(existing) => Some(existing),
+                None => {
+                    summary.environments += 1;
+                    if !dry_run {
+                        db.add_environment(env.get_name())
+                            .map_err(|e| e.to_string())?;
+                    }
+                    find(env.get_name())?
+                }
+            };
+            let vars = existing
+                .as_ref()
+                .map(|existing| existing.get_vars())
+                .unwrap_or_default();
+            for (key, value) in env.get_vars() {
+                if vars.iter().any(|(existing, _)| existing == key) {
+                    continue;
+                }
+                summary.variables += 1;
+                if let Some(existing) = existing.as_ref().filter(|_| !dry_run) {
+                    db.set_env_var(existing.get_id(), key, value)
+                        .map_err(|e| e.to_string())?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::request::assertion::Assertion;
+
+    fn setup() -> DB {
+        let db = DB::new_in_memory().unwrap();
+        let api = db.add_collection("api", None).unwrap();
+        let users = db.add_collection("users", Some(api)).unwrap();
+        let id = db
+            .add_command("curl http://localhost/users", String::from("{}"))
+            .unwrap();
+        db.rename_command(id, Some("List users")).unwrap();
+        db.move_command(id, Some(users)).unwrap();
+        db.set_command_assertions(id, &[Assertion::Status(200)])
+            .unwrap();
+        db.add_command("curl http://localhost/health", String::from("{}"))
+            .unwrap();
+        db.add_key("secret-token").unwrap();
+        db.add_environment("local").unwrap();
+        db.set_env_var(1, "base_url", "http://localhost").unwrap();
+        db
+    }
+
+    #[test]
+    fn test_bundle_round_trip() {
+        let bundle = Bundle::from_db(&setup()).unwrap();
+        for format in [BundleFormat::Json, BundleFormat::Toml] {
+            let text = bundle.to_string(format).unwrap();
+            assert_eq!(Bundle::parse(&text, format).unwrap(), bundle);
+        }
+        let newer = r#"{"version": 99}"#;
+        assert!(Bundle::parse(newer, BundleFormat::Json)
+            .unwrap_err()
+            .contains("newer"));
+        assert_eq!(BundleFormat::from_path("cute.TOML"), BundleFormat::Toml);
+        assert_eq!(BundleFormat::from_path("cute.bundle"), BundleFormat::Json);
+    }
+
+    #[test]
+    fn test_bundle_import() {
+        let bundle = Bundle::from_db(&setup()).unwrap();
+        let db = DB::new_in_memory().unwrap();
+        db.add_command("curl http://localhost/health", String::from("{}"))
+            .unwrap();
+
+        let preview = bundle.import(&db, true).unwrap();
+        assert_eq!(db.get_commands().unwrap().len(), 1);
+        assert!(db.get_collections().unwrap().is_empty());
+        let summary = bundle.import(&db, false).unwrap();
+        assert_eq!(preview, summary);
+        assert_eq!(summary.commands, 1);
+        assert_eq!(summary.collections, 2);
+        assert_eq!(summary.keys, 1);
+        assert_eq!(summary.environments, 1);
+        assert_eq!(summary.variables, 1);
+        assert_eq!(
+            summary.duplicate_commands,
+            vec![String::from("curl http://localhost/health")]
+        );
+
+        let collections = db.get_collections().unwrap();
+        let users = collections
+            .iter()
+            .find(|c| c.get_name() == "users")
+            .unwrap();
+        assert!(users.get_parent_id().is_some());
+        let command = db
+            .get_commands()
+            .unwrap()
+            .into_iter()
+            .find(|c| c.get_name() == Some("List users"))
+            .unwrap();
+        assert_eq!(command.get_collection_id(), Some(users.get_id()));
+        assert_eq!(command.get_assertions(), &[Assertion::Status(200)]);
+        assert_eq!(
+            db.get_environments().unwrap()[0].get_vars(),
+            &[(String::from("base_url"), String::from("http://localhost"))]
+        );
+
+        // importing it again finds everything already there
+        let again = bundle.import(&db, false).unwrap();
+        assert_eq!(again.commands + again.collections + again.keys, 0);
+        assert_eq!(again.duplicate_commands.len(), 2);
+        assert_eq!(again.duplicate_keys, 1);
+        assert_eq!(db.get_collections().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_bundle_import_key_ids() {
+        let source = DB::new_in_memory().unwrap();
+        source.add_key("shared-token").unwrap();
+        source.add_key("new-token").unwrap();
+        source
+            .add_command(
+                "curl -H 'Authorization: Bearer {{key:1}}' http://localhost/a",
+                String::from(r#"{"headers":["Authorization: Bearer {{key:1}}"]}"#),
+            )
+            .unwrap();
+        source
+            .add_command(
+                "curl -H 'Authorization: Bearer {{key:2}}' http://localhost/b",
+                String::from(r#"{"headers":["Authorization: Bearer {{key:2}}"]}"#),
+            )
+            .unwrap();
+        let bundle = Bundle::from_db(&source).unwrap();
+
+        // the target already has a key with ID 1, and the shared key under another ID
+        let db = DB::new_in_memory().unwrap();
+        db.add_key("other-token").unwrap();
+        db.add_key("shared-token").unwrap();
+        let summary = bundle.import(&db, false).unwrap();
+        assert_eq!((summary.keys, summary.duplicate_keys), (1, 1));
+
+        let keys = db.get_keys().unwrap();
+        let id = |value: &str| keys.iter().find(|key| key.is_key(value)).unwrap().get_id();
+        let commands = db.get_commands().unwrap();
+        for (command, key) in commands.iter().zip(["shared-token", "new-token"]) {
+            let reference = format!("{{{{key:{}}}}}", id(key));
+            assert!(command.get_command().contains(&reference));
+            assert!(command.get_curl_json().contains(&reference));
+        }
+        assert!(!commands[0].get_command().contains("{{key:1}}"));
+    }
+
+    #[test]
+    fn test_bundle_import_rolls_back() {
+        let mut bundle = Bundle::from_db(&setup()).unwrap();
+        // `api` is added before the loop between the other two is found
+        let looped = r#"{"version": 1, "collections": [
+            {"id": 10, "name": "a", "parent_id": 11},
+            {"id": 11, "name": "b", "parent_id": 10}
+        ]}"#;
+        let looped = Bundle::parse(looped, BundleFormat::Json).unwrap();
+        bundle.collections.extend(looped.collections);
+        let db = DB::new_in_memory().unwrap();
+        assert!(bundle.import(&db, false).unwrap_err().contains("loop"));
+        assert!(db.get_collections().unwrap().is_empty());
+        assert!(db.get_commands().unwrap().is_empty());
+        // the database is still usable afterwards
+        bundle.collections.truncate(2);
+        assert_eq!(bundle.import(&db, false).unwrap().collections, 2);
+    }
+}
@@ -1,5 +1,5 @@
 use dirs::data_local_dir;
-use rusqlite::{params, Connection, OpenFlags, Result};
+use rusqlite::{params, Connection, OpenFlags, OptionalExtension, Result};
 use serde::{Deserialize, Serialize};
 use serde_json;
 use std::env;
@@ -9,22 +9,121 @@ use std::{
     path::PathBuf,
 };
 
+use super::migrations;
+use super::vault::{self, Vault, MASK};
+use crate::display::menuopts::{
+    KEY_EXPIRY_ERROR, KEY_TYPE_ERROR, LOCKED_ERROR, NOT_ENCRYPTED_ERROR, PASSPHRASE_EMPTY_ERROR,
+    PASSPHRASE_ERROR,
+};
+use crate::request::assertion::Assertion;
+use crate::request::response::Response;
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct SavedCommand {
     id: i32,
     command: String,
     curl_json: String,
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    description: Option<String>,
+    // The collection (folder) the command lives in, None for the top level
+    #[serde(default)]
+    collection_id: Option<i32>,
+    // Checked against the response when the command is run as a test
+    #[serde(default)]
+    assertions: Vec<Assertion>,
+    // The encrypted auth secret, only kept while the vault is locked (it's masked in the command)
+    #[serde(skip)]
+    secret: Option<String>,
+}
+
+// A folder of saved commands, collections can be nested inside each other
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Collection {
+    id: i32,
+    name: String,
+    parent_id: Option<i32>,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct SavedKey {
     id: i32,
     key: String,
+    #[serde(default)]
+    label: Option<String>,
+    // The hosts the key is used with, e.g. api.example.com or *.example.com
+    #[serde(default)]
+    host: Option<String>,
+    #[serde(default)]
+    kind: KeyKind,
+    // None for keys saved before we kept track
+    #[serde(default)]
+    created: Option<String>,
+    // YYYY-MM-DD, the last day the key is valid
+    #[serde(default)]
+    expires: Option<String>,
+    // Days left until `expires`, negative once it's expired. Worked out by SQLite when loaded
+    #[serde(skip)]
+    days_left: Option<i64>,
+}
+
+// How a saved key is sent with a request
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum KeyKind {
+    #[default]
+    Bearer,
+    // user:password
+    Basic,
+    // user:password
+    Digest,
+    // sent in the named header, e.g. X-API-Key
+    Header(String),
+    // sent as the named query parameter, e.g. api_key
+    Query(String),
+}
+
+// Keys expiring within this many days are highlighted
+pub const EXPIRY_WARNING_DAYS: i64 = 7;
+
+// One execution of a request, recorded whether or not it was saved
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    id: i32,
+    url: String,
+    method: String,
+    curl_json: String,
+    // None if the request failed before we got a response
+    status: Option<u16>,
+    duration_ms: u64,
+    // size of the response body in bytes
+    size: usize,
+    timestamp: String,
+    body: Option<String>,
+    // The status line and headers we got back, the body is kept in `body`
+    response: Option<Response>,
+    // The encrypted auth secret, only kept while the vault is locked
+    #[serde(skip)]
+    secret: Option<String>,
+    // Why the request failed, there's no status or response then
+    #[serde(default)]
+    error: Option<String>,
+}
+
+// A named set of variables, e.g. local/staging/prod, substituted into requests as {{key}}
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Environment {
+    id: i32,
+    name: String,
+    active: bool,
+    vars: Vec<(String, String)>,
 }
 
 #[derive(Debug)]
 pub struct DB {
     pub conn: Connection,
+    // Unlocked with the master passphrase, None if there isn't one (or it wasn't entered yet)
+    vault: Option<Vault>,
 }
 
 impl DB {
@@ -41,7 +140,7 @@ impl DB {
                 std::fs::File::create(&_path).expect("failed to create database");
                 eprintln!("Failed to create CuTE directory: {}", err);
             } else {
-                println!("CuTE directory created at {:?}", _path);
+                eprintln!("CuTE directory created at {:?}", _path);
             }
         }
 
@@ -57,27 +156,29 @@ impl DB {
         let conn = match conn_result {
             Ok(connection) => connection,
             Err(e) => {
-                println!("CuTE Database Error: {:?}", e);
+                eprintln!("CuTE Database Error: {:?}", e);
                 return Err(e);
             }
         };
 
-        // Begin a transaction
-        conn.execute("BEGIN;", params![])?;
-
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS commands (id INTEGER PRIMARY KEY, command TEXT, curl_json TEXT);",
-            params![],
-        )?;
+        DB::init(conn)
+    }
 
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS keys (id INTEGER PRIMARY KEY, key TEXT);",
-            params![],
-        )?;
+    // In-memory database with the same schema, so tests never touch the user's CuTE.db
+    #[cfg(test)]
+    pub(crate) fn new_in_memory() -> Result<Self, rusqlite::Error> {
+        DB::init(Connection::open_in_memory()?)
+    }
 
-        conn.execute("COMMIT;", params![])?;
+    // Runs the schema setup and migrations against an existing connection
+    #[cfg(test)]
+    pub(crate) fn from_connection(conn: Connection) -> Result<Self, rusqlite::Error> {
+        DB::init(conn)
+    }
 
-        Ok(DB { conn })
+    fn init(conn: Connection) -> Result<Self, rusqlite::Error> {
+        migrations::run(&conn)?;
+        Ok(DB { conn, vault: None })
     }
 
     pub fn get_default_path() -> PathBuf {
@@ -85,12 +186,14 @@ impl DB {
         dir.join("CuTE")
     }
 
-    pub fn add_command(&self, command: &str, json_str: String) -> Result<(), rusqlite::Error> {
+    // Returns the ID of the new command
+    pub fn add_command(&self, command: &str, json_str: String) -> Result<i32, rusqlite::Error> {
+        let (command, json_str, secret) = self.seal_request(command, &json_str)?;
         let mut stmt = self
             .conn
-            .prepare("INSERT INTO commands (command, curl_json) VALUES (?1, ?2)")?;
-        let _ = stmt.execute(params![command, &json_str])?;
-        Ok(())
+            .prepare("INSERT INTO commands (command, curl_json, secret) VALUES (?1, ?2, ?3)")?;
+        let _ = stmt.execute(params![command, &json_str, secret])?;
+        Ok(self.conn.last_insert_rowid() as i32)
     }
 
     pub fn delete_command(&self, id: i32) -> Result<(), rusqlite::Error> {
@@ -100,14 +203,33 @@ impl DB {
     }
 
     pub fn key_exists(&self, key: &str) -> Result<bool> {
-        let mut stmt = self
-            .conn
-            .prepare("SELECT COUNT(*) FROM keys WHERE key = ?")?;
-        let count: i64 = stmt.query_row([&key], |row| row.get(0))?;
-        Ok(count > 0)
+        Ok(self.find_key(key)?.is_some())
+    }
+
+    // The ID of the saved key with this value
+    pub fn find_key(&self, key: &str) -> Result<Option<i32>> {
+        // every encryption uses a new nonce, so we can only compare the decrypted keys
+        if self.is_encrypted() {
+            return Ok(self
+                .get_keys()?
+                .into_iter()
+                .find(|saved| saved.is_key(key))
+                .map(|saved| saved.id));
+        }
+        self.conn
+            .query_row("SELECT id FROM keys WHERE key = ?", [&key], |row| {
+                row.get(0)
+            })
+            .optional()
     }
 
     pub fn command_exists(&self, command: &str) -> Result<bool, rusqlite::Error> {
+        if self.is_encrypted() {
+            return Ok(self
+                .get_commands()?
+                .iter()
+                .any(|saved| saved.get_command() == command));
+        }
         let mut stmt = self
             .conn
             .prepare("SELECT COUNT(*) FROM commands WHERE command = ?")?;
@@ -116,21 +238,120 @@ impl DB {
     }
 
     pub fn get_commands(&self) -> Result<Vec<SavedCommand>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, command, curl_json, name, description, collection_id, assertions, secret FROM commands",
+        )?;
+        let rows = stmt.query_map(params![], SavedCommand::from_row)?;
+        let mut commands = Vec::new();
+        rows.for_each(|row| {
+            commands.push(self.open_command(row.unwrap()));
+        });
+        Ok(commands)
+    }
+
+    pub fn get_command_by_id(&self, id: i32) -> Result<Option<SavedCommand>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, command, curl_json, name, description, collection_id, assertions, secret FROM commands WHERE id = ?",
+        )?;
+        let mut rows = stmt.query_map([id], SavedCommand::from_row)?;
+        Ok(rows
+            .next()
+            .transpose()?
+            .map(|command| self.open_command(command)))
+    }
+
+    // None clears the name, so the command string is shown again
+    pub fn rename_command(&self, id: i32, name: Option<&str>) -> Result<()> {
+        self.conn.execute(
+            "UPDATE commands SET name = ?1 WHERE id = ?2",
+            params![name, id],
+        )?;
+        Ok(())
+    }
+
+    pub fn set_command_description(&self, id: i32, description: Option<&str>) -> Result<()> {
+        self.conn.execute(
+            "UPDATE commands SET description = ?1 WHERE id = ?2",
+            params![description, id],
+        )?;
+        Ok(())
+    }
+
+    // None moves the command to the top level
+    pub fn move_command(&self, id: i32, collection_id: Option<i32>) -> Result<()> {
+        self.conn.execute(
+            "UPDATE commands SET collection_id = ?1 WHERE id = ?2",
+            params![collection_id, id],
+        )?;
+        Ok(())
+    }
+
+    pub fn set_command_assertions(&self, id: i32, assertions: &[Assertion]) -> Result<()> {
+        let json = serde_json::to_string(assertions).unwrap_or(String::from("[]"));
+        self.conn.execute(
+            "UPDATE commands SET assertions = ?1 WHERE id = ?2",
+            params![json, id],
+        )?;
+        Ok(())
+    }
+
+    // Returns the ID of the new collection
+    pub fn add_collection(&self, name: &str, parent_id: Option<i32>) -> Result<i32> {
+        self.conn.execute(
+            "INSERT INTO collections (name, parent_id) VALUES (?1, ?2)",
+            params![name, parent_id],
+        )?;
+        Ok(self.conn.last_insert_rowid() as i32)
+    }
+
+    pub fn get_collections(&self) -> Result<Vec<Collection>> {
         let mut stmt = self
             .conn
-            .prepare("SELECT id, command, curl_json FROM commands")?;
+            .prepare("SELECT id, name, parent_id FROM collections ORDER BY name")?;
         let rows = stmt.query_map(params![], |row| {
-            Ok(SavedCommand {
+            Ok(Collection {
                 id: row.get(0)?,
-                command: row.get(1)?,
-                curl_json: row.get(2)?,
+                name: row.get(1)?,
+                parent_id: row.get(2)?,
             })
         })?;
-        let mut commands = Vec::new();
-        rows.for_each(|row| {
-            commands.push(row.unwrap());
-        });
-        Ok(commands)
+        rows.collect()
+    }
+
+    pub fn rename_collection(&self, id: i32, name: &str) -> Result<()> {
+        self.conn.execute(
+            "UPDATE collections SET name = ?1 WHERE id = ?2",
+            params![name, id],
+        )?;
+        Ok(())
+    }
+
+    pub fn move_collection(&self, id: i32, parent_id: Option<i32>) -> Result<()> {
+        self.conn.execute(
+            "UPDATE collections SET parent_id = ?1 WHERE id = ?2",
+            params![parent_id, id],
+        )?;
+        Ok(())
+    }
+
+    // Whatever was inside the collection moves up to its parent
+    pub fn delete_collection(&self, id: i32) -> Result<()> {
+        let parent_id: Option<i32> = self.conn.query_row(
+            "SELECT parent_id FROM collections WHERE id = ?",
+            [id],
+            |row| row.get(0),
+        )?;
+        self.conn.execute(
+            "UPDATE commands SET collection_id = ?1 WHERE collection_id = ?2",
+            params![parent_id, id],
+        )?;
+        self.conn.execute(
+            "UPDATE collections SET parent_id = ?1 WHERE parent_id = ?2",
+            params![parent_id, id],
+        )?;
+        self.conn
+            .execute("DELETE FROM collections WHERE id = ?", [id])?;
+        Ok(())
     }
 
     pub fn delete_key(&self, id: i32) -> Result<()> {
@@ -140,20 +361,216 @@ impl DB {
     }
 
     pub fn add_key(&self, key: &str) -> Result<()> {
-        if self.key_exists(key).unwrap() {
-            return Ok(());
+        self.add_saved_key(&SavedKey::new(key))?;
+        Ok(())
+    }
+
+    // Saves a key along with its label, host, type and dates. Returns its ID, or the ID of the
+    // saved key with the same value
+    pub fn add_saved_key(&self, saved: &SavedKey) -> Result<i32> {
+        if let Some(id) = self.find_key(&saved.key)? {
+            return Ok(id);
+        }
+        let key = match self.vault {
+            Some(ref vault) => vault.encrypt(&saved.key),
+            None if self.is_encrypted() => return Err(locked_error()),
+            None => saved.key.clone(),
+        };
+        self.conn.execute(
+            "INSERT INTO keys (key, label, host, kind, created, expires) VALUES (?1, ?2, ?3, ?4, COALESCE(?5, datetime('now', 'localtime')), ?6)",
+            params![
+                key,
+                saved.label,
+                saved.host,
+                saved.kind.to_string(),
+                saved.created,
+                saved.expires
+            ],
+        )?;
+        Ok(self.conn.last_insert_rowid() as i32)
+    }
+
+    pub fn set_key_label(&self, id: i32, label: Option<&str>) -> Result<()> {
+        self.conn.execute(
+            "UPDATE keys SET label = ?1 WHERE id = ?2",
+            params![label, id],
+        )?;
+        Ok(())
+    }
+
+    pub fn set_key_host(&self, id: i32, host: Option<&str>) -> Result<()> {
+        self.conn
+            .execute("UPDATE keys SET host = ?1 WHERE id = ?2", params![host, id])?;
+        Ok(())
+    }
+
+    pub fn set_key_kind(&self, id: i32, kind: &KeyKind) -> Result<()> {
+        self.conn.execute(
+            "UPDATE keys SET kind = ?1 WHERE id = ?2",
+            params![kind.to_string(), id],
+        )?;
+        Ok(())
+    }
+
+    // The expiry must be a YYYY-MM-DD date, None removes it
+    pub fn set_key_expiry(&self, id: i32, expires: Option<&str>) -> Result<(), String> {
+        if expires.is_some_and(|date| !is_valid_date(date)) {
+            return Err(String::from(KEY_EXPIRY_ERROR));
         }
-        let mut stmt = self.conn.prepare("INSERT INTO keys (key) VALUES (?1)")?;
-        let _ = stmt.execute(params![key])?;
+        self.conn
+            .execute(
+                "UPDATE keys SET expires = ?1 WHERE id = ?2",
+                params![expires, id],
+            )
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    pub fn add_history(&self, entry: &HistoryEntry) -> Result<()> {
+        let (_, curl_json, secret) = self.seal_request("", &entry.curl_json)?;
+        let url = vault::seal_url(self.vault.as_ref(), &entry.url);
+        self.conn.execute(
+            "INSERT INTO history (url, method, curl_json, status, duration_ms, size, body, response, secret, error) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            params![
+                url,
+                entry.method,
+                curl_json,
+                entry.status,
+                entry.duration_ms as i64,
+                entry.size as i64,
+                entry.body,
+                entry
+                    .response
+                    .as_ref()
+                    .and_then(|response| serde_json::to_string(response).ok()),
+                secret,
+                entry.error
+            ],
+        )?;
+        Ok(())
+    }
+
+    // Newest first, only entries whose method or url contain the query (if any)
+    pub fn get_history(&self, query: &str) -> Result<Vec<HistoryEntry>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, url, method, curl_json, status, duration_ms, size, timestamp, body, response, secret, error FROM history
+            WHERE url LIKE ?1 OR method LIKE ?1 ORDER BY id DESC",
+        )?;
+        let rows = stmt.query_map([format!("%{}%", query.trim())], HistoryEntry::from_row)?;
+        rows.map(|entry| entry.map(|entry| self.open_history(entry)))
+            .collect()
+    }
+
+    pub fn get_history_by_id(&self, id: i32) -> Result<Option<HistoryEntry>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, url, method, curl_json, status, duration_ms, size, timestamp, body, response, secret, error FROM history WHERE id = ?",
+        )?;
+        let mut rows = stmt.query_map([id], HistoryEntry::from_row)?;
+        Ok(rows
+            .next()
+            .transpose()?
+            .map(|entry| self.open_history(entry)))
+    }
+
+    pub fn delete_history(&self, id: i32) -> Result<()> {
+        self.conn
+            .execute("DELETE FROM history WHERE id = ?", [id])?;
+        Ok(())
+    }
+
+    pub fn add_environment(&self, name: &str) -> Result<()> {
+        let mut stmt = self
+            .conn
+            .prepare("INSERT INTO environments (name) VALUES (?1)")?;
+        let _ = stmt.execute(params![name])?;
+        Ok(())
+    }
+
+    pub fn delete_environment(&self, id: i32) -> Result<()> {
+        self.conn
+            .execute("DELETE FROM env_vars WHERE env_id = ?", [id])?;
+        self.conn
+            .execute("DELETE FROM environments WHERE id = ?", [id])?;
+        Ok(())
+    }
+
+    // Only one environment is active at a time
+    pub fn set_active_environment(&self, id: i32) -> Result<()> {
+        self.conn
+            .execute("UPDATE environments SET active = (id = ?1)", [id])?;
+        Ok(())
+    }
+
+    pub fn get_environments(&self) -> Result<Vec<Environment>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT id, name, active FROM environments ORDER BY id")?;
+        let rows = stmt.query_map(params![], |row| {
+            Ok(Environment {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                active: row.get(2)?,
+                vars: Vec::new(),
+            })
+        })?;
+        let mut envs = Vec::new();
+        for env in rows {
+            let mut env = env?;
+            env.vars = self.get_env_vars(env.id)?;
+            envs.push(env);
+        }
+        Ok(envs)
+    }
+
+    pub fn get_active_environment(&self) -> Result<Option<Environment>> {
+        Ok(self
+            .get_environments()?
+            .into_iter()
+            .find(|env| env.is_active()))
+    }
+
+    pub fn get_env_vars(&self, env_id: i32) -> Result<Vec<(String, String)>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT key, value FROM env_vars WHERE env_id = ? ORDER BY key")?;
+        let rows = stmt.query_map([env_id], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        rows.collect()
+    }
+
+    // Adds the variable, or replaces its value if the environment already has it
+    pub fn set_env_var(&self, env_id: i32, key: &str, value: &str) -> Result<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO env_vars (env_id, key, value) VALUES (?1, ?2, ?3)",
+            params![env_id, key, value],
+        )?;
+        Ok(())
+    }
+
+    pub fn delete_env_var(&self, env_id: i32, key: &str) -> Result<()> {
+        self.conn.execute(
+            "DELETE FROM env_vars WHERE env_id = ?1 AND key = ?2",
+            params![env_id, key],
+        )?;
         Ok(())
     }
 
     pub fn get_keys(&self) -> Result<Vec<SavedKey>> {
-        let mut stmt = self.conn.prepare("SELECT id, key FROM keys")?;
+        let mut stmt = self.conn.prepare(
+            "SELECT id, key, label, host, kind, created, expires, CAST(julianday(expires) - julianday(date('now', 'localtime')) AS INTEGER) FROM keys",
+        )?;
         let rows = stmt.query_map(params![], |row| {
             Ok(SavedKey {
                 id: row.get(0)?,
-                key: row.get(1)?,
+                key: self.open_key(row.get(1)?),
+                label: row.get(2)?,
+                host: row.get(3)?,
+                kind: row
+                    .get::<_, Option<String>>(4)?
+                    .and_then(|kind| kind.parse().ok())
+                    .unwrap_or_default(),
+                created: row.get(5)?,
+                expires: row.get(6)?,
+                days_left: row.get(7)?,
             })
         })?;
         let mut keys = Vec::new();
@@ -162,6 +579,225 @@ impl DB {
         }
         Ok(keys)
     }
+
+    // Everything expanded into a request when it runs: the variables of the active environment,
+    // and the saved keys it refers to as {{key:<id>}}
+    pub fn get_request_vars(&self) -> Vec<(String, String)> {
+        let env = self.get_active_environment().ok().flatten();
+        self.get_request_vars_in(env.as_ref())
+    }
+
+    // The variables of an environment other than the active one, along with the saved keys
+    pub fn get_request_vars_in(&self, env: Option<&Environment>) -> Vec<(String, String)> {
+        let mut vars = env.map(|env| env.get_vars().to_vec()).unwrap_or_default();
+        vars.extend(
+            self.get_keys()
+                .unwrap_or_default()
+                .into_iter()
+                // a locked key would be sent as the mask
+                .filter(|key| key.key != MASK)
+                .map(|key| (key.get_var_name(), key.key)),
+        );
+        vars
+    }
+
+    // True once a master passphrase is set, whether or not it was entered yet
+    pub fn is_encrypted(&self) -> bool {
+        self.conn
+            .query_row("SELECT COUNT(*) FROM vault", [], |row| row.get::<_, i64>(0))
+            .is_ok_and(|count| count > 0)
+    }
+
+    pub fn is_locked(&self) -> bool {
+        self.vault.is_none() && self.is_encrypted()
+    }
+
+    pub fn unlock(&mut self, passphrase: &str) -> Result<(), String> {
+        let (salt, check_value): (String, String) = self
+            .conn
+            .query_row("SELECT salt, check_value FROM vault", [], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })
+            .map_err(|_| String::from(NOT_ENCRYPTED_ERROR))?;
+        let salt = vault::from_hex(&salt).ok_or(String::from(PASSPHRASE_ERROR))?;
+        let vault = Vault::derive(passphrase, &salt)?;
+        if !vault.verify(&check_value) {
+            return Err(String::from(PASSPHRASE_ERROR));
+        }
+        self.vault = Some(vault);
+        Ok(())
+    }
+
+    // Connections opened on other threads share the unlocked vault instead of asking again
+    pub fn get_vault(&self) -> Option<Vault> {
+        self.vault.clone()
+    }
+
+    pub fn set_vault(&mut self, vault: Option<Vault>) {
+        self.vault = vault;
+    }
+
+    // Encrypts the keys and saved auth with a new passphrase. This is also how a plaintext
+    // database is encrypted the first time, and how the passphrase is changed (re-keyed)
+    pub fn set_passphrase(&mut self, passphrase: &str) -> Result<(), String> {
+        if passphrase.is_empty() {
+            return Err(String::from(PASSPHRASE_EMPTY_ERROR));
+        }
+        let salt = Vault::new_salt();
+        let vault = Vault::derive(passphrase, &salt)?;
+        self.rewrite_secrets(Some((vault, salt)))
+    }
+
+    // Decrypts everything and stores it in plaintext again
+    pub fn remove_passphrase(&mut self) -> Result<(), String> {
+        if !self.is_encrypted() {
+            return Err(String::from(NOT_ENCRYPTED_ERROR));
+        }
+        self.rewrite_secrets(None)
+    }
+
+    fn rewrite_secrets(&mut self, vault: Option<(Vault, Vec<u8>)>) -> Result<(), String> {
+        if self.is_locked() {
+            return Err(String::from(LOCKED_ERROR));
+        }
+        let keys = self.get_keys().map_err(|e| e.to_string())?;
+        let commands = self.get_commands().map_err(|e| e.to_string())?;
+        let history = self.get_history("").map_err(|e| e.to_string())?;
+        // anything we couldn't decrypt would be lost
+        if commands.iter().any(|command| command.secret.is_some())
+            || history.iter().any(|entry| entry.secret.is_some())
+            || keys.iter().any(|key| key.key == MASK)
+        {
+            return Err(String::from(PASSPHRASE_ERROR));
+        }
+        let new_vault = vault.as_ref().map(|(vault, _)| vault);
+        let tx = self
+            .conn
+            .unchecked_transaction()
+            .map_err(|e| e.to_string())?;
+        let result = (|| -> Result<()> {
+            tx.execute("DELETE FROM vault", [])?;
+            if let Some((vault, salt)) = vault.as_ref() {
+                tx.execute(
+                    "INSERT INTO vault (id, salt, check_value) VALUES (1, ?1, ?2)",
+                    params![vault::to_hex(salt), vault.check_value()],
+                )?;
+            }
+            for key in keys.iter() {
+                let value = new_vault
+                    .map(|vault| vault.encrypt(&key.key))
+                    .unwrap_or(key.key.clone());
+                tx.execute(
+                    "UPDATE keys SET key = ?1 WHERE id = ?2",
+                    params![value, key.id],
+                )?;
+            }
+            for saved in commands.iter() {
+                let (command, curl_json, secret) =
+                    vault::seal_request(new_vault, &saved.command, &saved.curl_json);
+                tx.execute(
+                    "UPDATE commands SET command = ?1, curl_json = ?2, secret = ?3 WHERE id = ?4",
+                    params![command, curl_json, secret, saved.id],
+                )?;
+            }
+            for entry in history.iter() {
+                let (_, curl_json, secret) = vault::seal_request(new_vault, "", &entry.curl_json);
+                tx.execute(
+                    "UPDATE history SET url = ?1, curl_json = ?2, secret = ?3 WHERE id = ?4",
+                    params![
+                        vault::seal_url(new_vault, &entry.url),
+                        curl_json,
+                        secret,
+                        entry.id
+                    ],
+                )?;
+            }
+            Ok(())
+        })();
+        result
+            .and_then(|_| tx.commit())
+            .map_err(|e| e.to_string())?;
+        self.vault = vault.map(|(vault, _)| vault);
+        Ok(())
+    }
+
+    // Masks and encrypts the auth of a request before it's stored, when there's a passphrase
+    fn seal_request(
+        &self,
+        command: &str,
+        curl_json: &str,
+    ) -> Result<(String, String, Option<String>)> {
+        if self.is_locked() && vault::has_secret(curl_json) {
+            return Err(locked_error());
+        }
+        Ok(vault::seal_request(self.vault.as_ref(), command, curl_json))
+    }
+
+    // Puts the decrypted secret back, the command stays masked while the vault is locked
+    fn open_command(&self, mut saved: SavedCommand) -> SavedCommand {
+        if let (Some(vault), Some(secret)) = (self.vault.as_ref(), saved.secret.as_ref()) {
+            if let Ok((command, curl_json)) = vault::open_request(vault, &saved.curl_json, secret) {
+                saved.command = command;
+                saved.curl_json = curl_json;
+                saved.secret = None;
+            }
+        }
+        saved
+    }
+
+    fn open_history(&self, mut entry: HistoryEntry) -> HistoryEntry {
+        if let (Some(vault), Some(secret)) = (self.vault.as_ref(), entry.secret.as_ref()) {
+            if let Ok((_, curl_json)) = vault::open_request(vault, &entry.curl_json, secret) {
+                entry.url = vault::request_url(&curl_json).unwrap_or(entry.url);
+                entry.curl_json = curl_json;
+                entry.secret = None;
+            }
+        }
+        entry
+    }
+
+    // Keys stored before the passphrase was set are still in plaintext
+    fn open_key(&self, key: String) -> String {
+        if !vault::is_encrypted(&key) {
+            return key;
+        }
+        self.vault
+            .as_ref()
+            .and_then(|vault| vault.decrypt(&key).ok())
+            .unwrap_or(String::from(MASK))
+    }
+}
+
+// YYYY-MM-DD, older SQLite versions happily compare dates like 2024-02-30
+fn is_valid_date(date: &str) -> bool {
+    let parts: Vec<&str> = date.split('-').collect();
+    let [year, month, day] = parts.as_slice() else {
+        return false;
+    };
+    if year.len() != 4 || month.len() != 2 || day.len() != 2 {
+        return false;
+    }
+    let (Ok(year), Ok(month), Ok(day)) = (
+        year.parse::<u32>(),
+        month.parse::<u32>(),
+        day.parse::<u32>(),
+    ) else {
+        return false;
+    };
+    let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    let days = match month {
+        2 if leap => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        1..=12 => 31,
+        _ => return false,
+    };
+    (1..=days).contains(&day)
+}
+
+// The vault has to be unlocked before secrets can be stored
+fn locked_error() -> rusqlite::Error {
+    rusqlite::Error::ToSqlConversionFailure(String::from(LOCKED_ERROR).into())
 }
 
 impl Display for SavedCommand {
@@ -171,6 +807,23 @@ impl Display for SavedCommand {
 }
 
 impl SavedCommand {
+    fn from_row(row: &rusqlite::Row) -> Result<Self> {
+        Ok(SavedCommand {
+            id: row.get(0)?,
+            command: row.get(1)?,
+            curl_json: row.get(2)?,
+            name: row.get(3)?,
+            description: row.get(4)?,
+            collection_id: row.get(5)?,
+            // stored as a JSON array, NULL for commands without any
+            assertions: row
+                .get::<_, Option<String>>(6)?
+                .and_then(|json| serde_json::from_str(&json).ok())
+                .unwrap_or_default(),
+            secret: row.get(7)?,
+        })
+    }
+
     // We nned to allow the user to write out the response to a file,
     // so at some point we may need to read it back in
     pub fn to_json(&self) -> Result<String> {
@@ -192,11 +845,69 @@ impl SavedCommand {
     pub fn get_command(&self) -> &str {
         &self.command
     }
+
+    pub fn get_name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    pub fn get_description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    pub fn get_collection_id(&self) -> Option<i32> {
+        self.collection_id
+    }
+
+    pub fn get_assertions(&self) -> &[Assertion] {
+        &self.assertions
+    }
+
+    // The name if it has one, otherwise the command itself
+    pub fn get_label(&self) -> &str {
+        self.get_name().unwrap_or(&self.command)
+    }
+
+    // The auth is still masked, the vault wasn't unlocked
+    pub fn is_locked(&self) -> bool {
+        self.secret.is_some()
+    }
+}
+
+impl Collection {
+    pub fn get_id(&self) -> i32 {
+        self.id
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get_parent_id(&self) -> Option<i32> {
+        self.parent_id
+    }
 }
 
+// The key itself is always masked
 impl Display for SavedKey {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, " ID: {} | Key: {}", self.id, self.key)
+        write!(
+            f,
+            " {} | {} | {}",
+            self.get_label(),
+            self.kind,
+            self.get_masked_key()
+        )?;
+        if let Some(host) = self.host.as_ref() {
+            write!(f, " | {}", host)?;
+        }
+        match (self.expires.as_ref(), self.days_left) {
+            (Some(date), _) if self.is_expired() => write!(f, " | expired on {}", date),
+            (Some(date), Some(days)) if self.expires_soon() => {
+                write!(f, " | expires in {} days ({})", days, date)
+            }
+            (Some(date), _) => write!(f, " | expires {}", date),
+            _ => Ok(()),
+        }
     }
 }
 
@@ -205,9 +916,25 @@ impl SavedKey {
         SavedKey {
             id: 0,
             key: key.to_string(),
+            label: None,
+            host: None,
+            kind: KeyKind::default(),
+            created: None,
+            expires: None,
+            days_left: None,
         }
     }
 
+    pub fn with_kind(mut self, kind: KeyKind) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    pub fn with_host(mut self, host: Option<&str>) -> Self {
+        self.host = host.map(String::from);
+        self
+    }
+
     pub fn get_id(&self) -> i32 {
         self.id
     }
@@ -215,6 +942,73 @@ impl SavedKey {
         &self.key
     }
 
+    // The label if it has one, otherwise the ID
+    pub fn get_label(&self) -> String {
+        self.label
+            .clone()
+            .unwrap_or_else(|| format!("Key {}", self.id))
+    }
+
+    pub fn get_label_text(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+
+    pub fn get_host(&self) -> Option<&str> {
+        self.host.as_deref()
+    }
+
+    pub fn get_kind(&self) -> &KeyKind {
+        &self.kind
+    }
+
+    pub fn get_created(&self) -> Option<&str> {
+        self.created.as_deref()
+    }
+
+    pub fn get_expires(&self) -> Option<&str> {
+        self.expires.as_deref()
+    }
+
+    // Requests refer to the key as {{key:<id>}}, it's filled in like a variable when they run
+    pub fn get_reference(&self) -> String {
+        format!("{{{{{}}}}}", self.get_var_name())
+    }
+
+    fn get_var_name(&self) -> String {
+        format!("key:{}", self.id)
+    }
+
+    // Keys without a host can be used anywhere, *.example.com also matches example.com
+    pub fn matches_host(&self, host: &str) -> bool {
+        let Some(pattern) = self.host.as_deref() else {
+            return true;
+        };
+        let (pattern, host) = (pattern.to_lowercase(), host.to_lowercase());
+        match pattern.strip_prefix("*.") {
+            Some(domain) => host == domain || host.ends_with(&format!(".{}", domain)),
+            None => host == pattern,
+        }
+    }
+
+    // Only the last 4 characters, and only if the key is long enough that they don't give it away
+    pub fn get_masked_key(&self) -> String {
+        let chars: Vec<char> = self.key.chars().collect();
+        if self.key == MASK || chars.len() <= 12 {
+            return String::from(MASK);
+        }
+        let last: String = chars[chars.len() - 4..].iter().collect();
+        format!("{}{}", MASK, last)
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.days_left.is_some_and(|days| days < 0)
+    }
+
+    pub fn expires_soon(&self) -> bool {
+        self.days_left
+            .is_some_and(|days| (0..=EXPIRY_WARNING_DAYS).contains(&days))
+    }
+
     pub fn is_key(&self, key: &str) -> bool {
         self.key == key
     }
@@ -227,3 +1021,464 @@ impl SavedKey {
         Ok(serde_json::from_str(json).expect("Failed to deserialize"))
     }
 }
+
+impl Display for KeyKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            KeyKind::Bearer => write!(f, "bearer"),
+            KeyKind::Basic => write!(f, "basic"),
+            KeyKind::Digest => write!(f, "digest"),
+            KeyKind::Header(name) => write!(f, "header {}", name),
+            KeyKind::Query(param) => write!(f, "query {}", param),
+        }
+    }
+}
+
+// Parses what Display writes: bearer, basic, digest, header <name> or query <param>
+impl FromStr for KeyKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let s = s.trim();
+        let (kind, name) = s
+            .split_once([' ', ':'])
+            .map(|(kind, name)| (kind, name.trim()))
+            .unwrap_or((s, ""));
+        match (kind.to_lowercase().as_str(), name) {
+            ("bearer", "") => Ok(KeyKind::Bearer),
+            ("basic", "") => Ok(KeyKind::Basic),
+            ("digest", "") => Ok(KeyKind::Digest),
+            ("header", name) if !name.is_empty() && !name.contains(' ') => {
+                Ok(KeyKind::Header(name.to_string()))
+            }
+            ("query", param) if !param.is_empty() && !param.contains(' ') => {
+                Ok(KeyKind::Query(param.to_string()))
+            }
+            _ => Err(String::from(KEY_TYPE_ERROR)),
+        }
+    }
+}
+
+impl Display for HistoryEntry {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let status = match (self.status, &self.error) {
+            (Some(status), _) => status.to_string(),
+            (None, Some(error)) => format!("ERR {}", error.trim()),
+            (None, None) => String::from("ERR"),
+        };
+        write!(
+            f,
+            "{} | {} {} | {} | {}ms | {}B",
+            self.timestamp, self.method, self.url, status, self.duration_ms, self.size
+        )
+    }
+}
+
+impl HistoryEntry {
+    pub fn new(url: &str, method: &str, curl_json: String) -> Self {
+        HistoryEntry {
+            id: 0,
+            url: url.to_string(),
+            method: method.to_string(),
+            curl_json,
+            status: None,
+            duration_ms: 0,
+            size: 0,
+            timestamp: String::new(),
+            body: None,
+            response: None,
+            secret: None,
+            error: None,
+        }
+    }
+
+    fn from_row(row: &rusqlite::Row) -> Result<Self> {
+        Ok(HistoryEntry {
+            id: row.get(0)?,
+            url: row.get(1)?,
+            method: row.get(2)?,
+            curl_json: row.get(3)?,
+            status: row.get(4)?,
+            duration_ms: row.get::<_, i64>(5)? as u64,
+            size: row.get::<_, i64>(6)? as usize,
+            timestamp: row.get(7)?,
+            body: row.get(8)?,
+            response: row
+                .get::<_, Option<String>>(9)?
+                .and_then(|json| serde_json::from_str(&json).ok()),
+            secret: row.get(10)?,
+            error: row.get(11)?,
+        })
+    }
+
+    pub fn set_response(
+        &mut self,
+        status: Option<u16>,
+        duration_ms: u64,
+        size: usize,
+        body: Option<String>,
+    ) {
+        self.status = status;
+        self.duration_ms = duration_ms;
+        self.size = size;
+        self.body = body;
+    }
+
+    // A run that failed has nothing from a response, only how long it took and why
+    pub fn set_error(&mut self, duration_ms: u64, error: &str) {
+        self.set_response(None, duration_ms, 0, None);
+        self.response = None;
+        self.error = Some(error.to_string());
+    }
+
+    pub fn get_error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    // Keeps the status line and headers of the response, without its body
+    pub fn set_http_response(&mut self, response: &Response) {
+        self.response = Some(Response {
+            body: String::new(),
+            ..response.clone()
+        });
+    }
+
+    pub fn get_id(&self) -> i32 {
+        self.id
+    }
+
+    pub fn get_url(&self) -> &str {
+        &self.url
+    }
+
+    pub fn get_method(&self) -> &str {
+        &self.method
+    }
+
+    pub fn get_curl_json(&self) -> &str {
+        &self.curl_json
+    }
+
+    pub fn get_status(&self) -> Option<u16> {
+        self.status
+    }
+
+    pub fn get_duration_ms(&self) -> u64 {
+        self.duration_ms
+    }
+
+    pub fn get_size(&self) -> usize {
+        self.size
+    }
+
+    pub fn get_timestamp(&self) -> &str {
+        &self.timestamp
+    }
+
+    pub fn get_http_response(&self) -> Option<&Response> {
+        self.response.as_ref()
+    }
+
+    pub fn get_body(&self) -> Option<&str> {
+        self.body.as_deref()
+    }
+
+    // The response with its body, entries from before we kept the headers only have the status
+    pub fn to_response(&self) -> Response {
+        let mut response = self.response.clone().unwrap_or_else(|| Response {
+            status: self.status.unwrap_or_default(),
+            ..Default::default()
+        });
+        response.body = self.body.clone().unwrap_or_default();
+        response
+    }
+
+    // What the entry is when it's compared with another response
+    pub fn get_label(&self) -> String {
+        format!(
+            "#{} {} {} ({})",
+            self.id, self.method, self.url, self.timestamp
+        )
+    }
+
+    // The auth is still masked, the vault wasn't unlocked
+    pub fn is_locked(&self) -> bool {
+        self.secret.is_some()
+    }
+}
+
+impl Display for Environment {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.active {
+            write!(f, "{} (active)", self.name)
+        } else {
+            write!(f, "{}", self.name)
+        }
+    }
+}
+
+impl Environment {
+    pub fn get_id(&self) -> i32 {
+        self.id
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    pub fn get_vars(&self) -> &[(String, String)] {
+        &self.vars
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::request::command::{CmdOpts, CurlOpts};
+    use crate::request::curl::{AuthKind, Curl};
+    use crate::request::environment::expand_vars;
+
+    #[test]
+    fn test_history() {
+        let db = DB::new_in_memory().unwrap();
+        let mut entry = HistoryEntry::new("http://localhost/users", "GET", String::from("{}"));
+        entry.set_response(Some(200), 42, 2, Some(String::from("[]")));
+        entry.set_http_response(&Response {
+            status: 200,
+            reason: String::from("OK"),
+            headers: vec![(
+                String::from("Content-Type"),
+                String::from("application/json"),
+            )],
+            body: String::from("[]"),
+            ..Default::default()
+        });
+        db.add_history(&entry).unwrap();
+        let mut failed = HistoryEntry::new("http://localhost/posts", "POST", String::from("{}"));
+        failed.set_error(7, "Couldn't connect to server");
+        db.add_history(&failed).unwrap();
+
+        // newest first
+        let history = db.get_history("").unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].get_method(), "POST");
+        assert_eq!(history[0].get_status(), None);
+        assert_eq!(history[0].get_error(), Some("Couldn't connect to server"));
+        assert!(history[0]
+            .to_string()
+            .contains("ERR Couldn't connect to server"));
+        assert_eq!(history[1].get_error(), None);
+        assert_eq!(history[1].get_status(), Some(200));
+        assert_eq!(history[1].get_duration_ms(), 42);
+        assert_eq!(history[1].get_size(), 2);
+        assert_eq!(history[1].get_body(), Some("[]"));
+        assert!(!history[1].get_timestamp().is_empty());
+        assert_eq!(history[0].get_http_response(), None);
+        let response = history[1].get_http_response().unwrap();
+        assert_eq!(response.reason, "OK");
+        assert_eq!(response.headers.len(), 1);
+        // the body is only kept once
+        assert!(response.body.is_empty());
+
+        let users = db.get_history("users").unwrap();
+        assert_eq!(users.len(), 1);
+        assert_eq!(users[0].get_url(), "http://localhost/users");
+        assert_eq!(db.get_history("post").unwrap().len(), 1);
+
+        let id = users[0].get_id();
+        assert_eq!(db.get_history_by_id(id).unwrap(), Some(users[0].clone()));
+        db.delete_history(id).unwrap();
+        assert!(db.get_history_by_id(id).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_migrate_history() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute(
+            "CREATE TABLE history (id INTEGER PRIMARY KEY, url TEXT, method TEXT, curl_json TEXT, status INTEGER, duration_ms INTEGER, size INTEGER, timestamp TEXT DEFAULT (datetime('now', 'localtime')), body TEXT);",
+            [],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO history (url, method, curl_json, status, duration_ms, size) VALUES ('http://old', 'GET', '{}', 200, 5, 0);",
+            [],
+        )
+        .unwrap();
+        let db = DB::from_connection(conn).unwrap();
+        let history = db.get_history("").unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].get_status(), Some(200));
+        assert_eq!(history[0].get_http_response(), None);
+    }
+
+    #[test]
+    fn test_key_metadata() {
+        let db = DB::new_in_memory().unwrap();
+        db.add_saved_key(
+            &SavedKey::new("sk-live-0123456789abcdef")
+                .with_kind(KeyKind::Header(String::from("X-API-Key")))
+                .with_host(Some("api.example.com")),
+        )
+        .unwrap();
+        let key = db.get_keys().unwrap().remove(0);
+        assert_eq!(key.get_kind(), &KeyKind::Header(String::from("X-API-Key")));
+        assert_eq!(key.get_host(), Some("api.example.com"));
+        assert!(key.get_created().is_some());
+        assert_eq!(key.get_masked_key(), format!("{}cdef", MASK));
+        assert!(!key.to_string().contains("sk-live"));
+        assert!(!key.is_expired() && !key.expires_soon());
+
+        let id = key.get_id();
+        db.set_key_label(id, Some("Production")).unwrap();
+        db.set_key_kind(id, &"query api_key".parse().unwrap())
+            .unwrap();
+        assert_eq!(
+            db.set_key_expiry(id, Some("2024-02-30")),
+            Err(String::from(KEY_EXPIRY_ERROR))
+        );
+        db.set_key_expiry(id, Some("2000-01-01")).unwrap();
+        let key = db.get_keys().unwrap().remove(0);
+        assert_eq!(key.get_label(), "Production");
+        assert_eq!(key.get_kind(), &KeyKind::Query(String::from("api_key")));
+        assert!(key.is_expired());
+        assert!(key.to_string().contains("expired on 2000-01-01"));
+
+        let soon: String = db
+            .conn
+            .query_row("SELECT date('now', 'localtime', '+3 days')", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        db.set_key_expiry(id, Some(&soon)).unwrap();
+        let key = db.get_keys().unwrap().remove(0);
+        assert!(!key.is_expired() && key.expires_soon());
+        db.set_key_expiry(id, None).unwrap();
+        assert!(!db.get_keys().unwrap()[0].expires_soon());
+    }
+
+    #[test]
+    fn test_key_references() {
+        let db = DB::new_in_memory().unwrap();
+        db.add_saved_key(&SavedKey::new("abc123").with_host(Some("*.example.com")))
+            .unwrap();
+        db.add_key("no-host").unwrap();
+        let keys = db.get_keys().unwrap();
+        assert!(keys[0].matches_host("api.example.com"));
+        assert!(keys[0].matches_host("EXAMPLE.com"));
+        assert!(!keys[0].matches_host("example.org"));
+        assert!(!keys[0].matches_host("notexample.com"));
+        assert!(keys[1].matches_host("localhost"));
+
+        db.add_environment("local").unwrap();
+        let id = db.get_environments().unwrap()[0].get_id();
+        db.set_env_var(id, "base_url", "http://localhost").unwrap();
+        db.set_active_environment(id).unwrap();
+        let reference = keys[0].get_reference();
+        assert_eq!(reference, format!("{{{{key:{}}}}}", keys[0].get_id()));
+        let vars = db.get_request_vars();
+        assert_eq!(
+            expand_vars(&format!("{{{{base_url}}}} {}", reference), &vars),
+            "http://localhost abc123"
+        );
+    }
+
+    #[test]
+    fn test_key_kind() {
+        for kind in ["bearer", "basic", "header X-API-Key", "query api_key"] {
+            assert_eq!(kind.parse::<KeyKind>().unwrap().to_string(), kind);
+        }
+        assert_eq!(
+            "Header:X-Token".parse(),
+            Ok(KeyKind::Header(String::from("X-Token")))
+        );
+        assert!("header".parse::<KeyKind>().is_err());
+        assert!("cookie session".parse::<KeyKind>().is_err());
+    }
+
+    #[test]
+    fn test_migrate_keys() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute("CREATE TABLE keys (id INTEGER PRIMARY KEY, key TEXT);", [])
+            .unwrap();
+        conn.execute("INSERT INTO keys (key) VALUES ('old-key');", [])
+            .unwrap();
+        let db = DB::from_connection(conn).unwrap();
+        let key = db.get_keys().unwrap().remove(0);
+        assert_eq!(key.get_key(), "old-key");
+        assert_eq!(key.get_kind(), &KeyKind::Bearer);
+        assert_eq!(key.get_label(), format!("Key {}", key.get_id()));
+        assert_eq!((key.get_created(), key.get_expires()), (None, None));
+    }
+
+    #[test]
+    fn test_passphrase() {
+        let mut db = DB::new_in_memory().unwrap();
+        db.add_key("secret-token").unwrap();
+        let mut curl = Curl::new();
+        let url = "http://localhost/users?token=t-secret";
+        curl.set_url(url);
+        curl.set_auth(AuthKind::Basic(String::from("admin:hunter2")));
+        let json = serde_json::to_string(&curl).unwrap();
+        let command = curl.get_command_string();
+        db.add_command(&command, json.clone()).unwrap();
+        db.add_history(&HistoryEntry::new(url, "GET", json))
+            .unwrap();
+        let stored = |db: &DB| -> String {
+            db.conn
+                .query_row(
+                    "SELECT (SELECT group_concat(key) FROM keys) || (SELECT group_concat(command || curl_json) FROM commands) || (SELECT group_concat(url || curl_json) FROM history)",
+                    [],
+                    |row| row.get(0),
+                )
+                .unwrap()
+        };
+        assert!(!db.is_encrypted());
+        assert!(stored(&db).contains("hunter2"));
+
+        // encrypting an existing plaintext database
+        assert!(db.set_passphrase("").is_err());
+        db.set_passphrase("correct horse").unwrap();
+        assert!(db.is_encrypted() && !db.is_locked());
+        let raw = stored(&db);
+        assert!(!raw.contains("hunter2") && !raw.contains("secret-token"));
+        assert!(!raw.contains("t-secret"));
+        assert_eq!(db.get_keys().unwrap()[0].get_key(), "secret-token");
+        assert_eq!(db.get_commands().unwrap()[0].get_command(), command);
+        assert!(db.command_exists(&command).unwrap());
+        assert!(db.key_exists("secret-token").unwrap());
+
+        // a new session starts locked
+        db.set_vault(None);
+        assert!(db.is_locked());
+        assert_eq!(db.get_keys().unwrap()[0].get_key(), MASK);
+        assert!(db.get_commands().unwrap()[0].get_command().contains(MASK));
+        assert!(db.get_history("").unwrap()[0].get_url().contains(MASK));
+        assert!(db.add_key("another-token").is_err());
+        assert_eq!(
+            db.unlock("battery staple"),
+            Err(String::from(PASSPHRASE_ERROR))
+        );
+        db.unlock("correct horse").unwrap();
+        let history = db.get_history("").unwrap();
+        assert_eq!(history[0].get_url(), url);
+        let curl: Curl = serde_json::from_str(history[0].get_curl_json()).unwrap();
+        assert_eq!(
+            curl.get_auth(),
+            &AuthKind::Basic(String::from("admin:hunter2"))
+        );
+
+        // re-keying, then going back to plaintext
+        db.set_passphrase("battery staple").unwrap();
+        db.set_vault(None);
+        assert!(db.unlock("correct horse").is_err());
+        db.unlock("battery staple").unwrap();
+        db.remove_passphrase().unwrap();
+        assert!(!db.is_encrypted());
+        assert!(stored(&db).contains("hunter2") && stored(&db).contains("t-secret"));
+        assert_eq!(db.get_keys().unwrap()[0].get_key(), "secret-token");
+    }
+}
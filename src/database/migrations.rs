@@ -0,0 +1,264 @@
+/*
+* Migrations - the schema changes since the first release, in order. PRAGMA user_version
+* records how many of them a database has had, so each one only ever runs once.
+ */
+use rusqlite::{params, Connection, Result};
+
+use crate::display::menuopts::DB_VERSION_ERROR;
+
+type Migration = fn(&Connection) -> Result<()>;
+
+// Append new migrations to the end, never change or remove one that was released.
+// Databases from before we kept a version are at 0, but may already have some of the tables and
+// columns added by the first migrations, so those only create what's missing
+const MIGRATIONS: &[Migration] = &[
+    create_commands_and_keys,
+    add_collections,
+    add_history,
+    add_environments,
+    add_assertions,
+    add_history_responses,
+    add_vault,
+    add_key_metadata,
+    add_history_errors,
+];
+
+pub fn latest_version() -> usize {
+    MIGRATIONS.len()
+}
+
+pub fn get_version(conn: &Connection) -> Result<usize> {
+    conn.query_row("PRAGMA user_version", params![], |row| row.get(0))
+}
+
+// Brings the database up to the latest schema
+pub fn run(conn: &Connection) -> Result<()> {
+    run_migrations(conn, MIGRATIONS)
+}
+
+// All in one transaction, a failed migration leaves the database as it was
+fn run_migrations(conn: &Connection, migrations: &[Migration]) -> Result<()> {
+    let version = get_version(conn)?;
+    // a newer CuTE could have changed the schema in ways we don't understand
+    if version > migrations.len() {
+        return Err(rusqlite::Error::ToSqlConversionFailure(
+            String::from(DB_VERSION_ERROR).into(),
+        ));
+    }
+    let tx = conn.unchecked_transaction()?;
+    for (index, migration) in migrations.iter().enumerate().skip(version) {
+        migration(&tx)?;
+        tx.pragma_update(None, "user_version", index + 1)?;
+    }
+    tx.commit()
+}
+
+fn add_missing_columns(conn: &Connection, table: &str, columns: &[(&str, &str)]) -> Result<()> {
+    let mut stmt = conn.prepare(&format!("SELECT name FROM pragma_table_info('{}')", table))?;
+    let existing = stmt
+        .query_map(params![], |row| row.get::<_, String>(0))?
+        .collect::<Result<Vec<String>>>()?;
+    for (column, column_type) in columns {
+        if !existing.iter().any(|c| c == column) {
+            conn.execute(
+                &format!(
+                    "ALTER TABLE {} ADD COLUMN {} {};",
+                    table, column, column_type
+                ),
+                params![],
+            )?;
+        }
+    }
+    Ok(())
+}
+
+// 1: the original schema
+fn create_commands_and_keys(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS commands (id INTEGER PRIMARY KEY, command TEXT, curl_json TEXT);",
+        params![],
+    )?;
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS keys (id INTEGER PRIMARY KEY, key TEXT);",
+        params![],
+    )?;
+    Ok(())
+}
+
+// 2: folders of saved commands, and names/descriptions for the commands
+fn add_collections(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS collections (id INTEGER PRIMARY KEY, name TEXT, parent_id INTEGER);",
+        params![],
+    )?;
+    add_missing_columns(
+        conn,
+        "commands",
+        &[
+            ("name", "TEXT"),
+            ("description", "TEXT"),
+            ("collection_id", "INTEGER"),
+        ],
+    )
+}
+
+// 3: every request that was executed
+fn add_history(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS history (id INTEGER PRIMARY KEY, url TEXT, method TEXT, curl_json TEXT, status INTEGER, duration_ms INTEGER, size INTEGER, timestamp TEXT DEFAULT (datetime('now', 'localtime')), body TEXT);",
+        params![],
+    )?;
+    Ok(())
+}
+
+// 4: named sets of {{variables}}
+fn add_environments(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS environments (id INTEGER PRIMARY KEY, name TEXT UNIQUE, active INTEGER NOT NULL DEFAULT 0);",
+        params![],
+    )?;
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS env_vars (id INTEGER PRIMARY KEY, env_id INTEGER, key TEXT, value TEXT, UNIQUE(env_id, key));",
+        params![],
+    )?;
+    Ok(())
+}
+
+// 5: checks run against the response when a saved command is run as a test
+fn add_assertions(conn: &Connection) -> Result<()> {
+    add_missing_columns(conn, "commands", &[("assertions", "TEXT")])
+}
+
+// 6: the status line and headers of each response in the history
+fn add_history_responses(conn: &Connection) -> Result<()> {
+    add_missing_columns(conn, "history", &[("response", "TEXT")])
+}
+
+// 7: the salt and check value of the master passphrase, and the encrypted auth of requests
+fn add_vault(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS vault (id INTEGER PRIMARY KEY CHECK (id = 1), salt TEXT NOT NULL, check_value TEXT NOT NULL);",
+        params![],
+    )?;
+    add_missing_columns(conn, "commands", &[("secret", "TEXT")])?;
+    add_missing_columns(conn, "history", &[("secret", "TEXT")])
+}
+
+// 8: labels, hosts, types and dates of saved keys
+fn add_key_metadata(conn: &Connection) -> Result<()> {
+    add_missing_columns(
+        conn,
+        "keys",
+        &[
+            ("label", "TEXT"),
+            ("host", "TEXT"),
+            ("kind", "TEXT"),
+            ("created", "TEXT"),
+            ("expires", "TEXT"),
+        ],
+    )
+}
+
+// 9: why a request in the history failed
+fn add_history_errors(conn: &Connection) -> Result<()> {
+    add_missing_columns(conn, "history", &[("error", "TEXT")])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The schema of the first release, before there were any migrations
+    const V0_FIXTURE: &str = "
+        CREATE TABLE commands (id INTEGER PRIMARY KEY, command TEXT, curl_json TEXT);
+        CREATE TABLE keys (id INTEGER PRIMARY KEY, key TEXT);
+        INSERT INTO commands (command, curl_json) VALUES ('curl http://localhost/health', '{}');
+        INSERT INTO commands (command, curl_json) VALUES ('curl -X POST http://localhost/users', '{}');
+        INSERT INTO keys (key) VALUES ('secret-token');
+    ";
+
+    fn columns(conn: &Connection, table: &str) -> Vec<String> {
+        let mut stmt = conn
+            .prepare(&format!("SELECT name FROM pragma_table_info('{}')", table))
+            .unwrap();
+        stmt.query_map(params![], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<Vec<String>>>()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_upgrade_v0() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(V0_FIXTURE).unwrap();
+        assert_eq!(get_version(&conn).unwrap(), 0);
+
+        run(&conn).unwrap();
+        assert_eq!(get_version(&conn).unwrap(), latest_version());
+        for column in ["name", "collection_id", "assertions", "secret"] {
+            assert!(columns(&conn, "commands").contains(&column.to_string()));
+        }
+        assert!(columns(&conn, "keys").contains(&String::from("expires")));
+        assert!(columns(&conn, "history").contains(&String::from("response")));
+        assert!(columns(&conn, "history").contains(&String::from("error")));
+        let commands: Vec<(i32, String)> = conn
+            .prepare("SELECT id, command FROM commands ORDER BY id")
+            .unwrap()
+            .query_map(params![], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+            .collect::<Result<_>>()
+            .unwrap();
+        assert_eq!(
+            commands,
+            vec![
+                (1, String::from("curl http://localhost/health")),
+                (2, String::from("curl -X POST http://localhost/users")),
+            ]
+        );
+        let key: String = conn
+            .query_row("SELECT key FROM keys", params![], |row| row.get(0))
+            .unwrap();
+        assert_eq!(key, "secret-token");
+
+        // nothing left to do the second time
+        run(&conn).unwrap();
+        assert_eq!(get_version(&conn).unwrap(), latest_version());
+    }
+
+    #[test]
+    fn test_unversioned_with_new_columns() {
+        // databases from before versioning got their columns added on startup
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(V0_FIXTURE).unwrap();
+        conn.execute("ALTER TABLE commands ADD COLUMN name TEXT;", params![])
+            .unwrap();
+        conn.execute(
+            "CREATE TABLE collections (id INTEGER PRIMARY KEY, name TEXT, parent_id INTEGER);",
+            params![],
+        )
+        .unwrap();
+        run(&conn).unwrap();
+        assert_eq!(get_version(&conn).unwrap(), latest_version());
+    }
+
+    #[test]
+    fn test_failed_migration_rolls_back() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(V0_FIXTURE).unwrap();
+        let migrations: &[Migration] = &[create_commands_and_keys, add_collections, |conn| {
+            conn.execute("ALTER TABLE missing ADD COLUMN broken TEXT;", params![])
+                .map(|_| ())
+        }];
+        assert!(run_migrations(&conn, migrations).is_err());
+        assert_eq!(get_version(&conn).unwrap(), 0);
+        assert!(!columns(&conn, "commands").contains(&String::from("name")));
+    }
+
+    #[test]
+    fn test_newer_version() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.pragma_update(None, "user_version", latest_version() + 1)
+            .unwrap();
+        assert!(run(&conn).is_err());
+    }
+}
@@ -1 +1,13 @@
 pub mod db;
+
+// Versioned schema migrations, run when the database is opened
+pub mod migrations;
+
+// Collections of saved commands shown as a tree
+pub mod tree;
+
+// Portable JSON/TOML bundles of the whole database
+pub mod bundle;
+
+// Encryption of API keys and saved request auth with the master passphrase
+pub mod vault;
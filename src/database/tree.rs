@@ -0,0 +1,335 @@
+/*
+* Saved commands organized into a tree of collections, flattened into the rows
+* of the SavedCommands screen.
+ */
+use std::collections::HashSet;
+
+use super::db::{Collection, SavedCommand};
+
+const INDENT: &str = "  ";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TreeRow {
+    Collection {
+        id: i32,
+        depth: usize,
+        name: String,
+        expanded: bool,
+    },
+    // index is the position of the command in DB::get_commands()
+    Command {
+        index: usize,
+        depth: usize,
+        label: String,
+    },
+}
+
+impl TreeRow {
+    pub fn get_label(&self) -> String {
+        match self {
+            TreeRow::Collection {
+                depth,
+                name,
+                expanded,
+                ..
+            } => {
+                let arrow = if *expanded { "▾" } else { "▸" };
+                format!("{}{} {}/", INDENT.repeat(*depth), arrow, name)
+            }
+            TreeRow::Command { depth, label, .. } => {
+                format!("{}  {}", INDENT.repeat(*depth), label)
+            }
+        }
+    }
+}
+
+// Collections or commands whose parent no longer exists are shown at the top level
+fn parent_of(collections: &[Collection], parent_id: Option<i32>) -> Option<i32> {
+    parent_id.filter(|id| collections.iter().any(|c| c.get_id() == *id))
+}
+
+// Collections first (sorted by name), then commands, only descending into expanded collections
+pub fn build_tree(
+    collections: &[Collection],
+    commands: &[SavedCommand],
+    expanded: &HashSet<i32>,
+) -> Vec<TreeRow> {
+    let mut rows = Vec::new();
+    add_level(collections, commands, expanded, None, 0, &mut rows);
+    rows
+}
+
+fn add_level(
+    collections: &[Collection],
+    commands: &[SavedCommand],
+    expanded: &HashSet<i32>,
+    parent: Option<i32>,
+    depth: usize,
+    rows: &mut Vec<TreeRow>,
+) {
+    let mut children: Vec<&Collection> = collections
+        .iter()
+        .filter(|c| parent_of(collections, c.get_parent_id()) == parent)
+        .collect();
+    children.sort_by_key(|c| c.get_name().to_lowercase());
+    for collection in children {
+        let is_expanded = expanded.contains(&collection.get_id());
+        rows.push(TreeRow::Collection {
+            id: collection.get_id(),
+            depth,
+            name: collection.get_name().to_string(),
+            expanded: is_expanded,
+        });
+        if is_expanded {
+            add_level(
+                collections,
+                commands,
+                expanded,
+                Some(collection.get_id()),
+                depth + 1,
+                rows,
+            );
+        }
+    }
+    for (index, command) in commands.iter().enumerate() {
+        if parent_of(collections, command.get_collection_id()) == parent {
+            let label = match command.get_description() {
+                Some(description) => format!("{} - {}", command.get_label(), description),
+                None => command.get_label().to_string(),
+            };
+            rows.push(TreeRow::Command {
+                index,
+                depth,
+                label,
+            });
+        }
+    }
+}
+
+// "parent/child" for showing where a collection lives
+pub fn collection_path(collections: &[Collection], id: i32) -> String {
+    let mut names = Vec::new();
+    let mut current = Some(id);
+    // the depth check guards against a cycle in a hand edited database
+    while let Some(collection) = current
+        .and_then(|id| collections.iter().find(|c| c.get_id() == id))
+        .filter(|_| names.len() <= collections.len())
+    {
+        names.push(collection.get_name());
+        current = collection.get_parent_id();
+    }
+    names.reverse();
+    names.join("/")
+}
+
+// Whether the collection `id` is `ancestor` or somewhere inside it
+pub fn is_within(collections: &[Collection], id: i32, ancestor: i32) -> bool {
+    let mut current = Some(id);
+    let mut steps = 0;
+    while let Some(id) = current {
+        if id == ancestor {
+            return true;
+        }
+        steps += 1;
+        if steps > collections.len() {
+            return false;
+        }
+        current = collections
+            .iter()
+            .find(|c| c.get_id() == id)
+            .and_then(|c| c.get_parent_id());
+    }
+    false
+}
+
+// The commands in a collection and every collection nested inside it, in the order
+// the tree shows them. None means every saved command
+pub fn collection_commands(
+    collections: &[Collection],
+    commands: &[SavedCommand],
+    id: Option<i32>,
+) -> Vec<SavedCommand> {
+    let expanded = collections.iter().map(|c| c.get_id()).collect();
+    build_tree(collections, commands, &expanded)
+        .into_iter()
+        .filter_map(|row| match row {
+            TreeRow::Command { index, .. } => commands.get(index),
+            TreeRow::Collection { .. } => None,
+        })
+        .filter(|command| match id {
+            Some(id) => parent_of(collections, command.get_collection_id())
+                .is_some_and(|parent| is_within(collections, parent, id)),
+            None => true,
+        })
+        .cloned()
+        .collect()
+}
+
+// Looks up a collection by its ID, its path ("users/auth") or its name
+pub fn find_collection(collections: &[Collection], target: &str) -> Option<Collection> {
+    let target = target.trim().trim_matches('/');
+    collections
+        .iter()
+        .find(|c| target.parse::<i32>() == Ok(c.get_id()))
+        .or_else(|| {
+            collections
+                .iter()
+                .find(|c| collection_path(collections, c.get_id()) == target)
+        })
+        .or_else(|| collections.iter().find(|c| c.get_name() == target))
+        .cloned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::db::DB;
+
+    fn setup() -> DB {
+        let db = DB::new_in_memory().unwrap();
+        db.add_collection("users", None).unwrap();
+        db.add_collection("admin", None).unwrap();
+        let users = db.get_collections().unwrap()[1].get_id();
+        db.add_collection("auth", Some(users)).unwrap();
+        db.add_command("curl http://localhost/users", String::from("{}"))
+            .unwrap();
+        db.add_command("curl http://localhost/login", String::from("{}"))
+            .unwrap();
+        db.add_command("curl http://localhost/health", String::from("{}"))
+            .unwrap();
+        let auth = db
+            .get_collections()
+            .unwrap()
+            .into_iter()
+            .find(|c| c.get_name() == "auth")
+            .unwrap()
+            .get_id();
+        db.move_command(1, Some(users)).unwrap();
+        db.move_command(2, Some(auth)).unwrap();
+        db.rename_command(2, Some("Login")).unwrap();
+        db.set_command_description(2, Some("get a token")).unwrap();
+        db
+    }
+
+    #[test]
+    fn test_build_tree() {
+        let db = setup();
+        let collections = db.get_collections().unwrap();
+        let commands = db.get_commands().unwrap();
+        let labels = |expanded: &HashSet<i32>| {
+            build_tree(&collections, &commands, expanded)
+                .iter()
+                .map(|row| row.get_label())
+                .collect::<Vec<String>>()
+        };
+        assert_eq!(
+            labels(&HashSet::new()),
+            vec!["▸ admin/", "▸ users/", "  curl http://localhost/health"]
+        );
+        let expanded = collections.iter().map(|c| c.get_id()).collect();
+        assert_eq!(
+            labels(&expanded),
+            vec![
+                "▾ admin/",
+                "▾ users/",
+                "  ▾ auth/",
+                "      Login - get a token",
+                "    curl http://localhost/users",
+                "  curl http://localhost/health",
+            ]
+        );
+        // command rows point back into the list of saved commands
+        let rows = build_tree(&collections, &commands, &expanded);
+        assert_eq!(
+            rows[3],
+            TreeRow::Command {
+                index: 1,
+                depth: 2,
+                label: String::from("Login - get a token"),
+            }
+        );
+    }
+
+    #[test]
+    fn test_collection_paths() {
+        let db = setup();
+        let collections = db.get_collections().unwrap();
+        let id = |name: &str| {
+            collections
+                .iter()
+                .find(|c| c.get_name() == name)
+                .unwrap()
+                .get_id()
+        };
+        assert_eq!(collection_path(&collections, id("auth")), "users/auth");
+        assert!(is_within(&collections, id("auth"), id("users")));
+        assert!(is_within(&collections, id("users"), id("users")));
+        assert!(!is_within(&collections, id("users"), id("auth")));
+        assert!(!is_within(&collections, id("admin"), id("users")));
+    }
+
+    #[test]
+    fn test_collection_commands() {
+        let db = setup();
+        let collections = db.get_collections().unwrap();
+        let commands = db.get_commands().unwrap();
+        let ids = |id: Option<i32>| {
+            collection_commands(&collections, &commands, id)
+                .iter()
+                .map(|c| c.get_id())
+                .collect::<Vec<i32>>()
+        };
+        let users = find_collection(&collections, "users").unwrap();
+        let auth = find_collection(&collections, "users/auth").unwrap();
+        assert_eq!(
+            find_collection(&collections, &auth.get_id().to_string()),
+            Some(auth.clone())
+        );
+        assert_eq!(find_collection(&collections, "nope"), None);
+        assert_eq!(ids(Some(users.get_id())), vec![2, 1]);
+        assert_eq!(ids(Some(auth.get_id())), vec![2]);
+        assert_eq!(ids(None), vec![2, 1, 3]);
+    }
+
+    #[test]
+    fn test_delete_collection() {
+        let db = setup();
+        let users = db
+            .get_collections()
+            .unwrap()
+            .into_iter()
+            .find(|c| c.get_name() == "users")
+            .unwrap()
+            .get_id();
+        db.delete_collection(users).unwrap();
+        let collections = db.get_collections().unwrap();
+        // auth and the users command move up to the top level
+        assert_eq!(collections.len(), 2);
+        assert!(collections.iter().all(|c| c.get_parent_id().is_none()));
+        assert_eq!(db.get_commands().unwrap()[0].get_collection_id(), None);
+        assert_eq!(
+            db.get_command_by_id(2).unwrap().unwrap().get_name(),
+            Some("Login")
+        );
+    }
+
+    #[test]
+    fn test_migrate_commands() {
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute(
+            "CREATE TABLE commands (id INTEGER PRIMARY KEY, command TEXT, curl_json TEXT);",
+            [],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO commands (command, curl_json) VALUES ('curl http://old', '{}');",
+            [],
+        )
+        .unwrap();
+        let db = DB::from_connection(conn).unwrap();
+        let commands = db.get_commands().unwrap();
+        assert_eq!(commands.len(), 1);
+        assert_eq!(commands[0].get_label(), "curl http://old");
+        assert_eq!(commands[0].get_collection_id(), None);
+    }
+}
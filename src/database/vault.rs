@@ -0,0 +1,401 @@
+/*
+* Vault - encrypts API keys and the auth of saved requests with a key derived from the
+* master passphrase (Argon2id), using XChaCha20-Poly1305.
+ */
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use serde_json::Value;
+
+use crate::request::command::CmdOpts;
+use crate::request::curl::Curl;
+
+// Everything we encrypt is stored as "enc:v1:<hex nonce + ciphertext>"
+const PREFIX: &str = "enc:v1:";
+const NONCE_LEN: usize = 24;
+pub const SALT_LEN: usize = 16;
+// Encrypted next to the salt, so a wrong passphrase is caught before anything is decrypted
+const CHECK_VALUE: &str = "CuTE";
+// Takes the place of the secret in saved requests, until the vault is unlocked
+pub const MASK: &str = "********";
+// Headers whose values are credentials, matched ignoring case
+const SECRET_HEADERS: [&str; 5] = [
+    "authorization",
+    "proxy-authorization",
+    "cookie",
+    "x-api-key",
+    "x-auth-token",
+];
+// Query parameters that carry credentials, e.g. ?api_key=...
+const SECRET_PARAMS: [&str; 8] = [
+    "access_token",
+    "api_key",
+    "apikey",
+    "client_secret",
+    "key",
+    "password",
+    "signature",
+    "token",
+];
+
+#[derive(Clone)]
+pub struct Vault {
+    key: [u8; 32],
+}
+
+// Never print the key
+impl std::fmt::Debug for Vault {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Vault")
+    }
+}
+
+impl Vault {
+    pub fn derive(passphrase: &str, salt: &[u8]) -> Result<Vault, String> {
+        let mut key = [0u8; 32];
+        argon2()
+            .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+            .map_err(|e| format!("Error: unable to derive the key: {}", e))?;
+        Ok(Vault { key })
+    }
+
+    pub fn new_salt() -> Vec<u8> {
+        // a nonce is just random bytes, long enough for a salt
+        XChaCha20Poly1305::generate_nonce(&mut OsRng)[..SALT_LEN].to_vec()
+    }
+
+    // The value stored with the salt, to check the passphrase with
+    pub fn check_value(&self) -> String {
+        self.encrypt(CHECK_VALUE)
+    }
+
+    pub fn verify(&self, check_value: &str) -> bool {
+        self.decrypt(check_value)
+            .is_ok_and(|value| value == CHECK_VALUE)
+    }
+
+    pub fn encrypt(&self, plaintext: &str) -> String {
+        let cipher = XChaCha20Poly1305::new(&self.key.into());
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        // only fails if the plaintext is larger than the cipher allows (256GB)
+        let ciphertext = cipher
+            .encrypt(&nonce, plaintext.as_bytes())
+            .expect("Failed to encrypt");
+        format!("{}{}{}", PREFIX, to_hex(&nonce), to_hex(&ciphertext))
+    }
+
+    pub fn decrypt(&self, text: &str) -> Result<String, String> {
+        let bytes = text
+            .strip_prefix(PREFIX)
+            .and_then(from_hex)
+            .filter(|bytes| bytes.len() > NONCE_LEN)
+            .ok_or(String::from("Error: the encrypted value is corrupt"))?;
+        let (nonce, ciphertext) = bytes.split_at(NONCE_LEN);
+        let plaintext = XChaCha20Poly1305::new(&self.key.into())
+            .decrypt(XNonce::from_slice(nonce), ciphertext)
+            .map_err(|_| String::from("Error: unable to decrypt, wrong passphrase?"))?;
+        String::from_utf8(plaintext).map_err(|e| e.to_string())
+    }
+}
+
+// Argon2id with the recommended defaults, tests use the lowest cost so they stay fast
+fn argon2() -> Argon2<'static> {
+    if cfg!(test) {
+        let params = Params::new(Params::MIN_M_COST, Params::MIN_T_COST, 1, None)
+            .expect("Invalid Argon2 parameters");
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+    } else {
+        Argon2::default()
+    }
+}
+
+pub fn is_encrypted(text: &str) -> bool {
+    text.starts_with(PREFIX)
+}
+
+pub fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+pub fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+// A {{variable}} or {{key:<id>}} reference is filled in when the request runs, so there's nothing
+// to hide
+fn is_secret(value: &str) -> bool {
+    !value.is_empty() && value != MASK && !value.contains("{{")
+}
+
+// The token or user:password of the auth, the NTLM login and the values of credential headers
+fn request_secrets(curl: &Curl) -> Vec<String> {
+    let headers = curl.get_headers().into_iter().filter_map(|header| {
+        let (name, value) = header.split_once(':')?;
+        SECRET_HEADERS
+            .iter()
+            .any(|secret| secret.eq_ignore_ascii_case(name.trim()))
+            .then(|| value.trim().to_string())
+    });
+    let mut secrets: Vec<String> = curl
+        .get_auth()
+        .get_token()
+        .into_iter()
+        .chain(curl.get_ntlm_login())
+        .chain(headers)
+        .filter(|secret| is_secret(secret))
+        .collect();
+    // `Bearer abc` before `abc`, so the whole header value is masked
+    secrets.sort_by_key(|secret| std::cmp::Reverse(secret.len()));
+    secrets
+}
+
+// The url with the values of its credential query parameters masked, None if it has none.
+// Masked in place, a short value like `?key=1` would mask every `1` in the request otherwise
+fn mask_url(url: &str) -> Option<String> {
+    let (base, query) = url.split_once('?')?;
+    let (query, fragment) = match query.split_once('#') {
+        Some((query, fragment)) => (query, format!("#{}", fragment)),
+        None => (query, String::new()),
+    };
+    let mut masked = false;
+    let query = query
+        .split('&')
+        .map(|pair| match pair.split_once('=') {
+            Some((name, value))
+                if is_secret(value)
+                    && SECRET_PARAMS
+                        .iter()
+                        .any(|secret| secret.eq_ignore_ascii_case(name)) =>
+            {
+                masked = true;
+                format!("{}={}", name, MASK)
+            }
+            _ => pair.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("&");
+    masked.then(|| format!("{}?{}{}", base, query, fragment))
+}
+
+fn replace_strings(value: &mut Value, from: &str, to: &str) {
+    match value {
+        Value::String(s) if s.contains(from) => *s = s.replace(from, to),
+        Value::Array(values) => values
+            .iter_mut()
+            .for_each(|value| replace_strings(value, from, to)),
+        Value::Object(map) => map
+            .values_mut()
+            .for_each(|value| replace_strings(value, from, to)),
+        _ => {}
+    }
+}
+
+// Masks the secrets of a serialized request, false if it has none
+fn mask_request(json: &mut Value) -> bool {
+    let serialized = json.to_string();
+    let Ok(curl) = serde_json::from_str::<Curl>(&serialized) else {
+        return false;
+    };
+    let secrets = request_secrets(&curl);
+    secrets
+        .iter()
+        .for_each(|secret| replace_strings(json, secret, MASK));
+    match json.get("url").and_then(Value::as_str).and_then(mask_url) {
+        Some(url) => json["url"] = Value::String(url),
+        None => return !secrets.is_empty(),
+    }
+    true
+}
+
+// The command string is built again from the request, replacing the secret in the old one would
+// miss it wherever it was quoted, e.g. `'it'\''s'`
+fn rebuild_request(json: &Value) -> Option<(String, String)> {
+    let json = json.to_string();
+    let mut curl: Curl = serde_json::from_str(&json).ok()?;
+    let command = curl.get_command_string();
+    Some((command, serde_json::to_string(&curl).ok()?))
+}
+
+pub fn has_secret(curl_json: &str) -> bool {
+    serde_json::from_str::<Value>(curl_json).is_ok_and(|mut json| mask_request(&mut json))
+}
+
+/// Takes the secrets out of a request: the auth, credential headers and query parameters are
+/// replaced with `MASK`. Returns its command string and serialized curl, with the request as it
+/// was encrypted. None if there was nothing to hide (or no vault to encrypt it with).
+pub fn seal_request(
+    vault: Option<&Vault>,
+    command: &str,
+    curl_json: &str,
+) -> (String, String, Option<String>) {
+    let unchanged = || (command.to_string(), curl_json.to_string(), None);
+    let Some(vault) = vault else {
+        return unchanged();
+    };
+    let Ok(mut json) = serde_json::from_str::<Value>(curl_json) else {
+        return unchanged();
+    };
+    let mut original = json.clone();
+    if !mask_request(&mut json) {
+        return unchanged();
+    }
+    let Some((command, curl_json)) = rebuild_request(&json) else {
+        return unchanged();
+    };
+    // the response is kept as it is, so there's no need to encrypt it too
+    if let Some(original) = original.as_object_mut() {
+        original.remove("resp");
+    }
+    (
+        command,
+        curl_json,
+        Some(vault.encrypt(&original.to_string())),
+    )
+}
+
+/// Puts the secrets of a sealed request back, returning its command string and serialized curl.
+pub fn open_request(
+    vault: &Vault,
+    curl_json: &str,
+    secret: &str,
+) -> Result<(String, String), String> {
+    let corrupt = || String::from("Error: the saved request is corrupt");
+    let json = serde_json::from_str::<Value>(curl_json).map_err(|e| e.to_string())?;
+    let Ok(Value::Object(mut original)) = serde_json::from_str::<Value>(&vault.decrypt(secret)?)
+    else {
+        return Err(corrupt());
+    };
+    if let Some(resp) = json.get("resp") {
+        original.insert(String::from("resp"), resp.clone());
+    }
+    rebuild_request(&Value::Object(original)).ok_or_else(corrupt)
+}
+
+// The url of a history entry with the credentials in its query masked, like its request
+pub fn seal_url(vault: Option<&Vault>, url: &str) -> String {
+    match vault {
+        Some(_) => mask_url(url).unwrap_or(url.to_string()),
+        None => url.to_string(),
+    }
+}
+
+// The url of an opened request, for the history entry it was masked in
+pub fn request_url(curl_json: &str) -> Option<String> {
+    serde_json::from_str::<Value>(curl_json)
+        .ok()?
+        .get("url")?
+        .as_str()
+        .map(String::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::request::command::CurlOpts;
+    use crate::request::curl::AuthKind;
+
+    #[test]
+    fn test_vault() {
+        let salt = Vault::new_salt();
+        assert_eq!(salt.len(), SALT_LEN);
+        let vault = Vault::derive("correct horse", &salt).unwrap();
+        let encrypted = vault.encrypt("secret-token");
+        assert!(is_encrypted(&encrypted));
+        assert!(!encrypted.contains("secret-token"));
+        // a new nonce every time
+        assert_ne!(encrypted, vault.encrypt("secret-token"));
+        assert_eq!(vault.decrypt(&encrypted).unwrap(), "secret-token");
+        assert!(vault.verify(&vault.check_value()));
+
+        let wrong = Vault::derive("battery staple", &salt).unwrap();
+        assert!(wrong.decrypt(&encrypted).is_err());
+        assert!(!wrong.verify(&vault.check_value()));
+        assert!(vault.decrypt("enc:v1:zz").is_err());
+        assert_eq!(from_hex(&to_hex(&[0, 15, 255])), Some(vec![0, 15, 255]));
+    }
+
+    #[test]
+    fn test_seal_request() {
+        let vault = Vault::derive("correct horse", &Vault::new_salt()).unwrap();
+        let mut curl = Curl::new();
+        curl.set_url("http://localhost/users");
+        curl.set_auth(AuthKind::Bearer(String::from("abc123")));
+        let command = curl.get_command_string();
+        let json = serde_json::to_string(&curl).unwrap();
+
+        assert!(has_secret(&json));
+        let (sealed_command, sealed_json, secret) = seal_request(Some(&vault), &command, &json);
+        assert!(!sealed_command.contains("abc123") && sealed_command.contains(MASK));
+        assert!(!sealed_json.contains("abc123"));
+        let secret = secret.unwrap();
+        let (opened_command, opened_json) = open_request(&vault, &sealed_json, &secret).unwrap();
+        assert_eq!(opened_command, command);
+        let opened: Curl = serde_json::from_str(&opened_json).unwrap();
+        assert_eq!(opened.get_auth(), &AuthKind::Bearer(String::from("abc123")));
+
+        // nothing to hide without auth
+        let mut curl = Curl::new();
+        curl.set_url("http://localhost/health");
+        let json = serde_json::to_string(&curl).unwrap();
+        assert!(!has_secret(&json));
+        let (_, unchanged, secret) =
+            seal_request(Some(&vault), "curl http://localhost/health", &json);
+        assert_eq!((unchanged, secret), (json, None));
+
+        // a saved key reference isn't a secret
+        curl.set_auth(AuthKind::Bearer(String::from("{{key:1}}")));
+        assert!(!has_secret(&serde_json::to_string(&curl).unwrap()));
+    }
+
+    #[test]
+    fn test_seal_request_everywhere() {
+        let vault = Vault::derive("correct horse", &Vault::new_salt()).unwrap();
+        let mut curl = Curl::new();
+        curl.set_url("http://localhost/users?page=2&api_key=q-secret#top");
+        // quoted as 'admin:it'\''s-secret' in the command string
+        curl.set_auth(AuthKind::Basic(String::from("admin:it's-secret")));
+        curl.add_headers(String::from("X-Api-Key: h-secret"));
+        curl.add_headers(String::from("Accept: application/json"));
+        let command = curl.get_command_string();
+        let json = serde_json::to_string(&curl).unwrap();
+        assert!(command.contains(r"'admin:it'\''s-secret'"));
+
+        let (sealed_command, sealed_json, secret) = seal_request(Some(&vault), &command, &json);
+        for sealed in [&sealed_command, &sealed_json] {
+            assert!(!sealed.contains("secret") && !sealed.contains("admin"));
+            assert!(sealed.contains("Accept: application/json") && sealed.contains("page=2"));
+        }
+        assert_eq!(
+            seal_url(
+                Some(&vault),
+                "http://localhost/users?page=2&api_key=q-secret#top"
+            ),
+            format!("http://localhost/users?page=2&api_key={}#top", MASK)
+        );
+        assert!(!has_secret(&sealed_json));
+        let (opened_command, opened_json) =
+            open_request(&vault, &sealed_json, &secret.unwrap()).unwrap();
+        assert_eq!(opened_command, command);
+        let opened: Curl = serde_json::from_str(&opened_json).unwrap();
+        assert_eq!(opened.get_url(), curl.get_url());
+        assert_eq!(opened.get_headers(), curl.get_headers());
+        assert_eq!(request_url(&opened_json), Some(curl.get_url()));
+
+        // the NTLM login is kept on its flag
+        let mut curl = Curl::new();
+        curl.set_url("http://localhost/");
+        curl.set_ntlm_auth(Some(String::from("DOMAIN\\admin:n-secret")));
+        let json = serde_json::to_string(&curl).unwrap();
+        let (command, sealed_json, secret) = seal_request(Some(&vault), "", &json);
+        assert!(!command.contains("n-secret") && !sealed_json.contains("n-secret"));
+        let (_, opened_json) = open_request(&vault, &sealed_json, &secret.unwrap()).unwrap();
+        let opened: Curl = serde_json::from_str(&opened_json).unwrap();
+        assert_eq!(opened.get_ntlm_login(), curl.get_ntlm_login());
+    }
+}
@@ -0,0 +1,191 @@
+use crate::request::diff::{diff_responses, Change, DiffMode, DiffRow};
+use crate::request::response::Response;
+
+/*
+* Diff View - The scroll position and mode of two responses shown side by side
+ */
+#[derive(Debug, Clone)]
+pub struct DiffViewer {
+    // what each side is, e.g. the history entry it came from
+    labels: (String, String),
+    left: Response,
+    right: Response,
+    mode: DiffMode,
+    rows: Vec<DiffRow>,
+    // the JSON diff of a body that isn't JSON
+    error: Option<String>,
+    // the first visible row
+    scroll: usize,
+    // rows that fit on the screen, set every time we render
+    height: usize,
+}
+
+impl DiffViewer {
+    pub fn new(left: (String, Response), right: (String, Response)) -> Self {
+        let mut viewer = DiffViewer {
+            labels: (left.0, right.0),
+            left: left.1,
+            right: right.1,
+            mode: DiffMode::default(),
+            rows: Vec::new(),
+            error: None,
+            scroll: 0,
+            height: 1,
+        };
+        viewer.set_mode(DiffMode::default());
+        viewer
+    }
+
+    pub fn set_mode(&mut self, mode: DiffMode) {
+        self.mode = mode;
+        self.scroll = 0;
+        match diff_responses(&self.left, &self.right, mode) {
+            Ok(rows) => {
+                self.rows = rows;
+                self.error = None;
+            }
+            Err(e) => {
+                self.rows = Vec::new();
+                self.error = Some(e);
+            }
+        }
+    }
+
+    // m cycles through the body, JSON and header diffs
+    pub fn next_mode(&mut self) {
+        self.set_mode(self.mode.next());
+    }
+
+    pub fn get_mode(&self) -> DiffMode {
+        self.mode
+    }
+
+    pub fn get_labels(&self) -> (&str, &str) {
+        (&self.labels.0, &self.labels.1)
+    }
+
+    pub fn get_rows(&self) -> &[DiffRow] {
+        &self.rows
+    }
+
+    pub fn get_error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    pub fn get_scroll(&self) -> usize {
+        self.scroll
+    }
+
+    pub fn change_count(&self) -> usize {
+        self.rows
+            .iter()
+            .filter(|row| row.change != Change::Same)
+            .count()
+    }
+
+    pub fn set_height(&mut self, height: usize) {
+        self.height = height.max(1);
+        self.scroll_to(self.scroll);
+    }
+
+    fn scroll_to(&mut self, row: usize) {
+        self.scroll = row.min(self.rows.len().saturating_sub(self.height));
+    }
+
+    // j/k
+    pub fn down(&mut self, rows: usize) {
+        self.scroll_to(self.scroll.saturating_add(rows));
+    }
+
+    pub fn up(&mut self, rows: usize) {
+        self.scroll_to(self.scroll.saturating_sub(rows));
+    }
+
+    pub fn half_page_down(&mut self) {
+        self.down((self.height / 2).max(1));
+    }
+
+    pub fn half_page_up(&mut self) {
+        self.up((self.height / 2).max(1));
+    }
+
+    pub fn top(&mut self) {
+        self.scroll_to(0);
+    }
+
+    pub fn bottom(&mut self) {
+        self.scroll_to(usize::MAX);
+    }
+
+    // n/N scroll the next or previous run of changed lines to the top, both wrap around
+    pub fn next_change(&mut self, reverse: bool) {
+        let starts: Vec<usize> = (0..self.rows.len())
+            .filter(|i| {
+                self.rows[*i].change != Change::Same
+                    && (*i == 0 || self.rows[i - 1].change == Change::Same)
+            })
+            .collect();
+        let next = match reverse {
+            false => starts
+                .iter()
+                .find(|start| **start > self.scroll)
+                .or(starts.first()),
+            true => starts
+                .iter()
+                .rev()
+                .find(|start| **start < self.scroll)
+                .or(starts.last()),
+        };
+        if let Some(start) = next {
+            self.scroll_to(*start);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(body: &str) -> Response {
+        Response {
+            body: body.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_diff_viewer() {
+        let left = (1..=30).map(|n| n.to_string()).collect::<Vec<_>>();
+        let mut right = left.clone();
+        right[4] = String::from("five");
+        right[19] = String::from("twenty");
+        let mut viewer = DiffViewer::new(
+            (String::from("#1"), response(&left.join("\n"))),
+            (String::from("#2"), response(&right.join("\n"))),
+        );
+        viewer.set_height(10);
+        assert_eq!(viewer.change_count(), 2);
+        viewer.next_change(false);
+        assert_eq!(viewer.get_scroll(), 4);
+        viewer.next_change(false);
+        assert_eq!(viewer.get_scroll(), 19);
+        viewer.next_change(false);
+        assert_eq!(viewer.get_scroll(), 4);
+        viewer.next_change(true);
+        assert_eq!(viewer.get_scroll(), 19);
+        viewer.bottom();
+        assert_eq!(viewer.get_scroll(), 20);
+        viewer.half_page_up();
+        assert_eq!(viewer.get_scroll(), 15);
+
+        // neither body is JSON
+        viewer.next_mode();
+        assert_eq!(viewer.get_mode(), DiffMode::Json);
+        assert!(viewer.get_error().is_some());
+        assert!(viewer.get_rows().is_empty());
+        viewer.next_mode();
+        assert_eq!(viewer.get_mode(), DiffMode::Headers);
+        assert_eq!(viewer.change_count(), 0);
+        assert_eq!(viewer.get_labels(), ("#1", "#2"));
+    }
+}
@@ -0,0 +1,450 @@
+use tui::style::{Color, Modifier, Style};
+use tui::text::{Line, Span};
+
+use crate::Config;
+
+/*
+* Highlight - Colors response bodies line by line with the colors from the config
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Syntax {
+    Json,
+    Xml,
+    Html,
+    Yaml,
+    // `key=value&...` bodies, or the `key = value` lines they're pretty printed to
+    Form,
+    // status lines and `Name: value` headers
+    Headers,
+    Plain,
+}
+
+impl Syntax {
+    // The Content-Type wins, without one we go by what the body looks like
+    pub fn detect(content_type: Option<&str>, body: &str) -> Syntax {
+        if let Some(content_type) = content_type.map(|c| c.to_lowercase()) {
+            if content_type.contains("json") {
+                return Syntax::Json;
+            } else if content_type.contains("html") {
+                return Syntax::Html;
+            } else if content_type.contains("xml") {
+                return Syntax::Xml;
+            } else if content_type.contains("yaml") || content_type.contains("yml") {
+                return Syntax::Yaml;
+            } else if content_type.contains("x-www-form-urlencoded") {
+                return Syntax::Form;
+            }
+        }
+        let trimmed = body.trim_start();
+        if (trimmed.starts_with('{') || trimmed.starts_with('['))
+            && serde_json::from_str::<serde_json::Value>(body).is_ok()
+        {
+            Syntax::Json
+        } else if trimmed.len() >= 14 && trimmed[..14].eq_ignore_ascii_case("<!doctype html")
+            || trimmed.starts_with("<html")
+        {
+            Syntax::Html
+        } else if trimmed.starts_with('<') {
+            Syntax::Xml
+        } else {
+            Syntax::Plain
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Palette {
+    pub text: Style,
+    pub key: Style,
+    pub string: Style,
+    pub number: Style,
+    pub comment: Style,
+    // search hits, and the one the cursor is on
+    pub hit: Style,
+    pub current_hit: Style,
+}
+
+impl Palette {
+    pub fn from_config(config: &Config) -> Self {
+        let text = config.get_style();
+        Palette {
+            text,
+            key: text.fg(config.get_key_color()),
+            string: text.fg(config.get_string_color()),
+            number: text.fg(config.get_number_color()),
+            comment: text.fg(Color::DarkGray).add_modifier(Modifier::ITALIC),
+            hit: Style::default()
+                .fg(config.get_bg_color())
+                .bg(config.get_body_color()),
+            current_hit: Style::default()
+                .fg(config.get_bg_color())
+                .bg(config.get_outline_color())
+                .add_modifier(Modifier::BOLD),
+        }
+    }
+}
+
+// Where the markup tokenizer is when a line ends, tags and comments can span lines
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Markup {
+    Text,
+    Tag,
+    Value(char),
+    Comment,
+}
+
+pub fn highlight_lines(lines: &[String], syntax: Syntax, palette: &Palette) -> Vec<Line<'static>> {
+    let mut state = Markup::Text;
+    lines
+        .iter()
+        .map(|line| match syntax {
+            Syntax::Json => highlight_json(line, palette),
+            Syntax::Xml | Syntax::Html => highlight_markup(line, &mut state, palette),
+            Syntax::Headers => highlight_header(line, palette),
+            Syntax::Yaml => highlight_yaml(line, palette),
+            Syntax::Form => highlight_form(line, palette),
+            Syntax::Plain => Line::from(Span::styled(line.clone(), palette.text)),
+        })
+        .collect()
+}
+
+// Pretty printed JSON never has a string spanning lines, so each line stands on its own
+pub fn highlight_json(line: &str, palette: &Palette) -> Line<'static> {
+    let chars: Vec<char> = line.chars().collect();
+    let mut spans: Vec<Span<'static>> = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let start = i;
+        let style = match chars[i] {
+            '"' => {
+                i += 1;
+                while i < chars.len() && chars[i] != '"' {
+                    if chars[i] == '\\' {
+                        i += 1;
+                    }
+                    i += 1;
+                }
+                i = (i + 1).min(chars.len());
+                // a string followed by a colon is a key
+                match chars[i..].iter().find(|c| !c.is_whitespace()) {
+                    Some(':') => palette.key,
+                    _ => palette.string,
+                }
+            }
+            c if c == '-' || c.is_ascii_alphanumeric() => {
+                while i < chars.len()
+                    && (chars[i].is_ascii_alphanumeric() || "+-.".contains(chars[i]))
+                {
+                    i += 1;
+                }
+                palette.number
+            }
+            _ => {
+                while i < chars.len()
+                    && chars[i] != '"'
+                    && chars[i] != '-'
+                    && !chars[i].is_ascii_alphanumeric()
+                {
+                    i += 1;
+                }
+                palette.text
+            }
+        };
+        spans.push(Span::styled(
+            chars[start..i].iter().collect::<String>(),
+            style,
+        ));
+    }
+    Line::from(spans)
+}
+
+fn highlight_header(line: &str, palette: &Palette) -> Line<'static> {
+    if line.starts_with("HTTP/") {
+        return Line::from(Span::styled(line.to_string(), palette.number));
+    }
+    match line.split_once(':') {
+        Some((name, value)) => Line::from(vec![
+            Span::styled(name.to_string(), palette.key),
+            Span::styled(String::from(":"), palette.text),
+            Span::styled(value.to_string(), palette.string),
+        ]),
+        None => Line::from(Span::styled(line.to_string(), palette.text)),
+    }
+}
+
+// `key: value` with `- ` list markers and `#` comments, block scalars are shown as values
+fn highlight_yaml(line: &str, palette: &Palette) -> Line<'static> {
+    let rest = line.trim_start_matches([' ', '-']);
+    let indent = &line[..line.len() - rest.len()];
+    let mut spans = vec![Span::styled(indent.to_string(), palette.text)];
+    if rest.starts_with('#') {
+        spans.push(Span::styled(rest.to_string(), palette.comment));
+        return Line::from(spans);
+    }
+    let value = match yaml_key_end(rest) {
+        Some(end) => {
+            spans.push(Span::styled(rest[..end].to_string(), palette.key));
+            spans.push(Span::styled(String::from(":"), palette.text));
+            &rest[end + 1..]
+        }
+        None => rest,
+    };
+    let trimmed = value.trim_start();
+    spans.push(Span::styled(
+        value[..value.len() - trimmed.len()].to_string(),
+        palette.text,
+    ));
+    let style = match trimmed {
+        "" => palette.text,
+        _ if trimmed.starts_with('#') => palette.comment,
+        "true" | "false" | "null" | "~" => palette.number,
+        _ if trimmed.parse::<f64>().is_ok() => palette.number,
+        _ => palette.string,
+    };
+    spans.push(Span::styled(trimmed.to_string(), style));
+    Line::from(spans)
+}
+
+// The colon ending a key is followed by a space or the end of the line, and isn't in quotes
+fn yaml_key_end(rest: &str) -> Option<usize> {
+    let mut quote = None;
+    for (i, c) in rest.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') if i == 0 => quote = Some(c),
+            (Some(q), _) if q == c => quote = None,
+            (None, ':') if rest[i + 1..].is_empty() || rest[i + 1..].starts_with(' ') => {
+                return Some(i)
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+// Keys up to the `=`, values up to the `&` or the end of the line
+fn highlight_form(line: &str, palette: &Palette) -> Line<'static> {
+    let mut spans = Vec::new();
+    for (i, pair) in line.split('&').enumerate() {
+        if i > 0 {
+            spans.push(Span::styled(String::from("&"), palette.text));
+        }
+        match pair.split_once('=') {
+            Some((key, value)) => {
+                spans.push(Span::styled(key.to_string(), palette.key));
+                spans.push(Span::styled(String::from("="), palette.text));
+                spans.push(Span::styled(value.to_string(), palette.string));
+            }
+            None => spans.push(Span::styled(pair.to_string(), palette.key)),
+        }
+    }
+    Line::from(spans)
+}
+
+fn highlight_markup(line: &str, state: &mut Markup, palette: &Palette) -> Line<'static> {
+    let chars: Vec<char> = line.chars().collect();
+    let mut spans: Vec<Span<'static>> = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let start = i;
+        let rest: String = chars[i..].iter().take(4).collect();
+        let style = match *state {
+            Markup::Comment => {
+                i = end_of_comment(&chars, i, state);
+                palette.comment
+            }
+            Markup::Text if rest == "<!--" => {
+                i = end_of_comment(&chars, i + 4, state);
+                palette.comment
+            }
+            Markup::Text if chars[i] == '<' => {
+                // the bracket and the tag name, e.g. `</item` or `<?xml`
+                i += 1;
+                while i < chars.len() && "/?!".contains(chars[i]) {
+                    i += 1;
+                }
+                while i < chars.len() && !chars[i].is_whitespace() && !"/>".contains(chars[i]) {
+                    i += 1;
+                }
+                *state = Markup::Tag;
+                palette.key
+            }
+            Markup::Text => {
+                while i < chars.len() && chars[i] != '<' {
+                    i += 1;
+                }
+                palette.text
+            }
+            Markup::Tag if chars[i] == '"' || chars[i] == '\'' => {
+                *state = Markup::Value(chars[i]);
+                i = end_of_value(&chars, i + 1, state);
+                palette.string
+            }
+            Markup::Tag if "/?>".contains(chars[i]) => {
+                while i < chars.len() && "/?".contains(chars[i]) {
+                    i += 1;
+                }
+                if i < chars.len() && chars[i] == '>' {
+                    i += 1;
+                    *state = Markup::Text;
+                }
+                palette.key
+            }
+            Markup::Tag if chars[i].is_whitespace() || chars[i] == '=' => {
+                i += 1;
+                palette.text
+            }
+            // attribute names
+            Markup::Tag => {
+                while i < chars.len() && !chars[i].is_whitespace() && !"=/?>\"'".contains(chars[i])
+                {
+                    i += 1;
+                }
+                palette.number
+            }
+            Markup::Value(_) => {
+                i = end_of_value(&chars, i, state);
+                palette.string
+            }
+        };
+        spans.push(Span::styled(
+            chars[start..i].iter().collect::<String>(),
+            style,
+        ));
+    }
+    Line::from(spans)
+}
+
+// Past the `-->` closing the comment, or the end of the line when it goes on
+fn end_of_comment(chars: &[char], mut i: usize, state: &mut Markup) -> usize {
+    *state = Markup::Comment;
+    while i < chars.len() {
+        if chars[i..].starts_with(&['-', '-', '>']) {
+            *state = Markup::Text;
+            return i + 3;
+        }
+        i += 1;
+    }
+    i
+}
+
+// Past the quote closing the attribute value, or the end of the line when it goes on
+fn end_of_value(chars: &[char], mut i: usize, state: &mut Markup) -> usize {
+    let Markup::Value(quote) = *state else {
+        return i;
+    };
+    while i < chars.len() {
+        if chars[i] == quote {
+            *state = Markup::Tag;
+            return i + 1;
+        }
+        i += 1;
+    }
+    i
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn palette() -> Palette {
+        Palette {
+            text: Style::default(),
+            key: Style::default().fg(Color::Cyan),
+            string: Style::default().fg(Color::Yellow),
+            number: Style::default().fg(Color::Magenta),
+            comment: Style::default().fg(Color::DarkGray),
+            hit: Style::default().bg(Color::Yellow),
+            current_hit: Style::default().bg(Color::Cyan),
+        }
+    }
+
+    fn styled(line: &Line, style: Style) -> Vec<String> {
+        line.spans
+            .iter()
+            .filter(|span| span.style == style)
+            .map(|span| span.content.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_detect() {
+        let json = "{\"id\": 1}";
+        assert_eq!(Syntax::detect(None, json), Syntax::Json);
+        assert_eq!(
+            Syntax::detect(Some("application/problem+json"), "not json"),
+            Syntax::Json
+        );
+        assert_eq!(
+            Syntax::detect(Some("text/html; charset=utf-8"), json),
+            Syntax::Html
+        );
+        assert_eq!(Syntax::detect(None, "<!DOCTYPE html><html>"), Syntax::Html);
+        assert_eq!(Syntax::detect(None, "<?xml version=\"1.0\"?>"), Syntax::Xml);
+        assert_eq!(Syntax::detect(None, "{not json"), Syntax::Plain);
+        assert_eq!(
+            Syntax::detect(Some("application/x-yaml"), "a: 1"),
+            Syntax::Yaml
+        );
+        assert_eq!(
+            Syntax::detect(Some("application/x-www-form-urlencoded"), "a=1"),
+            Syntax::Form
+        );
+    }
+
+    #[test]
+    fn test_highlight_json() {
+        let palette = palette();
+        let line = highlight_json(
+            "  \"name\": \"a \\\"b\\\"\", \"n\": -1.5e3, \"ok\": true",
+            &palette,
+        );
+        assert_eq!(
+            styled(&line, palette.key),
+            vec!["\"name\"", "\"n\"", "\"ok\""]
+        );
+        assert_eq!(styled(&line, palette.string), vec!["\"a \\\"b\\\"\""]);
+        assert_eq!(styled(&line, palette.number), vec!["-1.5e3", "true"]);
+    }
+
+    #[test]
+    fn test_highlight_markup() {
+        let palette = palette();
+        let lines = vec![
+            String::from("<item id=\"1\" name='a"),
+            String::from("b'>text</item> <!-- a"),
+            String::from("comment -->"),
+        ];
+        let lines = highlight_lines(&lines, Syntax::Xml, &palette);
+        assert_eq!(styled(&lines[0], palette.key), vec!["<item"]);
+        assert_eq!(styled(&lines[0], palette.number), vec!["id", "name"]);
+        assert_eq!(styled(&lines[0], palette.string), vec!["\"1\"", "'a"]);
+        assert_eq!(styled(&lines[1], palette.string), vec!["b'"]);
+        assert_eq!(styled(&lines[1], palette.key), vec![">", "</item", ">"]);
+        assert_eq!(styled(&lines[1], palette.comment), vec!["<!-- a"]);
+        assert_eq!(styled(&lines[2], palette.comment), vec!["comment -->"]);
+    }
+
+    #[test]
+    fn test_highlight_yaml_and_form() {
+        let palette = palette();
+        let lines = vec![
+            String::from("owner:"),
+            String::from("  - name: \"a: b\" # c"),
+            String::from("    id: 1"),
+            String::from("# comment"),
+        ];
+        let lines = highlight_lines(&lines, Syntax::Yaml, &palette);
+        assert_eq!(styled(&lines[0], palette.key), vec!["owner"]);
+        assert_eq!(styled(&lines[1], palette.key), vec!["name"]);
+        assert_eq!(styled(&lines[1], palette.string), vec!["\"a: b\" # c"]);
+        assert_eq!(styled(&lines[2], palette.number), vec!["1"]);
+        assert_eq!(styled(&lines[3], palette.comment), vec!["# comment"]);
+
+        let line = highlight_form("name=a+b&id=1&flag", &palette);
+        assert_eq!(styled(&line, palette.key), vec!["name", "id", "flag"]);
+        assert_eq!(styled(&line, palette.string), vec!["a+b", "1"]);
+        let line = highlight_form("name = a b", &palette);
+        assert_eq!(styled(&line, palette.key), vec!["name "]);
+        assert_eq!(styled(&line, palette.string), vec![" a b"]);
+    }
+}
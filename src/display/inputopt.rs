@@ -1,6 +1,9 @@
 use std::fmt::Display;
 
 use crate::request::command::CmdType;
+use crate::request::har::HarExport;
+use crate::request::importer::ImportFormat;
+use crate::request::snippet::SnippetLang;
 use crate::screens::auth::AuthType;
 
 #[derive(Debug, Clone, PartialEq)]
@@ -24,6 +27,37 @@ pub enum InputOpt {
     FtpAccount,
     CaPath,
     CaCert,
+    ImportCurl,
+    ImportCollection(ImportFormat),
+    ExportHar(HarExport),
+    ImportBundle,
+    ExportBundle,
+    SnippetFile(SnippetLang),
+    EnvName,
+    EnvVar(i32),
+    HistorySearch,
+    // parent collection, None for the top level
+    NewCollection(Option<i32>),
+    RenameCollection(i32),
+    RenameCommand(i32),
+    CommandDescription(i32),
+    // the ID of the saved key being edited
+    KeyLabel(i32),
+    KeyHost(i32),
+    KeyType(i32),
+    KeyExpiry(i32),
+    // saved command id, and the assertion being edited (None to add one)
+    Assertion(i32, Option<usize>),
+    OpenHttpFile,
+    // index of the request in the open .http file
+    HttpRequestLine(usize),
+    HttpHeader(usize),
+    HttpBody(usize),
+    HttpRequestName(usize),
+    Unlock,
+    NewPassphrase,
+    // the passphrase typed the first time, to compare with
+    ConfirmPassphrase(String),
 }
 
 impl Display for InputOpt {
@@ -48,6 +82,32 @@ impl Display for InputOpt {
             InputOpt::CaCert => write!(f, "| Ca Cert"),
             InputOpt::VerifyPeer => write!(f, "| Verify Peer DNS-Over-HTTPS"),
             InputOpt::FtpAccount => write!(f, "| FTP Account"),
+            InputOpt::ImportCurl => write!(f, "| Import cURL command"),
+            InputOpt::ImportCollection(format) => write!(f, "| Import {}", format),
+            InputOpt::ExportHar(_) => write!(f, "| Export HAR"),
+            InputOpt::ImportBundle => write!(f, "| Import bundle"),
+            InputOpt::ExportBundle => write!(f, "| Export bundle"),
+            InputOpt::SnippetFile(lang) => write!(f, "| Save {} snippet", lang),
+            InputOpt::EnvName => write!(f, "| Environment Name"),
+            InputOpt::EnvVar(_) => write!(f, "| Environment Variable"),
+            InputOpt::HistorySearch => write!(f, "| Search History"),
+            InputOpt::NewCollection(_) => write!(f, "| New Collection"),
+            InputOpt::RenameCollection(_) => write!(f, "| Rename Collection"),
+            InputOpt::RenameCommand(_) => write!(f, "| Rename Command"),
+            InputOpt::CommandDescription(_) => write!(f, "| Command Description"),
+            InputOpt::KeyLabel(_) => write!(f, "| Key Label"),
+            InputOpt::KeyHost(_) => write!(f, "| Key Host"),
+            InputOpt::KeyType(_) => write!(f, "| Key Type"),
+            InputOpt::KeyExpiry(_) => write!(f, "| Key Expiry"),
+            InputOpt::Assertion(_, _) => write!(f, "| Assertion"),
+            InputOpt::OpenHttpFile => write!(f, "| Open .http File"),
+            InputOpt::HttpRequestLine(_) => write!(f, "| Request Line"),
+            InputOpt::HttpHeader(_) => write!(f, "| Header"),
+            InputOpt::HttpBody(_) => write!(f, "| Request Body"),
+            InputOpt::HttpRequestName(_) => write!(f, "| Request Name"),
+            InputOpt::Unlock => write!(f, "| Unlock"),
+            InputOpt::NewPassphrase => write!(f, "| New Passphrase"),
+            InputOpt::ConfirmPassphrase(_) => write!(f, "| Confirm Passphrase"),
         }
     }
 }
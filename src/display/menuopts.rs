@@ -4,13 +4,13 @@
 use lazy_static::lazy_static;
 
 pub const SAVED_COMMANDS_PARAGRAPH: &str =
-    "\nPress q to exit\nPress Enter to Execute command\nPress 'ESC' or 'h' to go back\n";
+    "\nPress q to exit\nPress Enter for Menu or to open a collection\nPress 'a' to add a collection, 'o' for collection options, 't' to test everything, 'x' to export everything as HAR\nPress 'ESC' or 'h' to go back\n";
 pub const CURL: &str = "curl";
 pub const WGET: &str = "wget";
 pub const CUSTOM: &str = "custom";
 pub const DOWNLOAD: &str = "Download";
 pub const API_KEY_PARAGRAPH: &str =
-    "Press q to quit\nPress 'ESC' or 'h' to go back\nPress Enter for Menu\n";
+    "Press q to quit\nPress 'ESC' or 'h' to go back\nPress Enter for Menu\nPress 'p' to set or change the master passphrase\n";
 pub const HTTP_REQUEST: &str = "HTTP Request";
 pub const DEFAULT_MENU_PARAGRAPH: &str =
     "\nPress q to exit \n Press Enter to select \n Please select a Menu item\n keybindings to navigate";
@@ -26,8 +26,35 @@ pub const SAVED_COMMANDS_TITLE: &str = "My Saved cURL Commands";
 pub const DEFAULT_MENU_TITLE: &str = "** CuTE **";
 pub const AUTH_MENU_TITLE: &str = "** CuTE ** Authentication Menu 󰌋";
 pub const VIEW_BODY_TITLE: &str = "** CuTE ** View Response Body";
+pub const VIEW_BODY_PARAGRAPH: &str =
+    "j/k to scroll, Ctrl-d/Ctrl-u for half a page, gg/G for the top and bottom, / or ? to search and n/N for the next match\nEnter folds the JSON object or array under the cursor, 'z' folds or opens them all, 'f' filters the JSON, 'r' switches between raw and pretty";
+pub const VIEW_HEADERS_TITLE: &str = "** CuTE ** View Response Headers";
+pub const VIEW_HEADERS_PARAGRAPH: &str =
+    "j/k to scroll, Ctrl-d/Ctrl-u for half a page, gg/G for the top and bottom\n/ or ? to search and n/N for the next match";
+pub const FILTER_INPUT_TITLE: &str = "Filter: .items[].id, .data | length, map(.name) | sort";
+pub const SEARCH_INPUT_TITLE: &str = "Search (Ctrl-r for a regex)";
+pub const SEARCH_REGEX_TITLE: &str = "Regex search (Ctrl-r for plain text)";
+pub const NOT_JSON_ERROR: &str = "Error: The response body isn't JSON";
 pub const INPUT_MENU_TITLE: &str = "** Press i to enter Insert mode **";
 pub const DOWNLOAD_MENU_TITLE: &str = "* CuTE ** Downloads *";
+pub const IMPORT_MENU_TITLE: &str = "** CuTE ** Import a cURL command, collection or bundle";
+pub const SNIPPET_MENU_TITLE: &str = "** CuTE ** Generate a code snippet";
+pub const ENVIRONMENTS_TITLE: &str = "** CuTE ** Environments";
+pub const ENVIRONMENTS_PARAGRAPH: &str =
+    "Use {{variables}} in the URL, headers, cookies and body of a request, they are filled in from the active environment";
+pub const HISTORY_TITLE: &str = "** CuTE ** Request History";
+pub const HISTORY_PARAGRAPH: &str =
+    "Every request you send is kept here. Press / to search, Enter to re-run, save or compare it, x to export the list as HAR";
+pub const DIFF_TITLE: &str = "** CuTE ** Compare Responses";
+pub const DIFF_PARAGRAPH: &str =
+    "j/k to scroll, Ctrl-d/Ctrl-u for half a page, g/G for the top and bottom, n/N for the next and previous change\n'm' switches between the body, JSON (ignoring key order) and header diffs";
+pub const DIFF_PICKER_PARAGRAPH: &str =
+    "Pick the response from the history to compare with, run the request again first to compare with a fresh one";
+pub const HTTP_FILE_TITLE: &str = "** CuTE ** .http Request File";
+pub const HTTP_FILE_PARAGRAPH: &str =
+    "Requests are separated by ### and can use @variables. Press Enter for options, 'r' to reload the file";
+pub const TEST_RESULTS_TITLE: &str = "** CuTE ** Test Results";
+pub const EXECUTING_TITLE: &str = "** CuTE ** Executing";
 pub const ERROR_MENU_TITLE: &str = "* CuTE ** Error! *";
 pub const SUCCESS_MENU_TITLE: &str = "* CuTE ** Success! *";
 pub const SUCCESS_MESSAGE: &str = "Command saved successfully";
@@ -39,6 +66,55 @@ pub const INPUT_OPT_AUTH_BASIC: &str = "Enter username:password and press Enter"
 pub const INPUT_OPT_AUTH_ANY: &str = "Enter your username and press Enter";
 pub const INPUT_OPT_AUTH_BEARER: &str = "Enter your API token and press Enter";
 pub const INPUT_OPT_BASIC: &str = "Enter a value and press Enter";
+pub const INPUT_OPT_IMPORT_CURL: &str =
+    "Paste or type a curl command and press Enter \n Example: curl -X POST https://example.com -d 'key=value'";
+pub const INPUT_OPT_ENV_NAME: &str = "Enter a name for the new environment and press Enter";
+pub const INPUT_OPT_ENV_VAR: &str =
+    "Enter a variable as key=value and press Enter, leave the value empty to remove it";
+pub const INPUT_OPT_HISTORY_SEARCH: &str =
+    "Search the history by URL or method and press Enter, leave it empty to show everything";
+pub const INPUT_OPT_COLLECTION_NAME: &str = "Enter a name for the collection and press Enter";
+pub const INPUT_OPT_COMMAND_NAME: &str =
+    "Enter a name for the command and press Enter, leave it empty to show the command instead";
+pub const INPUT_OPT_KEY_LABEL: &str =
+    "Enter a label for the key and press Enter, leave it empty to show its ID instead";
+pub const INPUT_OPT_KEY_HOST: &str =
+    "Enter the host the key is used with and press Enter, e.g. api.example.com or *.example.com, leave it empty to remove it";
+pub const INPUT_OPT_KEY_TYPE: &str =
+    "Enter how the key is sent and press Enter: bearer, basic or digest (user:password), header <name> (e.g. header X-API-Key) or query <param> (e.g. query api_key)";
+pub const INPUT_OPT_KEY_EXPIRY: &str =
+    "Enter the date the key expires (YYYY-MM-DD) and press Enter, leave it empty if it never expires";
+pub const INPUT_OPT_COMMAND_DESCRIPTION: &str =
+    "Enter a description for the command and press Enter, leave it empty to remove it";
+pub const INPUT_OPT_ASSERTION: &str =
+    "Enter an assertion and press Enter, leave it empty to remove it \n Examples: status == 200, header Content-Type == application/json, json $.user.id == 5, body ~ regex, time < 500";
+pub const INPUT_OPT_IMPORT_POSTMAN: &str =
+    "Enter the path to a Postman collection (v2.1) file and press Enter";
+pub const INPUT_OPT_IMPORT_OPENAPI: &str =
+    "Enter the path to an OpenAPI 3 or Swagger 2 spec (JSON or YAML) and press Enter";
+pub const INPUT_OPT_IMPORT_HAR: &str =
+    "Enter the path to a HAR file exported from your browser's dev tools and press Enter";
+pub const INPUT_OPT_EXPORT_HAR: &str =
+    "Enter a file path for the HAR file and press Enter, .har is added if there's no extension";
+pub const INPUT_OPT_IMPORT_BUNDLE: &str =
+    "Enter the path to a CuTE bundle (.json or .toml) and press Enter, you can review it before anything is saved";
+pub const INPUT_OPT_EXPORT_BUNDLE: &str =
+    "Enter a file path for the bundle and press Enter, it's written as TOML if the path ends in .toml, JSON otherwise";
+pub const INPUT_OPT_UNLOCK: &str =
+    "Your keys and saved auth are encrypted, enter your passphrase and press Enter";
+pub const INPUT_OPT_NEW_PASSPHRASE: &str =
+    "Enter a master passphrase to encrypt your keys and saved auth with and press Enter. Leave it empty to remove the passphrase";
+pub const INPUT_OPT_CONFIRM_PASSPHRASE: &str = "Enter the passphrase again and press Enter";
+pub const INPUT_OPT_OPEN_HTTP_FILE: &str = "Enter the path to a .http file and press Enter";
+pub const INPUT_OPT_HTTP_REQUEST_LINE: &str =
+    "Edit the method and URL and press Enter \n Example: POST {{base_url}}/users";
+pub const INPUT_OPT_HTTP_HEADER: &str =
+    "Enter a \"Key: Value\" header and press Enter, it replaces the header with the same name. Leave the value empty to remove it";
+pub const INPUT_OPT_HTTP_BODY: &str =
+    "Edit the body and press Enter, type newlines as \\n. Leave it empty to remove the body";
+pub const INPUT_OPT_HTTP_NAME: &str =
+    "Enter a name for the request and press Enter, leave it empty to remove it";
+pub const INPUT_OPT_SNIPPET_FILE: &str = "Enter a file path for the code snippet and press Enter";
 // This padds the choices in the menu. This is the least hideous way to do this.(I think)
 pub const OPTION_PADDING_MAX: &str = "\n\n\n\n";
 pub const OPTION_PADDING_MID: &str = "\n\n\n";
@@ -107,29 +183,116 @@ pub const CERT_ERROR: &str =
 pub const HEADER_ERROR: &str = "Error: Invalid header. Please use the format \"Key:Value\".";
 pub const SAVE_AUTH_ERROR: &str =
     "Error: You must have selected Authentication in order to save your token";
+pub const CLIPBOARD_EMPTY_ERROR: &str = "Error: The clipboard does not contain any text";
+pub const SNIPPET_ERROR: &str = "Error: Code snippets can only be generated for HTTP requests";
+pub const ENV_NAME_ERROR: &str = "Error: Please enter a name for the environment";
+pub const ENV_VAR_ERROR: &str = "Error: Invalid variable. Please use the format \"key=value\".";
+pub const ASSERTION_ERROR: &str =
+    "Error: Invalid assertion. Use status == 200, header Name [== value], json $.path == value, body ~ regex or time < ms";
+pub const COLLECTION_NAME_ERROR: &str = "Error: Please enter a name for the collection";
+pub const COLLECTION_MOVE_ERROR: &str = "Error: A collection cannot be moved into itself";
+pub const HISTORY_NOT_FOUND_ERROR: &str = "Error: History entry not found";
+pub const DIFF_BASE_ERROR: &str = "Error: Pick the first response to compare";
+pub const LOCKED_ERROR: &str =
+    "Error: Your keys are encrypted, unlock them with your passphrase first";
+pub const PASSPHRASE_ERROR: &str = "Error: Wrong passphrase";
+pub const DB_VERSION_ERROR: &str =
+    "Error: The database was created by a newer version of CuTE, please update CuTE to open it";
+pub const KEY_TYPE_ERROR: &str =
+    "Error: Invalid key type. Use bearer, basic, digest, header <name> or query <param>";
+pub const KEY_URL_ERROR: &str =
+    "Error: Enter the URL first, the key is added to it as a query parameter";
+pub const KEY_EXPIRY_ERROR: &str = "Error: Invalid date. Please use the format YYYY-MM-DD";
+pub const PASSPHRASE_EMPTY_ERROR: &str = "Error: Please enter a passphrase";
+pub const PASSPHRASE_MISMATCH_ERROR: &str = "Error: The passphrases don't match";
+pub const NOT_ENCRYPTED_ERROR: &str = "Error: There is no passphrase set";
+pub const BUNDLE_ERROR: &str = "Error: No bundle to import";
+pub const HTTP_FILE_ERROR: &str = "Error: No .http file is open";
+pub const HTTP_REQUEST_LINE_ERROR: &str = "Error: The request line needs a URL";
+pub const CANCELLED_ERROR: &str = "Error: Request cancelled";
+pub const NO_COMMAND_ERROR: &str = "Error: No command to execute";
+pub const SPINNER_FRAMES: [&str; 4] = ["|", "/", "-", "\\"];
 pub const VALID_COMMAND_ERROR: &str =
     "Error: Invalid command.\n You must add either a URL or Unix Socket to execute a command";
 
 lazy_static! {
-    pub static ref CMD_MENU_OPTIONS: [&'static str; 4] = [
+    pub static ref CMD_MENU_OPTIONS: [&'static str; 9] = [
         "Execute   ",
         "Delete   ",
         "Copy to Clipboard  󰅎 ",
+        "Generate code snippet  ",
+        "Rename  ",
+        "Edit description  ",
+        "Move to a collection  ",
+        "Edit assertions  ",
         "Cancel   ",
     ];
-    pub static ref KEY_MENU_OPTIONS: [&'static str; 4] = [
+    pub static ref COLLECTION_MENU_OPTIONS: [&'static str; 7] = [
+        "Run as tests  ",
+        "New collection inside  ",
+        "Rename  ",
+        "Move to a collection  ",
+        "Delete (keeps its commands)  ",
+        "Export as HAR  ",
+        "Cancel   ",
+    ];
+    pub static ref KEY_MENU_OPTIONS: [&'static str; 8] = [
         "Add a new key  ",
         "Delete   ",
         "Copy to Clipboard  󰅎 ",
+        "Edit label  ",
+        "Edit host  ",
+        "Change type  ",
+        "Set expiry date  ",
         "Cancel   ",
     ];
+    pub static ref ENV_MENU_OPTIONS: [&'static str; 4] = [
+        "Switch to this environment 󰒓 ",
+        "Edit variables  ",
+        "Delete   ",
+        "Cancel   ",
+    ];
+    pub static ref HISTORY_MENU_OPTIONS: [&'static str; 5] = [
+        "Execute again   ",
+        "Save as a command  ",
+        "Compare with another response  ",
+        "Delete   ",
+        "Cancel   ",
+    ];
+    pub static ref HTTP_REQUEST_MENU_OPTIONS: [&'static str; 7] = [
+        "Execute   ",
+        "Edit the request line  ",
+        "Add or change a header  ",
+        "Edit the body  ",
+        "Rename  ",
+        "Save as a command  ",
+        "Cancel   ",
+    ];
+    pub static ref BUNDLE_IMPORT_MENU_OPTIONS: [&'static str; 2] = [
+        "Import everything that is not already saved   ",
+        "Cancel   ",
+    ];
     pub static ref ALERT_MENU_OPTIONS_KEY: [&'static str; 3] =
         ["Delete", "Copy to Clipboard", "Cancel"];
-    pub static ref MAIN_MENU_OPTIONS: [&'static str; 4] = [
+    pub static ref MAIN_MENU_OPTIONS: [&'static str; 8] = [
         "Build and send an HTTP request 󰖟 ",
         "Download a remote file or directory 󰧩 ",
         "View my stored API keys 󱂛  ",
         "View or execute my saved commands  ",
+        "Import a cURL command 󰆏 ",
+        "Manage environments 󰒓 ",
+        "View request history 󰋚 ",
+        "Open a .http request file 󰆏 ",
+    ];
+    pub static ref IMPORT_MENU_OPTIONS: [&'static str; 8] = [
+        "Import from clipboard 󰅇 ",
+        "Type or paste a cURL command  ",
+        "Import a Postman collection 󰉋 ",
+        "Import an OpenAPI spec 󰉋 ",
+        "Import a HAR file 󰉋 ",
+        "Import a CuTE bundle 󰉋 ",
+        "Export everything as a bundle 󰉋 ",
+        "Cancel   ",
     ];
     pub static ref REQUEST_MENU_OPTIONS: [&'static str; 13] = [
         "Add a URL 󰖟 ",
@@ -161,13 +324,14 @@ lazy_static! {
         "PATCH",
         "HEAD",
     ];
-    pub static ref AUTHENTICATION_MENU_OPTIONS: [&'static str; 6] = [
+    pub static ref AUTHENTICATION_MENU_OPTIONS: [&'static str; 7] = [
         "Basic",
         "Bearer",
         "Digest",
         "AWS SignatureV4",
         "Ntlm",
         "SPNEGO",
+        "Use a saved key",
     ];
     pub static ref MORE_FLAGS_MENU: [&'static str; 13] = [
         "Follow Redirects 󱀀 ",
@@ -184,11 +348,26 @@ lazy_static! {
         "Specify User-Agent  󰖟 ",
         "Enable TCP keepalive 󰗶 ",
     ];
-    pub static ref RESPONSE_MENU_OPTIONS: [&'static str; 5] = [
+    pub static ref RESPONSE_MENU_OPTIONS: [&'static str; 7] = [
         "Write to file? 󱇧 ",
         "View response headers 󰰀 ",
         "View response body 󰈮 ",
         "Copy command to clipboard 󰅎 ",
+        "Generate code snippet  ",
+        "Compare with a response from history  ",
         "Return to main menu  ",
     ];
+    pub static ref SNIPPET_MENU_OPTIONS: [&'static str; 6] = [
+        "Rust (reqwest)  ",
+        "Python (requests)  ",
+        "JavaScript (fetch)  ",
+        "HTTPie  ",
+        "C (libcurl)  ",
+        "Cancel   ",
+    ];
+    pub static ref SNIPPET_EXPORT_OPTIONS: [&'static str; 3] = [
+        "Copy to Clipboard  󰅎 ",
+        "Save to a file  ",
+        "Cancel   ",
+    ];
 }
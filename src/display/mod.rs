@@ -22,6 +22,18 @@ pub mod inputopt;
 // Menu Options
 pub mod menuopts;
 
+// Syntax Highlighting Of Response Bodies
+pub mod highlight;
+
+// Pretty Printing Of XML, HTML, Form And YAML Bodies
+pub mod pretty;
+
+// Scrolling And Folding Of Response Bodies
+pub mod viewer;
+
+// Two Responses Side By Side
+pub mod diffview;
+
 /// Here are the options that require us to display a box letting
 /// the user know that they have selected that option.
 #[derive(Debug, Clone, PartialEq)]
@@ -0,0 +1,321 @@
+use super::highlight::Syntax;
+
+/*
+* Pretty - Re-indents XML and HTML, decodes form bodies and re-flows YAML for viewing
+ */
+
+// HTML elements that never have a closing tag
+const VOID_ELEMENTS: [&str; 14] = [
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "param", "source",
+    "track", "wbr",
+];
+// HTML elements whose content is shown exactly as it came in
+const RAW_TEXT_ELEMENTS: [&str; 4] = ["pre", "script", "style", "textarea"];
+
+// JSON is formatted by the viewer, `None` leaves the body as it is
+pub fn pretty_print(body: &str, syntax: Syntax) -> Option<String> {
+    match syntax {
+        Syntax::Xml => Some(format_markup(body, false)),
+        Syntax::Html => Some(format_markup(body, true)),
+        Syntax::Form => format_form(body),
+        Syntax::Yaml => format_yaml(body),
+        Syntax::Json | Syntax::Headers | Syntax::Plain => None,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token<'a> {
+    // the element name and the whole tag
+    Open(&'a str, &'a str),
+    Close(&'a str, &'a str),
+    // self closing tags, comments, CDATA, doctypes and processing instructions
+    Single(&'a str),
+    Text(&'a str),
+    // the content of a <pre>, <script>, <style> or <textarea>
+    Raw(&'a str),
+}
+
+fn tokenize(body: &str, html: bool) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < body.len() {
+        let rest = &body[i..];
+        let end = match rest.starts_with('<').then(|| markup_end(rest)).flatten() {
+            Some(end) => end,
+            // text runs until the next tag, a `<` that doesn't open one (e.g. `a < b`) is part of it
+            None => {
+                let end = rest
+                    .char_indices()
+                    .skip(1)
+                    .find(|(_, c)| *c == '<')
+                    .map_or(rest.len(), |(end, _)| end);
+                let start = match tokens.last() {
+                    Some(Token::Text(text)) => {
+                        let start = i - text.len();
+                        tokens.pop();
+                        start
+                    }
+                    _ => i,
+                };
+                i += end;
+                tokens.push(Token::Text(&body[start..i]));
+                continue;
+            }
+        };
+        let tag = &rest[..end];
+        i += end;
+        if !tag.starts_with("</") && (tag.ends_with("/>") || !is_element(tag)) {
+            tokens.push(Token::Single(tag));
+        } else if let Some(name) = tag.strip_prefix("</") {
+            tokens.push(Token::Close(tag_name(name), tag));
+        } else {
+            let name = tag_name(&tag[1..]);
+            tokens.push(Token::Open(name, tag));
+            if html && is_one_of(name, &RAW_TEXT_ELEMENTS) {
+                let close = format!("</{}", name.to_ascii_lowercase());
+                let end = body[i..]
+                    .to_ascii_lowercase()
+                    .find(&close)
+                    .map_or(body.len(), |end| i + end);
+                tokens.push(Token::Raw(&body[i..end]));
+                i = end;
+            }
+        }
+    }
+    tokens
+}
+
+// The length of the tag, comment or CDATA at the start of `rest`, quotes can hide a `>`
+fn markup_end(rest: &str) -> Option<usize> {
+    let after = |open: &str, close: &str| {
+        rest.starts_with(open)
+            .then(|| rest.find(close).map(|end| end + close.len()))
+            .flatten()
+    };
+    if rest.starts_with("<!--") {
+        return after("<!--", "-->");
+    } else if rest.starts_with("<![CDATA[") {
+        return after("<![CDATA[", "]]>");
+    }
+    let next = rest[1..].chars().next()?;
+    if !next.is_alphabetic() && !"/!?".contains(next) {
+        return None;
+    }
+    let mut quote = None;
+    for (i, c) in rest.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), _) if q == c => quote = None,
+            (None, '>') => return Some(i + 1),
+            _ => {}
+        }
+    }
+    None
+}
+
+fn is_element(tag: &str) -> bool {
+    tag[1..].starts_with(|c: char| c.is_alphabetic())
+}
+
+fn tag_name(tag: &str) -> &str {
+    let end = tag
+        .find(|c: char| c.is_whitespace() || c == '/' || c == '>')
+        .unwrap_or(tag.len());
+    &tag[..end]
+}
+
+fn is_one_of(name: &str, names: &[&str]) -> bool {
+    names.iter().any(|n| n.eq_ignore_ascii_case(name))
+}
+
+// Tags spread over several lines are joined into one
+fn one_line(tag: &str) -> String {
+    match tag.contains('\n') {
+        true => tag.split_whitespace().collect::<Vec<_>>().join(" "),
+        false => tag.to_string(),
+    }
+}
+
+// Every tag on its own line, two spaces per level. An element holding nothing but a line of text
+// stays on one line, e.g. `<title>Home</title>`
+fn format_markup(body: &str, html: bool) -> String {
+    let tokens = tokenize(body, html);
+    let mut lines: Vec<String> = Vec::new();
+    let mut depth = 0;
+    let mut push = |depth: usize, line: &str| lines.push(format!("{}{}", "  ".repeat(depth), line));
+    let mut i = 0;
+    while i < tokens.len() {
+        match tokens[i] {
+            Token::Open(name, tag) => match (tokens.get(i + 1), tokens.get(i + 2)) {
+                (Some(Token::Text(text)), Some(Token::Close(close, end)))
+                    if close.eq_ignore_ascii_case(name) && !text.trim().contains('\n') =>
+                {
+                    push(depth, &format!("{}{}{}", one_line(tag), text.trim(), end));
+                    i += 2;
+                }
+                (Some(Token::Close(close, end)), _) if close.eq_ignore_ascii_case(name) => {
+                    push(depth, &format!("{}{}", one_line(tag), end));
+                    i += 1;
+                }
+                _ if html && is_one_of(name, &VOID_ELEMENTS) => push(depth, &one_line(tag)),
+                _ => {
+                    push(depth, &one_line(tag));
+                    depth += 1;
+                }
+            },
+            Token::Close(_, tag) => {
+                depth = depth.saturating_sub(1);
+                push(depth, tag);
+            }
+            Token::Single(tag) => push(depth, &one_line(tag)),
+            Token::Text(text) => text
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .for_each(|line| push(depth, line)),
+            // indenting this would change it, only the blank lines around it go
+            Token::Raw(text) if !text.trim().is_empty() => text
+                .trim_matches(|c| c == '\n' || c == '\r')
+                .lines()
+                .for_each(|line| push(0, line.trim_end())),
+            Token::Raw(_) => {}
+        }
+        i += 1;
+    }
+    lines.join("\n")
+}
+
+// `key = value` lines with the keys lined up, e.g. `name=a+b&id=%231` -> `name = a b`, `id   = #1`
+fn format_form(body: &str) -> Option<String> {
+    let pairs: Vec<(String, String)> = body
+        .trim()
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (percent_decode(key), percent_decode(value))
+        })
+        .collect();
+    let width = pairs.iter().map(|(key, _)| key.chars().count()).max()?;
+    Some(
+        pairs
+            .iter()
+            .map(|(key, value)| {
+                format!("{:<width$} = {}", key, value, width = width)
+                    .trim_end()
+                    .to_string()
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+    )
+}
+
+// `+` is a space in form bodies, a `%` that isn't followed by two hex digits is kept as it is
+pub fn percent_decode(data: &str) -> String {
+    let bytes = data.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => decoded.push(b' '),
+            b'%' => match data
+                .get(i + 1..i + 3)
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+            {
+                Some(byte) => {
+                    decoded.push(byte);
+                    i += 2;
+                }
+                None => decoded.push(b'%'),
+            },
+            byte => decoded.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+// Flow style mappings and lists come out in block style with two spaces per level
+fn format_yaml(body: &str) -> Option<String> {
+    let value = serde_yaml::from_str::<serde_yaml::Value>(body).ok()?;
+    serde_yaml::to_string(&value)
+        .ok()
+        .map(|yaml| yaml.trim_end().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_xml() {
+        let body = "<?xml version=\"1.0\"?><items count=\"2\"><item id=\"a>b\">one</item>\
+                    <item/><!-- none --><empty></empty><list>\n  <name>x</name>\n</list></items>";
+        assert_eq!(
+            pretty_print(body, Syntax::Xml).unwrap(),
+            "<?xml version=\"1.0\"?>\n<items count=\"2\">\n  <item id=\"a>b\">one</item>\n  \
+             <item/>\n  <!-- none -->\n  <empty></empty>\n  <list>\n    <name>x</name>\n  \
+             </list>\n</items>"
+        );
+        // unbalanced closing tags don't take the indentation below zero
+        assert_eq!(
+            format_markup("</a><b>1 < 2</b>", false),
+            "</a>\n<b>1 < 2</b>"
+        );
+    }
+
+    #[test]
+    fn test_format_html() {
+        let body = "<!DOCTYPE html><html><head><meta charset=\"utf-8\"><title>Home</title></head>\
+                    <body><p>Hello <b>world</b><br>!</p><pre>\n  keep\n    this</pre></body></html>";
+        assert_eq!(
+            pretty_print(body, Syntax::Html).unwrap(),
+            [
+                "<!DOCTYPE html>",
+                "<html>",
+                "  <head>",
+                "    <meta charset=\"utf-8\">",
+                "    <title>Home</title>",
+                "  </head>",
+                "  <body>",
+                "    <p>",
+                "      Hello",
+                "      <b>world</b>",
+                "      <br>",
+                "      !",
+                "    </p>",
+                "    <pre>",
+                "  keep",
+                "    this",
+                "    </pre>",
+                "  </body>",
+                "</html>",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn test_format_form() {
+        assert_eq!(
+            pretty_print("name=Jane+Doe&id=%231&empty&bad=100%", Syntax::Form).unwrap(),
+            "name  = Jane Doe\nid    = #1\nempty =\nbad   = 100%"
+        );
+        assert_eq!(percent_decode("caf%C3%A9"), "café");
+        assert_eq!(pretty_print("", Syntax::Form), None);
+    }
+
+    #[test]
+    fn test_format_yaml() {
+        assert_eq!(
+            pretty_print(
+                "name: cute\ntags: [tui, http]\nowner: {id: 1}",
+                Syntax::Yaml
+            )
+            .unwrap(),
+            "name: cute\ntags:\n- tui\n- http\nowner:\n  id: 1"
+        );
+        assert_eq!(pretty_print("a: [1", Syntax::Yaml), None);
+        assert_eq!(pretty_print("{\"id\": 1}", Syntax::Json), None);
+    }
+}
@@ -0,0 +1,683 @@
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+
+use regex::{Regex, RegexBuilder};
+use tui::style::Style;
+use tui::text::{Line, Span};
+
+use super::highlight::{highlight_json, highlight_lines, Palette, Syntax};
+use super::pretty::pretty_print;
+
+/*
+* Viewer - The scroll position, folds and search of a response body or headers being viewed
+ */
+
+// What's being typed below the body or headers
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ViewInput {
+    Filter,
+    // `/` searches forward and `?` backward, n/N go on in the same direction or the other one
+    Search { forward: bool, regex: bool },
+}
+
+#[derive(Debug, Clone)]
+pub struct Search {
+    query: String,
+    forward: bool,
+    regex: bool,
+    // every hit as the line it's on and the bytes of the line it covers
+    matches: Vec<(usize, Range<usize>)>,
+    current: Option<usize>,
+}
+
+#[derive(Debug, Clone)]
+pub struct BodyViewer {
+    syntax: Syntax,
+    lines: Vec<String>,
+    highlighted: Vec<Line<'static>>,
+    palette: Palette,
+    // the line opening a JSON object or array -> the line closing it
+    blocks: HashMap<usize, usize>,
+    folded: HashSet<usize>,
+    // the line under the cursor, folding keeps it on the same line of the body
+    cursor: usize,
+    // the first visible row
+    scroll: usize,
+    // rows that fit on the screen, set every time we render
+    height: usize,
+    // the first `g` of `gg`
+    pending_g: bool,
+    search: Option<Search>,
+    // where the cursor was when the search started, Esc goes back there
+    search_origin: usize,
+    search_error: Option<String>,
+}
+
+impl BodyViewer {
+    // Pretty printed by its syntax, `raw` shows it exactly as it came in
+    pub fn new(body: &str, syntax: Syntax, palette: Palette) -> Self {
+        let text = match syntax {
+            Syntax::Json => format_json(body),
+            _ => pretty_print(body, syntax).unwrap_or_else(|| body.to_string()),
+        };
+        Self::raw(&text, syntax, palette)
+    }
+
+    pub fn raw(text: &str, syntax: Syntax, palette: Palette) -> Self {
+        let lines: Vec<String> = text.lines().map(|line| line.to_string()).collect();
+        let blocks = match syntax {
+            Syntax::Json => find_blocks(&lines),
+            _ => HashMap::new(),
+        };
+        BodyViewer {
+            syntax,
+            highlighted: highlight_lines(&lines, syntax, &palette),
+            lines,
+            palette,
+            blocks,
+            folded: HashSet::new(),
+            cursor: 0,
+            scroll: 0,
+            height: 1,
+            pending_g: false,
+            search: None,
+            search_origin: 0,
+            search_error: None,
+        }
+    }
+
+    pub fn get_syntax(&self) -> Syntax {
+        self.syntax
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.lines.len()
+    }
+
+    pub fn get_scroll(&self) -> usize {
+        self.scroll
+    }
+
+    pub fn get_cursor(&self) -> usize {
+        self.cursor
+    }
+
+    pub fn set_height(&mut self, height: usize) {
+        self.height = height.max(1);
+        self.keep_cursor_visible();
+    }
+
+    // The line numbers of the body that aren't hidden inside a fold
+    pub fn visible_lines(&self) -> Vec<usize> {
+        let mut visible = Vec::new();
+        let mut line = 0;
+        while line < self.lines.len() {
+            visible.push(line);
+            line = match self.folded.contains(&line) {
+                true => self.blocks[&line] + 1,
+                false => line + 1,
+            };
+        }
+        visible
+    }
+
+    // A folded block shows its first and last line with the number of lines in between, search
+    // hits are marked on top of the syntax colors
+    pub fn get_line(&self, line: usize) -> Line<'static> {
+        let text = match self.folded.contains(&line) {
+            true => {
+                let end = self.blocks[&line];
+                let summary = format!(
+                    "{} … {}  ({} lines)",
+                    self.lines[line],
+                    self.lines[end].trim(),
+                    end - line - 1
+                );
+                highlight_json(&summary, &self.palette)
+            }
+            false => self.highlighted[line].clone(),
+        };
+        let Some(ref search) = self.search else {
+            return text;
+        };
+        let hits = search
+            .matches
+            .iter()
+            .enumerate()
+            .filter(|(_, (hit_line, _))| *hit_line == line)
+            .map(|(index, (_, range))| match search.current == Some(index) {
+                true => (range.clone(), self.palette.current_hit),
+                false => (range.clone(), self.palette.hit),
+            })
+            .collect::<Vec<_>>();
+        mark_ranges(text, &hits)
+    }
+
+    pub fn is_folded(&self, line: usize) -> bool {
+        self.folded.contains(&line)
+    }
+
+    fn cursor_row(&self, visible: &[usize]) -> usize {
+        visible
+            .iter()
+            .position(|line| *line == self.cursor)
+            .unwrap_or_default()
+    }
+
+    fn move_to_row(&mut self, row: usize) {
+        let visible = self.visible_lines();
+        if let Some(line) = visible.get(row.min(visible.len().saturating_sub(1))) {
+            self.cursor = *line;
+        }
+        self.keep_cursor_visible();
+    }
+
+    fn keep_cursor_visible(&mut self) {
+        let visible = self.visible_lines();
+        let row = self.cursor_row(&visible);
+        if row < self.scroll {
+            self.scroll = row;
+        } else if row >= self.scroll + self.height {
+            self.scroll = row + 1 - self.height;
+        }
+        self.scroll = self.scroll.min(visible.len().saturating_sub(self.height));
+    }
+
+    // j/k
+    pub fn down(&mut self, rows: usize) {
+        let row = self.cursor_row(&self.visible_lines());
+        self.move_to_row(row + rows);
+    }
+
+    pub fn up(&mut self, rows: usize) {
+        let row = self.cursor_row(&self.visible_lines());
+        self.move_to_row(row.saturating_sub(rows));
+    }
+
+    // Ctrl-d/Ctrl-u move the screen along with the cursor, like vim
+    pub fn half_page_down(&mut self) {
+        let half = (self.height / 2).max(1);
+        self.scroll += half;
+        self.down(half);
+    }
+
+    pub fn half_page_up(&mut self) {
+        let half = (self.height / 2).max(1);
+        self.scroll = self.scroll.saturating_sub(half);
+        self.up(half);
+    }
+
+    // gg
+    pub fn top(&mut self) {
+        self.move_to_row(0);
+    }
+
+    // G
+    pub fn bottom(&mut self) {
+        self.move_to_row(usize::MAX);
+    }
+
+    // The first `g` waits for the second, returns true when that was it
+    pub fn press_g(&mut self) -> bool {
+        self.pending_g = !self.pending_g;
+        if !self.pending_g {
+            self.top();
+        }
+        !self.pending_g
+    }
+
+    pub fn clear_pending(&mut self) {
+        self.pending_g = false;
+    }
+
+    // Remembers where the cursor is, so cancelling the search can go back there
+    pub fn start_search(&mut self) {
+        self.search_origin = self.cursor;
+        self.search_error = None;
+    }
+
+    // Finds every hit and jumps to the first one from where the search started, runs again with
+    // every key typed. Lowercase queries ignore case
+    pub fn search(&mut self, query: &str, forward: bool, regex: bool) {
+        self.search_error = None;
+        if query.is_empty() {
+            self.search = None;
+            self.cursor = self.search_origin;
+            self.keep_cursor_visible();
+            return;
+        }
+        let pattern = match regex {
+            true => query.to_string(),
+            false => regex::escape(query),
+        };
+        let matcher: Regex = match RegexBuilder::new(&pattern)
+            .case_insensitive(!query.chars().any(|c| c.is_uppercase()))
+            .build()
+        {
+            Ok(matcher) => matcher,
+            Err(_) => {
+                // half typed patterns are invalid all the time, keep the last hits until it's fixed
+                self.search_error = Some(format!("Error: Invalid regex '{}'", query));
+                return;
+            }
+        };
+        let matches = self
+            .lines
+            .iter()
+            .enumerate()
+            .flat_map(|(index, line)| {
+                matcher
+                    .find_iter(line)
+                    .filter(|hit| !hit.is_empty())
+                    .map(move |hit| (index, hit.range()))
+            })
+            .collect::<Vec<_>>();
+        let origin = self.search_origin;
+        let current = match forward {
+            true => matches
+                .iter()
+                .position(|(line, _)| *line >= origin)
+                .or((!matches.is_empty()).then_some(0)),
+            false => matches
+                .iter()
+                .rposition(|(line, _)| *line <= origin)
+                .or(matches.len().checked_sub(1)),
+        };
+        self.search = Some(Search {
+            query: query.to_string(),
+            forward,
+            regex,
+            matches,
+            current,
+        });
+        match current {
+            Some(current) => self.jump_to_match(current),
+            None => {
+                self.cursor = origin;
+                self.keep_cursor_visible();
+            }
+        }
+    }
+
+    // Esc while typing the search
+    pub fn cancel_search(&mut self) {
+        self.search = None;
+        self.search_error = None;
+        self.cursor = self.search_origin;
+        self.keep_cursor_visible();
+    }
+
+    // n goes on in the direction of the search, N (reverse) the other way, both wrap around
+    pub fn next_match(&mut self, reverse: bool) {
+        let Some(ref search) = self.search else {
+            return;
+        };
+        let count = search.matches.len();
+        let Some(current) = search.current else {
+            return;
+        };
+        let next = match search.forward != reverse {
+            true => (current + 1) % count,
+            false => (current + count - 1) % count,
+        };
+        self.jump_to_match(next);
+    }
+
+    // Opens the folds the hit is hidden in, like vim does
+    fn jump_to_match(&mut self, index: usize) {
+        let Some(ref mut search) = self.search else {
+            return;
+        };
+        search.current = Some(index);
+        let line = search.matches[index].0;
+        let blocks = &self.blocks;
+        self.folded
+            .retain(|start| !(*start < line && line <= blocks[start]));
+        self.cursor = line;
+        self.keep_cursor_visible();
+    }
+
+    pub fn get_search(&self) -> Option<&Search> {
+        self.search.as_ref()
+    }
+
+    pub fn get_search_error(&self) -> Option<&str> {
+        self.search_error.as_deref()
+    }
+
+    // New searches start in the mode of the last one
+    pub fn is_regex_search(&self) -> bool {
+        self.search.as_ref().is_some_and(|search| search.regex)
+    }
+
+    // Folds or unfolds the block opened on the cursor line, or else the innermost block the cursor
+    // is in
+    pub fn toggle_fold(&mut self) {
+        let start = match self.blocks.contains_key(&self.cursor) {
+            true => Some(self.cursor),
+            false => self
+                .blocks
+                .iter()
+                .filter(|(start, end)| **start < self.cursor && self.cursor <= **end)
+                .map(|(start, _)| *start)
+                .max(),
+        };
+        if let Some(start) = start {
+            if !self.folded.remove(&start) {
+                self.folded.insert(start);
+            }
+            self.cursor = start;
+            self.keep_cursor_visible();
+        }
+    }
+
+    // Folds every block below the top level, or opens them all again when some are folded
+    pub fn toggle_fold_all(&mut self) {
+        if self.folded.is_empty() {
+            let top = self.blocks.keys().min().copied();
+            self.folded = self
+                .blocks
+                .keys()
+                .filter(|start| Some(**start) != top)
+                .copied()
+                .collect();
+        } else {
+            self.folded.clear();
+        }
+        // the cursor goes to the fold it ended up inside of
+        if let Some(start) = self
+            .folded
+            .iter()
+            .filter(|start| **start < self.cursor && self.cursor <= self.blocks[start])
+            .min()
+        {
+            self.cursor = *start;
+        }
+        self.keep_cursor_visible();
+    }
+}
+
+impl Search {
+    pub fn get_query(&self) -> &str {
+        &self.query
+    }
+
+    pub fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    // 1 based, for "3/17" in the title
+    pub fn current_match(&self) -> Option<usize> {
+        self.current.map(|current| current + 1)
+    }
+}
+
+// Restyles byte ranges of a line on top of its colors, splitting the spans where a range starts
+// or ends
+fn mark_ranges(line: Line<'static>, ranges: &[(Range<usize>, Style)]) -> Line<'static> {
+    if ranges.is_empty() {
+        return line;
+    }
+    let mut spans = Vec::new();
+    let mut offset = 0;
+    for span in line.spans {
+        let text = span.content.to_string();
+        let end = offset + text.len();
+        let mut cuts = vec![0, text.len()];
+        for (range, _) in ranges {
+            for bound in [range.start, range.end] {
+                if bound > offset && bound < end {
+                    cuts.push(bound - offset);
+                }
+            }
+        }
+        cuts.sort_unstable();
+        cuts.dedup();
+        for cut in cuts.windows(2) {
+            let style = ranges
+                .iter()
+                .find(|(range, _)| range.contains(&(offset + cut[0])))
+                .map(|(_, style)| span.style.patch(*style))
+                .unwrap_or(span.style);
+            spans.push(Span::styled(text[cut[0]..cut[1]].to_string(), style));
+        }
+        offset = end;
+    }
+    Line::from(spans)
+}
+
+// Re-indents valid JSON two spaces per level, keeping the keys in the order they came in
+pub fn format_json(body: &str) -> String {
+    if serde_json::from_str::<serde_json::Value>(body).is_err() {
+        return body.to_string();
+    }
+    let chars: Vec<char> = body.trim().chars().collect();
+    let mut out = String::with_capacity(body.len() * 2);
+    let mut depth = 0;
+    let mut i = 0;
+    let newline = |out: &mut String, depth: usize| {
+        out.push('\n');
+        out.push_str(&"  ".repeat(depth));
+    };
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            '"' => {
+                out.push(c);
+                i += 1;
+                while i < chars.len() && chars[i] != '"' {
+                    if chars[i] == '\\' {
+                        out.push(chars[i]);
+                        i += 1;
+                    }
+                    out.push(chars[i]);
+                    i += 1;
+                }
+                out.push('"');
+            }
+            '{' | '[' => {
+                let close = if c == '{' { '}' } else { ']' };
+                let next = (i + 1..chars.len()).find(|j| !chars[*j].is_whitespace());
+                // empty objects and arrays stay on one line
+                if let Some(next) = next.filter(|next| chars[*next] == close) {
+                    out.push(c);
+                    out.push(close);
+                    i = next;
+                } else {
+                    depth += 1;
+                    out.push(c);
+                    newline(&mut out, depth);
+                }
+            }
+            '}' | ']' => {
+                depth -= 1;
+                newline(&mut out, depth);
+                out.push(c);
+            }
+            ',' => {
+                out.push(c);
+                newline(&mut out, depth);
+            }
+            ':' => out.push_str(": "),
+            c if c.is_whitespace() => {}
+            c => out.push(c),
+        }
+        i += 1;
+    }
+    out
+}
+
+// Matches each line ending in `{` or `[` to the line that closes it
+fn find_blocks(lines: &[String]) -> HashMap<usize, usize> {
+    let mut blocks = HashMap::new();
+    let mut open: Vec<usize> = Vec::new();
+    for (index, line) in lines.iter().enumerate() {
+        let line = line.trim();
+        if line.starts_with('}') || line.starts_with(']') {
+            if let Some(start) = open.pop() {
+                blocks.insert(start, index);
+            }
+        }
+        if line.ends_with('{') || line.ends_with('[') {
+            open.push(index);
+        }
+    }
+    blocks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tui::style::{Color, Style};
+
+    const BODY: &str =
+        r#"{"name":"CuTE","tags":["tui","http"],"empty":{},"owner":{"login":"a, b: {c}","id":1}}"#;
+
+    fn viewer(body: &str) -> BodyViewer {
+        let style = Style::default();
+        let palette = Palette {
+            text: style,
+            key: style,
+            string: style,
+            number: style,
+            comment: style,
+            hit: style.bg(Color::Yellow),
+            current_hit: style.bg(Color::Cyan),
+        };
+        BodyViewer::new(body, Syntax::detect(None, body), palette)
+    }
+
+    fn text(line: Line) -> String {
+        line.spans
+            .iter()
+            .map(|span| span.content.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_format_json() {
+        assert_eq!(
+            format_json(BODY),
+            r#"{
+  "name": "CuTE",
+  "tags": [
+    "tui",
+    "http"
+  ],
+  "empty": {},
+  "owner": {
+    "login": "a, b: {c}",
+    "id": 1
+  }
+}"#
+        );
+        assert_eq!(format_json("{not json"), "{not json");
+    }
+
+    #[test]
+    fn test_folding() {
+        let mut viewer = viewer(BODY);
+        assert_eq!(viewer.line_count(), 12);
+        viewer.down(3);
+        // inside the tags array, folds the array
+        viewer.toggle_fold();
+        assert_eq!(viewer.get_cursor(), 2);
+        assert!(viewer.is_folded(2));
+        assert_eq!(viewer.visible_lines(), vec![0, 1, 2, 6, 7, 8, 9, 10, 11]);
+        assert_eq!(text(viewer.get_line(2)), r#"  "tags": [ … ],  (2 lines)"#);
+        viewer.down(1);
+        assert_eq!(viewer.get_cursor(), 6);
+        viewer.up(1);
+        viewer.toggle_fold();
+        assert_eq!(viewer.visible_lines().len(), 12);
+
+        viewer.toggle_fold_all();
+        assert_eq!(viewer.visible_lines(), vec![0, 1, 2, 6, 7, 11]);
+        viewer.toggle_fold_all();
+        assert_eq!(viewer.visible_lines().len(), 12);
+    }
+
+    #[test]
+    fn test_scrolling() {
+        let body = (1..=100)
+            .map(|n| n.to_string())
+            .collect::<Vec<_>>()
+            .join("\n");
+        let mut viewer = viewer(&body);
+        viewer.set_height(10);
+        viewer.down(15);
+        assert_eq!(viewer.get_cursor(), 15);
+        assert_eq!(viewer.get_scroll(), 6);
+        viewer.half_page_down();
+        assert_eq!((viewer.get_cursor(), viewer.get_scroll()), (20, 11));
+        viewer.half_page_up();
+        assert_eq!((viewer.get_cursor(), viewer.get_scroll()), (15, 6));
+        viewer.bottom();
+        assert_eq!((viewer.get_cursor(), viewer.get_scroll()), (99, 90));
+        assert!(!viewer.press_g());
+        assert!(viewer.press_g());
+        assert_eq!((viewer.get_cursor(), viewer.get_scroll()), (0, 0));
+        viewer.up(1);
+        assert_eq!(viewer.get_cursor(), 0);
+    }
+
+    #[test]
+    fn test_search() {
+        let mut viewer = viewer(BODY);
+        let hits = |viewer: &BodyViewer, line: usize, style: Color| -> Vec<String> {
+            viewer
+                .get_line(line)
+                .spans
+                .iter()
+                .filter(|span| span.style.bg == Some(style))
+                .map(|span| span.content.to_string())
+                .collect()
+        };
+        viewer.down(3);
+        viewer.start_search();
+        // lowercase ignores case, the first hit from the cursor on
+        viewer.search("cute", true, false);
+        let search = viewer.get_search().unwrap();
+        assert_eq!((search.current_match(), search.match_count()), (Some(1), 1));
+        assert_eq!(viewer.get_cursor(), 1);
+        assert_eq!(hits(&viewer, 1, Color::Cyan), vec!["CuTE"]);
+        viewer.search("Cute", true, false);
+        assert_eq!(viewer.get_search().unwrap().current_match(), None);
+        assert_eq!(viewer.get_cursor(), 3);
+
+        // the 2 strings in the tags array
+        viewer.search(r#""(tui|http)""#, true, true);
+        assert_eq!(viewer.get_search().unwrap().match_count(), 2);
+        assert_eq!(viewer.get_cursor(), 3);
+        assert_eq!(hits(&viewer, 3, Color::Cyan), vec!["\"tui\""]);
+        assert_eq!(hits(&viewer, 4, Color::Yellow), vec!["\"http\""]);
+        // a half typed pattern keeps the last hits
+        viewer.search(r#""(tui|http"#, true, true);
+        assert!(viewer.get_search_error().is_some());
+        assert_eq!(viewer.get_search().unwrap().match_count(), 2);
+        // the same pattern is taken literally without regex
+        viewer.search(r#""(tui|http)""#, true, false);
+        assert_eq!(viewer.get_search().unwrap().match_count(), 0);
+
+        // n goes on in the direction of the search and N back, both wrap around
+        viewer.search(r#""(tui|http)""#, false, true);
+        assert_eq!(viewer.get_search().unwrap().current_match(), Some(1));
+        viewer.next_match(false);
+        assert_eq!(viewer.get_search().unwrap().current_match(), Some(2));
+        assert_eq!(viewer.get_cursor(), 4);
+        viewer.next_match(true);
+        viewer.next_match(true);
+        assert_eq!(viewer.get_search().unwrap().current_match(), Some(2));
+
+        // jumping to a hit opens the folds it's in
+        viewer.toggle_fold_all();
+        viewer.search("login", false, false);
+        assert_eq!(viewer.get_cursor(), 8);
+        assert!(!viewer.is_folded(7));
+        assert!(viewer.is_folded(2));
+
+        viewer.cancel_search();
+        assert!(viewer.get_search().is_none());
+        assert_eq!(viewer.get_cursor(), 3);
+    }
+}
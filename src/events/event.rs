@@ -5,9 +5,11 @@ use std::time::{Duration, Instant};
 use crossterm::event::{self, Event as CrosstermEvent, KeyEvent, MouseEvent};
 
 use crate::app::AppResult;
+use crate::request::command::CMD;
+use crate::request::runner::TestResult;
 
 /// Terminal events.
-#[derive(Clone, Copy, Debug)]
+#[derive(Debug)]
 pub enum Event {
     Tick,
     Key(KeyEvent),
@@ -15,6 +17,24 @@ pub enum Event {
     Mouse(MouseEvent),
     /// Terminal resize.
     Resize(u16, u16),
+    /// A command finished executing on its worker thread.
+    Executed(Executed),
+    /// A test run finished on its worker thread.
+    TestsFinished(Vec<TestResult>),
+}
+
+/// The command handed back by the worker thread, along with the result of executing it.
+pub struct Executed {
+    pub command: Box<dyn CMD>,
+    pub result: Result<(), String>,
+}
+
+impl std::fmt::Debug for Executed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Executed")
+            .field("result", &self.result)
+            .finish()
+    }
 }
 
 /// Terminal event handler.
@@ -69,6 +89,11 @@ impl EventHandler {
         }
     }
 
+    /// Returns a sender so worker threads can deliver their own events.
+    pub fn sender(&self) -> mpsc::Sender<Event> {
+        self.sender.clone()
+    }
+
     /// Receive the next event from the handler thread.
     ///
     /// This function will always block the current thread if
@@ -1,13 +1,29 @@
 use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
-use tui_input::InputRequest;
+use tui_input::{Input, InputRequest};
 
 use crate::app::InputMode;
 use crate::app::{App, AppResult};
+use crate::database::tree::TreeRow;
 use crate::display::inputopt::InputOpt;
+use crate::display::viewer::ViewInput;
+use crate::request::har::HarExport;
 use crate::screens::screen::Screen;
 
 /// Handles the key events and updates the state of [`App`].
 pub fn handle_key_events(key_event: KeyEvent, app: &mut App) -> AppResult<()> {
+    if app.is_executing() {
+        return handle_executing_keys(key_event, app);
+    }
+    if matches!(app.current_screen, Screen::ViewBody | Screen::ViewHeaders) {
+        if app.input_mode == InputMode::Editing {
+            return handle_view_input_keys(key_event, app);
+        } else if handle_viewer_keys(key_event, app) {
+            return Ok(());
+        }
+    }
+    if app.current_screen == Screen::Diff && handle_diff_keys(key_event, app) {
+        return Ok(());
+    }
     match app.input_mode {
         InputMode::Normal => {
             match key_event.kind {
@@ -17,10 +33,10 @@ pub fn handle_key_events(key_event: KeyEvent, app: &mut App) -> AppResult<()> {
                             app.quit();
                         }
                         // Exit application on `Ctrl-C`
-                        KeyCode::Char('c') | KeyCode::Char('C') => {
-                            if key_event.modifiers == KeyModifiers::CONTROL {
-                                app.quit();
-                            }
+                        KeyCode::Char('c') | KeyCode::Char('C')
+                            if key_event.modifiers == KeyModifiers::CONTROL =>
+                        {
+                            app.quit();
                         }
                         KeyCode::Esc => {
                             app.go_back_screen(); // Escape Should Bring You Back
@@ -44,9 +60,61 @@ pub fn handle_key_events(key_event: KeyEvent, app: &mut App) -> AppResult<()> {
                             }
                             app.select_item();
                         }
-                        KeyCode::Char('a') => {
-                            if app.current_screen == Screen::SavedKeys {
-                                app.goto_screen(Screen::InputMenu(InputOpt::ApiKey));
+                        KeyCode::Char('a') if app.current_screen == Screen::SavedKeys => {
+                            app.goto_screen(Screen::InputMenu(InputOpt::ApiKey));
+                        }
+                        KeyCode::Char('p') if app.current_screen == Screen::SavedKeys => {
+                            if app.db.is_locked() {
+                                app.goto_screen(Screen::InputMenu(InputOpt::Unlock));
+                            } else {
+                                app.goto_screen(Screen::InputMenu(InputOpt::NewPassphrase));
+                            }
+                        }
+                        KeyCode::Char('a') if app.current_screen == Screen::Environments => {
+                            app.goto_screen(Screen::InputMenu(InputOpt::EnvName));
+                        }
+                        KeyCode::Char('a') if app.current_screen == Screen::SavedCommands => {
+                            app.goto_screen(Screen::InputMenu(InputOpt::NewCollection(None)));
+                        }
+                        KeyCode::Char('t') if app.current_screen == Screen::SavedCommands => {
+                            app.spawn_tests(None);
+                        }
+                        KeyCode::Char('x') if app.current_screen == Screen::SavedCommands => {
+                            app.goto_screen(Screen::InputMenu(InputOpt::ExportHar(
+                                HarExport::Commands(None),
+                            )));
+                        }
+                        KeyCode::Char('o') if app.current_screen == Screen::SavedCommands => {
+                            if let Some(TreeRow::Collection { id, .. }) =
+                                app.get_command_tree().get(app.cursor)
+                            {
+                                app.goto_screen(Screen::CollectionMenu(*id));
+                            }
+                        }
+                        KeyCode::Char('a') => match app.current_screen {
+                            Screen::EnvVars(id) => {
+                                app.goto_screen(Screen::InputMenu(InputOpt::EnvVar(id)));
+                            }
+                            Screen::Assertions(id) => {
+                                app.goto_screen(Screen::InputMenu(InputOpt::Assertion(id, None)));
+                            }
+                            _ => {}
+                        },
+                        KeyCode::Char('r') if app.current_screen == Screen::HttpFile => {
+                            match app.reload_http_file() {
+                                Ok(_) => app.goto_screen(Screen::HttpFile),
+                                Err(e) => app.goto_screen(Screen::Error(e)),
+                            }
+                        }
+                        KeyCode::Char('/') => {
+                            if let Screen::History(_) = app.current_screen {
+                                app.goto_screen(Screen::InputMenu(InputOpt::HistorySearch));
+                            }
+                        }
+                        KeyCode::Char('x') => {
+                            if let Screen::History(ref query) = app.current_screen {
+                                let export = HarExport::History(query.clone());
+                                app.goto_screen(Screen::InputMenu(InputOpt::ExportHar(export)));
                             }
                         }
                         KeyCode::Char('i') => match app.current_screen {
@@ -90,18 +158,20 @@ pub fn handle_key_events(key_event: KeyEvent, app: &mut App) -> AppResult<()> {
                     app.input.reset();
                     app.input_mode = InputMode::Normal;
                 }
-                KeyCode::Char(c) => if app.input.handle(InputRequest::InsertChar(c)).is_some() {},
+                KeyCode::Char(c) => {
+                    app.input.handle(InputRequest::InsertChar(c));
+                }
                 KeyCode::Backspace => {
-                    if app.input.handle(InputRequest::DeletePrevChar).is_some() {}
+                    app.input.handle(InputRequest::DeletePrevChar);
+                }
+                KeyCode::Delete => {
+                    app.input.handle(InputRequest::DeleteNextChar);
+                }
+                KeyCode::Left => {
+                    app.input.handle(InputRequest::GoToPrevChar);
                 }
-                KeyCode::Delete => if app.input.handle(InputRequest::DeleteNextChar).is_some() {},
-                KeyCode::Left => if app.input.handle(InputRequest::GoToPrevChar).is_some() {},
                 KeyCode::Right => {
-                    if app
-                        .input
-                        .handle(tui_input::InputRequest::GoToNextChar)
-                        .is_some()
-                    {}
+                    app.input.handle(InputRequest::GoToNextChar);
                 }
                 KeyCode::Esc => {
                     app.input_mode = InputMode::Normal;
@@ -114,3 +184,155 @@ pub fn handle_key_events(key_event: KeyEvent, app: &mut App) -> AppResult<()> {
     }
     Ok(())
 }
+
+// While a command is running we only listen for cancel and quit
+fn handle_executing_keys(key_event: KeyEvent, app: &mut App) -> AppResult<()> {
+    if key_event.kind != KeyEventKind::Press {
+        return Ok(());
+    }
+    match key_event.code {
+        KeyCode::Esc => app.cancel_command(),
+        KeyCode::Char('x') | KeyCode::Char('X') if key_event.modifiers == KeyModifiers::CONTROL => {
+            app.cancel_command()
+        }
+        KeyCode::Char('c') | KeyCode::Char('C') if key_event.modifiers == KeyModifiers::CONTROL => {
+            app.quit()
+        }
+        KeyCode::Char('q') => app.quit(),
+        _ => {}
+    }
+    Ok(())
+}
+
+// Scrolling, folding and searching in the body and headers viewer, returns false for keys it
+// leaves to the other screens
+fn handle_viewer_keys(key_event: KeyEvent, app: &mut App) -> bool {
+    let Some(viewer) = app.viewer.as_mut() else {
+        return false;
+    };
+    if key_event.kind != KeyEventKind::Press {
+        return false;
+    }
+    if key_event.code != KeyCode::Char('g') {
+        viewer.clear_pending();
+    }
+    match key_event.code {
+        KeyCode::Char('d') if key_event.modifiers == KeyModifiers::CONTROL => {
+            viewer.half_page_down()
+        }
+        KeyCode::Char('u') if key_event.modifiers == KeyModifiers::CONTROL => viewer.half_page_up(),
+        KeyCode::Char('j') | KeyCode::Down => viewer.down(1),
+        KeyCode::Char('k') | KeyCode::Up => viewer.up(1),
+        KeyCode::PageDown => viewer.half_page_down(),
+        KeyCode::PageUp => viewer.half_page_up(),
+        KeyCode::Char('g') => {
+            viewer.press_g();
+        }
+        KeyCode::Home => viewer.top(),
+        KeyCode::Char('G') | KeyCode::End => viewer.bottom(),
+        KeyCode::Enter | KeyCode::Char(' ') => viewer.toggle_fold(),
+        KeyCode::Char('z') => viewer.toggle_fold_all(),
+        KeyCode::Char('/') | KeyCode::Char('?') => {
+            viewer.start_search();
+            app.view_input = Some(ViewInput::Search {
+                forward: key_event.code == KeyCode::Char('/'),
+                regex: viewer.is_regex_search(),
+            });
+            app.input = Input::default();
+            app.input_mode = InputMode::Editing;
+        }
+        KeyCode::Char('n') => viewer.next_match(false),
+        KeyCode::Char('N') => viewer.next_match(true),
+        KeyCode::Char('r') if app.current_screen == Screen::ViewBody => app.toggle_raw_body(),
+        KeyCode::Char('f') | KeyCode::Char('|') if app.current_screen == Screen::ViewBody => {
+            app.view_input = Some(ViewInput::Filter);
+            app.input = Input::new(app.body_filter.clone());
+            app.input_mode = InputMode::Editing;
+        }
+        _ => return false,
+    }
+    true
+}
+
+// Scrolling through the changes and switching between the body, JSON and header diffs
+fn handle_diff_keys(key_event: KeyEvent, app: &mut App) -> bool {
+    let Some(viewer) = app.diff.as_mut() else {
+        return false;
+    };
+    if key_event.kind != KeyEventKind::Press {
+        return false;
+    }
+    match key_event.code {
+        KeyCode::Char('d') if key_event.modifiers == KeyModifiers::CONTROL => {
+            viewer.half_page_down()
+        }
+        KeyCode::Char('u') if key_event.modifiers == KeyModifiers::CONTROL => viewer.half_page_up(),
+        KeyCode::Char('j') | KeyCode::Down => viewer.down(1),
+        KeyCode::Char('k') | KeyCode::Up => viewer.up(1),
+        KeyCode::PageDown => viewer.half_page_down(),
+        KeyCode::PageUp => viewer.half_page_up(),
+        KeyCode::Char('g') | KeyCode::Home => viewer.top(),
+        KeyCode::Char('G') | KeyCode::End => viewer.bottom(),
+        KeyCode::Char('n') => viewer.next_change(false),
+        KeyCode::Char('N') => viewer.next_change(true),
+        KeyCode::Char('m') | KeyCode::Tab => viewer.next_mode(),
+        _ => return false,
+    }
+    true
+}
+
+// Typing a filter or a search re-renders the body with every key
+fn handle_view_input_keys(key_event: KeyEvent, app: &mut App) -> AppResult<()> {
+    if key_event.kind != KeyEventKind::Press {
+        return Ok(());
+    }
+    let search = match app.view_input {
+        Some(ViewInput::Search { forward, regex }) => Some((forward, regex)),
+        _ => None,
+    };
+    let request = match key_event.code {
+        KeyCode::Enter | KeyCode::Esc => {
+            // Esc drops the search, an applied filter stays until it's cleared
+            if let (KeyCode::Esc, Some(_), Some(viewer)) =
+                (key_event.code, search, app.viewer.as_mut())
+            {
+                viewer.cancel_search();
+            }
+            app.view_input = None;
+            app.input_mode = InputMode::Normal;
+            return Ok(());
+        }
+        KeyCode::Char('r') if key_event.modifiers == KeyModifiers::CONTROL && search.is_some() => {
+            let (forward, regex) = search.unwrap();
+            app.view_input = Some(ViewInput::Search {
+                forward,
+                regex: !regex,
+            });
+            search_viewer(app);
+            return Ok(());
+        }
+        KeyCode::Char(c) => InputRequest::InsertChar(c),
+        KeyCode::Backspace => InputRequest::DeletePrevChar,
+        KeyCode::Delete => InputRequest::DeleteNextChar,
+        KeyCode::Left => InputRequest::GoToPrevChar,
+        KeyCode::Right => InputRequest::GoToNextChar,
+        _ => return Ok(()),
+    };
+    app.input.handle(request);
+    match search {
+        Some(_) => search_viewer(app),
+        None => {
+            let filter = app.input.value().to_string();
+            app.set_body_filter(&filter);
+        }
+    }
+    Ok(())
+}
+
+fn search_viewer(app: &mut App) {
+    if let (Some(ViewInput::Search { forward, regex }), Some(viewer)) =
+        (app.view_input, app.viewer.as_mut())
+    {
+        viewer.search(app.input.value(), forward, regex);
+    }
+}
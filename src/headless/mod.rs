@@ -0,0 +1,1086 @@
+/*
+* Headless - Run saved commands or one-off requests without starting the TUI,
+* so the same requests can be used from scripts and CI.
+ */
+use std::io::{IsTerminal, Write};
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+
+use crate::database::bundle::{Bundle, BundleFormat};
+use crate::database::db::{SavedCommand, DB};
+use crate::database::tree::{collection_commands, find_collection};
+use crate::display::menuopts::{
+    LOCKED_ERROR, METHOD_MENU_OPTIONS, NOT_JSON_ERROR, PASSPHRASE_MISMATCH_ERROR,
+};
+use crate::request::command::{CmdOpts, CurlOpts};
+use crate::request::curl::{AuthKind, Curl};
+use crate::request::diff::{self, DiffMode};
+use crate::request::har::HarExport;
+use crate::request::httpfile::HttpFile;
+use crate::request::importer::ImportFormat;
+use crate::request::response::Response;
+use crate::request::{jsonpath, runner};
+use report::{table_report, write_report, ReportFormat};
+
+// Table, JSON and JUnit XML reports of a test run
+pub mod report;
+
+// Exit codes returned by every headless command
+pub const EXIT_SUCCESS: i32 = 0;
+// The request could not be performed (connection refused, bad URL, etc)
+pub const EXIT_REQUEST_FAILED: i32 = 1;
+// Invalid arguments, same code clap uses for usage errors
+pub const EXIT_USAGE: i32 = 2;
+// The saved command could not be found in the database
+pub const EXIT_NOT_FOUND: i32 = 3;
+// The server responded, but with a 4xx or 5xx status
+pub const EXIT_HTTP_ERROR: i32 = 4;
+// At least one request in a test run failed its assertions
+pub const EXIT_TESTS_FAILED: i32 = 5;
+// The database is encrypted and the passphrase was wrong or missing
+pub const EXIT_LOCKED: i32 = 6;
+// The response body isn't JSON, or --filter can't be applied to it
+pub const EXIT_FILTER_FAILED: i32 = 7;
+// The two responses compared by `diff` are different
+pub const EXIT_DIFFERENT: i32 = 8;
+
+// Read instead of prompting, so scripts and CI can unlock the database
+pub const PASSPHRASE_ENV: &str = "CUTE_PASSPHRASE";
+pub const NEW_PASSPHRASE_ENV: &str = "CUTE_NEW_PASSPHRASE";
+
+/// A one-off request built from the command line.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct SendArgs {
+    pub method: String,
+    pub url: String,
+    pub headers: Vec<String>,
+    pub body: Option<String>,
+    pub user: Option<String>,
+    // jq style filter to print only part of a JSON response
+    pub filter: Option<String>,
+}
+
+/// Which saved commands to run as tests, and how to report the results.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct TestArgs {
+    // ID, path or name of a collection, None runs every saved command
+    pub collection: Option<String>,
+    pub report: ReportFormat,
+    // Write the report here and print the table to stdout, instead of printing the report
+    pub output: Option<String>,
+}
+
+/// What to export, and where to write it.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ExportArgs {
+    // ID, path or name of a collection, or a history search with `history`. None exports everything
+    pub collection: Option<String>,
+    // Export the request history instead of saved commands
+    pub history: bool,
+    // Write the file here, instead of printing it
+    pub output: Option<String>,
+}
+
+/// Which two responses to compare, and how.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct DiffArgs {
+    // the IDs of two history entries, or one saved command to run in both environments
+    pub targets: Vec<String>,
+    // names of the two environments to run the saved command in
+    pub envs: Vec<String>,
+    pub mode: DiffMode,
+}
+
+/// Prints every saved command as "ID<tab>command".
+pub fn list_commands(db: &DB, out: &mut dyn Write) -> i32 {
+    let commands = match db.get_commands() {
+        Ok(commands) => commands,
+        Err(e) => {
+            eprintln!("CuTE Database Error: {}", e);
+            return EXIT_REQUEST_FAILED;
+        }
+    };
+    for cmd in commands {
+        if writeln!(out, "{}\t{}", cmd.get_id(), cmd.get_command()).is_err() {
+            return EXIT_REQUEST_FAILED;
+        }
+    }
+    EXIT_SUCCESS
+}
+
+/// Looks up a saved command by its ID, or by its command string or URL.
+pub fn find_saved_command(db: &DB, target: &str) -> Option<SavedCommand> {
+    if let Ok(id) = target.parse::<i32>() {
+        if let Ok(Some(cmd)) = db.get_command_by_id(id) {
+            return Some(cmd);
+        }
+    }
+    db.get_commands().ok()?.into_iter().find(|cmd| {
+        cmd.get_command() == target
+            || serde_json::from_str::<Curl>(cmd.get_curl_json())
+                .map(|curl| curl.get_url() == target)
+                .unwrap_or(false)
+    })
+}
+
+/// Executes a saved command and prints the response, or what `filter` picks out of it, to `out`.
+pub fn run_saved_command(db: &DB, target: &str, filter: Option<&str>, out: &mut dyn Write) -> i32 {
+    let Some(saved) = find_saved_command(db, target) else {
+        eprintln!("Error: no saved command matching '{}'", target);
+        return EXIT_NOT_FOUND;
+    };
+    if saved.is_locked() {
+        eprintln!("{}", LOCKED_ERROR);
+        return EXIT_LOCKED;
+    }
+    // deserializing re-applies the stored options to the curl handle
+    let mut curl: Curl = match serde_json::from_str(saved.get_curl_json()) {
+        Ok(curl) => curl,
+        Err(e) => {
+            eprintln!("Error: saved command {} is invalid: {}", saved.get_id(), e);
+            return EXIT_REQUEST_FAILED;
+        }
+    };
+    curl.set_env_vars(db.get_request_vars());
+    execute_and_print(&mut curl, filter, out)
+}
+
+/// Runs the saved commands in a collection (or all of them) as tests and reports pass/fail for each.
+pub fn run_tests(db: &DB, args: &TestArgs, out: &mut dyn Write) -> i32 {
+    let (Ok(collections), Ok(commands)) = (db.get_collections(), db.get_commands()) else {
+        eprintln!("CuTE Database Error: unable to read saved commands");
+        return EXIT_REQUEST_FAILED;
+    };
+    let (id, name) = match args.collection.as_deref() {
+        Some(target) => match find_collection(&collections, target) {
+            Some(collection) => (Some(collection.get_id()), collection.get_name().to_string()),
+            None => {
+                eprintln!("Error: no collection matching '{}'", target);
+                return EXIT_NOT_FOUND;
+            }
+        },
+        None => (None, String::from("All saved commands")),
+    };
+    let env_vars = db.get_request_vars();
+    let commands = collection_commands(&collections, &commands, id);
+    let results = runner::run_tests(&commands, &env_vars, &Arc::new(AtomicBool::new(false)));
+    let report = write_report(args.report, &name, &results);
+    let printed = match args.output {
+        Some(ref path) => {
+            if let Err(e) = std::fs::write(path, report) {
+                eprintln!("Error: unable to write the report to {}: {}", path, e);
+                return EXIT_REQUEST_FAILED;
+            }
+            write!(out, "{}", table_report(&results))
+        }
+        None => write!(out, "{}", report),
+    };
+    if printed.is_err() {
+        return EXIT_REQUEST_FAILED;
+    }
+    if results.iter().all(|result| result.passed()) {
+        EXIT_SUCCESS
+    } else {
+        EXIT_TESTS_FAILED
+    }
+}
+
+/// Saves the requests in a Postman collection or OpenAPI spec and lists the items that were left out.
+pub fn import_collection(db: &DB, format: ImportFormat, path: &str, out: &mut dyn Write) -> i32 {
+    let input = match std::fs::read_to_string(path) {
+        Ok(input) => input,
+        Err(e) => {
+            eprintln!("Error: unable to read {}: {}", path, e);
+            return EXIT_NOT_FOUND;
+        }
+    };
+    let mut import = match format.parse(&input) {
+        Ok(import) => import,
+        Err(e) => {
+            eprintln!("{}", e);
+            return EXIT_USAGE;
+        }
+    };
+    let count = match import.save(db) {
+        Ok(count) => count,
+        Err(e) => {
+            eprintln!("CuTE Database Error: {}", e);
+            return EXIT_REQUEST_FAILED;
+        }
+    };
+    let mut lines = vec![format!(
+        "Imported {} commands into the collection '{}'",
+        count, import.name
+    )];
+    if !import.variables.is_empty() {
+        lines.push(format!(
+            "Set {} in the environment '{}'",
+            import
+                .variables
+                .iter()
+                .map(|(key, _)| format!("{{{{{}}}}}", key))
+                .collect::<Vec<String>>()
+                .join(", "),
+            import.name
+        ));
+    }
+    lines.extend(
+        import
+            .ignored
+            .iter()
+            .map(|item| format!("Not imported: {}", item)),
+    );
+    if writeln!(out, "{}", lines.join("\n")).is_err() {
+        return EXIT_REQUEST_FAILED;
+    }
+    EXIT_SUCCESS
+}
+
+/// Builds a request from the command line arguments, executes it and prints the response.
+pub fn send_request(args: &SendArgs, out: &mut dyn Write) -> i32 {
+    let method = args.method.to_uppercase();
+    if !METHOD_MENU_OPTIONS[1..].contains(&method.as_str()) {
+        eprintln!("Error: unsupported method '{}'", args.method);
+        return EXIT_USAGE;
+    }
+    if args.url.is_empty() {
+        eprintln!("Error: a URL is required");
+        return EXIT_USAGE;
+    }
+    if let Some(header) = args.headers.iter().find(|h| !h.contains(':')) {
+        eprintln!(
+            "Error: invalid header '{}', use the format \"Key:Value\"",
+            header
+        );
+        return EXIT_USAGE;
+    }
+    let mut curl = Curl::new();
+    curl.set_method(method);
+    curl.set_url(&args.url);
+    args.headers
+        .iter()
+        .for_each(|h| curl.add_headers(h.to_string()));
+    if let Some(ref body) = args.body {
+        curl.set_request_body(body);
+    }
+    if let Some(ref user) = args.user {
+        curl.set_auth(AuthKind::Basic(user.clone()));
+    }
+    execute_and_print(&mut curl, args.filter.as_deref(), out)
+}
+
+fn execute_and_print(curl: &mut Curl, filter: Option<&str>, out: &mut dyn Write) -> i32 {
+    // a typo in the filter shouldn't cost a request
+    let filters = match filter.map(jsonpath::parse_filter).transpose() {
+        Ok(filters) => filters,
+        Err(e) => {
+            eprintln!("{}", e);
+            return EXIT_USAGE;
+        }
+    };
+    if let Err(e) = curl.execute(None) {
+        eprintln!("Error: {}", e);
+        return EXIT_REQUEST_FAILED;
+    }
+    let output = match filters {
+        Some(filters) => {
+            let Some(json) = curl.get_json() else {
+                eprintln!("{}", NOT_JSON_ERROR);
+                return EXIT_FILTER_FAILED;
+            };
+            match jsonpath::apply_filter(json, &filters) {
+                Ok(results) => jsonpath::format_results(&results),
+                Err(e) => {
+                    eprintln!("{}", e);
+                    return EXIT_FILTER_FAILED;
+                }
+            }
+        }
+        None => curl.get_response(),
+    };
+    if writeln!(out, "{}", output).is_err() {
+        return EXIT_REQUEST_FAILED;
+    }
+    match curl.get_response_code() {
+        Some(code) if code >= 400 => EXIT_HTTP_ERROR,
+        _ => EXIT_SUCCESS,
+    }
+}
+
+/// Compares two responses from the history, or what a saved command gets back in two
+/// environments, and prints the diff.
+pub fn diff_responses(db: &DB, args: &DiffArgs, out: &mut dyn Write) -> i32 {
+    let (left, right) = match diff_sides(db, args) {
+        Ok(sides) => sides,
+        Err(code) => return code,
+    };
+    let rows = match diff::diff_responses(&left.1, &right.1, args.mode) {
+        Ok(rows) => rows,
+        Err(e) => {
+            eprintln!("{}", e);
+            return EXIT_FILTER_FAILED;
+        }
+    };
+    let diff = format!(
+        "--- {}\n+++ {}\n{}",
+        left.0,
+        right.0,
+        diff::format_unified(&rows)
+    );
+    if writeln!(out, "{}", diff.trim_end()).is_err() {
+        return EXIT_REQUEST_FAILED;
+    }
+    match diff::has_changes(&rows) {
+        true => EXIT_DIFFERENT,
+        false => EXIT_SUCCESS,
+    }
+}
+
+type DiffSide = (String, Response);
+
+fn diff_sides(db: &DB, args: &DiffArgs) -> Result<(DiffSide, DiffSide), i32> {
+    match (args.targets.as_slice(), args.envs.as_slice()) {
+        ([left, right], []) => Ok((history_response(db, left)?, history_response(db, right)?)),
+        ([target], [left, right]) => {
+            let Some(saved) = find_saved_command(db, target) else {
+                eprintln!("Error: no saved command matching '{}'", target);
+                return Err(EXIT_NOT_FOUND);
+            };
+            if saved.is_locked() {
+                eprintln!("{}", LOCKED_ERROR);
+                return Err(EXIT_LOCKED);
+            }
+            Ok((
+                env_response(db, &saved, left)?,
+                env_response(db, &saved, right)?,
+            ))
+        }
+        _ => {
+            eprintln!("Error: compare two history entries, or one saved command with --env twice");
+            Err(EXIT_USAGE)
+        }
+    }
+}
+
+fn history_response(db: &DB, id: &str) -> Result<DiffSide, i32> {
+    let entry = id
+        .trim_start_matches('#')
+        .parse::<i32>()
+        .ok()
+        .and_then(|id| db.get_history_by_id(id).ok().flatten());
+    match entry {
+        Some(entry) => Ok((entry.get_label(), entry.to_response())),
+        None => {
+            eprintln!("Error: no history entry with the ID '{}'", id);
+            Err(EXIT_NOT_FOUND)
+        }
+    }
+}
+
+// Runs the saved command with the variables of the environment instead of the active one
+fn env_response(db: &DB, saved: &SavedCommand, name: &str) -> Result<DiffSide, i32> {
+    let Some(env) = db
+        .get_environments()
+        .unwrap_or_default()
+        .into_iter()
+        .find(|env| env.get_name() == name)
+    else {
+        eprintln!("Error: no environment named '{}'", name);
+        return Err(EXIT_NOT_FOUND);
+    };
+    let mut curl: Curl = serde_json::from_str(saved.get_curl_json()).map_err(|e| {
+        eprintln!("Error: saved command {} is invalid: {}", saved.get_id(), e);
+        EXIT_REQUEST_FAILED
+    })?;
+    curl.set_env_vars(db.get_request_vars_in(Some(&env)));
+    if let Err(e) = curl.execute(None) {
+        eprintln!("Error: {} in {}", e, name);
+        return Err(EXIT_REQUEST_FAILED);
+    }
+    match curl.get_http_response() {
+        Some(response) => Ok((format!("{} ({})", curl.get_url(), name), response.clone())),
+        None => {
+            eprintln!("Error: no response from {} in {}", curl.get_url(), name);
+            Err(EXIT_REQUEST_FAILED)
+        }
+    }
+}
+
+/// Lists the requests in a .http file, or executes one of them (by number or name) and prints the response.
+pub fn run_http_file(
+    db: &DB,
+    path: &str,
+    target: Option<&str>,
+    filter: Option<&str>,
+    out: &mut dyn Write,
+) -> i32 {
+    let file = match HttpFile::open(path) {
+        Ok(file) => file,
+        Err(e) => {
+            eprintln!("{}", e);
+            return EXIT_NOT_FOUND;
+        }
+    };
+    let Some(target) = target else {
+        for (index, request) in file.get_requests().iter().enumerate() {
+            if writeln!(out, "{}\t{}", index + 1, request).is_err() {
+                return EXIT_REQUEST_FAILED;
+            }
+        }
+        return EXIT_SUCCESS;
+    };
+    let Some(request) = file.find_request(target) else {
+        eprintln!("Error: no request matching '{}' in {}", target, path);
+        return EXIT_NOT_FOUND;
+    };
+    let mut curl = match request.to_curl(file.get_variables()) {
+        Ok(curl) => curl,
+        Err(e) => {
+            eprintln!("{}", e);
+            return EXIT_USAGE;
+        }
+    };
+    curl.set_env_vars(db.get_request_vars());
+    execute_and_print(&mut curl, filter, out)
+}
+
+/// Exports history or saved commands, with their last responses and timings, as a HAR file.
+pub fn export_har(db: &DB, args: &ExportArgs, out: &mut dyn Write) -> i32 {
+    let export = if args.history {
+        HarExport::History(args.collection.clone().unwrap_or_default())
+    } else {
+        let Ok(collections) = db.get_collections() else {
+            eprintln!("CuTE Database Error: unable to read collections");
+            return EXIT_REQUEST_FAILED;
+        };
+        match args.collection.as_deref() {
+            Some(target) => match find_collection(&collections, target) {
+                Some(collection) => HarExport::Commands(Some(collection.get_id())),
+                None => {
+                    eprintln!("Error: no collection matching '{}'", target);
+                    return EXIT_NOT_FOUND;
+                }
+            },
+            None => HarExport::Commands(None),
+        }
+    };
+    let (har, json) = match export
+        .export(db)
+        .and_then(|har| har.to_json().map(|json| (har, json)))
+    {
+        Ok(exported) => exported,
+        Err(e) => {
+            eprintln!("{}", e);
+            return EXIT_REQUEST_FAILED;
+        }
+    };
+    let printed = match args.output {
+        Some(ref path) => {
+            if let Err(e) = std::fs::write(path, json) {
+                eprintln!("Error: unable to write the HAR file to {}: {}", path, e);
+                return EXIT_REQUEST_FAILED;
+            }
+            writeln!(
+                out,
+                "Exported {} requests to {}",
+                har.log.entries.len(),
+                path
+            )
+        }
+        None => writeln!(out, "{}", json),
+    };
+    if printed.is_err() {
+        return EXIT_REQUEST_FAILED;
+    }
+    EXIT_SUCCESS
+}
+
+/// Writes the whole database to a bundle, TOML if the output file ends in .toml, JSON otherwise.
+pub fn export_bundle(db: &DB, output: Option<&str>, out: &mut dyn Write) -> i32 {
+    let format = output.map(BundleFormat::from_path).unwrap_or_default();
+    let (bundle, text) = match Bundle::from_db(db)
+        .and_then(|bundle| bundle.to_string(format).map(|text| (bundle, text)))
+    {
+        Ok(exported) => exported,
+        Err(e) => {
+            eprintln!("{}", e);
+            return EXIT_REQUEST_FAILED;
+        }
+    };
+    let printed = match output {
+        Some(path) => {
+            if let Err(e) = std::fs::write(path, text) {
+                eprintln!("Error: unable to write the bundle to {}: {}", path, e);
+                return EXIT_REQUEST_FAILED;
+            }
+            writeln!(
+                out,
+                "Exported {} commands, {} keys and {} environments to {}",
+                bundle.commands.len(),
+                bundle.keys.len(),
+                bundle.environments.len(),
+                path
+            )
+        }
+        None => writeln!(out, "{}", text),
+    };
+    if printed.is_err() {
+        return EXIT_REQUEST_FAILED;
+    }
+    EXIT_SUCCESS
+}
+
+/// Adds what the database doesn't already have from a bundle, or only lists it with `dry_run`.
+pub fn import_bundle(db: &DB, path: &str, dry_run: bool, out: &mut dyn Write) -> i32 {
+    if let Err(e) = std::fs::metadata(path) {
+        eprintln!("Error: unable to read {}: {}", path, e);
+        return EXIT_NOT_FOUND;
+    }
+    let bundle = match Bundle::open(path) {
+        Ok(bundle) => bundle,
+        Err(e) => {
+            eprintln!("{}", e);
+            return EXIT_USAGE;
+        }
+    };
+    let summary = match bundle.import(db, dry_run) {
+        Ok(summary) => summary,
+        Err(e) => {
+            eprintln!("CuTE Database Error: {}", e);
+            return EXIT_REQUEST_FAILED;
+        }
+    };
+    let mut lines = vec![format!(
+        "{} {}",
+        if dry_run { "Would import" } else { "Imported" },
+        summary
+    )];
+    lines.extend(
+        summary
+            .duplicate_commands
+            .iter()
+            .map(|label| format!("  already saved: {}", label)),
+    );
+    if writeln!(out, "{}", lines.join("\n")).is_err() {
+        return EXIT_REQUEST_FAILED;
+    }
+    EXIT_SUCCESS
+}
+
+/// Reads a passphrase from `env_var`, or prompts for it on the terminal without echoing it.
+fn read_passphrase(env_var: &str, prompt: &str) -> std::io::Result<String> {
+    if let Ok(passphrase) = std::env::var(env_var) {
+        return Ok(passphrase);
+    }
+    let stdin = std::io::stdin();
+    eprint!("{}", prompt);
+    if !stdin.is_terminal() {
+        let mut line = String::new();
+        stdin.read_line(&mut line)?;
+        return Ok(line.trim_end_matches(['\r', '\n']).to_string());
+    }
+    crossterm::terminal::enable_raw_mode()?;
+    let passphrase = read_hidden();
+    crossterm::terminal::disable_raw_mode()?;
+    eprintln!();
+    passphrase
+}
+
+fn read_hidden() -> std::io::Result<String> {
+    use crossterm::event::{read, Event, KeyCode, KeyEventKind, KeyModifiers};
+    let mut passphrase = String::new();
+    loop {
+        if let Event::Key(key) = read()? {
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            match key.code {
+                KeyCode::Enter => return Ok(passphrase),
+                KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    return Err(std::io::ErrorKind::Interrupted.into());
+                }
+                KeyCode::Char(c) => passphrase.push(c),
+                KeyCode::Backspace => {
+                    passphrase.pop();
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Asks for the passphrase of an encrypted database, does nothing if it isn't encrypted.
+pub fn unlock(db: &mut DB) -> i32 {
+    if !db.is_locked() {
+        return EXIT_SUCCESS;
+    }
+    let passphrase = match read_passphrase(PASSPHRASE_ENV, "Passphrase: ") {
+        Ok(passphrase) => passphrase,
+        Err(e) => {
+            eprintln!("Error: unable to read the passphrase: {}", e);
+            return EXIT_LOCKED;
+        }
+    };
+    if let Err(e) = db.unlock(&passphrase) {
+        eprintln!("{}", e);
+        return EXIT_LOCKED;
+    }
+    EXIT_SUCCESS
+}
+
+/// Asks for the new passphrase twice, unless it's set in the environment.
+pub fn read_new_passphrase() -> Result<String, i32> {
+    if let Ok(passphrase) = std::env::var(NEW_PASSPHRASE_ENV) {
+        return Ok(passphrase);
+    }
+    let read = |prompt| {
+        read_passphrase(NEW_PASSPHRASE_ENV, prompt).map_err(|e| {
+            eprintln!("Error: unable to read the passphrase: {}", e);
+            EXIT_REQUEST_FAILED
+        })
+    };
+    let passphrase = read("New passphrase: ")?;
+    if read("Confirm passphrase: ")? != passphrase {
+        eprintln!("{}", PASSPHRASE_MISMATCH_ERROR);
+        return Err(EXIT_USAGE);
+    }
+    Ok(passphrase)
+}
+
+/// Encrypts the database with a new passphrase (re-encrypting everything if it already had one),
+/// or decrypts it for good when `passphrase` is None. The database must be unlocked first.
+pub fn rekey(db: &mut DB, passphrase: Option<&str>, out: &mut dyn Write) -> i32 {
+    let (result, done) = match passphrase {
+        Some(passphrase) => (
+            db.set_passphrase(passphrase),
+            "Your keys and saved auth are encrypted with the new passphrase",
+        ),
+        None => (
+            db.remove_passphrase(),
+            "The passphrase was removed, your keys and saved auth are stored in plaintext",
+        ),
+    };
+    if let Err(e) = result {
+        eprintln!("{}", e);
+        return EXIT_USAGE;
+    }
+    if writeln!(out, "{}", done).is_err() {
+        return EXIT_REQUEST_FAILED;
+    }
+    EXIT_SUCCESS
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::db::HistoryEntry;
+    use mockito::ServerGuard;
+
+    fn setup(method: &str, status: usize) -> ServerGuard {
+        let mut server = mockito::Server::new();
+        let _ = server
+            .mock(method, "/api/resource")
+            .with_status(status)
+            .with_body("Mocked Response")
+            .create();
+        server
+    }
+
+    fn send_args(method: &str, url: &str) -> SendArgs {
+        SendArgs {
+            method: method.to_string(),
+            url: url.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_send_request() {
+        let server = setup("POST", 200);
+        let mut args = send_args("post", &format!("{}/api/resource", server.url()));
+        args.headers
+            .push(String::from("Content-Type: application/json"));
+        args.body = Some(String::from("{\"key\": \"value\"}"));
+        let mut out = Vec::new();
+        assert_eq!(send_request(&args, &mut out), EXIT_SUCCESS);
+        assert_eq!(String::from_utf8(out).unwrap(), "Mocked Response\n");
+    }
+
+    #[test]
+    fn test_send_request_http_error() {
+        let server = setup("GET", 404);
+        let args = send_args("GET", &format!("{}/api/resource", server.url()));
+        let mut out = Vec::new();
+        assert_eq!(send_request(&args, &mut out), EXIT_HTTP_ERROR);
+    }
+
+    #[test]
+    fn test_send_request_bad_args() {
+        let mut out = Vec::new();
+        let args = send_args("FETCH", "http://localhost");
+        assert_eq!(send_request(&args, &mut out), EXIT_USAGE);
+        let mut args = send_args("GET", "http://localhost");
+        args.headers.push(String::from("no separator"));
+        assert_eq!(send_request(&args, &mut out), EXIT_USAGE);
+        assert!(out.is_empty());
+    }
+
+    #[test]
+    fn test_send_request_filter() {
+        let mut server = mockito::Server::new();
+        let _ = server
+            .mock("GET", "/api/items")
+            .with_header("Content-Type", "application/json")
+            .with_body(r#"{"items": [{"id": 1}, {"id": 2}], "data": {"a": 1}}"#)
+            .create();
+        let mut args = send_args("GET", &format!("{}/api/items", server.url()));
+        args.filter = Some(String::from(".items[].id"));
+        let mut out = Vec::new();
+        assert_eq!(send_request(&args, &mut out), EXIT_SUCCESS);
+        assert_eq!(String::from_utf8(out).unwrap(), "1\n2\n");
+
+        args.filter = Some(String::from(".data | length"));
+        let mut out = Vec::new();
+        assert_eq!(send_request(&args, &mut out), EXIT_SUCCESS);
+        assert_eq!(String::from_utf8(out).unwrap(), "1\n");
+
+        args.filter = Some(String::from(".items.id"));
+        assert_eq!(send_request(&args, &mut Vec::new()), EXIT_FILTER_FAILED);
+        args.filter = Some(String::from(".items |"));
+        assert_eq!(send_request(&args, &mut Vec::new()), EXIT_USAGE);
+        // not JSON
+        let server = setup("GET", 200);
+        let mut args = send_args("GET", &format!("{}/api/resource", server.url()));
+        args.filter = Some(String::from("."));
+        assert_eq!(send_request(&args, &mut Vec::new()), EXIT_FILTER_FAILED);
+    }
+
+    #[test]
+    fn test_send_request_connection_failed() {
+        let args = send_args("GET", "http://127.0.0.1:1");
+        let mut out = Vec::new();
+        assert_eq!(send_request(&args, &mut out), EXIT_REQUEST_FAILED);
+    }
+
+    #[test]
+    fn test_run_saved_command() {
+        let server = setup("GET", 200);
+        let url = format!("{}/api/resource", server.url());
+        let db = DB::new_in_memory().unwrap();
+        let mut curl = Curl::new();
+        curl.set_get_method();
+        curl.set_url(&url);
+        let cmd = curl.get_command_string();
+        db.add_command(&cmd, serde_json::to_string(&curl).unwrap())
+            .unwrap();
+
+        let mut out = Vec::new();
+        assert_eq!(run_saved_command(&db, "1", None, &mut out), EXIT_SUCCESS);
+        assert_eq!(String::from_utf8(out).unwrap(), "Mocked Response\n");
+        let mut out = Vec::new();
+        assert_eq!(run_saved_command(&db, &url, None, &mut out), EXIT_SUCCESS);
+        assert_eq!(run_saved_command(&db, "42", None, &mut out), EXIT_NOT_FOUND);
+
+        let mut out = Vec::new();
+        assert_eq!(list_commands(&db, &mut out), EXIT_SUCCESS);
+        assert_eq!(String::from_utf8(out).unwrap(), format!("1\t{}\n", cmd));
+    }
+
+    #[test]
+    fn test_run_tests() {
+        let server = setup("GET", 200);
+        let db = DB::new_in_memory().unwrap();
+        let mut curl = Curl::new();
+        curl.set_get_method();
+        curl.set_url(&format!("{}/api/resource", server.url()));
+        db.add_command(
+            &curl.get_command_string(),
+            serde_json::to_string(&curl).unwrap(),
+        )
+        .unwrap();
+        db.add_collection("smoke", None).unwrap();
+        db.move_command(1, Some(1)).unwrap();
+        db.rename_command(1, Some("resource")).unwrap();
+        db.set_command_assertions(1, &["status == 200".parse().unwrap()])
+            .unwrap();
+
+        let mut args = TestArgs {
+            collection: Some(String::from("smoke")),
+            ..Default::default()
+        };
+        let mut out = Vec::new();
+        assert_eq!(run_tests(&db, &args, &mut out), EXIT_SUCCESS);
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.contains("\nPASS    GET     resource  200   "));
+        assert!(out.contains("1 requests, 1 passed, 0 failed in "));
+
+        db.set_command_assertions(1, &["body ~ ^nope".parse().unwrap()])
+            .unwrap();
+        args.report = ReportFormat::Json;
+        let mut out = Vec::new();
+        assert_eq!(run_tests(&db, &args, &mut out), EXIT_TESTS_FAILED);
+        let report: serde_json::Value = serde_json::from_slice(&out).unwrap();
+        assert_eq!(report["name"], "smoke");
+        assert_eq!(
+            report["requests"][0]["assertions"][0]["message"],
+            "body does not match ^nope"
+        );
+
+        // with an output file the report goes there, and the table to stdout
+        let path = std::env::temp_dir().join("cute_test_report.xml");
+        args.report = ReportFormat::Junit;
+        args.output = Some(path.to_string_lossy().to_string());
+        let mut out = Vec::new();
+        assert_eq!(run_tests(&db, &args, &mut out), EXIT_TESTS_FAILED);
+        assert!(String::from_utf8(out).unwrap().starts_with("RESULT"));
+        let xml = std::fs::read_to_string(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert!(xml.contains("<testsuite name=\"smoke\" tests=\"1\" failures=\"1\""));
+
+        args.collection = Some(String::from("nope"));
+        assert_eq!(run_tests(&db, &args, &mut Vec::new()), EXIT_NOT_FOUND);
+    }
+
+    #[test]
+    fn test_import_collection() {
+        let path = std::env::temp_dir().join("cute_postman_collection.json");
+        std::fs::write(
+            &path,
+            r#"{"info": {"name": "Smoke"}, "item": [
+                {"name": "Health", "request": {"method": "GET", "url": "{{base_url}}/health"}},
+                {"name": "Trace", "request": {"method": "TRACE", "url": "{{base_url}}"}}
+            ]}"#,
+        )
+        .unwrap();
+        let db = DB::new_in_memory().unwrap();
+        let mut out = Vec::new();
+        let path = path.to_string_lossy().to_string();
+        let code = import_collection(&db, ImportFormat::Postman, &path, &mut out);
+        assert_eq!(code, EXIT_SUCCESS);
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "Imported 1 commands into the collection 'Smoke'\nNot imported: Trace: unsupported method TRACE\n"
+        );
+        assert_eq!(db.get_commands().unwrap()[0].get_label(), "Health");
+        // a Postman collection isn't a valid OpenAPI spec
+        let code = import_collection(&db, ImportFormat::OpenApi, &path, &mut Vec::new());
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(code, EXIT_USAGE);
+        let missing = "/nonexistent/collection.json";
+        assert_eq!(
+            import_collection(&db, ImportFormat::Postman, missing, &mut Vec::new()),
+            EXIT_NOT_FOUND
+        );
+    }
+
+    #[test]
+    fn test_run_http_file() {
+        let server = setup("POST", 200);
+        let path = std::env::temp_dir().join("cute_headless.http");
+        std::fs::write(
+            &path,
+            format!(
+                "@host = {}\n\n### health\nGET {{{{host}}}}/health\n\n### create\nPOST {{{{host}}}}/api/resource\nContent-Type: application/json\n\n{{\"id\": 1}}\n",
+                server.url()
+            ),
+        )
+        .unwrap();
+        let path = path.to_string_lossy().to_string();
+        let db = DB::new_in_memory().unwrap();
+        let mut out = Vec::new();
+        assert_eq!(
+            run_http_file(&db, &path, None, None, &mut out),
+            EXIT_SUCCESS
+        );
+        let listed = String::from_utf8(out).unwrap();
+        assert!(listed.starts_with("1\thealth | GET {{host}}/health\n2\tcreate | POST"));
+
+        let mut out = Vec::new();
+        assert_eq!(
+            run_http_file(&db, &path, Some("create"), None, &mut out),
+            EXIT_SUCCESS
+        );
+        assert!(String::from_utf8(out).unwrap().contains("Mocked Response"));
+        assert_eq!(
+            run_http_file(&db, &path, Some("3"), None, &mut Vec::new()),
+            EXIT_NOT_FOUND
+        );
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(
+            run_http_file(&db, &path, None, None, &mut Vec::new()),
+            EXIT_NOT_FOUND
+        );
+    }
+
+    #[test]
+    fn test_bundle() {
+        let db = DB::new_in_memory().unwrap();
+        db.add_command("curl http://localhost/health", String::from("{}"))
+            .unwrap();
+        db.add_key("secret-token").unwrap();
+        let path = std::env::temp_dir().join("cute_headless_bundle.toml");
+        let path = path.to_string_lossy().to_string();
+        let mut out = Vec::new();
+        assert_eq!(export_bundle(&db, Some(&path), &mut out), EXIT_SUCCESS);
+        assert!(String::from_utf8(out)
+            .unwrap()
+            .starts_with("Exported 1 commands, 1 keys and 0 environments to "));
+        assert!(std::fs::read_to_string(&path)
+            .unwrap()
+            .contains("version = 1"));
+
+        let other = DB::new_in_memory().unwrap();
+        let mut out = Vec::new();
+        assert_eq!(import_bundle(&other, &path, true, &mut out), EXIT_SUCCESS);
+        assert!(String::from_utf8(out)
+            .unwrap()
+            .starts_with("Would import 1 commands"));
+        assert!(other.get_commands().unwrap().is_empty());
+        let mut out = Vec::new();
+        assert_eq!(import_bundle(&db, &path, false, &mut out), EXIT_SUCCESS);
+        assert!(String::from_utf8(out)
+            .unwrap()
+            .contains("  already saved: curl http://localhost/health"));
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(
+            import_bundle(&db, &path, false, &mut Vec::new()),
+            EXIT_NOT_FOUND
+        );
+    }
+
+    #[test]
+    fn test_rekey() {
+        let mut db = DB::new_in_memory().unwrap();
+        db.add_key("secret-token").unwrap();
+        assert_eq!(rekey(&mut db, None, &mut Vec::new()), EXIT_USAGE);
+        let mut out = Vec::new();
+        assert_eq!(
+            rekey(&mut db, Some("correct horse"), &mut out),
+            EXIT_SUCCESS
+        );
+        assert!(String::from_utf8(out)
+            .unwrap()
+            .starts_with("Your keys and saved auth are encrypted"));
+        assert!(db.is_encrypted());
+        assert_eq!(
+            rekey(&mut db, Some("battery staple"), &mut Vec::new()),
+            EXIT_SUCCESS
+        );
+        assert_eq!(rekey(&mut db, None, &mut Vec::new()), EXIT_SUCCESS);
+        assert!(!db.is_encrypted());
+        assert_eq!(db.get_keys().unwrap()[0].get_key(), "secret-token");
+    }
+
+    #[test]
+    fn test_diff_responses() {
+        let mut server = mockito::Server::new();
+        for (env, body) in [
+            ("staging", r#"{"id":1,"name":"a"}"#),
+            ("prod", r#"{"name":"a","id":2}"#),
+        ] {
+            let _ = server
+                .mock("GET", format!("/{}/user", env).as_str())
+                .with_header("content-type", "application/json")
+                .with_body(body)
+                .create();
+        }
+        let db = DB::new_in_memory().unwrap();
+        for (id, env) in [(1, "staging"), (2, "prod")] {
+            db.add_environment(env).unwrap();
+            db.set_env_var(id, "base_url", &format!("{}/{}", server.url(), env))
+                .unwrap();
+        }
+        let mut curl = Curl::new();
+        curl.set_get_method();
+        curl.set_url("{{base_url}}/user");
+        db.add_command(
+            &curl.get_command_string(),
+            serde_json::to_string(&curl).unwrap(),
+        )
+        .unwrap();
+
+        // the same saved command in two environments, the key order doesn't matter to the JSON diff
+        let mut args = DiffArgs {
+            targets: vec![String::from("1")],
+            envs: vec![String::from("staging"), String::from("prod")],
+            mode: DiffMode::Json,
+        };
+        let mut out = Vec::new();
+        assert_eq!(diff_responses(&db, &args, &mut out), EXIT_DIFFERENT);
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.starts_with("--- "));
+        assert!(out.ends_with("- .id = 1\n+ .id = 2\n  .name = \"a\"\n"));
+        args.envs[1] = String::from("staging");
+        assert_eq!(diff_responses(&db, &args, &mut Vec::new()), EXIT_SUCCESS);
+        args.envs[1] = String::from("qa");
+        assert_eq!(diff_responses(&db, &args, &mut Vec::new()), EXIT_NOT_FOUND);
+        args.envs.pop();
+        assert_eq!(diff_responses(&db, &args, &mut Vec::new()), EXIT_USAGE);
+
+        // two responses from the history
+        let mut entry = HistoryEntry::new("http://localhost/a", "GET", String::from("{}"));
+        for body in ["one\nsame", "two\nsame"] {
+            entry.set_response(Some(200), 1, body.len(), Some(body.to_string()));
+            db.add_history(&entry).unwrap();
+        }
+        let mut args = DiffArgs {
+            targets: vec![String::from("1"), String::from("#2")],
+            ..Default::default()
+        };
+        let mut out = Vec::new();
+        assert_eq!(diff_responses(&db, &args, &mut out), EXIT_DIFFERENT);
+        assert!(String::from_utf8(out)
+            .unwrap()
+            .ends_with("- one\n+ two\n  same\n"));
+        args.mode = DiffMode::Json;
+        assert_eq!(
+            diff_responses(&db, &args, &mut Vec::new()),
+            EXIT_FILTER_FAILED
+        );
+        args.targets[1] = String::from("3");
+        assert_eq!(diff_responses(&db, &args, &mut Vec::new()), EXIT_NOT_FOUND);
+    }
+
+    #[test]
+    fn test_export_har() {
+        let db = DB::new_in_memory().unwrap();
+        let mut curl = Curl::new();
+        curl.set_url("http://localhost/health");
+        db.add_command(
+            "curl http://localhost/health",
+            serde_json::to_string(&curl).unwrap(),
+        )
+        .unwrap();
+        let mut out = Vec::new();
+        assert_eq!(
+            export_har(&db, &ExportArgs::default(), &mut out),
+            EXIT_SUCCESS
+        );
+        let har: serde_json::Value = serde_json::from_slice(&out).unwrap();
+        assert_eq!(
+            har["log"]["entries"][0]["request"]["url"],
+            "http://localhost/health"
+        );
+
+        let path = std::env::temp_dir().join("cute_history.har");
+        let args = ExportArgs {
+            collection: Some(String::from("posts")),
+            history: true,
+            output: Some(path.to_string_lossy().to_string()),
+        };
+        let mut out = Vec::new();
+        assert_eq!(export_har(&db, &args, &mut out), EXIT_SUCCESS);
+        let written = std::fs::read_to_string(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert!(written.contains("\"entries\": []"));
+        assert!(String::from_utf8(out)
+            .unwrap()
+            .starts_with("Exported 0 requests to "));
+
+        let args = ExportArgs {
+            collection: Some(String::from("missing")),
+            ..Default::default()
+        };
+        assert_eq!(export_har(&db, &args, &mut Vec::new()), EXIT_NOT_FOUND);
+    }
+}
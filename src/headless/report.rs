@@ -0,0 +1,333 @@
+/*
+* Reports written after a headless test run: a table for people, JSON and JUnit XML for CI.
+ */
+use std::fmt::Display;
+use std::str::FromStr;
+use std::time::Duration;
+
+use serde_json::{json, Value};
+
+use crate::request::runner::{summary, TestResult};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum ReportFormat {
+    #[default]
+    Table,
+    Json,
+    Junit,
+}
+
+impl Display for ReportFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReportFormat::Table => write!(f, "table"),
+            ReportFormat::Json => write!(f, "json"),
+            ReportFormat::Junit => write!(f, "junit"),
+        }
+    }
+}
+
+impl FromStr for ReportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "table" => Ok(ReportFormat::Table),
+            "json" => Ok(ReportFormat::Json),
+            "junit" | "xml" => Ok(ReportFormat::Junit),
+            _ => Err(format!(
+                "Error: unknown report format '{}', use table, json or junit",
+                s
+            )),
+        }
+    }
+}
+
+pub fn write_report(format: ReportFormat, name: &str, results: &[TestResult]) -> String {
+    match format {
+        ReportFormat::Table => table_report(results),
+        ReportFormat::Json => json_report(name, results),
+        ReportFormat::Junit => junit_report(name, results),
+    }
+}
+
+fn total_duration(results: &[TestResult]) -> Duration {
+    results.iter().map(|result| result.duration).sum()
+}
+
+fn status_text(result: &TestResult) -> String {
+    result
+        .status
+        .map(|status| status.to_string())
+        .unwrap_or(String::from("---"))
+}
+
+// One row per request with its failures listed under it, then the totals
+pub fn table_report(results: &[TestResult]) -> String {
+    let name_width = results
+        .iter()
+        .map(|result| result.label.chars().count())
+        .max()
+        .unwrap_or(0)
+        .max("NAME".len());
+    let method_width = results
+        .iter()
+        .map(|result| result.method.len())
+        .max()
+        .unwrap_or(0)
+        .max("METHOD".len());
+    let mut lines = vec![format!(
+        "{:<6}  {:<method_width$}  {:<name_width$}  {:<6}  {:>8}",
+        "RESULT", "METHOD", "NAME", "STATUS", "TIME"
+    )];
+    for result in results {
+        lines.push(format!(
+            "{:<6}  {:<method_width$}  {:<name_width$}  {:<6}  {:>6}ms",
+            if result.passed() { "PASS" } else { "FAIL" },
+            result.method,
+            result.label,
+            status_text(result),
+            result.duration.as_millis()
+        ));
+        lines.extend(
+            result
+                .failures()
+                .iter()
+                .map(|failure| format!("        - {}", failure)),
+        );
+    }
+    lines.push(format!(
+        "{} in {}ms",
+        summary(results),
+        total_duration(results).as_millis()
+    ));
+    lines.join("\n") + "\n"
+}
+
+pub fn json_report(name: &str, results: &[TestResult]) -> String {
+    let passed = results.iter().filter(|result| result.passed()).count();
+    let requests: Vec<Value> = results
+        .iter()
+        .map(|result| {
+            json!({
+                "id": result.id,
+                "name": result.label,
+                "method": result.method,
+                "url": result.url,
+                "status": result.status,
+                "duration_ms": result.duration.as_millis() as u64,
+                "size": result.size,
+                "passed": result.passed(),
+                "error": result.error,
+                "assertions": result
+                    .checks
+                    .iter()
+                    .map(|(assertion, check)| json!({
+                        "assertion": assertion.to_string(),
+                        "passed": check.is_ok(),
+                        "message": check.as_ref().err(),
+                    }))
+                    .collect::<Vec<Value>>(),
+            })
+        })
+        .collect();
+    let report = json!({
+        "name": name,
+        "total": results.len(),
+        "passed": passed,
+        "failed": results.len() - passed,
+        "duration_ms": total_duration(results).as_millis() as u64,
+        "requests": requests,
+    });
+    serde_json::to_string_pretty(&report).unwrap_or_default() + "\n"
+}
+
+fn escape_xml(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            '&' => String::from("&amp;"),
+            '<' => String::from("&lt;"),
+            '>' => String::from("&gt;"),
+            '"' => String::from("&quot;"),
+            '\'' => String::from("&apos;"),
+            c => c.to_string(),
+        })
+        .collect()
+}
+
+fn seconds(duration: Duration) -> String {
+    format!("{:.3}", duration.as_secs_f64())
+}
+
+// One testcase per request. Requests that never got a response are errors,
+// anything else that didn't pass is a failure
+pub fn junit_report(name: &str, results: &[TestResult]) -> String {
+    let errors = results
+        .iter()
+        .filter(|result| result.status.is_none() && !result.passed())
+        .count();
+    let failures = results
+        .iter()
+        .filter(|result| result.status.is_some() && !result.passed())
+        .count();
+    let name = escape_xml(name);
+    let time = seconds(total_duration(results));
+    let mut xml = vec![
+        String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>"),
+        format!(
+            "<testsuites name=\"CuTE\" tests=\"{}\" failures=\"{}\" errors=\"{}\" time=\"{}\">",
+            results.len(),
+            failures,
+            errors,
+            time
+        ),
+        format!(
+            "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"{}\" time=\"{}\">",
+            name,
+            results.len(),
+            failures,
+            errors,
+            time
+        ),
+    ];
+    for result in results {
+        let testcase = format!(
+            "    <testcase name=\"{} {}\" classname=\"{}\" time=\"{}\"",
+            escape_xml(&result.method),
+            escape_xml(&result.label),
+            name,
+            seconds(result.duration)
+        );
+        if result.passed() {
+            xml.push(format!("{} />", testcase));
+            continue;
+        }
+        xml.push(format!("{}>", testcase));
+        match (&result.error, result.status) {
+            (Some(error), None) => xml.push(format!(
+                "      <error message=\"{}\" type=\"request\" />",
+                escape_xml(error)
+            )),
+            (Some(error), Some(_)) => xml.push(format!(
+                "      <failure message=\"{}\" type=\"status\" />",
+                escape_xml(error)
+            )),
+            (None, _) => {}
+        }
+        for (assertion, check) in result.checks.iter() {
+            if let Err(message) = check {
+                xml.push(format!(
+                    "      <failure message=\"{}\" type=\"assertion\">{}</failure>",
+                    escape_xml(message),
+                    escape_xml(&assertion.to_string())
+                ));
+            }
+        }
+        xml.push(String::from("    </testcase>"));
+    }
+    xml.push(String::from("  </testsuite>"));
+    xml.push(String::from("</testsuites>"));
+    xml.join("\n") + "\n"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::request::assertion::Assertion;
+
+    fn results() -> Vec<TestResult> {
+        let passed = TestResult {
+            id: 1,
+            label: String::from("users"),
+            method: String::from("GET"),
+            url: String::from("http://localhost/users"),
+            status: Some(200),
+            duration: Duration::from_millis(12),
+            size: 2,
+            checks: vec![(Assertion::Status(200), Ok(()))],
+            error: None,
+        };
+        let failed = TestResult {
+            id: 2,
+            label: String::from("create <user>"),
+            method: String::from("POST"),
+            status: Some(500),
+            duration: Duration::from_millis(30),
+            checks: vec![(
+                Assertion::Status(201),
+                Err(String::from("expected status 201, got 500")),
+            )],
+            ..passed.clone()
+        };
+        let refused = TestResult {
+            id: 3,
+            label: String::from("health"),
+            status: None,
+            duration: Duration::from_millis(1),
+            size: 0,
+            checks: Vec::new(),
+            error: Some(String::from("Couldn't connect to server")),
+            ..passed.clone()
+        };
+        vec![passed, failed, refused]
+    }
+
+    #[test]
+    fn test_report_format() {
+        assert_eq!("JUnit".parse::<ReportFormat>(), Ok(ReportFormat::Junit));
+        assert_eq!("json".parse::<ReportFormat>(), Ok(ReportFormat::Json));
+        assert!("csv".parse::<ReportFormat>().is_err());
+        assert_eq!(ReportFormat::default().to_string(), "table");
+    }
+
+    #[test]
+    fn test_table_report() {
+        assert_eq!(
+            table_report(&results()),
+            "RESULT  METHOD  NAME           STATUS      TIME
+PASS    GET     users          200         12ms
+FAIL    POST    create <user>  500         30ms
+        - expected status 201, got 500
+FAIL    GET     health         ---          1ms
+        - Couldn't connect to server
+3 requests, 1 passed, 2 failed in 43ms
+"
+        );
+    }
+
+    #[test]
+    fn test_json_report() {
+        let report: Value = serde_json::from_str(&json_report("smoke", &results())).unwrap();
+        assert_eq!(report["name"], "smoke");
+        assert_eq!(report["total"], 3);
+        assert_eq!(report["failed"], 2);
+        assert_eq!(report["duration_ms"], 43);
+        assert_eq!(report["requests"][0]["status"], 200);
+        assert_eq!(report["requests"][0]["assertions"][0]["passed"], true);
+        assert_eq!(
+            report["requests"][1]["assertions"][0]["message"],
+            "expected status 201, got 500"
+        );
+        assert_eq!(report["requests"][2]["status"], Value::Null);
+        assert_eq!(report["requests"][2]["error"], "Couldn't connect to server");
+    }
+
+    #[test]
+    fn test_junit_report() {
+        let xml = junit_report("smoke & co", &results());
+        assert!(xml.contains(
+            "<testsuite name=\"smoke &amp; co\" tests=\"3\" failures=\"1\" errors=\"1\" time=\"0.043\">"
+        ));
+        assert!(xml.contains(
+            "<testcase name=\"GET users\" classname=\"smoke &amp; co\" time=\"0.012\" />"
+        ));
+        assert!(xml.contains("<testcase name=\"POST create &lt;user&gt;\""));
+        assert!(xml.contains(
+            "<failure message=\"expected status 201, got 500\" type=\"assertion\">status == 201</failure>"
+        ));
+        assert!(
+            xml.contains("<error message=\"Couldn&apos;t connect to server\" type=\"request\" />")
+        );
+        assert!(xml.ends_with("</testsuites>\n"));
+    }
+}
@@ -26,6 +26,9 @@ pub mod request;
 // Events & Event Handler
 pub mod events;
 
+// Running Requests From The Command Line Without The TUI
+pub mod headless;
+
 pub mod tui_cute;
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -33,6 +36,8 @@ pub struct Config {
     colors: Colors,
     logo: Option<Logo>,
     db_path: Option<PathBuf>,
+    // Whether response bodies are kept in the request history, defaults to true
+    history_bodies: Option<bool>,
 }
 
 impl Config {
@@ -51,6 +56,28 @@ impl Config {
     pub fn get_outline_color(&self) -> tui::style::Color {
         self.colors.outline.get_value()
     }
+    // Syntax colors of response bodies, older configs don't have them
+    pub fn get_key_color(&self) -> tui::style::Color {
+        self.colors
+            .key
+            .as_ref()
+            .unwrap_or(&self.colors.outline)
+            .get_value()
+    }
+    pub fn get_string_color(&self) -> tui::style::Color {
+        self.colors
+            .string
+            .as_ref()
+            .unwrap_or(&self.colors.body)
+            .get_value()
+    }
+    pub fn get_number_color(&self) -> tui::style::Color {
+        self.colors
+            .number
+            .as_ref()
+            .unwrap_or(&ConfigColor::Magenta)
+            .get_value()
+    }
     pub fn get_logo(&self) -> &str {
         if self.logo == Some(Logo::Default) {
             CUTE_LOGO
@@ -78,9 +105,13 @@ impl Config {
                 bg: ConfigColor::Black,
                 body: ConfigColor::Yellow,
                 outline: ConfigColor::Cyan,
+                key: Some(ConfigColor::Cyan),
+                string: Some(ConfigColor::Yellow),
+                number: Some(ConfigColor::Magenta),
             },
             logo: Some(Logo::Default),
             db_path: Some(DB::get_default_path()),
+            history_bodies: Some(true),
         }
     }
 
@@ -101,17 +132,17 @@ impl Config {
         }
     }
 
+    pub fn save_history_bodies(&self) -> bool {
+        self.history_bodies.unwrap_or(true)
+    }
+
     pub fn get_db_path(&self) -> Option<PathBuf> {
         self.db_path.as_ref().cloned()
     }
 }
 impl Default for Config {
     fn default() -> Self {
-        if let Ok(config) = Self::load() {
-            config
-        } else {
-            Self::get_default_config()
-        }
+        Self::load().unwrap_or_else(|_| Self::get_default_config())
     }
 }
 
@@ -137,6 +168,11 @@ pub struct Colors {
     bg: ConfigColor,
     body: ConfigColor,
     outline: ConfigColor,
+    // JSON keys and XML/HTML tags
+    key: Option<ConfigColor>,
+    string: Option<ConfigColor>,
+    // numbers, booleans, null and XML/HTML attribute names
+    number: Option<ConfigColor>,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -1,23 +1,33 @@
 #![allow(non_snake_case)]
 use clap::builder::Command;
-use clap::Arg;
+use clap::{Arg, ArgAction, ArgMatches};
 use dirs::config_dir;
 use once_cell::sync::Lazy;
 use std::io;
 use tui::backend::CrosstermBackend;
 use tui::Terminal;
 use CuTE_tui::app::{App, AppResult};
+use CuTE_tui::database::db::DB;
 use CuTE_tui::events::event::{Event, EventHandler};
 use CuTE_tui::events::handler::handle_key_events;
+use CuTE_tui::headless::{self, DiffArgs, ExportArgs, SendArgs, TestArgs};
 use CuTE_tui::tui_cute::Tui;
 use CuTE_tui::Config;
 
 fn main() -> AppResult<()> {
+    let args = parse_cmdline();
+    let config = handle_config_args(&args);
+    // Subcommands run headless, without ever touching the terminal
+    if let Some((name, sub_args)) = args.subcommand() {
+        std::process::exit(run_subcommand(name, sub_args));
+    }
     let mut app = App::new();
-    app.set_config(parse_cmdline().unwrap_or_default());
+    app.set_config(config.unwrap_or_default());
+    app.prompt_unlock();
     let backend = CrosstermBackend::new(io::stdout());
     let terminal = Terminal::new(backend)?;
     let events = EventHandler::new(250);
+    app.set_event_sender(events.sender());
     let mut tui = Tui::new(terminal, events);
     tui.init()?;
 
@@ -28,6 +38,8 @@ fn main() -> AppResult<()> {
             Event::Key(key_event) => handle_key_events(key_event, &mut app)?,
             Event::Mouse(_) => {}
             Event::Resize(_, _) => {}
+            Event::Executed(executed) => app.finish_command(executed),
+            Event::TestsFinished(results) => app.finish_tests(results),
         }
     }
 
@@ -44,26 +56,204 @@ pub static CONFIG_PATH: Lazy<String> = Lazy::new(|| {
         .to_string()
 });
 
-fn parse_cmdline() -> Option<Config> {
-    let args = Command::new("CuTE")
+fn parse_cmdline() -> ArgMatches {
+    Command::new("CuTE")
         .author("PThorpe92 <preston@unlockedlabs.org>")
         .version("0.1.0")
         .about("Simple TUI for libcurl powered http requests, wget powered recursive downloads, and API key/command storage")
         .after_help("Arguments are '--dump-config {path}' to write the default config file to the specified path,
             \nand '--db-path' to define a custom path to the database\nDB path can also be defined in the config file at $CONFIG/CuTE/config.toml\n
-            or you can set the $CUTE_DB_PATH environment variable")
+            or you can set the $CUTE_DB_PATH environment variable\n
+            Run a subcommand (list, run, send, test, import, export, http, diff, rekey) to make requests without starting the TUI")
         .arg(
             Arg::new("db-path")
                 .help("Define a custom path to the database")
                 .id("db-path")
-                .long("db-path"), // Added this line to indicate it takes a value
+                .long("db-path") // Added this line to indicate it takes a value
+                .global(true),
         )
         .arg(
             Arg::new("dump-config")
                 .help("Write the default config file to the current working directory")
                 .id("dump-config")
                 .long("dump-config")
-        ).get_matches();
+        )
+        .subcommand(Command::new("list").about("List your saved commands and their IDs"))
+        .subcommand(
+            Command::new("run")
+                .about("Execute a saved command and print the response")
+                .arg(
+                    Arg::new("command")
+                        .help("ID, command string or URL of the saved command")
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("filter")
+                        .help("Print only what a jq style filter picks out of a JSON response, e.g. '.items[].id' or '.data | length'")
+                        .long("filter"),
+                ),
+        )
+        .subcommand(
+            Command::new("test")
+                .about("Run saved commands as tests and check their assertions")
+                .arg(
+                    Arg::new("collection")
+                        .help("ID, path or name of the collection to run, runs every saved command if omitted"),
+                )
+                .arg(
+                    Arg::new("report")
+                        .help("Report format: table, json or junit")
+                        .long("report")
+                        .default_value("table"),
+                )
+                .arg(
+                    Arg::new("output")
+                        .help("Write the report to a file, and print a table of the results")
+                        .short('o')
+                        .long("output"),
+                ),
+        )
+        .subcommand(
+            Command::new("import")
+                .about("Import a Postman collection (v2.1), an OpenAPI 3 / Swagger 2 spec or a HAR file as saved commands, or a CuTE bundle")
+                .arg(
+                    Arg::new("format")
+                        .help("Format of the file: postman, openapi, har or bundle")
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("file")
+                        .help("Path to the collection, spec or bundle")
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("dry-run")
+                        .help("Only list what a bundle would add, without saving anything")
+                        .long("dry-run")
+                        .action(ArgAction::SetTrue),
+                ),
+        )
+        .subcommand(
+            Command::new("export")
+                .about("Export saved commands or history as HAR, or the whole database as a bundle")
+                .arg(
+                    Arg::new("format")
+                        .help("Format of the export: har, or bundle for commands, keys and environments")
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("collection")
+                        .help("ID, path or name of the collection to export, or a search with --history. Exports everything if omitted"),
+                )
+                .arg(
+                    Arg::new("history")
+                        .help("Export the request history instead of saved commands")
+                        .long("history")
+                        .action(ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("output")
+                        .help("Write the export to a file instead of printing it, bundles ending in .toml are written as TOML")
+                        .short('o')
+                        .long("output"),
+                ),
+        )
+        .subcommand(
+            Command::new("http")
+                .about("List the requests in a .http file, or execute one of them and print the response")
+                .arg(
+                    Arg::new("file")
+                        .help("Path to the .http file")
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("request")
+                        .help("Number or name of the request to execute, lists the requests if omitted"),
+                )
+                .arg(
+                    Arg::new("filter")
+                        .help("Print only what a jq style filter picks out of a JSON response, e.g. '.items[].id' or '.data | length'")
+                        .long("filter"),
+                ),
+        )
+        .subcommand(
+            Command::new("diff")
+                .about("Compare two responses from the history, or a saved command run in two environments")
+                .arg(
+                    Arg::new("targets")
+                        .help("IDs of two history entries, or the ID, command string or URL of a saved command with --env")
+                        .num_args(1..=2)
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("env")
+                        .help("Run the saved command in this environment, use twice to compare two environments")
+                        .short('e')
+                        .long("env")
+                        .action(ArgAction::Append),
+                )
+                .arg(
+                    Arg::new("mode")
+                        .help("What to compare: body, json (ignores the order of keys) or headers")
+                        .long("mode")
+                        .default_value("body"),
+                ),
+        )
+        .subcommand(
+            Command::new("rekey")
+                .about("Set, change or remove the master passphrase, also encrypts a plaintext database")
+                .arg(
+                    Arg::new("remove")
+                        .help("Remove the passphrase and store keys and auth in plaintext")
+                        .long("remove")
+                        .action(ArgAction::SetTrue),
+                ),
+        )
+        .subcommand(
+            Command::new("send")
+                .about("Send a one-off HTTP request and print the response")
+                .arg(
+                    Arg::new("method")
+                        .help("HTTP method: GET, POST, PUT, PATCH, DELETE or HEAD")
+                        .short('X')
+                        .long("method")
+                        .default_value("GET"),
+                )
+                .arg(
+                    Arg::new("url")
+                        .help("URL to send the request to")
+                        .long("url")
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("header")
+                        .help("Add a \"Key: Value\" header, can be used multiple times")
+                        .short('H')
+                        .long("header")
+                        .action(ArgAction::Append),
+                )
+                .arg(
+                    Arg::new("data")
+                        .help("Request body")
+                        .short('d')
+                        .long("data"),
+                )
+                .arg(
+                    Arg::new("user")
+                        .help("username:password for basic authentication")
+                        .short('u')
+                        .long("user"),
+                )
+                .arg(
+                    Arg::new("filter")
+                        .help("Print only what a jq style filter picks out of a JSON response, e.g. '.items[].id' or '.data | length'")
+                        .long("filter"),
+                ),
+        )
+        .get_matches()
+}
+
+fn handle_config_args(args: &ArgMatches) -> Option<Config> {
     if args.contains_id("dump-config") {
         let mut config_path: String = args
             .get_one::<String>("dump-config")
@@ -78,14 +268,164 @@ fn parse_cmdline() -> Option<Config> {
     }
     if args.contains_id("db-path") {
         let db_path: String = args
-            .get_one::<String>("db")
+            .get_one::<String>("db-path")
             .expect("Missing db-path argument")
             .to_string();
         let db_path = std::path::Path::new(&db_path);
         let db_path = std::fs::canonicalize(db_path).expect("Failed to canonicalize path");
+        // DB::new() reads the path from the environment
+        std::env::set_var("CUTE_DB_PATH", &db_path);
         let mut config = Config::default();
         config.set_db_path(db_path);
         return Some(config);
     }
     None
 }
+
+fn run_subcommand(name: &str, args: &ArgMatches) -> i32 {
+    let mut stdout = io::stdout();
+    match name {
+        "send" => {
+            let send_args = SendArgs {
+                method: args
+                    .get_one::<String>("method")
+                    .cloned()
+                    .unwrap_or_default(),
+                url: args.get_one::<String>("url").cloned().unwrap_or_default(),
+                headers: args
+                    .get_many::<String>("header")
+                    .map(|headers| headers.cloned().collect())
+                    .unwrap_or_default(),
+                body: args.get_one::<String>("data").cloned(),
+                user: args.get_one::<String>("user").cloned(),
+                filter: args.get_one::<String>("filter").cloned(),
+            };
+            headless::send_request(&send_args, &mut stdout)
+        }
+        "list" | "run" | "test" | "import" | "export" | "http" | "diff" | "rekey" => {
+            let mut db = match DB::new() {
+                Ok(db) => db,
+                Err(e) => {
+                    eprintln!("CuTE Database Error: {}", e);
+                    return headless::EXIT_REQUEST_FAILED;
+                }
+            };
+            let unlocked = headless::unlock(&mut db);
+            if unlocked != headless::EXIT_SUCCESS {
+                return unlocked;
+            }
+            if name == "list" {
+                headless::list_commands(&db, &mut stdout)
+            } else if name == "import" {
+                let file = args
+                    .get_one::<String>("file")
+                    .expect("Missing file argument");
+                let format = args
+                    .get_one::<String>("format")
+                    .expect("Missing format argument");
+                if format.eq_ignore_ascii_case("bundle") {
+                    return headless::import_bundle(
+                        &db,
+                        file,
+                        args.get_flag("dry-run"),
+                        &mut stdout,
+                    );
+                }
+                let format = match format.parse() {
+                    Ok(format) => format,
+                    Err(e) => {
+                        eprintln!("{}", e);
+                        return headless::EXIT_USAGE;
+                    }
+                };
+                headless::import_collection(&db, format, file, &mut stdout)
+            } else if name == "rekey" {
+                if args.get_flag("remove") {
+                    return headless::rekey(&mut db, None, &mut stdout);
+                }
+                match headless::read_new_passphrase() {
+                    Ok(passphrase) => headless::rekey(&mut db, Some(&passphrase), &mut stdout),
+                    Err(code) => code,
+                }
+            } else if name == "http" {
+                let file = args
+                    .get_one::<String>("file")
+                    .expect("Missing file argument");
+                let target = args.get_one::<String>("request").map(|s| s.as_str());
+                let filter = args.get_one::<String>("filter").map(|s| s.as_str());
+                headless::run_http_file(&db, file, target, filter, &mut stdout)
+            } else if name == "diff" {
+                let mode = match args.get_one::<String>("mode").map(|mode| mode.parse()) {
+                    Some(Ok(mode)) => mode,
+                    Some(Err(e)) => {
+                        eprintln!("{}", e);
+                        return headless::EXIT_USAGE;
+                    }
+                    None => Default::default(),
+                };
+                let diff_args = DiffArgs {
+                    targets: args
+                        .get_many::<String>("targets")
+                        .map(|targets| targets.cloned().collect())
+                        .unwrap_or_default(),
+                    envs: args
+                        .get_many::<String>("env")
+                        .map(|envs| envs.cloned().collect())
+                        .unwrap_or_default(),
+                    mode,
+                };
+                headless::diff_responses(&db, &diff_args, &mut stdout)
+            } else if name == "export" {
+                let format = args
+                    .get_one::<String>("format")
+                    .expect("Missing format argument");
+                let output = args.get_one::<String>("output").cloned();
+                if format.eq_ignore_ascii_case("bundle") {
+                    if args.contains_id("collection") || args.get_flag("history") {
+                        eprintln!("Error: a bundle always contains the whole database");
+                        return headless::EXIT_USAGE;
+                    }
+                    return headless::export_bundle(&db, output.as_deref(), &mut stdout);
+                }
+                if !format.eq_ignore_ascii_case("har") {
+                    eprintln!(
+                        "Error: unknown export format '{}', use har or bundle",
+                        format
+                    );
+                    return headless::EXIT_USAGE;
+                }
+                let export_args = ExportArgs {
+                    collection: args.get_one::<String>("collection").cloned(),
+                    history: args.get_flag("history"),
+                    output,
+                };
+                headless::export_har(&db, &export_args, &mut stdout)
+            } else if name == "test" {
+                let report = args
+                    .get_one::<String>("report")
+                    .map(|report| report.parse())
+                    .unwrap_or(Ok(Default::default()));
+                let report = match report {
+                    Ok(report) => report,
+                    Err(e) => {
+                        eprintln!("{}", e);
+                        return headless::EXIT_USAGE;
+                    }
+                };
+                let test_args = TestArgs {
+                    collection: args.get_one::<String>("collection").cloned(),
+                    report,
+                    output: args.get_one::<String>("output").cloned(),
+                };
+                headless::run_tests(&db, &test_args, &mut stdout)
+            } else {
+                let target = args
+                    .get_one::<String>("command")
+                    .expect("Missing command argument");
+                let filter = args.get_one::<String>("filter").map(|s| s.as_str());
+                headless::run_saved_command(&db, target, filter, &mut stdout)
+            }
+        }
+        _ => headless::EXIT_USAGE,
+    }
+}
@@ -0,0 +1,219 @@
+/*
+* Assertions checked against the response of a saved command when it is run as a test.
+ */
+use std::fmt::Display;
+use std::str::FromStr;
+use std::time::Duration;
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::jsonpath;
+use super::response::Response;
+use crate::display::menuopts::ASSERTION_ERROR;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Assertion {
+    // status == 200
+    Status(u16),
+    // header Content-Type, or header Content-Type == application/json
+    Header { name: String, value: Option<String> },
+    // json $.user.id == 5, the expected value is compared as JSON when it parses as JSON
+    JsonPath { path: String, expected: String },
+    // body ~ "id":\s*\d+
+    BodyMatches(String),
+    // time < 500, in milliseconds
+    MaxTime(u64),
+}
+
+impl Display for Assertion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Assertion::Status(status) => write!(f, "status == {}", status),
+            Assertion::Header { name, value: None } => write!(f, "header {}", name),
+            Assertion::Header {
+                name,
+                value: Some(value),
+            } => write!(f, "header {} == {}", name, value),
+            Assertion::JsonPath { path, expected } => write!(f, "json {} == {}", path, expected),
+            Assertion::BodyMatches(pattern) => write!(f, "body ~ {}", pattern),
+            Assertion::MaxTime(ms) => write!(f, "time < {}", ms),
+        }
+    }
+}
+
+impl FromStr for Assertion {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (kind, rest) = s.trim().split_once(char::is_whitespace).unwrap_or((s, ""));
+        let rest = rest.trim();
+        let assertion = match kind.to_lowercase().as_str() {
+            "status" => rest
+                .trim_start_matches("==")
+                .trim()
+                .parse::<u16>()
+                .ok()
+                .map(Assertion::Status),
+            "header" => {
+                let (name, value) = match rest.split_once("==") {
+                    Some((name, value)) => (name.trim(), Some(value.trim().to_string())),
+                    None => (rest, None),
+                };
+                Some(Assertion::Header {
+                    name: name.to_string(),
+                    value,
+                })
+                .filter(|_| !name.is_empty())
+            }
+            "json" => rest.split_once("==").and_then(|(path, expected)| {
+                jsonpath::parse_path(path).ok()?;
+                Some(Assertion::JsonPath {
+                    path: path.trim().to_string(),
+                    expected: expected.trim().to_string(),
+                })
+            }),
+            "body" => {
+                let pattern = rest.strip_prefix('~').unwrap_or(rest).trim();
+                Regex::new(pattern)
+                    .ok()
+                    .filter(|_| !pattern.is_empty())
+                    .map(|_| Assertion::BodyMatches(pattern.to_string()))
+            }
+            "time" => rest
+                .trim_start_matches('<')
+                .trim()
+                .trim_end_matches("ms")
+                .trim()
+                .parse::<u64>()
+                .ok()
+                .map(Assertion::MaxTime),
+            _ => None,
+        };
+        assertion.ok_or(String::from(ASSERTION_ERROR))
+    }
+}
+
+impl Assertion {
+    // Err holds a message explaining why the response failed the assertion
+    pub fn check(&self, response: &Response, elapsed: Duration) -> Result<(), String> {
+        match self {
+            Assertion::Status(status) if response.status == *status => Ok(()),
+            Assertion::Status(status) => Err(format!(
+                "expected status {}, got {}",
+                status, response.status
+            )),
+            Assertion::Header { name, value } => match (response.get_header(name), value) {
+                (None, _) => Err(format!("missing header {}", name)),
+                (Some(actual), Some(value)) if actual != value => Err(format!(
+                    "expected header {} to be {}, got {}",
+                    name, value, actual
+                )),
+                _ => Ok(()),
+            },
+            Assertion::JsonPath { path, expected } => {
+                let body = serde_json::from_str::<Value>(&response.body)
+                    .map_err(|_| String::from("response body is not JSON"))?;
+                let actual = jsonpath::query(&body, path)?
+                    .ok_or(format!("{} not found in the response", path))?;
+                // unquoted text is compared as a string, so `== alice` matches "alice"
+                let expected_value = serde_json::from_str::<Value>(expected)
+                    .unwrap_or(Value::String(expected.clone()));
+                if *actual == expected_value {
+                    Ok(())
+                } else {
+                    Err(format!(
+                        "expected {} to be {}, got {}",
+                        path, expected, actual
+                    ))
+                }
+            }
+            Assertion::BodyMatches(pattern) => {
+                let regex = Regex::new(pattern).map_err(|e| e.to_string())?;
+                if regex.is_match(&response.body) {
+                    Ok(())
+                } else {
+                    Err(format!("body does not match {}", pattern))
+                }
+            }
+            Assertion::MaxTime(ms) if elapsed.as_millis() <= u128::from(*ms) => Ok(()),
+            Assertion::MaxTime(ms) => Err(format!(
+                "took {}ms, expected at most {}ms",
+                elapsed.as_millis(),
+                ms
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response() -> Response {
+        Response::from_parts(
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\n",
+            String::from("{\"user\": {\"id\": 5, \"name\": \"alice\"}}"),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_parse_assertions() {
+        for text in [
+            "status == 200",
+            "header Content-Type",
+            "header Content-Type == application/json",
+            "json $.user.id == 5",
+            "body ~ \"id\":\\s*\\d+",
+            "time < 500",
+        ] {
+            assert_eq!(text.parse::<Assertion>().unwrap().to_string(), text);
+        }
+        assert_eq!(
+            "status 201".parse::<Assertion>(),
+            Ok(Assertion::Status(201))
+        );
+        assert_eq!(
+            "time 300ms".parse::<Assertion>(),
+            Ok(Assertion::MaxTime(300))
+        );
+        assert!("status ok".parse::<Assertion>().is_err());
+        assert!("header".parse::<Assertion>().is_err());
+        assert!("json $..id == 1".parse::<Assertion>().is_err());
+        assert!("body ~ (".parse::<Assertion>().is_err());
+        assert!("size < 10".parse::<Assertion>().is_err());
+    }
+
+    #[test]
+    fn test_check_assertions() {
+        let response = response();
+        let fast = Duration::from_millis(20);
+        let check = |text: &str| text.parse::<Assertion>().unwrap().check(&response, fast);
+        assert!(check("status == 200").is_ok());
+        assert_eq!(
+            check("status == 201"),
+            Err(String::from("expected status 201, got 200"))
+        );
+        assert!(check("header content-type").is_ok());
+        assert!(check("header Content-Type == application/json").is_ok());
+        assert!(check("header Content-Type == text/html").is_err());
+        assert_eq!(
+            check("header X-Request-Id"),
+            Err(String::from("missing header X-Request-Id"))
+        );
+        assert!(check("json $.user.id == 5").is_ok());
+        assert!(check("json $.user.name == alice").is_ok());
+        assert!(check("json $.user.name == \"alice\"").is_ok());
+        assert!(check("json $.user.id == \"5\"").is_err());
+        assert!(check("json $.user.email == alice@example.com").is_err());
+        assert!(check("body ~ \"name\":\\s*\"a").is_ok());
+        assert!(check("body ~ ^\\[").is_err());
+        assert!(check("time < 50").is_ok());
+        assert_eq!(
+            Assertion::MaxTime(10).check(&response, Duration::from_millis(15)),
+            Err(String::from("took 15ms, expected at most 10ms"))
+        );
+    }
+}
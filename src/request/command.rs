@@ -4,6 +4,8 @@ use super::{
 };
 use crate::database::db::DB;
 use std::fmt::{Display, Error, Formatter};
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
 
 pub enum Cmd<'a> {
     Curl(Curl<'a>),
@@ -24,7 +26,8 @@ impl Display for CmdType {
         }
     }
 }
-pub trait CMD: CurlOpts + CmdOpts {}
+// Send, so commands can be executed on a worker thread
+pub trait CMD: CurlOpts + CmdOpts + Send {}
 
 impl<'a> CMD for Cmd<'a> {}
 
@@ -49,6 +52,13 @@ impl<'a> CmdOpts for Cmd<'a> {
         }
     }
 
+    fn as_curl(&self) -> Option<&Curl<'_>> {
+        match self {
+            Cmd::Curl(curl) => Some(curl),
+            Cmd::Wget(_) => None,
+        }
+    }
+
     fn get_url(&self) -> String {
         match self {
             Cmd::Curl(curl) => curl.get_url(),
@@ -91,6 +101,18 @@ impl<'a> CmdOpts for Cmd<'a> {
             Cmd::Wget(wget) => wget.get_command_string(),
         }
     }
+    fn set_env_vars(&mut self, vars: Vec<(String, String)>) {
+        match self {
+            Cmd::Curl(curl) => curl.set_env_vars(vars),
+            Cmd::Wget(wget) => wget.set_env_vars(vars),
+        }
+    }
+    fn set_cancel_flag(&mut self, cancel: Arc<AtomicBool>) {
+        match self {
+            Cmd::Curl(curl) => curl.set_cancel_flag(cancel),
+            Cmd::Wget(wget) => wget.set_cancel_flag(cancel),
+        }
+    }
 }
 
 impl<'a> CurlOpts for Cmd<'a> {
@@ -254,6 +276,11 @@ pub trait CmdOpts {
     fn set_response(&mut self, response: &str);
     fn get_command_string(&mut self) -> String;
     fn has_auth(&self) -> bool;
+    fn as_curl(&self) -> Option<&Curl<'_>>;
+    // {{key}} in the command is replaced with the value when it's executed
+    fn set_env_vars(&mut self, vars: Vec<(String, String)>);
+    // Execution stops as soon as possible once the flag is set
+    fn set_cancel_flag(&mut self, cancel: Arc<AtomicBool>);
 }
 pub trait CurlOpts {
     fn set_request_body(&mut self, body: &str);
@@ -1,27 +1,49 @@
-use crate::database::db::DB;
+use crate::database::db::{KeyKind, SavedKey, DB};
 use serde::de::{MapAccess, Visitor};
 use serde::ser::SerializeStruct;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
-use curl::easy::{Auth, Easy2, Handler, List, WriteError};
+use curl::easy::{Auth, Easy2, Form, Handler, List, WriteError};
 use std::io::Read;
-use std::u8;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 use std::{
     fmt::{Display, Formatter},
     io::Write,
 };
 
 use super::command::{CmdOpts, CurlOpts, CMD};
-use crate::display::menuopts::CURL;
+use super::environment::expand_vars;
+use super::parser::quote_arg;
+use super::response::Response;
+use crate::display::highlight::Syntax;
+use crate::display::menuopts::{CANCELLED_ERROR, CURL};
+use crate::display::pretty::pretty_print;
 
-#[derive(Debug, Serialize, Deserialize, Eq, Clone, PartialEq)]
-struct Collector(Vec<u8>);
+const BEARER_PREFIX: &str = "Authorization: Bearer ";
+// the provider libcurl uses when CURLOPT_AWS_SIGV4 is enabled without one
+const AWS_SIGV4_PROVIDER: &str = "aws:amz";
+
+// (body, raw header lines, cancel flag)
+#[derive(Debug, Default, Clone)]
+struct Collector(Vec<u8>, Vec<u8>, Arc<AtomicBool>);
 
 impl Handler for Collector {
     fn write(&mut self, data: &[u8]) -> Result<usize, WriteError> {
         self.0.extend_from_slice(data);
         Ok(data.len())
     }
+
+    // libcurl calls this once per header line, including the status line of every response
+    fn header(&mut self, data: &[u8]) -> bool {
+        self.1.extend_from_slice(data);
+        true
+    }
+
+    // Returning false aborts the transfer
+    fn progress(&mut self, _dltotal: f64, _dlnow: f64, _ultotal: f64, _ulnow: f64) -> bool {
+        !self.2.load(Ordering::Relaxed)
+    }
 }
 
 #[derive(Debug)]
@@ -42,6 +64,12 @@ pub struct Curl<'a> {
     opts: Vec<CurlFlag<'a>>,
     // The response we get back from the command if not sent to file
     resp: Option<String>,
+    // The status, headers and body of the last response
+    response: Option<Box<Response>>,
+    // The body of the last response parsed as JSON, for filtering
+    json: Option<serde_json::Value>,
+    // Variables of the active environment, expanded into the request when it's executed
+    env_vars: Vec<(String, String)>,
     // Filepath of file to be uploaded
     upload_file: Option<String>,
     // Filepath of the response output file or download
@@ -136,7 +164,7 @@ impl<'de> Deserialize<'de> for Curl<'de> {
                         &_ => {}
                     }
                 }
-                let curl = Easy2::new(Collector(Vec::new()));
+                let curl = Easy2::new(Collector::default());
                 let mut res = Curl {
                     curl,
                     method: method.ok_or_else(|| serde::de::Error::missing_field("method"))?,
@@ -149,6 +177,9 @@ impl<'de> Deserialize<'de> for Curl<'de> {
                     upload_file: upload_file
                         .ok_or_else(|| serde::de::Error::missing_field("upload_file"))?,
                     outfile: outfile.ok_or_else(|| serde::de::Error::missing_field("outfile"))?,
+                    response: None,
+                    json: None,
+                    env_vars: Vec::new(),
                     save: (false, false),
                 };
                 res.easy_from_opts();
@@ -207,7 +238,7 @@ impl<'a> Clone for Curl<'a> {
         }
         curl.build_command_str();
         Self {
-            curl: Easy2::new(Collector(Vec::new())),
+            curl: Easy2::new(Collector::default()),
             method: self.method.clone(),
             auth: self.auth.clone(),
             cmd: self.cmd.clone(),
@@ -217,6 +248,9 @@ impl<'a> Clone for Curl<'a> {
             headers: self.headers.clone(),
             upload_file: self.upload_file.clone(),
             outfile: self.outfile.clone(),
+            response: self.response.clone(),
+            json: self.json.clone(),
+            env_vars: self.env_vars.clone(),
             save: self.save,
         }
     }
@@ -278,7 +312,7 @@ impl<'a> CMD for Curl<'a> {}
 impl<'a> Default for Curl<'a> {
     fn default() -> Self {
         Self {
-            curl: Easy2::new(Collector(Vec::new())),
+            curl: Easy2::new(Collector::default()),
             method: None,
             auth: AuthKind::None,
             cmd: String::from(CURL),
@@ -286,6 +320,9 @@ impl<'a> Default for Curl<'a> {
             opts: Vec::new(),
             headers: None,
             resp: None,
+            response: None,
+            json: None,
+            env_vars: Vec::new(),
             upload_file: None,
             outfile: None,
             save: (false, false),
@@ -312,7 +349,10 @@ impl<'a> CmdOpts for Curl<'a> {
     }
 
     fn set_outfile(&mut self, outfile: &str) {
-        self.add_flag(CurlFlag::Output(CurlFlagType::Output.get_value(), None));
+        self.add_flag(CurlFlag::Output(
+            CurlFlagType::Output.get_value(),
+            Some(String::from(outfile)),
+        ));
         self.outfile = Some(String::from(outfile));
     }
 
@@ -325,6 +365,20 @@ impl<'a> CmdOpts for Curl<'a> {
         self.auth != AuthKind::None
     }
 
+    fn as_curl(&self) -> Option<&Curl<'_>> {
+        Some(self)
+    }
+
+    fn set_env_vars(&mut self, vars: Vec<(String, String)>) {
+        self.env_vars = vars;
+    }
+
+    fn set_cancel_flag(&mut self, cancel: Arc<AtomicBool>) {
+        self.curl.get_mut().2 = cancel;
+        // libcurl only calls our progress callback with the progress meter enabled
+        self.curl.progress(true).unwrap();
+    }
+
     fn set_response(&mut self, response: &str) {
         self.resp = Some(String::from(response));
     }
@@ -336,16 +390,18 @@ impl<'a> CmdOpts for Curl<'a> {
     }
 
     fn execute(&mut self, mut db: Option<&mut Box<DB>>) -> Result<(), String> {
+        // a failed run mustn't leave the previous response behind
+        self.response = None;
+        self.resp = None;
+        self.json = None;
         let mut list = List::new();
         // Setup auth if we have it, will return whether we appended to the list
         let mut has_headers = self.handle_auth_exec(&mut list);
-        if self.headers.is_some() {
+        if let Some(ref headers) = self.headers {
             has_headers = true;
-            self.headers
-                .as_ref()
-                .unwrap()
+            headers
                 .iter()
-                .for_each(|h| list.append(h.as_str()).unwrap());
+                .for_each(|h| list.append(&expand_vars(h, &self.env_vars)).unwrap());
         }
         if self.will_save_command() {
             let _ = db.as_mut().unwrap().add_command(
@@ -353,16 +409,25 @@ impl<'a> CmdOpts for Curl<'a> {
                 serde_json::to_string(&self).unwrap_or(String::from("Error serializing command")),
             );
         }
-        if self.will_save_token() {
-            let _ = db
-                .unwrap()
-                .add_key(&self.auth.get_token().unwrap_or_default());
+        // a saved key is already saved
+        if self.will_save_token() && !self.uses_saved_key() {
+            let kind = match self.auth {
+                AuthKind::Basic(_) => KeyKind::Basic,
+                _ => KeyKind::Bearer,
+            };
+            let key = SavedKey::new(&self.auth.get_token().unwrap_or_default())
+                .with_kind(kind)
+                .with_host(self.get_host().as_deref());
+            let _ = db.unwrap().add_saved_key(&key);
         }
+        // the command was saved with its {{variables}}, now fill them in for this run
+        self.apply_env_vars();
         // We have to append the list of headers all at once
         // but if we never appended to the list, we skip this
         if has_headers {
             self.curl.http_headers(list).unwrap();
         }
+        self.apply_form_parts()?;
 
         // If we are uploading a file...
         if let Some(ref upload_file) = self.upload_file {
@@ -383,16 +448,34 @@ impl<'a> CmdOpts for Curl<'a> {
                 Err(String::from("Error making connection"))
             }
         } else {
-            self.curl.perform().unwrap();
+            // don't mix in anything from a previous run of this command
+            let contents = self.curl.get_mut();
+            contents.0.clear();
+            contents.1.clear();
+            if let Err(e) = self.curl.perform() {
+                if e.is_aborted_by_callback() {
+                    return Err(String::from(CANCELLED_ERROR));
+                }
+                return Err(e.to_string());
+            }
             let contents = self.curl.get_ref();
-            let res = String::from_utf8_lossy(&contents.0);
-            if let Ok(json) =
-                serde_json::from_str::<serde_json::Value>(&String::from_utf8_lossy(&contents.0))
-            {
-                self.resp = Some(serde_json::to_string_pretty(&json).unwrap());
+            let res = String::from_utf8_lossy(&contents.0).to_string();
+            self.response =
+                Response::from_parts(&String::from_utf8_lossy(&contents.1), res.clone())
+                    .ok()
+                    .map(Box::new);
+            self.json = serde_json::from_str::<serde_json::Value>(&res).ok();
+            if let Some(ref json) = self.json {
+                self.resp = Some(serde_json::to_string_pretty(json).unwrap());
                 Ok(())
             } else {
-                self.resp = Some(res.to_string());
+                // XML, HTML, form and YAML bodies are pretty printed by their Content-Type
+                let content_type = self
+                    .response
+                    .as_ref()
+                    .and_then(|response| response.get_header("Content-Type"));
+                let syntax = Syntax::detect(content_type, &res);
+                self.resp = Some(pretty_print(&res, syntax).unwrap_or(res));
                 Ok(())
             }
         }
@@ -402,12 +485,12 @@ impl<'a> CurlOpts for Curl<'a> {
     fn set_auth(&mut self, auth: AuthKind) {
         match auth {
             AuthKind::Basic(info) => self.set_basic_auth(info),
-            AuthKind::Ntlm => self.set_ntlm_auth(),
+            AuthKind::Ntlm => self.set_ntlm_auth(None),
             AuthKind::Bearer(token) => self.set_bearer_auth(token),
             AuthKind::AwsSigv4 => self.set_aws_sigv4_auth(),
             AuthKind::Digest(login) => self.set_digest_auth(&login),
             AuthKind::Spnego => self.set_spnego_auth(),
-            AuthKind::None => {}
+            AuthKind::None => self.clear_auth(),
         }
     }
 
@@ -433,7 +516,10 @@ impl<'a> CurlOpts for Curl<'a> {
     }
 
     fn set_referrer(&mut self, referrer: &str) {
-        let flag = CurlFlag::Referrer(CurlFlagType::Referrer.get_value(), None);
+        let flag = CurlFlag::Referrer(
+            CurlFlagType::Referrer.get_value(),
+            Some(String::from(referrer)),
+        );
         self.toggle_flag(&flag);
         self.curl.referer(referrer).unwrap();
     }
@@ -461,7 +547,10 @@ impl<'a> CurlOpts for Curl<'a> {
     }
 
     fn set_unix_socket(&mut self, socket: &str) {
-        let flag = CurlFlag::UnixSocket(CurlFlagType::UnixSocket.get_value(), None);
+        let flag = CurlFlag::UnixSocket(
+            CurlFlagType::UnixSocket.get_value(),
+            Some(String::from(socket)),
+        );
         self.toggle_flag(&flag);
         self.curl.unix_socket(socket).unwrap();
     }
@@ -477,8 +566,8 @@ impl<'a> CurlOpts for Curl<'a> {
     }
 
     fn add_headers(&mut self, headers: String) {
-        if self.headers.is_some() {
-            self.headers.as_mut().unwrap().push(headers);
+        if let Some(ref mut existing) = self.headers {
+            existing.push(headers);
         } else {
             self.headers = Some(vec![headers]);
         }
@@ -493,11 +582,8 @@ impl<'a> CurlOpts for Curl<'a> {
     }
 
     fn remove_headers(&mut self, headers: String) {
-        if self.headers.is_some() {
-            self.headers
-                .as_mut()
-                .unwrap()
-                .retain(|x| !headers.contains(x));
+        if let Some(ref mut existing) = self.headers {
+            existing.retain(|x| !headers.contains(x));
         }
     }
     fn match_wildcard(&mut self, opt: bool) {
@@ -513,7 +599,7 @@ impl<'a> CurlOpts for Curl<'a> {
     }
 
     fn set_user_agent(&mut self, ua: &str) {
-        let flag = CurlFlag::User(CurlFlagType::User.get_value(), None);
+        let flag = CurlFlag::User(CurlFlagType::User.get_value(), Some(String::from(ua)));
         self.toggle_flag(&flag);
         self.curl.useragent(ua).unwrap();
     }
@@ -528,7 +614,10 @@ impl<'a> CurlOpts for Curl<'a> {
     }
 
     fn set_ca_path(&mut self, ca_path: &str) {
-        let flag = CurlFlag::CaPath(CurlFlagType::CaPath.get_value(), None);
+        let flag = CurlFlag::CaPath(
+            CurlFlagType::CaPath.get_value(),
+            Some(String::from(ca_path)),
+        );
         self.toggle_flag(&flag);
         self.curl.cainfo(ca_path).unwrap();
     }
@@ -607,6 +696,89 @@ impl<'a> Curl<'a> {
         Self::default()
     }
 
+    // Deserialized flags borrow their names from the JSON, this makes
+    // a command that can outlive it, e.g. to store it in the App
+    pub fn into_owned(self) -> Curl<'static> {
+        Curl {
+            curl: self.curl,
+            method: self.method,
+            auth: self.auth,
+            cmd: self.cmd,
+            headers: self.headers,
+            url: self.url,
+            opts: self
+                .opts
+                .iter()
+                .map(|flag| CurlFlag::new(flag.get_type(), flag.get_arg()))
+                .collect(),
+            resp: self.resp,
+            upload_file: self.upload_file,
+            outfile: self.outfile,
+            response: self.response,
+            json: self.json,
+            env_vars: self.env_vars,
+            save: self.save,
+        }
+    }
+
+    pub fn get_method(&self) -> Option<&Method> {
+        self.method.as_ref()
+    }
+
+    pub fn get_headers(&self) -> Vec<String> {
+        self.headers.clone().unwrap_or_default()
+    }
+
+    pub fn get_auth(&self) -> &AuthKind {
+        &self.auth
+    }
+
+    // The auth refers to a saved key instead of holding the secret
+    pub fn uses_saved_key(&self) -> bool {
+        self.auth
+            .get_token()
+            .is_some_and(|token| token.starts_with("{{key:"))
+    }
+
+    // The host of the URL, with its {{variables}} filled in
+    pub fn get_host(&self) -> Option<String> {
+        expand_vars(&self.url, &self.env_vars)
+            .parse::<http::Uri>()
+            .ok()?
+            .host()
+            .map(String::from)
+    }
+
+    pub fn get_request_body(&self) -> Option<String> {
+        self.opts.iter().find_map(|flag| match flag {
+            CurlFlag::RequestBody(_, body) => body.clone(),
+            _ => None,
+        })
+    }
+
+    pub fn get_upload_file(&self) -> Option<&str> {
+        self.upload_file.as_deref()
+    }
+
+    pub fn get_outfile(&self) -> Option<&str> {
+        self.outfile.as_deref()
+    }
+
+    // The status, headers and body of the last response, None if we never got one
+    pub fn get_http_response(&self) -> Option<&Response> {
+        self.response.as_deref()
+    }
+
+    // The body of the last response, None if it wasn't JSON
+    pub fn get_json(&self) -> Option<&serde_json::Value> {
+        self.json.as_ref()
+    }
+
+    // The HTTP status code of the last transfer, None if we never got a response
+    pub fn get_response_code(&mut self) -> Option<u32> {
+        self.curl.response_code().ok().filter(|code| *code != 0)
+    }
+
     fn will_save_token(&self) -> bool {
         // (0: save_command, 1: save_token)
         self.save.1
@@ -624,7 +796,8 @@ impl<'a> Curl<'a> {
     // field, so we have to manually add, then set the options one at a time from the opts vector.
     // ANY time we get a command from the database to run, we have to call this method first.
     pub fn easy_from_opts(&mut self) {
-        let opts = self.opts.clone();
+        // the setters below add their flags back, so start from an empty list
+        let opts = std::mem::take(&mut self.opts);
         let url = self.url.clone();
         self.set_url(&url);
         if let Some(ref method) = self.method {
@@ -642,7 +815,7 @@ impl<'a> Curl<'a> {
                 CurlFlag::Verbose(..) => self.set_verbose(true),
                 CurlFlag::Headers(_, val) => self.add_headers(val.unwrap_or(String::new())),
                 CurlFlag::Output(..) => {
-                    if let Some(val) = opt.get_arg() {
+                    if let Some(val) = opt.get_arg().or(self.outfile.clone()) {
                         self.set_outfile(&val);
                     }
                 }
@@ -651,13 +824,16 @@ impl<'a> Curl<'a> {
                         self.add_cookie(val);
                     }
                 }
-                CurlFlag::MatchWildcard(..) => {
-                    self.curl.wildcard_match(true).unwrap();
+                CurlFlag::MatchWildcard(..) => self.match_wildcard(true),
+                CurlFlag::User(..) => {
+                    if let Some(val) = opt.get_arg() {
+                        self.set_user_agent(&val);
+                    }
                 }
-                CurlFlag::User(..) => {}
                 CurlFlag::Bearer(..) => {
                     if let Some(val) = opt.get_arg() {
-                        self.set_bearer_auth(val);
+                        let token = val.strip_prefix(BEARER_PREFIX).unwrap_or(&val);
+                        self.set_bearer_auth(token.to_string());
                     }
                 }
                 CurlFlag::Digest(..) => {
@@ -671,7 +847,7 @@ impl<'a> Curl<'a> {
                     }
                 }
                 CurlFlag::AnyAuth(..) => self.set_any_auth(),
-                CurlFlag::Ntlm(..) => self.set_ntlm_auth(),
+                CurlFlag::Ntlm(_, login) => self.set_ntlm_auth(login),
 
                 CurlFlag::AwsSigv4(..) => self.set_aws_sigv4_auth(),
 
@@ -704,15 +880,24 @@ impl<'a> Curl<'a> {
                 }
                 CurlFlag::CertInfo(..) => self.set_cert_info(true),
                 CurlFlag::FailOnError(..) => self.set_fail_on_error(true),
-                CurlFlag::Proxy(..) => {}
                 CurlFlag::ProxyTunnel(..) => self.set_proxy_tunnel(true),
-                CurlFlag::File(..) => {}
-                CurlFlag::DataUrlEncode(..) => {}
-                CurlFlag::Referrer(..) => {}
+                CurlFlag::Referrer(..) => {
+                    if let Some(val) = opt.get_arg() {
+                        self.set_referrer(&val);
+                    }
+                }
                 CurlFlag::FollowRedirects(..) => self.set_follow_redirects(true),
                 CurlFlag::TcpKeepAlive(..) => self.set_tcp_keepalive(true),
-                CurlFlag::PreventDefaultConfig(..) => {}
-                CurlFlag::Progress(..) => {}
+                CurlFlag::Progress(..) => self.enable_progress_bar(true),
+                CurlFlag::Proxy(..) => {
+                    if let Some(val) = opt.get_arg() {
+                        self.set_proxy(&val);
+                    }
+                }
+                // no easy handle option to restore, keep the flag so it's still exported
+                CurlFlag::File(..)
+                | CurlFlag::DataUrlEncode(..)
+                | CurlFlag::PreventDefaultConfig(..) => self.add_flag(opt),
                 CurlFlag::RequestBody(..) => {
                     if let Some(val) = opt.get_arg() {
                         self.set_request_body(&val);
@@ -777,6 +962,22 @@ impl<'a> Curl<'a> {
         self.auth = AuthKind::Spnego;
     }
 
+    // Drops the auth flags along with their secrets, before another auth takes their place
+    pub fn clear_auth(&mut self) {
+        self.opts.retain(|flag| {
+            !matches!(
+                flag,
+                CurlFlag::Bearer(..)
+                    | CurlFlag::Basic(..)
+                    | CurlFlag::Digest(..)
+                    | CurlFlag::Ntlm(..)
+                    | CurlFlag::AwsSigv4(..)
+                    | CurlFlag::SpnegoAuth(..)
+            )
+        });
+        self.auth = AuthKind::None;
+    }
+
     pub fn will_save_command(&self) -> bool {
         // (0: save_command, 1: save_token)
         self.save.0
@@ -807,15 +1008,31 @@ impl<'a> Curl<'a> {
         self.curl.custom_request("DELETE").unwrap();
     }
 
-    pub fn set_ntlm_auth(&mut self) {
-        self.add_flag(CurlFlag::Ntlm(CurlFlagType::Ntlm.get_value(), None));
+    // Without a login curl uses the credentials of the logged in user
+    pub fn set_ntlm_auth(&mut self, login: Option<String>) {
+        self.add_flag(CurlFlag::Ntlm(CurlFlagType::Ntlm.get_value(), login));
         self.auth = AuthKind::Ntlm;
     }
 
+    pub fn get_ntlm_login(&self) -> Option<String> {
+        self.opts.iter().find_map(|flag| match flag {
+            CurlFlag::Ntlm(_, login) => login.clone(),
+            _ => None,
+        })
+    }
+
+    pub fn set_proxy(&mut self, proxy: &str) {
+        self.add_flag(CurlFlag::Proxy(
+            CurlFlagType::Proxy.get_value(),
+            Some(proxy.to_string()),
+        ));
+        let _ = self.curl.proxy(proxy);
+    }
+
     pub fn set_bearer_auth(&mut self, token: String) {
         self.add_flag(CurlFlag::Bearer(
             CurlFlagType::Bearer.get_value(),
-            Some(format!("Authorization: Bearer {token}")),
+            Some(format!("{BEARER_PREFIX}{token}")),
         ));
         self.auth = AuthKind::Bearer(token);
     }
@@ -828,26 +1045,40 @@ impl<'a> Curl<'a> {
         self.curl.show_header(true).unwrap();
     }
 
+    // Builds a shell-quoted command line that can be pasted back into a terminal,
+    // or imported again with `parser::parse_curl_command`
     fn build_command_str(&mut self) {
-        let mut cmd: Vec<String> = vec![self.cmd.clone()];
-        if let Some(ref method) = &self.method {
-            cmd.push(String::from("-X"));
-            cmd.push(method.to_string());
-        }
-        cmd.push(self.url.clone());
-        for flag in &self.opts {
-            cmd.push(flag.get_value().to_string());
-            if let Some(arg) = &flag.get_arg() {
-                cmd.push(arg.to_owned());
+        let mut cmd: Vec<String> = vec![String::from(CURL)];
+        match self.method {
+            // -X HEAD makes curl wait for a body that never comes
+            Some(Method::Head) => cmd.push(String::from("-I")),
+            Some(ref method) => {
+                cmd.push(String::from("-X"));
+                cmd.push(method.to_string());
             }
+            None => {}
         }
-        if self.headers.is_some() {
-            self.headers.as_ref().unwrap().iter().for_each(|h| {
+        if !self.url.is_empty() {
+            cmd.push(self.url.clone());
+        }
+        // in the order the flags are defined below, so the same request always exports the same
+        // command however its flags were added
+        let mut flags: Vec<&CurlFlag> = self.opts.iter().collect();
+        flags.sort_by_key(|flag| flag.get_type());
+        for flag in flags {
+            cmd.extend(flag.to_args());
+        }
+        if let Some(ref headers) = self.headers {
+            headers.iter().for_each(|h| {
                 cmd.push(String::from("-H"));
                 cmd.push(h.clone());
             });
         }
-        self.cmd = cmd.join(" ").trim().to_string();
+        self.cmd = cmd
+            .iter()
+            .map(|arg| quote_arg(arg))
+            .collect::<Vec<String>>()
+            .join(" ");
     }
 
     pub fn handle_auth_exec(&mut self, list: &mut List) -> bool {
@@ -856,28 +1087,37 @@ impl<'a> Curl<'a> {
         match &self.auth {
             AuthKind::None => {}
             AuthKind::Basic(login) => {
+                let login = expand_vars(login, &self.env_vars);
                 self.curl
                     .username(login.split(':').next().unwrap())
                     .unwrap();
                 self.curl
-                    .password(login.split(':').last().unwrap())
+                    .password(login.split(':').next_back().unwrap())
                     .unwrap();
                 let _ = self.curl.http_auth(Auth::new().basic(true));
             }
             AuthKind::Bearer(token) => {
                 list_edited = true;
-                let _ = list.append(&format!("Authorization: Bearer {}", token.clone()));
+                let token = expand_vars(token, &self.env_vars);
+                let _ = list.append(&format!("Authorization: Bearer {}", token));
             }
             AuthKind::Digest(login) => {
+                let login = expand_vars(login, &self.env_vars);
                 self.curl
                     .username(login.split(':').next().unwrap())
                     .unwrap();
                 self.curl
-                    .password(login.split(':').last().unwrap())
+                    .password(login.split(':').next_back().unwrap())
                     .unwrap();
                 let _ = self.curl.http_auth(Auth::new().digest(true));
             }
             AuthKind::Ntlm => {
+                if let Some(login) = self.get_ntlm_login() {
+                    let login = expand_vars(&login, &self.env_vars);
+                    let (user, password) = login.split_once(':').unwrap_or((&login, ""));
+                    self.curl.username(user).unwrap();
+                    self.curl.password(password).unwrap();
+                }
                 let _ = self.curl.http_auth(Auth::new().ntlm(true));
             }
             AuthKind::Spnego => {
@@ -890,6 +1130,26 @@ impl<'a> Curl<'a> {
         list_edited
     }
 
+    // Sets the expanded url, cookies and body on the handle, leaving our own copies untouched.
+    // Done without variables too, so the handle doesn't keep what the last environment filled in
+    fn apply_env_vars(&mut self) {
+        if !self.url.is_empty() {
+            let _ = self.curl.url(&expand_vars(&self.url, &self.env_vars));
+        }
+        for flag in self.opts.iter() {
+            match flag {
+                CurlFlag::Cookie(_, Some(cookie)) => {
+                    let _ = self.curl.cookie(&expand_vars(cookie, &self.env_vars));
+                }
+                CurlFlag::RequestBody(_, Some(body)) => {
+                    let body = expand_vars(body, &self.env_vars);
+                    let _ = self.curl.post_fields_copy(body.as_bytes());
+                }
+                _ => {}
+            }
+        }
+    }
+
     pub fn url_encode(&mut self, data: &str) {
         self.add_flag(CurlFlag::DataUrlEncode(
             CurlFlagType::DataUrlEncode.get_value(),
@@ -898,6 +1158,42 @@ impl<'a> Curl<'a> {
         self.url = self.curl.url_encode(data.as_bytes());
     }
 
+    // A multipart form field like curl's -F, "name=value" or "name=@path" to send a file
+    pub fn add_form_part(&mut self, part: &str) {
+        self.add_flag(CurlFlag::File(
+            CurlFlagType::File.get_value(),
+            Some(part.to_string()),
+        ));
+    }
+
+    pub fn get_form_parts(&self) -> Vec<String> {
+        self.opts
+            .iter()
+            .filter(|flag| matches!(flag, CurlFlag::File(..)))
+            .filter_map(|flag| flag.get_arg())
+            .collect()
+    }
+
+    // Sends the -F parts as a multipart/form-data body
+    fn apply_form_parts(&mut self) -> Result<(), String> {
+        let parts = self.get_form_parts();
+        if parts.is_empty() {
+            return Ok(());
+        }
+        let mut form = Form::new();
+        for part in parts {
+            let part = expand_vars(&part, &self.env_vars);
+            let (name, value) = part.split_once('=').unwrap_or((&part, ""));
+            let mut field = form.part(name);
+            match value.strip_prefix('@') {
+                Some(path) => field.file(path),
+                None => field.contents(value.as_bytes()),
+            };
+            field.add().map_err(|e| e.to_string())?;
+        }
+        self.curl.httppost(form).map_err(|e| e.to_string())
+    }
+
     pub fn add_flag(&mut self, flag: CurlFlag<'a>) {
         self.opts.push(flag);
     }
@@ -936,9 +1232,15 @@ macro_rules! define_curl_flags {
                     $( CurlFlag::$variant(flag, _) => flag, )*
                 }
             }
+
+            pub fn get_type(&self) -> CurlFlagType {
+                match self {
+                    $( CurlFlag::$variant(..) => CurlFlagType::$variant, )*
+                }
+            }
         }
 
-        #[derive(Debug, Eq, Copy, Clone, PartialEq)]
+        #[derive(Debug, Eq, Copy, Clone, PartialEq, PartialOrd, Ord)]
         pub enum CurlFlagType {
             $( $variant, )*
         }
@@ -956,34 +1258,68 @@ define_curl_flags! {
     Verbose("-v"),
     Cookie("-b"),
     Output("-o"),
-    User("-u"),
-    Bearer("-H"),// bearer/basic auth are just headers
+    // the user agent
+    User("-A"),
+    Bearer("-H"),// bearer auth is just a header
     CertInfo("--certinfo"),
     Headers("-H"),
     Digest("--digest"),
-    Basic("-H"),
-    AnyAuth("--any-auth"),
+    Basic("-u"),
+    AnyAuth("--anyauth"),
     UnixSocket("--unix-socket"),
     UploadFile("--upload-file"),
     Ntlm("--ntlm"),
     Proxy("-x"),
     AwsSigv4("--aws-sigv4"),
-    ProxyTunnel("--proxy-tunnel"),
+    ProxyTunnel("--proxytunnel"),
     File("-F"),
     DataUrlEncode("--data-urlencode"),
-    DumpHeaders("--dump-headers"),
+    DumpHeaders("--dump-header"),
     Referrer("-e"),
     MatchWildcard("--glob"),
     FailOnError("--fail"),
     FollowRedirects("-L"),
     MaxRedirects("--max-redirs"),
     PreventDefaultConfig("-q"),
-    // is default on CLI, so flag has no value
-    TcpKeepAlive(" "),
-    CaPath("--capath"),
-    SpnegoAuth("--negotiate -u:"),
+    // is default on CLI, but we spell it out
+    TcpKeepAlive("--keepalive"),
+    // we load the file with CURLOPT_CAINFO
+    CaPath("--cacert"),
+    SpnegoAuth("--negotiate"),
     Progress("--progress-bar"),
-    RequestBody("--data"),
+    // --data would read the body from a file if it starts with '@'
+    RequestBody("--data-raw"),
+}
+
+impl<'a> CurlFlag<'a> {
+    // The arguments this flag exports as. We always use the current flag for the variant,
+    // because the one stored with the flag may be from an older version of a saved command.
+    pub fn to_args(&self) -> Vec<String> {
+        let flag = String::from(self.get_type().get_value());
+        match self {
+            CurlFlag::Bearer(..) => {
+                let header = self.get_arg().unwrap_or_default();
+                let token = header.strip_prefix(BEARER_PREFIX).unwrap_or(&header);
+                vec![flag, format!("{BEARER_PREFIX}{token}")]
+            }
+            CurlFlag::Digest(..) | CurlFlag::Ntlm(..) => match self.get_arg() {
+                Some(login) => vec![flag, String::from("-u"), login],
+                None => vec![flag],
+            },
+            // curl needs -u to enable SPNEGO, but the credentials come from the ticket
+            CurlFlag::SpnegoAuth(..) => vec![flag, String::from("-u"), String::from(":")],
+            CurlFlag::AwsSigv4(..) => vec![
+                flag,
+                self.get_arg()
+                    .unwrap_or_else(|| String::from(AWS_SIGV4_PROVIDER)),
+            ],
+            _ => {
+                let mut args = vec![flag];
+                args.extend(self.get_arg());
+                args
+            }
+        }
+    }
 }
 
 #[cfg(test)]
@@ -991,6 +1327,7 @@ mod tests {
     use std::ops::DerefMut;
 
     use super::*;
+    use crate::request::parser::parse_curl_command;
     use mockito::ServerGuard;
     use serde_json::json;
 
@@ -1078,7 +1415,7 @@ mod tests {
         assert_eq!(curl.opts.len(), 1);
         assert!(curl.opts.contains(&CurlFlag::UnixSocket(
             CurlFlagType::UnixSocket.get_value(),
-            None
+            Some(String::from("/var/run/docker.sock"))
         )));
     }
 
@@ -1266,7 +1603,7 @@ mod tests {
         curl.build_command_str();
         assert_eq!(curl.opts.len(), 1);
         assert_eq!(curl.auth, AuthKind::AwsSigv4);
-        assert_eq!(curl.cmd, "curl  --aws-sigv4");
+        assert_eq!(curl.cmd, "curl --aws-sigv4 aws:amz");
         assert!(curl.opts.contains(&CurlFlag::AwsSigv4(
             CurlFlagType::AwsSigv4.get_value(),
             None
@@ -1286,6 +1623,31 @@ mod tests {
         )));
     }
 
+    #[test]
+    fn test_replace_auth_with_saved_key() {
+        for auth in [
+            AuthKind::Bearer(String::from("plaintext-secret")),
+            AuthKind::Basic(String::from("admin:plaintext-secret")),
+        ] {
+            let mut curl = Curl::new();
+            curl.set_url("http://localhost/");
+            curl.set_auth(auth);
+            curl.set_auth(AuthKind::None);
+            assert_eq!(curl.auth, AuthKind::None);
+            curl.set_auth(AuthKind::Bearer(String::from("{{key:1}}")));
+            assert!(!curl.get_command_string().contains("plaintext-secret"));
+            assert!(curl
+                .opts
+                .iter()
+                .flat_map(|flag| flag.to_args())
+                .all(|arg| !arg.contains("plaintext-secret")));
+            assert_eq!(
+                curl.get_command_string(),
+                "curl http://localhost/ -H 'Authorization: Bearer {{key:1}}'"
+            );
+        }
+    }
+
     #[test]
     fn test_set_get_method() {
         let mut curl = Curl::new();
@@ -1355,4 +1717,302 @@ mod tests {
         curl.execute(None).unwrap();
         assert!(curl.resp.is_some());
     }
+
+    #[test]
+    fn test_command_string_quoting() {
+        let mut curl = Curl::new();
+        curl.set_post_method();
+        curl.set_url("https://example.com/search?q=a b&page=2");
+        curl.add_headers(String::from("Content-Type: application/json"));
+        curl.set_request_body("{\"name\": \"it's\"}");
+        curl.set_bearer_auth(String::from("abc123"));
+        curl.set_tcp_keepalive(true);
+        assert_eq!(
+            curl.get_command_string(),
+            "curl -X POST 'https://example.com/search?q=a b&page=2' -H 'Authorization: Bearer abc123' --keepalive --data-raw '{\"name\": \"it'\\''s\"}' -H 'Content-Type: application/json'"
+        );
+        // building it again doesn't append to the previous command
+        assert_eq!(curl.get_command_string(), curl.cmd);
+    }
+
+    #[test]
+    fn test_command_string_auth() {
+        let mut curl = Curl::new();
+        curl.set_basic_auth(String::from("user:p@ss word"));
+        curl.set_head_method();
+        curl.set_url("https://example.com");
+        assert_eq!(
+            curl.get_command_string(),
+            "curl -I https://example.com -u 'user:p@ss word'"
+        );
+        let mut curl = Curl::new();
+        curl.set_digest_auth("user:pass");
+        assert_eq!(curl.get_command_string(), "curl --digest -u user:pass");
+        let mut curl = Curl::new();
+        curl.set_spnego_auth();
+        assert_eq!(curl.get_command_string(), "curl --negotiate -u :");
+    }
+
+    #[test]
+    fn test_export_import_export() {
+        let mut curl = Curl::new();
+        curl.set_put_method();
+        curl.set_url(
+            "http://localhost/v1.43/containers/json?all=1&filters={\"status\":[\"exited\"]}",
+        );
+        curl.set_unix_socket("/var/run/docker.sock");
+        curl.add_headers(String::from("Accept: */*"));
+        curl.add_headers(String::from("X-Quote: \"double\" and 'single' $HOME `cmd`"));
+        curl.set_request_body("line one\nline two\t\\");
+        curl.add_cookie(String::from("session=abc; theme=dark"));
+        curl.set_outfile("my output.json");
+        curl.set_referrer("https://example.com/ref");
+        curl.set_user_agent("CuTE/1.0 (Linux)");
+        curl.set_ca_path("/etc/ssl/certs/ca bundle.pem");
+        curl.set_max_redirects(5);
+        curl.set_verbose(true);
+        curl.set_cert_info(true);
+        curl.set_fail_on_error(true);
+        curl.set_follow_redirects(true);
+        curl.set_proxy_tunnel(true);
+        curl.enable_progress_bar(true);
+        curl.set_tcp_keepalive(true);
+        curl.set_unrestricted_auth(true);
+        curl.set_upload_file("upload.txt");
+        curl.set_auth(AuthKind::Digest(String::from("me:s3cr'et")));
+
+        let exported = curl.get_command_string();
+        let mut imported = parse_curl_command(&exported).unwrap().curl;
+        assert_eq!(imported.get_url(), curl.get_url());
+        assert_eq!(imported.get_headers(), curl.get_headers());
+        assert_eq!(imported.get_request_body(), curl.get_request_body());
+        assert_eq!(imported.get_auth(), curl.get_auth());
+        assert_eq!(imported.get_outfile(), curl.get_outfile());
+        assert_eq!(imported.get_method(), curl.get_method());
+        // the flags are exported in the same order however they were added
+        let reexported = imported.get_command_string();
+        assert_eq!(reexported, exported);
+        let mut reimported = parse_curl_command(&reexported).unwrap().curl;
+        assert_eq!(reimported.get_command_string(), exported);
+    }
+
+    #[test]
+    fn test_deserialize_keeps_flags() {
+        let mut curl = Curl::new();
+        curl.set_get_method();
+        curl.set_url("https://example.com");
+        curl.set_verbose(true);
+        curl.set_bearer_auth(String::from("abc123"));
+        curl.set_referrer("https://example.com/ref");
+        let cmd = curl.get_command_string();
+        let json = serde_json::to_string(&curl).unwrap();
+        let mut new_curl: Curl = serde_json::from_str(&json).unwrap();
+        assert_eq!(new_curl.get_command_string(), cmd);
+        // replaying the options again doesn't duplicate or toggle them off
+        new_curl.easy_from_opts();
+        assert_eq!(new_curl.get_command_string(), cmd);
+    }
+
+    #[test]
+    fn test_execute_captures_response() {
+        let mut server = mockito::Server::new();
+        let _ = server
+            .mock("GET", "/old")
+            .with_status(301)
+            .with_header("Location", "/new")
+            .create();
+        let _ = server
+            .mock("GET", "/new")
+            .with_status(200)
+            .with_header("Content-Type", "application/json")
+            .with_header("Set-Cookie", "a=1")
+            .with_header("Set-Cookie", "b=2")
+            .with_body("{\"key\": \"value\"}")
+            .create();
+        let mut curl = Curl::new();
+        curl.set_get_method();
+        curl.set_url(&format!("{}/old", server.url()));
+        curl.set_follow_redirects(true);
+        curl.execute(None).unwrap();
+        // executing again doesn't add to the previous response
+        curl.execute(None).unwrap();
+        let response = curl.get_http_response().unwrap();
+        assert_eq!(response.status, 200);
+        assert_eq!(response.reason, "OK");
+        assert_eq!(response.version, "HTTP/1.1");
+        assert_eq!(response.hops.len(), 2);
+        assert_eq!(response.hops[0].status, 301);
+        assert_eq!(
+            response.get_header("content-type"),
+            Some("application/json")
+        );
+        assert_eq!(response.get_header_values("set-cookie"), vec!["a=1", "b=2"]);
+        assert_eq!(response.body, "{\"key\": \"value\"}");
+        // the body is still pretty printed for display
+        assert_eq!(curl.get_response(), "{\n  \"key\": \"value\"\n}");
+    }
+
+    #[test]
+    fn test_execute_pretty_prints_by_content_type() {
+        let mut server = mockito::Server::new();
+        let _ = server
+            .mock("GET", "/xml")
+            .with_header("Content-Type", "application/xml")
+            .with_body("<items><item>one</item></items>")
+            .create();
+        let _ = server
+            .mock("GET", "/form")
+            .with_header("Content-Type", "application/x-www-form-urlencoded")
+            .with_body("name=Jane+Doe&id=1")
+            .create();
+        let _ = server
+            .mock("GET", "/text")
+            .with_header("Content-Type", "text/plain")
+            .with_body("a=1&b=2")
+            .create();
+        let mut curl = Curl::new();
+        curl.set_get_method();
+        curl.set_url(&format!("{}/xml", server.url()));
+        curl.execute(None).unwrap();
+        assert_eq!(curl.get_response(), "<items>\n  <item>one</item>\n</items>");
+        assert_eq!(
+            curl.get_http_response().unwrap().body,
+            "<items><item>one</item></items>"
+        );
+        curl.set_url(&format!("{}/form", server.url()));
+        curl.execute(None).unwrap();
+        assert_eq!(curl.get_response(), "name = Jane Doe\nid   = 1");
+        curl.set_url(&format!("{}/text", server.url()));
+        curl.execute(None).unwrap();
+        assert_eq!(curl.get_response(), "a=1&b=2");
+    }
+
+    #[test]
+    fn test_execute_failure_clears_response() {
+        let mut server = mockito::Server::new();
+        let _ = server
+            .mock("GET", "/")
+            .with_header("Content-Type", "application/json")
+            .with_body("{\"id\": 1}")
+            .create();
+        let mut curl = Curl::new();
+        curl.set_get_method();
+        curl.set_url(&server.url());
+        curl.execute(None).unwrap();
+        assert!(curl.get_http_response().is_some() && curl.get_json().is_some());
+        // nothing listens on port 1
+        curl.set_url("http://127.0.0.1:1");
+        assert!(curl.execute(None).is_err());
+        assert!(curl.get_http_response().is_none());
+        assert!(curl.get_json().is_none());
+        assert!(curl.resp.is_none());
+    }
+
+    #[test]
+    fn test_execute_form_parts() {
+        let mut server = mockito::Server::new();
+        let mock = server
+            .mock("POST", "/upload")
+            .match_header(
+                "content-type",
+                mockito::Matcher::Regex(String::from("^multipart/form-data")),
+            )
+            .match_body(mockito::Matcher::Regex(String::from(
+                "name=\"title\"\r\n\r\nhello",
+            )))
+            .with_status(201)
+            .create();
+        let mut curl = Curl::new();
+        curl.set_post_method();
+        curl.set_url(&format!("{}/upload", server.url()));
+        curl.add_form_part("title=hello");
+        assert_eq!(curl.get_form_parts(), vec![String::from("title=hello")]);
+        assert!(curl.get_command_string().ends_with("-F title=hello"));
+        curl.execute(None).unwrap();
+        mock.assert();
+    }
+
+    #[test]
+    fn test_execute_cancelled() {
+        let mut server = mockito::Server::new();
+        let _ = server.mock("GET", "/").with_status(200).create();
+        let mut curl = Curl::new();
+        curl.set_url(&server.url());
+        curl.set_get_method();
+        let cancel = Arc::new(AtomicBool::new(true));
+        curl.set_cancel_flag(cancel.clone());
+        assert_eq!(curl.execute(None), Err(String::from(CANCELLED_ERROR)));
+        cancel.store(false, Ordering::Relaxed);
+        assert!(curl.execute(None).is_ok());
+    }
+
+    #[test]
+    fn test_execute_expands_env_vars() {
+        let mut server = mockito::Server::new();
+        let mock = server
+            .mock("POST", "/api/users")
+            .match_header("X-Token", "abc123")
+            .match_body("{\"name\": \"prod\"}")
+            .with_status(201)
+            .create();
+        let mut curl = Curl::new();
+        curl.set_post_method();
+        curl.set_url("{{base_url}}/api/users");
+        curl.add_headers(String::from("X-Token: {{token}}"));
+        curl.set_request_body("{\"name\": \"{{env}}\"}");
+        curl.set_env_vars(vec![
+            (String::from("base_url"), server.url()),
+            (String::from("token"), String::from("abc123")),
+            (String::from("env"), String::from("prod")),
+        ]);
+        assert!(curl.execute(None).is_ok());
+        mock.assert();
+        // the command itself keeps its variables, so it can be saved and reused
+        assert_eq!(curl.get_url(), "{{base_url}}/api/users");
+        assert!(curl.get_command_string().contains("{{token}}"));
+    }
+
+    #[test]
+    fn test_execute_after_clearing_env() {
+        let mut server = mockito::Server::new();
+        let mock = server
+            .mock("GET", "/api/users")
+            .match_header("X-Token", "abc123")
+            .with_status(200)
+            .expect(1)
+            .create();
+        let mut curl = Curl::new();
+        curl.set_get_method();
+        curl.set_url("{{base_url}}/api/users");
+        curl.add_headers(String::from("X-Token: {{token}}"));
+        curl.set_env_vars(vec![
+            (String::from("base_url"), server.url()),
+            (String::from("token"), String::from("abc123")),
+        ]);
+        assert!(curl.execute(None).is_ok());
+        // without the environment the request doesn't go to its host again
+        curl.set_env_vars(Vec::new());
+        assert!(curl.execute(None).is_err());
+        mock.assert();
+    }
+
+    #[test]
+    fn test_execute_expands_saved_key() {
+        let mut server = mockito::Server::new();
+        // "user:pass" in base64
+        let mock = server
+            .mock("GET", "/api/users")
+            .match_header("Authorization", "Basic dXNlcjpwYXNz")
+            .with_status(200)
+            .create();
+        let mut curl = Curl::new();
+        curl.set_get_method();
+        curl.set_url(&format!("{}/api/users", server.url()));
+        curl.set_auth(AuthKind::Basic(String::from("{{key:1}}")));
+        curl.set_env_vars(vec![(String::from("key:1"), String::from("user:pass"))]);
+        assert!(curl.uses_saved_key());
+        assert!(curl.execute(None).is_ok());
+        mock.assert();
+        assert!(!curl.get_command_string().contains("user:pass"));
+    }
 }
@@ -0,0 +1,402 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use serde_json::Value;
+
+use crate::display::menuopts::NOT_JSON_ERROR;
+use crate::display::viewer::format_json;
+use crate::request::response::Response;
+
+/*
+* Diff - Compares two responses: their bodies line by line, their JSON bodies path by path
+* (ignoring the order of the keys), or their status lines and headers
+ */
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum DiffMode {
+    #[default]
+    Body,
+    Json,
+    Headers,
+}
+
+impl DiffMode {
+    // The mode after this one, the diff view cycles through them
+    pub fn next(self) -> Self {
+        match self {
+            DiffMode::Body => DiffMode::Json,
+            DiffMode::Json => DiffMode::Headers,
+            DiffMode::Headers => DiffMode::Body,
+        }
+    }
+}
+
+impl Display for DiffMode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DiffMode::Body => write!(f, "body"),
+            DiffMode::Json => write!(f, "json"),
+            DiffMode::Headers => write!(f, "headers"),
+        }
+    }
+}
+
+impl FromStr for DiffMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "body" => Ok(DiffMode::Body),
+            "json" => Ok(DiffMode::Json),
+            "headers" => Ok(DiffMode::Headers),
+            _ => Err(format!(
+                "Error: unknown diff mode '{}', use body, json or headers",
+                s
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Change {
+    Same,
+    Removed,
+    Added,
+    // a removed line shown next to the line that replaced it
+    Changed,
+}
+
+// One row of the side by side diff, a side is None when the line only exists on the other one
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiffRow {
+    pub change: Change,
+    pub left: Option<String>,
+    pub right: Option<String>,
+}
+
+impl DiffRow {
+    fn same(line: &str) -> Self {
+        DiffRow {
+            change: Change::Same,
+            left: Some(line.to_string()),
+            right: Some(line.to_string()),
+        }
+    }
+}
+
+enum Edit<'a> {
+    Keep(&'a str),
+    Remove(&'a str),
+    Add(&'a str),
+}
+
+// Past this many cells in the table of the longest common subsequence, the lines in between the
+// common start and end are shown as replaced instead
+const MAX_TABLE_SIZE: usize = 4_000_000;
+
+pub fn diff_lines(left: &str, right: &str) -> Vec<DiffRow> {
+    let left: Vec<&str> = left.lines().collect();
+    let right: Vec<&str> = right.lines().collect();
+    // most responses only differ in a few places, the table only covers what's in between
+    let prefix = left
+        .iter()
+        .zip(right.iter())
+        .take_while(|(l, r)| l == r)
+        .count();
+    let suffix = left[prefix..]
+        .iter()
+        .rev()
+        .zip(right[prefix..].iter().rev())
+        .take_while(|(l, r)| l == r)
+        .count();
+    let mut rows: Vec<DiffRow> = left[..prefix].iter().map(|l| DiffRow::same(l)).collect();
+    let mut removed = Vec::new();
+    let mut added = Vec::new();
+    let edits = edit_script(
+        &left[prefix..left.len() - suffix],
+        &right[prefix..right.len() - suffix],
+    );
+    for edit in edits {
+        match edit {
+            Edit::Keep(line) => {
+                pair_changes(&mut rows, &mut removed, &mut added);
+                rows.push(DiffRow::same(line));
+            }
+            Edit::Remove(line) => removed.push(line),
+            Edit::Add(line) => added.push(line),
+        }
+    }
+    pair_changes(&mut rows, &mut removed, &mut added);
+    rows.extend(left[left.len() - suffix..].iter().map(|l| DiffRow::same(l)));
+    rows
+}
+
+fn edit_script<'a>(left: &[&'a str], right: &[&'a str]) -> Vec<Edit<'a>> {
+    if left.len().saturating_mul(right.len()) > MAX_TABLE_SIZE {
+        return left
+            .iter()
+            .map(|l| Edit::Remove(l))
+            .chain(right.iter().map(|r| Edit::Add(r)))
+            .collect();
+    }
+    // lengths[i * width + j] is the longest common subsequence of left[i..] and right[j..]
+    let width = right.len() + 1;
+    let mut lengths = vec![0u32; (left.len() + 1) * width];
+    for i in (0..left.len()).rev() {
+        for j in (0..right.len()).rev() {
+            lengths[i * width + j] = if left[i] == right[j] {
+                lengths[(i + 1) * width + j + 1] + 1
+            } else {
+                lengths[(i + 1) * width + j].max(lengths[i * width + j + 1])
+            };
+        }
+    }
+    let (mut i, mut j) = (0, 0);
+    let mut edits = Vec::new();
+    while i < left.len() && j < right.len() {
+        if left[i] == right[j] {
+            edits.push(Edit::Keep(left[i]));
+            i += 1;
+            j += 1;
+        } else if lengths[(i + 1) * width + j] >= lengths[i * width + j + 1] {
+            // removals first, so they end up next to the lines that replaced them
+            edits.push(Edit::Remove(left[i]));
+            i += 1;
+        } else {
+            edits.push(Edit::Add(right[j]));
+            j += 1;
+        }
+    }
+    edits.extend(left[i..].iter().map(|l| Edit::Remove(l)));
+    edits.extend(right[j..].iter().map(|r| Edit::Add(r)));
+    edits
+}
+
+// Lines removed and added between the same two common lines are shown side by side as changed
+fn pair_changes(rows: &mut Vec<DiffRow>, removed: &mut Vec<&str>, added: &mut Vec<&str>) {
+    for i in 0..removed.len().max(added.len()) {
+        let (left, right) = (removed.get(i), added.get(i));
+        let change = match (left, right) {
+            (Some(_), Some(_)) => Change::Changed,
+            (Some(_), None) => Change::Removed,
+            _ => Change::Added,
+        };
+        rows.push(DiffRow {
+            change,
+            left: left.map(|l| l.to_string()),
+            right: right.map(|r| r.to_string()),
+        });
+    }
+    removed.clear();
+    added.clear();
+}
+
+// Every value in the JSON as a `path = value` line, with the keys of objects sorted, e.g.
+// `.items[0].id = 1`. Empty objects and arrays are values of their own
+pub fn flatten_json(value: &Value) -> Vec<String> {
+    let mut lines = Vec::new();
+    flatten(value, String::new(), &mut lines);
+    lines
+}
+
+fn flatten(value: &Value, path: String, lines: &mut Vec<String>) {
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            let mut keys: Vec<&String> = map.keys().collect();
+            keys.sort();
+            for key in keys {
+                flatten(&map[key], format!("{}{}", path, key_path(key)), lines);
+            }
+        }
+        Value::Array(items) if !items.is_empty() => {
+            for (index, item) in items.iter().enumerate() {
+                flatten(item, format!("{}[{}]", path, index), lines);
+            }
+        }
+        _ if path.is_empty() => lines.push(format!(". = {}", value)),
+        _ => lines.push(format!("{} = {}", path, value)),
+    }
+}
+
+// `.name`, or `.["first name"]` for keys that aren't identifiers
+fn key_path(key: &str) -> String {
+    let identifier = key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && key.chars().next().is_some_and(|c| !c.is_ascii_digit());
+    match identifier {
+        true => format!(".{}", key),
+        false => format!(".[{}]", Value::String(key.to_string())),
+    }
+}
+
+// The status line, then the headers of the final response sorted by their lowercased name
+pub fn header_lines(response: &Response) -> Vec<String> {
+    let status = format!(
+        "{} {} {}",
+        response.version, response.status, response.reason
+    );
+    let mut headers: Vec<String> = response
+        .headers
+        .iter()
+        .map(|(key, value)| format!("{}: {}", key.to_lowercase(), value))
+        .collect();
+    headers.sort();
+    std::iter::once(status.trim_end().to_string())
+        .chain(headers)
+        .collect()
+}
+
+pub fn diff_responses(
+    left: &Response,
+    right: &Response,
+    mode: DiffMode,
+) -> Result<Vec<DiffRow>, String> {
+    match mode {
+        // a minified body is a single line, JSON is compared pretty printed
+        DiffMode::Body => Ok(diff_lines(
+            &format_json(&left.body),
+            &format_json(&right.body),
+        )),
+        DiffMode::Json => {
+            let parse = |body: &str| {
+                serde_json::from_str::<Value>(body)
+                    .map(|json| flatten_json(&json).join("\n"))
+                    .map_err(|_| String::from(NOT_JSON_ERROR))
+            };
+            Ok(diff_lines(&parse(&left.body)?, &parse(&right.body)?))
+        }
+        DiffMode::Headers => Ok(diff_lines(
+            &header_lines(left).join("\n"),
+            &header_lines(right).join("\n"),
+        )),
+    }
+}
+
+pub fn has_changes(rows: &[DiffRow]) -> bool {
+    rows.iter().any(|row| row.change != Change::Same)
+}
+
+// `  ` before common lines, `- ` before lines only on the left and `+ ` before lines only on the
+// right, like a unified diff without the hunk headers
+pub fn format_unified(rows: &[DiffRow]) -> String {
+    let mut lines = Vec::new();
+    for row in rows {
+        match (row.change, &row.left, &row.right) {
+            (Change::Same, Some(line), _) => lines.push(format!("  {}", line)),
+            (_, left, right) => {
+                if let Some(left) = left {
+                    lines.push(format!("- {}", left));
+                }
+                if let Some(right) = right {
+                    lines.push(format!("+ {}", right));
+                }
+            }
+        }
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(body: &str, headers: &[(&str, &str)]) -> Response {
+        Response {
+            status: 200,
+            reason: String::from("OK"),
+            version: String::from("HTTP/1.1"),
+            headers: headers
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect(),
+            hops: Vec::new(),
+            body: body.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_diff_lines() {
+        let rows = diff_lines("a\nb\nc\nd\ne", "a\nc\nD\ne\nf");
+        let changes: Vec<Change> = rows.iter().map(|row| row.change).collect();
+        assert_eq!(
+            changes,
+            vec![
+                Change::Same,
+                Change::Removed,
+                Change::Same,
+                Change::Changed,
+                Change::Same,
+                Change::Added,
+            ]
+        );
+        assert_eq!(rows[1].left.as_deref(), Some("b"));
+        assert_eq!(rows[1].right, None);
+        assert_eq!(
+            (rows[3].left.as_deref(), rows[3].right.as_deref()),
+            (Some("d"), Some("D"))
+        );
+        assert_eq!(format_unified(&rows), "  a\n- b\n  c\n- d\n+ D\n  e\n+ f");
+        assert!(!has_changes(&diff_lines("same\n", "same")));
+        assert_eq!(diff_lines("", "new").len(), 1);
+    }
+
+    #[test]
+    fn test_flatten_json() {
+        let json = serde_json::json!({
+            "name": "CuTE",
+            "tags": ["tui", {"first name": null}],
+            "empty": {},
+            "1st": []
+        });
+        assert_eq!(
+            flatten_json(&json),
+            vec![
+                ".[\"1st\"] = []",
+                ".empty = {}",
+                ".name = \"CuTE\"",
+                ".tags[0] = \"tui\"",
+                ".tags[1].[\"first name\"] = null",
+            ]
+        );
+        assert_eq!(flatten_json(&serde_json::json!(1)), vec![". = 1"]);
+    }
+
+    #[test]
+    fn test_diff_responses() {
+        let left = response(
+            r#"{"id":1,"name":"CuTE","tags":["tui"]}"#,
+            &[("Content-Type", "application/json"), ("ETag", "\"a\"")],
+        );
+        let right = response(
+            r#"{"tags":["tui"],"name":"CuTE","id":2}"#,
+            &[("etag", "\"b\""), ("content-type", "application/json")],
+        );
+        // the key order doesn't matter to the JSON diff
+        let rows = diff_responses(&left, &right, DiffMode::Json).unwrap();
+        assert_eq!(
+            format_unified(&rows),
+            "- .id = 1\n+ .id = 2\n  .name = \"CuTE\"\n  .tags[0] = \"tui\""
+        );
+        // but it does to the line diff of the pretty printed bodies
+        let rows = diff_responses(&left, &right, DiffMode::Body).unwrap();
+        assert_eq!(
+            rows.iter().filter(|row| row.change != Change::Same).count(),
+            5
+        );
+
+        let rows = diff_responses(&left, &right, DiffMode::Headers).unwrap();
+        assert_eq!(
+            format_unified(&rows),
+            "  HTTP/1.1 200 OK\n  content-type: application/json\n- etag: \"a\"\n+ etag: \"b\""
+        );
+
+        let text = response("plain text", &[]);
+        assert_eq!(
+            diff_responses(&left, &text, DiffMode::Json),
+            Err(String::from(NOT_JSON_ERROR))
+        );
+        assert!(has_changes(
+            &diff_responses(&left, &text, DiffMode::Body).unwrap()
+        ));
+        assert_eq!("JSON".parse::<DiffMode>(), Ok(DiffMode::Json));
+        assert!("xml".parse::<DiffMode>().is_err());
+    }
+}
@@ -0,0 +1,99 @@
+// Replaces every {{key}} in the text with its value, whitespace inside the braces is ignored.
+// Variables the environment doesn't define are left as they are
+pub fn expand_vars(text: &str, vars: &[(String, String)]) -> String {
+    if vars.is_empty() || !text.contains("{{") {
+        return text.to_string();
+    }
+    let mut expanded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let Some(end) = rest[start + 2..].find("}}") else {
+            break;
+        };
+        let name = rest[start + 2..start + 2 + end].trim();
+        expanded.push_str(&rest[..start]);
+        match vars.iter().find(|(key, _)| key == name) {
+            Some((_, value)) => expanded.push_str(value),
+            None => expanded.push_str(&rest[start..start + 4 + end]),
+        }
+        rest = &rest[start + 4 + end..];
+    }
+    expanded.push_str(rest);
+    expanded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::db::DB;
+
+    fn vars() -> Vec<(String, String)> {
+        vec![
+            (
+                String::from("base_url"),
+                String::from("http://localhost:8080"),
+            ),
+            (String::from("token"), String::from("abc123")),
+        ]
+    }
+
+    #[test]
+    fn test_expand_vars() {
+        assert_eq!(
+            expand_vars("{{base_url}}/api/users", &vars()),
+            "http://localhost:8080/api/users"
+        );
+        assert_eq!(
+            expand_vars("Authorization: Bearer {{ token }}", &vars()),
+            "Authorization: Bearer abc123"
+        );
+        // unknown and unterminated variables are left alone
+        assert_eq!(
+            expand_vars("{{missing}}/{{token}}/{{base_url", &vars()),
+            "{{missing}}/abc123/{{base_url"
+        );
+        assert_eq!(expand_vars("{{token}}", &[]), "{{token}}");
+    }
+
+    #[test]
+    fn test_environments_in_db() {
+        let db = DB::new_in_memory().unwrap();
+        db.add_environment("local").unwrap();
+        db.add_environment("prod").unwrap();
+        assert!(db.add_environment("local").is_err());
+        let envs = db.get_environments().unwrap();
+        assert_eq!(envs.len(), 2);
+        assert!(db.get_active_environment().unwrap().is_none());
+
+        let prod = envs[1].get_id();
+        db.set_env_var(prod, "base_url", "https://example.com")
+            .unwrap();
+        db.set_env_var(prod, "base_url", "https://api.example.com")
+            .unwrap();
+        db.set_env_var(prod, "token", "secret").unwrap();
+        db.set_active_environment(prod).unwrap();
+        let active = db.get_active_environment().unwrap().unwrap();
+        assert_eq!(active.get_name(), "prod");
+        assert_eq!(
+            active.get_vars(),
+            &[
+                (
+                    String::from("base_url"),
+                    String::from("https://api.example.com")
+                ),
+                (String::from("token"), String::from("secret")),
+            ]
+        );
+
+        db.set_active_environment(envs[0].get_id()).unwrap();
+        assert_eq!(
+            db.get_active_environment().unwrap().unwrap().get_name(),
+            "local"
+        );
+        db.delete_env_var(prod, "token").unwrap();
+        assert_eq!(db.get_env_vars(prod).unwrap().len(), 1);
+        db.delete_environment(prod).unwrap();
+        assert_eq!(db.get_environments().unwrap().len(), 1);
+        assert!(db.get_env_vars(prod).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,778 @@
+/*
+* HAR 1.2 (HTTP Archive) files, the format browsers' dev tools export their network log in.
+* Entries can be imported as saved commands, and history or saved commands are exported
+* along with their responses and timings.
+ */
+use serde::{Deserialize, Serialize};
+
+use super::command::{CmdOpts, CurlOpts};
+use super::curl::{AuthKind, Curl};
+use super::importer::{CollectionImport, ImportedRequest};
+use super::response::Response;
+use crate::database::db::{HistoryEntry, SavedCommand, DB};
+use crate::database::tree::collection_commands;
+
+/// What to export as HAR: history entries matching a search, or the saved commands in a
+/// collection (None for all of them).
+#[derive(Debug, Clone, PartialEq)]
+pub enum HarExport {
+    History(String),
+    Commands(Option<i32>),
+}
+
+impl HarExport {
+    pub fn export(&self, db: &DB) -> Result<Har, String> {
+        match self {
+            HarExport::History(query) => export_history(db, query),
+            HarExport::Commands(collection) => export_commands(db, *collection),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Har {
+    pub log: HarLog,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct HarLog {
+    pub version: String,
+    pub creator: HarCreator,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub pages: Vec<HarPage>,
+    pub entries: Vec<HarEntry>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HarCreator {
+    pub name: String,
+    pub version: String,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct HarPage {
+    pub id: String,
+    pub title: String,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct HarEntry {
+    pub started_date_time: String,
+    // Total time of the request in milliseconds
+    pub time: f64,
+    pub request: HarRequest,
+    pub response: HarResponse,
+    pub timings: HarTimings,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct HarRequest {
+    pub method: String,
+    pub url: String,
+    pub http_version: String,
+    pub headers: Vec<NameValue>,
+    pub query_string: Vec<NameValue>,
+    pub cookies: Vec<NameValue>,
+    pub headers_size: i64,
+    pub body_size: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub post_data: Option<HarPostData>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NameValue {
+    pub name: String,
+    pub value: String,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct HarPostData {
+    pub mime_type: String,
+    pub text: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub params: Vec<HarParam>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct HarParam {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file_name: Option<String>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct HarResponse {
+    // 0 when the request failed, or a saved command was never sent
+    pub status: u16,
+    pub status_text: String,
+    pub http_version: String,
+    pub headers: Vec<NameValue>,
+    pub cookies: Vec<NameValue>,
+    pub content: HarContent,
+    #[serde(rename = "redirectURL")]
+    pub redirect_url: String,
+    pub headers_size: i64,
+    pub body_size: i64,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct HarContent {
+    pub size: i64,
+    pub mime_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+}
+
+// We only know how long the whole request took, so it's all counted as waiting
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HarTimings {
+    pub send: f64,
+    pub wait: f64,
+    pub receive: f64,
+}
+
+impl Har {
+    pub fn new(entries: Vec<HarEntry>) -> Self {
+        Har {
+            log: HarLog {
+                version: String::from("1.2"),
+                creator: HarCreator {
+                    name: String::from("CuTE"),
+                    version: env!("CARGO_PKG_VERSION").to_string(),
+                },
+                pages: Vec::new(),
+                entries,
+            },
+        }
+    }
+
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string_pretty(self).map_err(|e| e.to_string())
+    }
+}
+
+fn pair(name: &str, value: &str) -> NameValue {
+    NameValue {
+        name: name.to_string(),
+        value: value.to_string(),
+    }
+}
+
+fn find_header<'a>(headers: &'a [NameValue], name: &str) -> Option<&'a str> {
+    headers
+        .iter()
+        .find(|header| header.name.eq_ignore_ascii_case(name))
+        .map(|header| header.value.as_str())
+}
+
+// Headers the browser or libcurl sets on its own, importing them would only cause trouble
+const SKIPPED_HEADERS: [&str; 4] = ["host", "content-length", "connection", "accept-encoding"];
+
+pub fn parse_har(input: &str) -> Result<CollectionImport<'static>, String> {
+    let har: Har =
+        serde_json::from_str(input).map_err(|e| format!("Error: invalid HAR file: {}", e))?;
+    let name = har
+        .log
+        .pages
+        .first()
+        .map(|page| page.title.clone())
+        .filter(|title| !title.is_empty())
+        .unwrap_or(String::from("HAR import"));
+    let mut import = CollectionImport::new(&name);
+    for entry in har.log.entries.iter() {
+        let (host, path) = split_url(&entry.request.url);
+        let name = format!("{} {}", entry.request.method.to_uppercase(), path);
+        let label = format!("{}/{}", host, name);
+        match import_request(&entry.request) {
+            Ok((curl, ignored)) => {
+                let folders = vec![host];
+                import.add_folder(&folders);
+                import.requests.push(ImportedRequest {
+                    name,
+                    description: entry.comment.clone().filter(|c| !c.is_empty()),
+                    folders,
+                    curl,
+                });
+                import.ignored.extend(
+                    ignored
+                        .iter()
+                        .map(|reason| format!("{}: {}", label, reason)),
+                );
+            }
+            Err(reason) => import.ignored.push(format!("{}: {}", label, reason)),
+        }
+    }
+    Ok(import)
+}
+
+// "https://api.example.com/users?page=2" -> ("api.example.com", "/users")
+fn split_url(url: &str) -> (String, String) {
+    let rest = url.split_once("://").map(|(_, rest)| rest).unwrap_or(url);
+    let rest = rest.split(['?', '#']).next().unwrap_or_default();
+    match rest.split_once('/') {
+        Some((host, path)) => (host.to_string(), format!("/{}", path)),
+        None => (rest.to_string(), String::from("/")),
+    }
+}
+
+fn import_request(request: &HarRequest) -> Result<(Curl<'static>, Vec<String>), String> {
+    let mut ignored = Vec::new();
+    if request.url.is_empty() {
+        return Err(String::from("no URL"));
+    }
+    let method = request.method.to_uppercase();
+    if !["GET", "POST", "PUT", "PATCH", "DELETE", "HEAD"].contains(&method.as_str()) {
+        return Err(format!("unsupported method {}", method));
+    }
+    let mut curl = Curl::new();
+    curl.set_method(method);
+    curl.set_url(&request.url);
+
+    let multipart = request
+        .post_data
+        .as_ref()
+        .is_some_and(|data| data.text.is_empty() && data.mime_type.starts_with("multipart/"));
+    for header in request.headers.iter() {
+        let name = header.name.to_lowercase();
+        // HTTP/2 pseudo headers like :authority
+        if name.starts_with(':') || SKIPPED_HEADERS.contains(&name.as_str()) {
+            continue;
+        }
+        // libcurl picks its own multipart boundary
+        if multipart && name == "content-type" {
+            continue;
+        }
+        match header.value.strip_prefix("Bearer ") {
+            Some(token) if name == "authorization" => {
+                curl.set_auth(AuthKind::Bearer(token.to_string()))
+            }
+            _ => curl.add_headers(format!("{}: {}", header.name, header.value)),
+        }
+    }
+
+    if let Some(data) = request.post_data.as_ref() {
+        if !data.text.is_empty() {
+            curl.set_request_body(&data.text);
+        } else if multipart {
+            for param in data.params.iter() {
+                match param.file_name.as_ref() {
+                    Some(file) => ignored.push(format!("form file \"{}\" ({})", param.name, file)),
+                    None => curl.add_form_part(&format!(
+                        "{}={}",
+                        param.name,
+                        param.value.clone().unwrap_or_default()
+                    )),
+                }
+            }
+        } else if !data.params.is_empty() {
+            // params of urlencoded bodies are already encoded
+            let body = data
+                .params
+                .iter()
+                .map(|param| format!("{}={}", param.name, param.value.clone().unwrap_or_default()))
+                .collect::<Vec<String>>()
+                .join("&");
+            curl.set_request_body(&body);
+        }
+    }
+    Ok((curl, ignored))
+}
+
+const BASE64_CHARS: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+// Basic auth is stored as user:pass, HAR wants the header that was sent
+fn base64_encode(input: &str) -> String {
+    let mut encoded = String::new();
+    for chunk in input.as_bytes().chunks(3) {
+        let bytes = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let n = (bytes[0] as u32) << 16 | (bytes[1] as u32) << 8 | bytes[2] as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(BASE64_CHARS[(n >> (18 - i * 6) & 63) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+fn har_request(curl: &Curl, http_version: &str) -> HarRequest {
+    let url = curl.get_url();
+    let mut headers: Vec<NameValue> = curl
+        .get_headers()
+        .iter()
+        .filter_map(|header| header.split_once(':'))
+        .map(|(name, value)| pair(name.trim(), value.trim()))
+        .collect();
+    match curl.get_auth() {
+        AuthKind::Basic(login) => headers.push(pair(
+            "Authorization",
+            &format!("Basic {}", base64_encode(login)),
+        )),
+        AuthKind::Bearer(token) => {
+            headers.push(pair("Authorization", &format!("Bearer {}", token)))
+        }
+        _ => {}
+    }
+    let query_string = url
+        .split_once('?')
+        .map(|(_, query)| {
+            query
+                .split('&')
+                .filter(|param| !param.is_empty())
+                .map(|param| match param.split_once('=') {
+                    Some((name, value)) => pair(name, value),
+                    None => pair(param, ""),
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    let form_parts = curl.get_form_parts();
+    let post_data = match curl.get_request_body() {
+        Some(body) => Some(HarPostData {
+            mime_type: find_header(&headers, "content-type")
+                .unwrap_or("application/x-www-form-urlencoded")
+                .to_string(),
+            text: body,
+            params: Vec::new(),
+        }),
+        None if !form_parts.is_empty() => Some(HarPostData {
+            mime_type: String::from("multipart/form-data"),
+            text: String::new(),
+            params: form_parts
+                .iter()
+                .map(|part| {
+                    let (name, value) = part.split_once('=').unwrap_or((part, ""));
+                    match value.strip_prefix('@') {
+                        Some(file) => HarParam {
+                            name: name.to_string(),
+                            value: None,
+                            file_name: Some(file.to_string()),
+                        },
+                        None => HarParam {
+                            name: name.to_string(),
+                            value: Some(value.to_string()),
+                            file_name: None,
+                        },
+                    }
+                })
+                .collect(),
+        }),
+        None => None,
+    };
+    HarRequest {
+        method: curl
+            .get_method()
+            .map(|method| method.to_string())
+            .unwrap_or(String::from("GET")),
+        url,
+        http_version: http_version.to_string(),
+        headers,
+        query_string,
+        cookies: Vec::new(),
+        headers_size: -1,
+        body_size: post_data
+            .as_ref()
+            .map(|data| data.text.len() as i64)
+            .unwrap_or(0),
+        post_data,
+    }
+}
+
+fn har_response(response: Option<&Response>, body: Option<&str>, size: usize) -> HarResponse {
+    let Some(response) = response else {
+        return HarResponse {
+            headers_size: -1,
+            body_size: -1,
+            ..Default::default()
+        };
+    };
+    let headers: Vec<NameValue> = response
+        .headers
+        .iter()
+        .map(|(name, value)| pair(name, value))
+        .collect();
+    HarResponse {
+        status: response.status,
+        status_text: response.reason.clone(),
+        http_version: response.version.clone(),
+        content: HarContent {
+            size: size as i64,
+            mime_type: find_header(&headers, "content-type")
+                .unwrap_or_default()
+                .to_string(),
+            text: body.map(str::to_string),
+        },
+        redirect_url: find_header(&headers, "location")
+            .unwrap_or_default()
+            .to_string(),
+        headers,
+        cookies: Vec::new(),
+        headers_size: -1,
+        body_size: size as i64,
+    }
+}
+
+// One request and what we got back. `started` is a history timestamp, "YYYY-MM-DD HH:MM:SS"
+pub fn har_entry(
+    curl: &Curl,
+    response: Option<&Response>,
+    body: Option<&str>,
+    size: usize,
+    duration_ms: u64,
+    started: &str,
+) -> HarEntry {
+    let http_version = response
+        .map(|response| response.version.as_str())
+        .filter(|version| !version.is_empty())
+        .unwrap_or("HTTP/1.1");
+    HarEntry {
+        started_date_time: started.replacen(' ', "T", 1),
+        time: duration_ms as f64,
+        request: har_request(curl, http_version),
+        response: har_response(response, body, size),
+        timings: HarTimings {
+            send: 0.0,
+            wait: duration_ms as f64,
+            receive: 0.0,
+        },
+        comment: None,
+    }
+}
+
+// History stores the curl it sent, a curl_json we can't read still has the method and url
+fn history_curl(entry: &HistoryEntry) -> Curl<'_> {
+    serde_json::from_str(entry.get_curl_json()).unwrap_or_else(|_| {
+        let mut curl = Curl::new();
+        curl.set_method(entry.get_method().to_string());
+        curl.set_url(entry.get_url());
+        curl
+    })
+}
+
+fn history_har_entry(entry: &HistoryEntry) -> HarEntry {
+    har_entry(
+        &history_curl(entry),
+        entry.get_http_response(),
+        entry.get_body(),
+        entry.get_size(),
+        entry.get_duration_ms(),
+        entry.get_timestamp(),
+    )
+}
+
+// Oldest first like a browser's log, only entries whose method or url contain the query
+pub fn export_history(db: &DB, query: &str) -> Result<Har, String> {
+    let history = db.get_history(query).map_err(|e| e.to_string())?;
+    Ok(Har::new(
+        history.iter().rev().map(history_har_entry).collect(),
+    ))
+}
+
+// Saved commands in a collection (None exports all of them), each with the response of the
+// last time it was sent
+pub fn export_commands(db: &DB, collection: Option<i32>) -> Result<Har, String> {
+    let collections = db.get_collections().map_err(|e| e.to_string())?;
+    let commands = db.get_commands().map_err(|e| e.to_string())?;
+    let history = db.get_history("").map_err(|e| e.to_string())?;
+    let now: String = db
+        .conn
+        .query_row("SELECT datetime('now', 'localtime')", [], |row| row.get(0))
+        .map_err(|e| e.to_string())?;
+    let entries = collection_commands(&collections, &commands, collection)
+        .iter()
+        .map(|command| command_har_entry(command, &history, &now))
+        .collect::<Result<Vec<HarEntry>, String>>()?;
+    Ok(Har::new(entries))
+}
+
+fn command_har_entry(
+    command: &SavedCommand,
+    history: &[HistoryEntry],
+    now: &str,
+) -> Result<HarEntry, String> {
+    let curl: Curl = serde_json::from_str(command.get_curl_json()).map_err(|e| {
+        format!(
+            "Error: saved command {} is invalid: {}",
+            command.get_id(),
+            e
+        )
+    })?;
+    let method = curl
+        .get_method()
+        .map(|method| method.to_string())
+        .unwrap_or(String::from("GET"));
+    let url = curl.get_url();
+    // history is newest first
+    let sent = history
+        .iter()
+        .find(|entry| entry.get_method() == method && entry.get_url() == url);
+    let mut entry = match sent {
+        Some(sent) => har_entry(
+            &curl,
+            sent.get_http_response(),
+            sent.get_body(),
+            sent.get_size(),
+            sent.get_duration_ms(),
+            sent.get_timestamp(),
+        ),
+        None => har_entry(&curl, None, None, 0, 0, now),
+    };
+    entry.comment = Some(command.get_label().to_string());
+    Ok(entry)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HAR: &str = r#"{
+      "log": {
+        "version": "1.2",
+        "creator": { "name": "Firefox", "version": "120.0" },
+        "pages": [{ "id": "page_1", "title": "Example App", "startedDateTime": "2023-11-20T10:00:00.000Z" }],
+        "entries": [
+          {
+            "startedDateTime": "2023-11-20T10:00:01.000Z",
+            "time": 42.5,
+            "request": {
+              "method": "GET",
+              "url": "https://api.example.com/users?page=2",
+              "httpVersion": "HTTP/2",
+              "headers": [
+                { "name": ":authority", "value": "api.example.com" },
+                { "name": "Accept", "value": "application/json" },
+                { "name": "Authorization", "value": "Bearer abc123" },
+                { "name": "Accept-Encoding", "value": "gzip, br" }
+              ],
+              "queryString": [{ "name": "page", "value": "2" }],
+              "cookies": [],
+              "headersSize": -1,
+              "bodySize": 0
+            },
+            "response": { "status": 200, "statusText": "OK", "content": { "size": 2, "mimeType": "application/json", "text": "[]" } },
+            "timings": { "send": 1, "wait": 40, "receive": 1.5 }
+          },
+          {
+            "startedDateTime": "2023-11-20T10:00:02.000Z",
+            "request": {
+              "method": "POST",
+              "url": "https://api.example.com/login",
+              "headers": [{ "name": "Content-Type", "value": "application/x-www-form-urlencoded" }],
+              "postData": {
+                "mimeType": "application/x-www-form-urlencoded",
+                "params": [{ "name": "user", "value": "a%40b" }, { "name": "pass", "value": "x" }]
+              }
+            },
+            "response": { "status": 302 }
+          },
+          {
+            "request": {
+              "method": "POST",
+              "url": "https://cdn.example.com/upload",
+              "headers": [{ "name": "Content-Type", "value": "multipart/form-data; boundary=xyz" }],
+              "postData": {
+                "mimeType": "multipart/form-data; boundary=xyz",
+                "params": [{ "name": "title", "value": "cat" }, { "name": "file", "fileName": "cat.png" }]
+              }
+            }
+          },
+          { "request": { "method": "OPTIONS", "url": "https://api.example.com/users" } }
+        ]
+      }
+    }"#;
+
+    #[test]
+    fn test_parse_har() {
+        let import = parse_har(HAR).unwrap();
+        assert_eq!(import.name, "Example App");
+        assert_eq!(
+            import.folders,
+            vec![
+                vec![String::from("api.example.com")],
+                vec![String::from("cdn.example.com")]
+            ]
+        );
+        assert_eq!(import.requests.len(), 3);
+
+        let users = &import.requests[0];
+        assert_eq!(users.name, "GET /users");
+        assert_eq!(users.curl.get_url(), "https://api.example.com/users?page=2");
+        assert_eq!(users.curl.get_headers(), vec!["Accept: application/json"]);
+        assert_eq!(
+            users.curl.get_auth(),
+            &AuthKind::Bearer(String::from("abc123"))
+        );
+
+        let login = &import.requests[1];
+        assert_eq!(login.name, "POST /login");
+        assert_eq!(
+            login.curl.get_request_body(),
+            Some(String::from("user=a%40b&pass=x"))
+        );
+
+        let upload = &import.requests[2];
+        assert_eq!(upload.folders, vec![String::from("cdn.example.com")]);
+        assert!(upload.curl.get_headers().is_empty());
+        assert_eq!(upload.curl.get_form_parts(), vec!["title=cat"]);
+        assert_eq!(
+            import.ignored,
+            vec![
+                "cdn.example.com/POST /upload: form file \"file\" (cat.png)",
+                "api.example.com/OPTIONS /users: unsupported method OPTIONS",
+            ]
+        );
+        assert!(parse_har("{ not json").is_err());
+    }
+
+    #[test]
+    fn test_base64_encode() {
+        assert_eq!(base64_encode("user:pass"), "dXNlcjpwYXNz");
+        assert_eq!(base64_encode("a"), "YQ==");
+        assert_eq!(base64_encode("ab"), "YWI=");
+        assert_eq!(base64_encode(""), "");
+    }
+
+    #[test]
+    fn test_export_history() {
+        let db = DB::new_in_memory().unwrap();
+        let mut curl = Curl::new();
+        curl.set_post_method();
+        curl.set_url("http://localhost/users?active=true");
+        curl.add_headers(String::from("Content-Type: application/json"));
+        curl.set_basic_auth(String::from("user:pass"));
+        curl.set_request_body("{\"name\":\"a\"}");
+        let mut entry = HistoryEntry::new(
+            "http://localhost/users?active=true",
+            "POST",
+            serde_json::to_string(&curl).unwrap(),
+        );
+        entry.set_response(Some(201), 15, 9, Some(String::from("{\"id\":1}")));
+        entry.set_http_response(&Response {
+            status: 201,
+            reason: String::from("Created"),
+            version: String::from("HTTP/1.1"),
+            headers: vec![
+                (
+                    String::from("Content-Type"),
+                    String::from("application/json"),
+                ),
+                (String::from("Location"), String::from("/users/1")),
+            ],
+            ..Default::default()
+        });
+        db.add_history(&entry).unwrap();
+        let mut failed = HistoryEntry::new("http://localhost:1/down", "GET", String::new());
+        failed.set_response(None, 3, 0, None);
+        db.add_history(&failed).unwrap();
+
+        let har = export_history(&db, "").unwrap();
+        // round trips through our own parser
+        let har: Har = serde_json::from_str(&har.to_json().unwrap()).unwrap();
+        assert_eq!(har.log.version, "1.2");
+        assert_eq!(har.log.creator.name, "CuTE");
+        assert_eq!(har.log.entries.len(), 2);
+
+        let created = &har.log.entries[0];
+        assert!(created.started_date_time.contains('T'));
+        assert_eq!(created.time, 15.0);
+        assert_eq!(created.request.method, "POST");
+        assert_eq!(created.request.query_string, vec![pair("active", "true")]);
+        assert!(created
+            .request
+            .headers
+            .contains(&pair("Authorization", "Basic dXNlcjpwYXNz")));
+        let post_data = created.request.post_data.as_ref().unwrap();
+        assert_eq!(post_data.mime_type, "application/json");
+        assert_eq!(post_data.text, "{\"name\":\"a\"}");
+        assert_eq!(created.response.status, 201);
+        assert_eq!(created.response.status_text, "Created");
+        assert_eq!(created.response.redirect_url, "/users/1");
+        assert_eq!(created.response.content.mime_type, "application/json");
+        assert_eq!(created.response.content.text.as_deref(), Some("{\"id\":1}"));
+
+        let down = &har.log.entries[1];
+        assert_eq!(down.request.url, "http://localhost:1/down");
+        assert_eq!(down.response.status, 0);
+        assert_eq!(down.timings.wait, 3.0);
+
+        // and imports back as the same request
+        let import = parse_har(&serde_json::to_string(&har).unwrap()).unwrap();
+        assert_eq!(import.requests[0].name, "POST /users");
+        assert_eq!(
+            import.requests[0].curl.get_request_body(),
+            Some(String::from("{\"name\":\"a\"}"))
+        );
+    }
+
+    #[test]
+    fn test_export_commands() {
+        let db = DB::new_in_memory().unwrap();
+        let api = db.add_collection("api", None).unwrap();
+        let mut curl = Curl::new();
+        curl.set_get_method();
+        curl.set_url("http://localhost/health");
+        let id = db
+            .add_command(
+                "curl http://localhost/health",
+                serde_json::to_string(&curl).unwrap(),
+            )
+            .unwrap();
+        db.rename_command(id, Some("Health")).unwrap();
+        db.move_command(id, Some(api)).unwrap();
+        let mut unsent = Curl::new();
+        unsent.set_url("http://localhost/other");
+        db.add_command(
+            "curl http://localhost/other",
+            serde_json::to_string(&unsent).unwrap(),
+        )
+        .unwrap();
+
+        let mut entry = HistoryEntry::new("http://localhost/health", "GET", String::new());
+        entry.set_response(Some(204), 8, 0, None);
+        entry.set_http_response(&Response {
+            status: 204,
+            reason: String::from("No Content"),
+            ..Default::default()
+        });
+        db.add_history(&entry).unwrap();
+
+        let har = export_commands(&db, Some(api)).unwrap();
+        assert_eq!(har.log.entries.len(), 1);
+        let health = &har.log.entries[0];
+        assert_eq!(health.comment.as_deref(), Some("Health"));
+        assert_eq!(health.response.status, 204);
+        assert_eq!(health.time, 8.0);
+
+        let har = export_commands(&db, None).unwrap();
+        assert_eq!(har.log.entries.len(), 2);
+        let other = har
+            .log
+            .entries
+            .iter()
+            .find(|entry| entry.request.url == "http://localhost/other")
+            .unwrap();
+        assert_eq!(other.response.status, 0);
+        assert!(!other.started_date_time.is_empty());
+    }
+}
@@ -0,0 +1,585 @@
+/*
+* `.http` request files, the format of the JetBrains HTTP client and VS Code REST Client.
+* Requests are separated by `###` lines and can use `@name = value` file variables as {{name}}.
+* Edited requests are written back in place, the rest of the file is kept as it was.
+ */
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+
+use super::command::{CmdOpts, CurlOpts};
+use super::curl::{AuthKind, Curl};
+use super::environment::expand_vars;
+
+const METHODS: [&str; 9] = [
+    "GET", "POST", "PUT", "PATCH", "DELETE", "HEAD", "OPTIONS", "TRACE", "CONNECT",
+];
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct HttpRequest {
+    // From `### name` or `# @name name`, empty if the request has no name
+    pub name: String,
+    pub method: String,
+    pub url: String,
+    // The HTTP/1.1 after the URL, if there was one
+    pub version: Option<String>,
+    pub headers: Vec<(String, String)>,
+    pub body: Option<String>,
+    // The separator, comments and variables above the request line, written back as they were
+    prefix: Vec<String>,
+    // Response handlers and anything else after the body (`> {% ... %}`, `<> previous.json`)
+    suffix: Vec<String>,
+    // Index of the section of the file this request was read from
+    section: usize,
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct HttpFile {
+    path: PathBuf,
+    // The text between the ### separators, each one starts with its separator line
+    sections: Vec<String>,
+    variables: Vec<(String, String)>,
+    requests: Vec<HttpRequest>,
+}
+
+impl Display for HttpRequest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.name.is_empty() {
+            write!(f, "{} {}", self.method, self.url)
+        } else {
+            write!(f, "{} | {} {}", self.name, self.method, self.url)
+        }
+    }
+}
+
+fn is_separator(line: &str) -> bool {
+    line.trim_start().starts_with("###")
+}
+
+fn is_comment(line: &str) -> bool {
+    let line = line.trim_start();
+    line.starts_with('#') || line.starts_with("//")
+}
+
+// "@base_url = http://localhost" -> ("base_url", "http://localhost")
+fn parse_variable(line: &str) -> Option<(String, String)> {
+    let (name, value) = line.trim().strip_prefix('@')?.split_once('=')?;
+    let name = name.trim();
+    if name.is_empty() || name.contains(char::is_whitespace) {
+        return None;
+    }
+    Some((name.to_string(), value.trim().to_string()))
+}
+
+// "# @name login" -> "login"
+fn parse_name_comment(line: &str) -> Option<String> {
+    let line = line.trim_start();
+    let comment = line
+        .strip_prefix("//")
+        .or_else(|| line.strip_prefix('#'))?
+        .trim_start();
+    let name = comment.strip_prefix("@name")?;
+    // "@name=login" works too
+    let name = name.trim_start().trim_start_matches('=').trim();
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+// "POST {{base}}/users HTTP/1.1" -> ("POST", "{{base}}/users", Some("HTTP/1.1")), a bare URL is a GET
+pub fn parse_request_line(line: &str) -> (String, String, Option<String>) {
+    let line = line.trim();
+    let (method, rest) = match line.split_once(char::is_whitespace) {
+        Some((method, rest)) if METHODS.contains(&method.to_uppercase().as_str()) => {
+            (method.to_uppercase(), rest.trim())
+        }
+        _ => (String::from("GET"), line),
+    };
+    match rest.rsplit_once(char::is_whitespace) {
+        Some((url, version)) if version.starts_with("HTTP/") => {
+            (method, url.trim().to_string(), Some(version.to_string()))
+        }
+        _ => (method, rest.to_string(), None),
+    }
+}
+
+impl HttpRequest {
+    // Reads the request in one section of the file, None if it only has comments and variables
+    fn parse(section: &str, index: usize) -> Option<HttpRequest> {
+        let mut request = HttpRequest {
+            section: index,
+            ..Default::default()
+        };
+        let mut lines = section.lines().peekable();
+        // the separator, comments and variables come before the request line
+        for line in lines.by_ref() {
+            if is_separator(line) {
+                request.name = line.trim_start().trim_start_matches('#').trim().to_string();
+            } else if let Some(name) = parse_name_comment(line) {
+                request.name = name;
+            } else if !(line.trim().is_empty()
+                || is_comment(line)
+                || parse_variable(line).is_some())
+            {
+                (request.method, request.url, request.version) = parse_request_line(line);
+                break;
+            }
+            request.prefix.push(line.to_string());
+        }
+        if request.url.is_empty() {
+            return None;
+        }
+        // long query strings can continue on the next lines
+        while let Some(line) = lines.next_if(|line| {
+            let trimmed = line.trim_start();
+            line.starts_with(char::is_whitespace)
+                && (trimmed.starts_with('?') || trimmed.starts_with('&'))
+        }) {
+            request.url.push_str(line.trim());
+        }
+        while let Some(line) = lines.next_if(|line| !line.trim().is_empty()) {
+            if is_comment(line) {
+                continue;
+            }
+            if let Some((key, value)) = line.split_once(':') {
+                request
+                    .headers
+                    .push((key.trim().to_string(), value.trim().to_string()));
+            }
+        }
+        let mut body: Vec<&str> = Vec::new();
+        for line in lines.by_ref() {
+            let trimmed = line.trim_start();
+            if trimmed.starts_with("> {%")
+                || trimmed.starts_with("<> ")
+                || trimmed.starts_with(">> ")
+            {
+                request.suffix.push(line.to_string());
+                break;
+            }
+            body.push(line);
+        }
+        request.suffix.extend(lines.map(str::to_string));
+        while request
+            .suffix
+            .last()
+            .is_some_and(|line| line.trim().is_empty())
+        {
+            request.suffix.pop();
+        }
+        // the blank line after the headers, and the ones before the next request
+        while body.first().is_some_and(|line| line.trim().is_empty()) {
+            body.remove(0);
+        }
+        while body.last().is_some_and(|line| line.trim().is_empty()) {
+            body.pop();
+        }
+        if !body.is_empty() {
+            request.body = Some(body.join("\n"));
+        }
+        Some(request)
+    }
+
+    // The request as it's written in the file
+    pub fn to_text(&self) -> String {
+        let mut lines = self.prefix.clone();
+        // the name goes back where it was, on a `# @name` comment or the separator
+        let name_comment = lines
+            .iter()
+            .position(|line| parse_name_comment(line).is_some());
+        match (
+            name_comment,
+            lines.iter().position(|line| is_separator(line)),
+        ) {
+            (Some(index), _) => {
+                let comment = if lines[index].trim_start().starts_with("//") {
+                    "//"
+                } else {
+                    "#"
+                };
+                lines[index] = format!("{} @name {}", comment, self.name);
+            }
+            (None, Some(index)) => {
+                lines[index] = format!("### {}", self.name).trim_end().to_string()
+            }
+            (None, None) if !self.name.is_empty() => lines.insert(0, format!("### {}", self.name)),
+            (None, None) => {}
+        }
+        let mut request_line = format!("{} {}", self.method, self.url);
+        if let Some(version) = self.version.as_ref() {
+            request_line = format!("{} {}", request_line, version);
+        }
+        lines.push(request_line);
+        lines.extend(
+            self.headers
+                .iter()
+                .map(|(key, value)| format!("{}: {}", key, value)),
+        );
+        if let Some(body) = self.body.as_ref() {
+            lines.push(String::new());
+            lines.push(body.clone());
+        }
+        if !self.suffix.is_empty() {
+            lines.push(String::new());
+            lines.extend(self.suffix.iter().cloned());
+        }
+        lines.join("\n") + "\n"
+    }
+
+    pub fn get_label(&self) -> &str {
+        if self.name.is_empty() {
+            &self.url
+        } else {
+            &self.name
+        }
+    }
+
+    // Replaces the header with the same name, an empty value removes it
+    pub fn set_header(&mut self, header: &str) -> Result<(), String> {
+        let (key, value) = header
+            .split_once(':')
+            .map(|(key, value)| (key.trim(), value.trim()))
+            .filter(|(key, _)| !key.is_empty())
+            .ok_or(String::from("Error: headers must be \"Key: Value\""))?;
+        let existing = self
+            .headers
+            .iter()
+            .position(|(name, _)| name.eq_ignore_ascii_case(key));
+        match (existing, value.is_empty()) {
+            (Some(index), true) => {
+                self.headers.remove(index);
+            }
+            (Some(index), false) => self.headers[index] = (key.to_string(), value.to_string()),
+            (None, true) => {}
+            (None, false) => self.headers.push((key.to_string(), value.to_string())),
+        }
+        Ok(())
+    }
+
+    // Builds the request with the file's variables filled in. Variables the file doesn't
+    // define are left for the active environment
+    pub fn to_curl(&self, variables: &[(String, String)]) -> Result<Curl<'static>, String> {
+        if !["GET", "POST", "PUT", "PATCH", "DELETE", "HEAD"].contains(&self.method.as_str()) {
+            return Err(format!("Error: unsupported method {}", self.method));
+        }
+        let mut curl = Curl::new();
+        curl.set_method(self.method.clone());
+        curl.set_url(&expand_vars(&self.url, variables));
+        for (key, value) in self.headers.iter() {
+            let value = expand_vars(value, variables);
+            // both clients accept "Basic user password" and encode it themselves
+            match auth_from_header(key, &value) {
+                Some(auth) => curl.set_auth(auth),
+                None => curl.add_headers(format!("{}: {}", key, value)),
+            }
+        }
+        if let Some(body) = self.body.as_ref() {
+            curl.set_request_body(&expand_vars(body, variables));
+        }
+        Ok(curl)
+    }
+}
+
+fn auth_from_header(key: &str, value: &str) -> Option<AuthKind> {
+    if !key.eq_ignore_ascii_case("authorization") {
+        return None;
+    }
+    let (scheme, credentials) = value.split_once(' ')?;
+    let credentials = credentials.trim();
+    let login = match credentials.split_once(char::is_whitespace) {
+        Some((user, password)) => format!("{}:{}", user, password.trim()),
+        None if credentials.contains(':') => credentials.to_string(),
+        // already encoded
+        None => return None,
+    };
+    match scheme.to_lowercase().as_str() {
+        "basic" => Some(AuthKind::Basic(login)),
+        "digest" => Some(AuthKind::Digest(login)),
+        _ => None,
+    }
+}
+
+impl HttpFile {
+    pub fn parse(path: &Path, input: &str) -> HttpFile {
+        let mut sections: Vec<String> = Vec::new();
+        let mut current = String::new();
+        for line in input.split_inclusive('\n') {
+            if is_separator(line) && !current.is_empty() {
+                sections.push(std::mem::take(&mut current));
+            }
+            current.push_str(line);
+        }
+        if !current.is_empty() {
+            sections.push(current);
+        }
+        let mut file = HttpFile {
+            path: path.to_path_buf(),
+            sections,
+            ..Default::default()
+        };
+        file.read_sections();
+        file
+    }
+
+    pub fn open(path: &str) -> Result<HttpFile, String> {
+        let path = PathBuf::from(path.trim());
+        let input = std::fs::read_to_string(&path)
+            .map_err(|e| format!("Error: unable to read {}: {}", path.display(), e))?;
+        Ok(HttpFile::parse(&path, &input))
+    }
+
+    fn read_sections(&mut self) {
+        self.variables.clear();
+        self.requests.clear();
+        for (index, section) in self.sections.iter().enumerate() {
+            for line in section.lines() {
+                if let Some((name, value)) = parse_variable(line) {
+                    // variables can use the ones defined before them
+                    let value = expand_vars(&value, &self.variables);
+                    self.variables.retain(|(existing, _)| existing != &name);
+                    self.variables.push((name, value));
+                }
+            }
+            if let Some(request) = HttpRequest::parse(section, index) {
+                self.requests.push(request);
+            }
+        }
+    }
+
+    pub fn get_path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn get_variables(&self) -> &[(String, String)] {
+        &self.variables
+    }
+
+    pub fn get_requests(&self) -> &[HttpRequest] {
+        &self.requests
+    }
+
+    pub fn get_request(&self, index: usize) -> Result<&HttpRequest, String> {
+        self.requests
+            .get(index)
+            .ok_or(String::from("Error: request not found in the .http file"))
+    }
+
+    // Looks up a request by its number in the list (starting at 1) or its name
+    pub fn find_request(&self, target: &str) -> Option<&HttpRequest> {
+        match target.trim().parse::<usize>() {
+            Ok(number) if number > 0 => self.requests.get(number - 1),
+            _ => self
+                .requests
+                .iter()
+                .find(|request| request.name == target.trim()),
+        }
+    }
+
+    pub fn to_curl(&self, index: usize) -> Result<Curl<'static>, String> {
+        self.get_request(index)?.to_curl(&self.variables)
+    }
+
+    pub fn to_text(&self) -> String {
+        self.sections.concat()
+    }
+
+    // Puts the edited request in place of the original and writes the file
+    pub fn update_request(&mut self, index: usize, request: HttpRequest) -> Result<(), String> {
+        let section = self.get_request(index)?.section;
+        let original = &self.sections[section];
+        // keep the blank lines that separated it from the next request
+        let trailing = original.len() - original.trim_end().len();
+        let blank_lines = original[original.len() - trailing..].matches('\n').count();
+        let mut text = request.to_text();
+        if blank_lines > 1 {
+            text.push_str(&"\n".repeat(blank_lines - 1));
+        }
+        self.sections[section] = text;
+        self.read_sections();
+        self.save()
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        std::fs::write(&self.path, self.to_text())
+            .map_err(|e| format!("Error: unable to write {}: {}", self.path.display(), e))
+    }
+}
+
+// The body input is a single line, so newlines are typed as \n and backslashes as \\
+pub fn escape_body(body: &str) -> String {
+    body.replace('\\', "\\\\").replace('\n', "\\n")
+}
+
+pub fn unescape_body(body: &str) -> String {
+    let mut unescaped = String::with_capacity(body.len());
+    let mut chars = body.chars();
+    while let Some(c) = chars.next() {
+        match (c, chars.clone().next()) {
+            ('\\', Some('n')) => {
+                unescaped.push('\n');
+                chars.next();
+            }
+            ('\\', Some('\\')) => {
+                unescaped.push('\\');
+                chars.next();
+            }
+            (c, _) => unescaped.push(c),
+        }
+    }
+    unescaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HTTP_FILE: &str = "@host = localhost:8080
+@base = http://{{host}}/api
+
+### List users
+GET {{base}}/users
+    ?page=2
+    &limit=10
+Accept: application/json
+
+###
+# @name create
+POST {{base}}/users HTTP/1.1
+Content-Type: application/json
+Authorization: Basic admin secret
+
+{
+  \"name\": \"{{name}}\"
+}
+
+> {%
+    client.global.set(\"id\", response.body.id);
+%}
+
+### Only a comment
+# nothing to send here
+
+### Delete
+DELETE {{base}}/users/1
+";
+
+    #[test]
+    fn test_parse_http_file() {
+        let file = HttpFile::parse(Path::new("api.http"), HTTP_FILE);
+        assert_eq!(
+            file.get_variables(),
+            &[
+                (String::from("host"), String::from("localhost:8080")),
+                (
+                    String::from("base"),
+                    String::from("http://localhost:8080/api")
+                ),
+            ]
+        );
+        let requests = file.get_requests();
+        assert_eq!(requests.len(), 3);
+        assert_eq!(requests[0].name, "List users");
+        assert_eq!(requests[0].url, "{{base}}/users?page=2&limit=10");
+        assert_eq!(
+            requests[0].headers,
+            vec![(String::from("Accept"), String::from("application/json"))]
+        );
+        assert_eq!(requests[0].body, None);
+        assert_eq!(requests[1].name, "create");
+        assert_eq!(requests[1].method, "POST");
+        assert_eq!(requests[1].version.as_deref(), Some("HTTP/1.1"));
+        assert_eq!(
+            requests[1].body.as_deref(),
+            Some("{\n  \"name\": \"{{name}}\"\n}")
+        );
+        assert_eq!(requests[2].to_string(), "Delete | DELETE {{base}}/users/1");
+        assert_eq!(
+            file.find_request("3").map(|r| r.method.as_str()),
+            Some("DELETE")
+        );
+        assert_eq!(file.find_request("create"), Some(&requests[1]));
+        assert_eq!(file.to_text(), HTTP_FILE);
+    }
+
+    #[test]
+    fn test_http_request_to_curl() {
+        let file = HttpFile::parse(Path::new("api.http"), HTTP_FILE);
+        let curl = file.to_curl(0).unwrap();
+        assert_eq!(
+            curl.get_url(),
+            "http://localhost:8080/api/users?page=2&limit=10"
+        );
+        let create = file.to_curl(1).unwrap();
+        assert_eq!(
+            create.get_auth(),
+            &AuthKind::Basic(String::from("admin:secret"))
+        );
+        assert_eq!(create.get_headers(), vec!["Content-Type: application/json"]);
+        // {{name}} is left for the active environment
+        assert_eq!(
+            create.get_request_body(),
+            Some(String::from("{\n  \"name\": \"{{name}}\"\n}"))
+        );
+        assert!(HttpFile::parse(Path::new("a.http"), "OPTIONS http://a")
+            .to_curl(0)
+            .is_err());
+    }
+
+    #[test]
+    fn test_update_request() {
+        let path = std::env::temp_dir().join("cute_update_request.http");
+        std::fs::write(&path, HTTP_FILE).unwrap();
+        let mut file = HttpFile::open(&path.to_string_lossy()).unwrap();
+        let mut request = file.get_request(1).unwrap().clone();
+        request.name = String::from("create user");
+        request.set_header("content-type: text/plain").unwrap();
+        request.set_header("X-Trace: 1").unwrap();
+        request.set_header("Authorization:").unwrap();
+        request.body = Some(String::from("plain"));
+        file.update_request(1, request).unwrap();
+
+        let saved = std::fs::read_to_string(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert!(saved.contains(
+            "###
+# @name create user
+POST {{base}}/users HTTP/1.1
+content-type: text/plain
+X-Trace: 1
+
+plain
+
+> {%"
+        ));
+        // the other requests are untouched
+        assert!(saved.starts_with("@host = localhost:8080\n@base = http://{{host}}/api\n\n### List users\nGET {{base}}/users\n    ?page=2\n"));
+        assert!(saved.contains("%}\n\n### Only a comment\n"));
+        assert!(saved.ends_with("### Delete\nDELETE {{base}}/users/1\n"));
+        assert_eq!(file.get_requests()[1].name, "create user");
+        assert_eq!(file.get_requests().len(), 3);
+    }
+
+    #[test]
+    fn test_parse_request_line() {
+        assert_eq!(
+            parse_request_line("https://example.com"),
+            (
+                String::from("GET"),
+                String::from("https://example.com"),
+                None
+            )
+        );
+        assert_eq!(
+            parse_request_line("patch /a b HTTP/2"),
+            (
+                String::from("PATCH"),
+                String::from("/a b"),
+                Some(String::from("HTTP/2"))
+            )
+        );
+    }
+
+    #[test]
+    fn test_escape_body() {
+        let body = "{\n  \"path\": \"C:\\\\temp\\n\"\n}";
+        assert_eq!(unescape_body(&escape_body(body)), body);
+        assert!(!escape_body(body).contains('\n'));
+    }
+}
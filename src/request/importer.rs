@@ -0,0 +1,204 @@
+/*
+* Requests read from another tool's files (Postman collections, OpenAPI specs, HAR files),
+* and saving them into the database as a collection of saved commands.
+ */
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::str::FromStr;
+
+use super::command::CmdOpts;
+use super::curl::Curl;
+use super::har::parse_har;
+use super::openapi::parse_openapi_spec;
+use super::postman::parse_postman_collection;
+use crate::database::db::DB;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ImportFormat {
+    Postman,
+    OpenApi,
+    Har,
+}
+
+impl Display for ImportFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ImportFormat::Postman => write!(f, "Postman collection"),
+            ImportFormat::OpenApi => write!(f, "OpenAPI spec"),
+            ImportFormat::Har => write!(f, "HAR file"),
+        }
+    }
+}
+
+impl FromStr for ImportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "postman" => Ok(ImportFormat::Postman),
+            "openapi" | "swagger" => Ok(ImportFormat::OpenApi),
+            "har" => Ok(ImportFormat::Har),
+            _ => Err(format!(
+                "Error: unknown import format '{}', use postman, openapi, har or bundle",
+                s
+            )),
+        }
+    }
+}
+
+impl ImportFormat {
+    pub fn parse(&self, input: &str) -> Result<CollectionImport<'static>, String> {
+        match self {
+            ImportFormat::Postman => parse_postman_collection(input),
+            ImportFormat::OpenApi => parse_openapi_spec(input),
+            ImportFormat::Har => parse_har(input),
+        }
+    }
+}
+
+/// A request from the imported file, translated to a `Curl`.
+#[derive(Debug)]
+pub struct ImportedRequest<'a> {
+    pub name: String,
+    pub description: Option<String>,
+    // The folders the request is nested in, outermost first
+    pub folders: Vec<String>,
+    pub curl: Curl<'a>,
+}
+
+/// Everything read from an imported file, along with what we could not translate.
+#[derive(Debug, Default)]
+pub struct CollectionImport<'a> {
+    pub name: String,
+    // Every folder path in the collection, parents before their children
+    pub folders: Vec<Vec<String>>,
+    pub requests: Vec<ImportedRequest<'a>>,
+    // Variables the requests use, like base_url, saved to an environment named after the collection
+    pub variables: Vec<(String, String)>,
+    // "Folder/Request: reason", for items or parts of items that were skipped
+    pub ignored: Vec<String>,
+}
+
+impl<'a> CollectionImport<'a> {
+    pub fn new(name: &str) -> Self {
+        CollectionImport {
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    // Adds the folder unless it's already there
+    pub fn add_folder(&mut self, path: &[String]) {
+        if !self.folders.iter().any(|folder| folder == path) {
+            self.folders.push(path.to_vec());
+        }
+    }
+
+    // Saves the requests under a new top level collection, returns how many commands were saved
+    pub fn save(&mut self, db: &DB) -> Result<usize, String> {
+        let root = db
+            .add_collection(&self.name, None)
+            .map_err(|e| e.to_string())?;
+        let mut ids: HashMap<Vec<String>, i32> = HashMap::new();
+        for path in self.folders.iter() {
+            let parent = match path.split_last() {
+                Some((_, [])) | None => root,
+                Some((_, parent)) => ids.get(parent).copied().unwrap_or(root),
+            };
+            let name = path.last().map(String::as_str).unwrap_or_default();
+            let id = db
+                .add_collection(name, Some(parent))
+                .map_err(|e| e.to_string())?;
+            ids.insert(path.clone(), id);
+        }
+        for request in self.requests.iter_mut() {
+            let json = serde_json::to_string(&request.curl).map_err(|e| e.to_string())?;
+            let id = db
+                .add_command(&request.curl.get_command_string(), json)
+                .map_err(|e| e.to_string())?;
+            db.rename_command(id, Some(&request.name))
+                .map_err(|e| e.to_string())?;
+            db.set_command_description(id, request.description.as_deref())
+                .map_err(|e| e.to_string())?;
+            let collection = ids.get(&request.folders).copied().unwrap_or(root);
+            db.move_command(id, Some(collection))
+                .map_err(|e| e.to_string())?;
+        }
+        self.save_variables(db)?;
+        Ok(self.requests.len())
+    }
+
+    // Variables the environment already has keep their values, so importing again doesn't
+    // overwrite what the user set
+    fn save_variables(&self, db: &DB) -> Result<(), String> {
+        if self.variables.is_empty() {
+            return Ok(());
+        }
+        let find = || {
+            db.get_environments()
+                .map(|envs| envs.into_iter().find(|env| env.get_name() == self.name))
+                .map_err(|e| e.to_string())
+        };
+        let env = match find()? {
+            Some(env) => env,
+            None => {
+                db.add_environment(&self.name).map_err(|e| e.to_string())?;
+                find()?.ok_or(String::from("Error: unable to create the environment"))?
+            }
+        };
+        for (key, value) in self.variables.iter() {
+            if !env.get_vars().iter().any(|(existing, _)| existing == key) {
+                db.set_env_var(env.get_id(), key, value)
+                    .map_err(|e| e.to_string())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_import_format() {
+        assert_eq!("Postman".parse::<ImportFormat>(), Ok(ImportFormat::Postman));
+        assert_eq!("swagger".parse::<ImportFormat>(), Ok(ImportFormat::OpenApi));
+        assert_eq!("HAR".parse::<ImportFormat>(), Ok(ImportFormat::Har));
+        assert!("insomnia".parse::<ImportFormat>().is_err());
+    }
+
+    #[test]
+    fn test_save_variables() {
+        let db = DB::new_in_memory().unwrap();
+        let mut import = CollectionImport::new("Petstore");
+        let mut curl = Curl::new();
+        curl.set_url("{{base_url}}/pets");
+        import.requests.push(ImportedRequest {
+            name: String::from("List pets"),
+            description: None,
+            folders: Vec::new(),
+            curl,
+        });
+        import.variables = vec![(String::from("base_url"), String::from("http://a"))];
+        assert_eq!(import.save(&db).unwrap(), 1);
+        let env = db.get_environments().unwrap()[0].clone();
+        db.set_env_var(env.get_id(), "base_url", "http://localhost")
+            .unwrap();
+
+        // importing again keeps the user's value and adds the new variable
+        import
+            .variables
+            .push((String::from("token"), String::new()));
+        import.save(&db).unwrap();
+        let envs = db.get_environments().unwrap();
+        assert_eq!(envs.len(), 1);
+        assert_eq!(
+            envs[0].get_vars(),
+            &[
+                (String::from("base_url"), String::from("http://localhost")),
+                (String::from("token"), String::new()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,446 @@
+/*
+* A small subset of JSONPath for picking a value out of a response body:
+* $.users[0].name, $['content-type'], with the leading $ being optional.
+* Filters add a subset of jq on top: .items[].id, .data | length, map(.name) | sort
+ */
+use std::cmp::Ordering;
+
+use serde_json::Value;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Segment {
+    Key(String),
+    Index(usize),
+    // [], [*] or .* for every element of an array or value of an object
+    Iterate,
+}
+
+// One step of a filter, the values coming out of a step are fed to the next one
+#[derive(Debug, Clone, PartialEq)]
+pub enum Filter {
+    Path(Vec<Segment>),
+    Function(String),
+    // map(f) runs f on every element of an array
+    Map(Vec<Filter>),
+}
+
+pub const FUNCTIONS: [&str; 11] = [
+    "length", "keys", "first", "last", "type", "sort", "reverse", "unique", "add", "min", "max",
+];
+
+static NULL: Value = Value::Null;
+
+pub fn parse_path(path: &str) -> Result<Vec<Segment>, String> {
+    let path = path.trim();
+    let mut rest = path.strip_prefix('$').unwrap_or(path);
+    let mut segments = Vec::new();
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix('.') {
+            let end = after.find(['.', '[']).unwrap_or(after.len());
+            // `.` alone is the whole value, and jq writes `.[0]` for `[0]`
+            if after.is_empty() || after.starts_with('[') {
+                rest = after;
+                continue;
+            }
+            if end == 0 {
+                return Err(format!("Error: Invalid JSON path '{}'", path));
+            }
+            segments.push(match &after[..end] {
+                "*" => Segment::Iterate,
+                key => Segment::Key(key.to_string()),
+            });
+            rest = &after[end..];
+        } else if let Some(after) = rest.strip_prefix('[') {
+            let end = after
+                .find(']')
+                .ok_or(format!("Error: Missing ']' in JSON path '{}'", path))?;
+            let inner = after[..end].trim();
+            let quoted = inner
+                .strip_prefix('\'')
+                .and_then(|s| s.strip_suffix('\''))
+                .or_else(|| inner.strip_prefix('"').and_then(|s| s.strip_suffix('"')));
+            match quoted {
+                Some(key) => segments.push(Segment::Key(key.to_string())),
+                None if inner.is_empty() || inner == "*" => segments.push(Segment::Iterate),
+                None => segments.push(Segment::Index(inner.parse::<usize>().map_err(|_| {
+                    format!("Error: Invalid index '{}' in JSON path '{}'", inner, path)
+                })?)),
+            }
+            rest = &after[end + 1..];
+        } else if segments.is_empty() && rest.len() == path.len() {
+            // "users[0].name" reads the same as "$.users[0].name"
+            let end = rest.find(['.', '[']).unwrap_or(rest.len());
+            segments.push(Segment::Key(rest[..end].to_string()));
+            rest = &rest[end..];
+        } else {
+            return Err(format!("Error: Invalid JSON path '{}'", path));
+        }
+    }
+    Ok(segments)
+}
+
+// The value at the path, None if some part of it doesn't exist
+pub fn query<'v>(value: &'v Value, path: &str) -> Result<Option<&'v Value>, String> {
+    let segments = parse_path(path)?;
+    if segments.contains(&Segment::Iterate) {
+        return Err(format!(
+            "Error: JSON path '{}' has to point at a single value",
+            path
+        ));
+    }
+    Ok(segments
+        .iter()
+        .try_fold(value, |value, segment| match segment {
+            Segment::Key(key) => value.get(key),
+            Segment::Index(index) => value.get(index),
+            Segment::Iterate => None,
+        }))
+}
+
+pub fn parse_filter(expr: &str) -> Result<Vec<Filter>, String> {
+    split_pipes(expr)
+        .iter()
+        .map(|step| {
+            let step = step.trim();
+            if let Some(inner) = step.strip_prefix("map(").and_then(|s| s.strip_suffix(')')) {
+                Ok(Filter::Map(parse_filter(inner)?))
+            } else if step.starts_with('.') || step.starts_with('$') {
+                Ok(Filter::Path(parse_path(step)?))
+            } else if FUNCTIONS.contains(&step) {
+                Ok(Filter::Function(step.to_string()))
+            } else if step.is_empty() {
+                Err(format!("Error: Empty step in filter '{}'", expr.trim()))
+            } else {
+                Err(format!("Error: Unknown filter '{}'", step))
+            }
+        })
+        .collect()
+}
+
+// Splits on the pipes that aren't inside quotes, brackets or parentheses
+fn split_pipes(expr: &str) -> Vec<&str> {
+    let mut steps = Vec::new();
+    let mut depth = 0;
+    let mut quote: Option<char> = None;
+    let mut start = 0;
+    for (index, c) in expr.char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '[' | '(') => depth += 1,
+            (None, ']' | ')') => depth -= 1,
+            (None, '|') if depth == 0 => {
+                steps.push(&expr[start..index]);
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    steps.push(&expr[start..]);
+    steps
+}
+
+// Every value the filter produces, like jq a path with [] in it can produce many
+pub fn filter(value: &Value, expr: &str) -> Result<Vec<Value>, String> {
+    apply_filter(value, &parse_filter(expr)?)
+}
+
+pub fn apply_filter(value: &Value, filters: &[Filter]) -> Result<Vec<Value>, String> {
+    filters
+        .iter()
+        .try_fold(vec![value.clone()], |values, filter| {
+            values
+                .iter()
+                .map(|value| apply_step(value, filter))
+                .collect::<Result<Vec<Vec<Value>>, String>>()
+                .map(|values| values.concat())
+        })
+}
+
+// Each result pretty printed, one after the other like jq prints them
+pub fn format_results(values: &[Value]) -> String {
+    values
+        .iter()
+        .map(|value| serde_json::to_string_pretty(value).unwrap_or_default())
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+fn apply_step(value: &Value, filter: &Filter) -> Result<Vec<Value>, String> {
+    match filter {
+        Filter::Path(segments) => walk(value, segments),
+        Filter::Function(name) => call(name, value).map(|value| vec![value]),
+        Filter::Map(filters) => {
+            let mapped = walk(value, &[Segment::Iterate])?
+                .iter()
+                .map(|item| apply_filter(item, filters))
+                .collect::<Result<Vec<Vec<Value>>, String>>()?;
+            Ok(vec![Value::Array(mapped.concat())])
+        }
+    }
+}
+
+// Missing keys and indexes are null, like in jq
+fn walk(value: &Value, segments: &[Segment]) -> Result<Vec<Value>, String> {
+    let mut values = vec![value];
+    for segment in segments {
+        let mut next = Vec::new();
+        for value in values {
+            match (segment, value) {
+                (Segment::Key(key), Value::Object(map)) => next.push(map.get(key).unwrap_or(&NULL)),
+                (Segment::Index(index), Value::Array(items)) => {
+                    next.push(items.get(*index).unwrap_or(&NULL))
+                }
+                (Segment::Key(_) | Segment::Index(_), Value::Null) => next.push(&NULL),
+                (Segment::Iterate, Value::Array(items)) => next.extend(items.iter()),
+                (Segment::Iterate, Value::Object(map)) => next.extend(map.values()),
+                (Segment::Key(key), value) => {
+                    return Err(format!(
+                        "Error: Cannot index {} with \"{}\"",
+                        type_name(value),
+                        key
+                    ))
+                }
+                (Segment::Index(_), value) => {
+                    return Err(format!(
+                        "Error: Cannot index {} with a number",
+                        type_name(value)
+                    ))
+                }
+                (Segment::Iterate, value) => {
+                    return Err(format!("Error: Cannot iterate over {}", type_name(value)))
+                }
+            }
+        }
+        values = next;
+    }
+    Ok(values.into_iter().cloned().collect())
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+// jq's order: null, false, true, numbers, strings, arrays, objects
+fn compare(a: &Value, b: &Value) -> Ordering {
+    let rank = |value: &Value| match value {
+        Value::Null => 0,
+        Value::Bool(false) => 1,
+        Value::Bool(true) => 2,
+        Value::Number(_) => 3,
+        Value::String(_) => 4,
+        Value::Array(_) => 5,
+        Value::Object(_) => 6,
+    };
+    match (a, b) {
+        (Value::Number(a), Value::Number(b)) => a
+            .as_f64()
+            .partial_cmp(&b.as_f64())
+            .unwrap_or(Ordering::Equal),
+        (Value::String(a), Value::String(b)) => a.cmp(b),
+        (Value::Array(a), Value::Array(b)) => a
+            .iter()
+            .zip(b.iter())
+            .map(|(a, b)| compare(a, b))
+            .find(|order| order.is_ne())
+            .unwrap_or(a.len().cmp(&b.len())),
+        (a, b) if rank(a) == rank(b) => a.to_string().cmp(&b.to_string()),
+        (a, b) => rank(a).cmp(&rank(b)),
+    }
+}
+
+fn call(name: &str, value: &Value) -> Result<Value, String> {
+    let error = || format!("Error: {} can't be applied to {}", name, type_name(value));
+    match (name, value) {
+        ("length", Value::Null) => Ok(Value::from(0)),
+        ("length", Value::Number(n)) => Ok(match n.as_i64() {
+            Some(n) => Value::from(n.unsigned_abs()),
+            None => Value::from(n.as_f64().unwrap_or_default().abs()),
+        }),
+        ("length", Value::String(s)) => Ok(Value::from(s.chars().count())),
+        ("length", Value::Array(items)) => Ok(Value::from(items.len())),
+        ("length", Value::Object(map)) => Ok(Value::from(map.len())),
+        ("keys", Value::Object(map)) => {
+            Ok(Value::from(map.keys().cloned().collect::<Vec<String>>()))
+        }
+        ("keys", Value::Array(items)) => Ok(Value::from((0..items.len()).collect::<Vec<usize>>())),
+        ("type", value) => Ok(Value::from(type_name(value))),
+        ("reverse", Value::String(s)) => Ok(Value::from(s.chars().rev().collect::<String>())),
+        (_, Value::Array(items)) => call_on_array(name, items).ok_or_else(error),
+        _ => Err(error()),
+    }
+}
+
+fn call_on_array(name: &str, items: &[Value]) -> Option<Value> {
+    let mut sorted = items.to_vec();
+    sorted.sort_by(compare);
+    match name {
+        "first" => Some(items.first().cloned().unwrap_or_default()),
+        "last" => Some(items.last().cloned().unwrap_or_default()),
+        "sort" => Some(Value::Array(sorted)),
+        "reverse" => Some(Value::Array(items.iter().rev().cloned().collect())),
+        "unique" => {
+            sorted.dedup();
+            Some(Value::Array(sorted))
+        }
+        "min" => Some(sorted.first().cloned().unwrap_or_default()),
+        "max" => Some(sorted.last().cloned().unwrap_or_default()),
+        "add" => items.iter().try_fold(Value::Null, add),
+        _ => None,
+    }
+}
+
+// Numbers are summed, strings and arrays joined and objects merged, null adds nothing
+fn add(sum: Value, item: &Value) -> Option<Value> {
+    match (sum, item) {
+        (sum, Value::Null) => Some(sum),
+        (Value::Null, item) => Some(item.clone()),
+        (Value::Number(a), Value::Number(b)) => match (a.as_i64(), b.as_i64()) {
+            (Some(a), Some(b)) => a.checked_add(b).map(Value::from),
+            _ => None,
+        }
+        .or_else(|| Some(Value::from(a.as_f64()? + b.as_f64()?))),
+        (Value::String(a), Value::String(b)) => Some(Value::from(a + b)),
+        (Value::Array(mut a), Value::Array(b)) => {
+            a.extend(b.iter().cloned());
+            Some(Value::Array(a))
+        }
+        (Value::Object(mut a), Value::Object(b)) => {
+            a.extend(b.clone());
+            Some(Value::Object(a))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_path() {
+        assert_eq!(
+            parse_path("$.users[0]['first name']").unwrap(),
+            vec![
+                Segment::Key(String::from("users")),
+                Segment::Index(0),
+                Segment::Key(String::from("first name")),
+            ]
+        );
+        assert_eq!(
+            parse_path("users.id").unwrap(),
+            parse_path("$.users.id").unwrap()
+        );
+        assert!(parse_path("$").unwrap().is_empty());
+        assert!(parse_path("$..id").is_err());
+        assert!(parse_path("$.users[x]").is_err());
+        assert!(parse_path("$.users[0").is_err());
+    }
+
+    #[test]
+    fn test_query() {
+        let value = json!({"users": [{"id": 1, "name": "alice"}], "count": 1});
+        assert_eq!(
+            query(&value, "$.users[0].name").unwrap(),
+            Some(&json!("alice"))
+        );
+        assert_eq!(query(&value, "$.count").unwrap(), Some(&json!(1)));
+        assert_eq!(query(&value, "$").unwrap(), Some(&value));
+        assert_eq!(query(&value, "$.users[1].name").unwrap(), None);
+        assert_eq!(query(&value, "$.missing").unwrap(), None);
+        assert!(query(&value, "$.users[*].id").is_err());
+    }
+
+    #[test]
+    fn test_parse_filter() {
+        assert_eq!(
+            parse_filter(".items[].id | length").unwrap(),
+            vec![
+                Filter::Path(vec![
+                    Segment::Key(String::from("items")),
+                    Segment::Iterate,
+                    Segment::Key(String::from("id")),
+                ]),
+                Filter::Function(String::from("length")),
+            ]
+        );
+        assert_eq!(
+            parse_filter("$.items[*] | map(.['a|b'] | first)").unwrap(),
+            vec![
+                Filter::Path(vec![Segment::Key(String::from("items")), Segment::Iterate]),
+                Filter::Map(vec![
+                    Filter::Path(vec![Segment::Key(String::from("a|b"))]),
+                    Filter::Function(String::from("first")),
+                ]),
+            ]
+        );
+        assert_eq!(parse_filter(".").unwrap(), vec![Filter::Path(vec![])]);
+        assert!(parse_filter(".items |").is_err());
+        assert!(parse_filter("lenght").is_err());
+        assert!(parse_filter(".items[x]").is_err());
+    }
+
+    #[test]
+    fn test_filter() {
+        let value = json!({
+            "items": [
+                {"id": 3, "name": "c", "tags": ["x"]},
+                {"id": 1, "name": "a", "tags": ["y", "z"]},
+                {"id": 2, "name": "b"}
+            ],
+            "data": {"total": 3, "page": 1}
+        });
+        assert_eq!(
+            filter(&value, ".items[].id").unwrap(),
+            vec![json!(3), json!(1), json!(2)]
+        );
+        assert_eq!(filter(&value, ".data | length").unwrap(), vec![json!(2)]);
+        assert_eq!(
+            filter(&value, ".data | keys").unwrap(),
+            vec![json!(["page", "total"])]
+        );
+        assert_eq!(
+            filter(&value, "map(.name)"),
+            Err(String::from("Error: Cannot index array with \"name\""))
+        );
+        assert_eq!(
+            filter(&value, ".items | map(.name) | sort | reverse").unwrap(),
+            vec![json!(["c", "b", "a"])]
+        );
+        assert_eq!(
+            filter(&value, ".items | map(.id) | add").unwrap(),
+            vec![json!(6)]
+        );
+        assert_eq!(
+            filter(&value, ".items[].tags | length").unwrap(),
+            vec![json!(1), json!(2), json!(0)]
+        );
+        assert_eq!(
+            filter(&value, ".items | map(.name) | unique | last").unwrap(),
+            vec![json!("c")]
+        );
+        assert_eq!(
+            filter(&value, ".items | map(.tags[])"),
+            Err(String::from("Error: Cannot iterate over null"))
+        );
+        assert_eq!(
+            filter(&value, ".items[5].missing | type").unwrap(),
+            vec![json!("null")]
+        );
+        assert!(filter(&value, ".items.id").is_err());
+        assert!(filter(&value, ".data.total[]").is_err());
+        assert_eq!(
+            format_results(&filter(&value, ".items[1].tags").unwrap()),
+            "[\n  \"y\",\n  \"z\"\n]"
+        );
+    }
+}
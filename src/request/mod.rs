@@ -5,3 +5,27 @@ pub mod curl;
 pub mod wget;
 // Response parser
 pub mod response;
+// Raw curl command line parser
+pub mod parser;
+// Client code snippets generated from a request
+pub mod snippet;
+// {{variable}} substitution from the active environment
+pub mod environment;
+// Picking values out of a JSON body with a path like $.users[0].id
+pub mod jsonpath;
+// Checks on the status, headers, body and timing of a response
+pub mod assertion;
+// Runs saved commands as tests and collects the results
+pub mod runner;
+// Saves requests imported from other tools as a collection of saved commands
+pub mod importer;
+// Imports Postman collection exports
+pub mod postman;
+// Generates requests from OpenAPI 3 and Swagger 2 specs
+pub mod openapi;
+// Imports HAR files and exports history or saved commands as HAR
+pub mod har;
+// Reads and writes .http request files
+pub mod httpfile;
+// Compares two responses, by body, JSON paths or headers
+pub mod diff;
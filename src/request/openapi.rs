@@ -0,0 +1,751 @@
+/*
+* Generates one request per operation of an OpenAPI 3 or Swagger 2 spec (JSON or YAML).
+* URLs start with {{base_url}}, which is saved to an environment named after the spec,
+* and operations are grouped into folders by their first tag.
+ */
+use serde_json::{json, Map, Value};
+
+use super::command::{CmdOpts, CurlOpts};
+use super::curl::{AuthKind, Curl};
+use super::importer::{CollectionImport, ImportedRequest};
+use super::parser::percent_encode;
+
+const METHODS: [&str; 8] = [
+    "get", "put", "post", "delete", "patch", "head", "options", "trace",
+];
+
+// How deep we follow nested schemas when generating an example body
+const MAX_SCHEMA_DEPTH: usize = 8;
+
+pub fn parse_openapi_spec(input: &str) -> Result<CollectionImport<'static>, String> {
+    let spec: Value = match serde_json::from_str(input) {
+        Ok(spec) => spec,
+        Err(_) => serde_yaml::from_str::<serde_yaml::Value>(input)
+            .map(yaml_to_json)
+            .map_err(|e| format!("Error: invalid OpenAPI spec: {}", e))?,
+    };
+    let swagger = spec["swagger"].as_str().is_some_and(|v| v.starts_with('2'));
+    let openapi = spec["openapi"].as_str().is_some_and(|v| v.starts_with('3'));
+    if !swagger && !openapi {
+        return Err(String::from(
+            "Error: not an OpenAPI 3 or Swagger 2 spec, the 'openapi' or 'swagger' version is missing",
+        ));
+    }
+    let Some(paths) = spec["paths"].as_object() else {
+        return Err(String::from("Error: the spec has no paths"));
+    };
+    let name = spec["info"]["title"].as_str().unwrap_or("OpenAPI");
+    let mut import = CollectionImport::new(name);
+    import
+        .variables
+        .push((String::from("base_url"), server_url(&spec, swagger)));
+
+    for (path, item) in paths {
+        let item = resolve(&spec, item);
+        for method in METHODS {
+            let Some(operation) = item.get(method).filter(|op| op.is_object()) else {
+                continue;
+            };
+            let label = format!("{} {}", method.to_uppercase(), path);
+            if ["options", "trace"].contains(&method) {
+                import
+                    .ignored
+                    .push(format!("{}: unsupported method", label));
+                continue;
+            }
+            let folders: Vec<String> = operation["tags"]
+                .get(0)
+                .and_then(Value::as_str)
+                .map(|tag| vec![tag.to_string()])
+                .unwrap_or_default();
+            if !folders.is_empty() {
+                import.add_folder(&folders);
+            }
+            let mut op = Operation {
+                spec: &spec,
+                swagger,
+                label: label.clone(),
+                ignored: Vec::new(),
+                variables: Vec::new(),
+            };
+            let curl = op.import(method, path, item, operation);
+            import.ignored.extend(op.ignored);
+            for var in op.variables {
+                if !import.variables.iter().any(|(key, _)| *key == var) {
+                    import.variables.push((var, String::new()));
+                }
+            }
+            import.requests.push(ImportedRequest {
+                name: text(&operation["summary"])
+                    .or_else(|| text(&operation["operationId"]))
+                    .unwrap_or(label),
+                description: text(&operation["description"]),
+                folders,
+                curl,
+            });
+        }
+    }
+    Ok(import)
+}
+
+// The first server, with its {variables} set to their defaults
+fn server_url(spec: &Value, swagger: bool) -> String {
+    let url = if swagger {
+        spec["host"].as_str().map(|host| {
+            let scheme = spec["schemes"]
+                .get(0)
+                .and_then(Value::as_str)
+                .unwrap_or("https");
+            let base_path = spec["basePath"].as_str().unwrap_or_default();
+            format!("{}://{}{}", scheme, host, base_path)
+        })
+    } else {
+        spec["servers"].get(0).and_then(|server| {
+            let mut url = text(&server["url"])?;
+            if let Some(vars) = server["variables"].as_object() {
+                for (name, var) in vars {
+                    let default = var["default"].as_str().unwrap_or_default();
+                    url = url.replace(&format!("{{{}}}", name), default);
+                }
+            }
+            Some(url)
+        })
+    };
+    url.unwrap_or(String::from("http://localhost"))
+        .trim_end_matches('/')
+        .to_string()
+}
+
+struct Operation<'s> {
+    spec: &'s Value,
+    swagger: bool,
+    label: String,
+    // parts of the operation we were unable to translate
+    ignored: Vec<String>,
+    // environment variables the request uses for its credentials
+    variables: Vec<String>,
+}
+
+impl<'s> Operation<'s> {
+    fn import(
+        &mut self,
+        method: &str,
+        path: &str,
+        item: &'s Value,
+        operation: &'s Value,
+    ) -> Curl<'static> {
+        let mut curl = Curl::new();
+        curl.set_method(method.to_uppercase());
+        let params = self.parameters(item, operation);
+
+        let mut url = format!("{{{{base_url}}}}{}", path);
+        let mut query = Vec::new();
+        let mut form = Vec::new();
+        let mut body_schema = None;
+        for param in params.iter() {
+            let name = param["name"].as_str().unwrap_or_default();
+            let required = param["required"] == true;
+            match param["in"].as_str().unwrap_or_default() {
+                "path" => url = url.replace(&format!("{{{}}}", name), &self.param_value(param)),
+                "query" if required => query.push(format!("{}={}", name, self.param_value(param))),
+                "header" if required => {
+                    curl.add_headers(format!("{}: {}", name, self.param_value(param)))
+                }
+                "cookie" if required => {
+                    curl.add_cookie(format!("{}={}", name, self.param_value(param)))
+                }
+                // Swagger 2 describes the body as a parameter
+                "body" => body_schema = Some(&param["schema"]),
+                "formData" => form.push(*param),
+                _ => {}
+            }
+        }
+        if !query.is_empty() {
+            url = format!("{}?{}", url, query.join("&"));
+        }
+        curl.set_url(&url);
+
+        if self.swagger {
+            self.swagger_body(&mut curl, operation, body_schema, &form);
+        } else if let Some(body) = operation.get("requestBody") {
+            self.request_body(&mut curl, resolve(self.spec, body));
+        }
+        self.security(&mut curl, operation);
+        curl
+    }
+
+    // Path level parameters apply to every operation, unless the operation redefines them
+    fn parameters(&self, item: &'s Value, operation: &'s Value) -> Vec<&'s Value> {
+        let own: Vec<&Value> = list(&operation["parameters"])
+            .map(|param| resolve(self.spec, param))
+            .collect();
+        let mut params: Vec<&Value> = list(&item["parameters"])
+            .map(|param| resolve(self.spec, param))
+            .filter(|param| {
+                !own.iter()
+                    .any(|p| p["name"] == param["name"] && p["in"] == param["in"])
+            })
+            .collect();
+        params.extend(own);
+        params
+    }
+
+    // The parameter's example, otherwise a {{variable}} with its name to fill in
+    fn param_value(&self, param: &Value) -> String {
+        let schema = resolve(self.spec, &param["schema"]);
+        param
+            .get("example")
+            .or_else(|| schema.get("example"))
+            .or_else(|| param.get("default"))
+            .or_else(|| schema.get("default"))
+            .map(scalar)
+            .unwrap_or(format!(
+                "{{{{{}}}}}",
+                param["name"].as_str().unwrap_or_default()
+            ))
+    }
+
+    fn request_body(&mut self, curl: &mut Curl, body: &Value) {
+        let Some(content) = body["content"].as_object() else {
+            return;
+        };
+        // prefer JSON when the operation accepts more than one content type
+        let Some((media_type, media)) = content
+            .iter()
+            .find(|(media_type, _)| media_type.contains("json"))
+            .or_else(|| content.iter().next())
+        else {
+            return;
+        };
+        let schema = resolve(self.spec, &media["schema"]);
+        let example = media
+            .get("example")
+            .cloned()
+            .or_else(|| {
+                media["examples"]
+                    .as_object()
+                    .and_then(|examples| examples.values().next())
+                    .map(|example| resolve(self.spec, example)["value"].clone())
+            })
+            .filter(|example| !example.is_null())
+            .unwrap_or_else(|| sample(self.spec, schema, 0));
+        self.set_body(curl, media_type, &example, schema);
+    }
+
+    fn swagger_body(
+        &mut self,
+        curl: &mut Curl,
+        operation: &Value,
+        schema: Option<&Value>,
+        form: &[&Value],
+    ) {
+        let consumes = operation["consumes"]
+            .get(0)
+            .or_else(|| self.spec["consumes"].get(0))
+            .and_then(Value::as_str);
+        if let Some(schema) = schema {
+            let schema = resolve(self.spec, schema);
+            let example = sample(self.spec, schema, 0);
+            let media_type = consumes.unwrap_or("application/json");
+            self.set_body(curl, media_type, &example, schema);
+        } else if !form.is_empty() {
+            let fields: Map<String, Value> = form
+                .iter()
+                .map(|param| {
+                    let value = match param["type"].as_str() {
+                        Some("file") => json!({"format": "binary"}),
+                        _ => Value::String(self.param_value(param)),
+                    };
+                    (text(&param["name"]).unwrap_or_default(), value)
+                })
+                .collect();
+            let multipart = consumes.is_some_and(|c| c.starts_with("multipart"))
+                || form.iter().any(|param| param["type"] == "file");
+            let media_type = if multipart {
+                "multipart/form-data"
+            } else {
+                "application/x-www-form-urlencoded"
+            };
+            let schema = json!({"properties": fields});
+            self.set_body(curl, media_type, &Value::Object(fields), &schema);
+        }
+    }
+
+    fn set_body(&mut self, curl: &mut Curl, media_type: &str, example: &Value, schema: &Value) {
+        if media_type.starts_with("multipart/") {
+            for (name, value) in example.as_object().into_iter().flatten() {
+                let binary =
+                    schema["properties"][name]["format"] == "binary" || value["format"] == "binary";
+                // files are sent from a path set in the environment
+                if binary {
+                    curl.add_form_part(&format!("{}=@{{{{{}}}}}", name, name));
+                } else {
+                    curl.add_form_part(&format!("{}={}", name, scalar(value)));
+                }
+            }
+            return;
+        }
+        let body = if media_type.contains("json") {
+            example.to_string()
+        } else if media_type == "application/x-www-form-urlencoded" {
+            example
+                .as_object()
+                .into_iter()
+                .flatten()
+                .map(|(key, value)| {
+                    format!("{}={}", percent_encode(key), percent_encode(&scalar(value)))
+                })
+                .collect::<Vec<String>>()
+                .join("&")
+        } else if let Some(text) = example.as_str() {
+            text.to_string()
+        } else {
+            self.ignored.push(format!(
+                "{}: no example for the {} body",
+                self.label, media_type
+            ));
+            return;
+        };
+        curl.add_headers(format!("Content-Type: {}", media_type));
+        curl.set_request_body(&body);
+    }
+
+    // Uses the first security requirement, credentials come from the environment
+    fn security(&mut self, curl: &mut Curl, operation: &Value) {
+        let requirements = operation.get("security").unwrap_or(&self.spec["security"]);
+        let Some(requirement) = requirements.get(0).and_then(Value::as_object) else {
+            return;
+        };
+        let schemes = if self.swagger {
+            &self.spec["securityDefinitions"]
+        } else {
+            &self.spec["components"]["securitySchemes"]
+        };
+        for name in requirement.keys() {
+            let scheme = resolve(self.spec, &schemes[name]);
+            let kind = scheme["type"].as_str().unwrap_or_default();
+            let http = scheme["scheme"].as_str().unwrap_or_default().to_lowercase();
+            match (kind, http.as_str()) {
+                ("http", "basic") | ("basic", _) => {
+                    self.use_vars(&["username", "password"]);
+                    curl.set_auth(AuthKind::Basic(String::from("{{username}}:{{password}}")));
+                }
+                ("http", "bearer") | ("oauth2", _) | ("openIdConnect", _) => {
+                    self.use_vars(&["token"]);
+                    curl.set_auth(AuthKind::Bearer(String::from("{{token}}")));
+                }
+                ("apiKey", _) => {
+                    self.use_vars(&["api_key"]);
+                    let key = scheme["name"].as_str().unwrap_or_default();
+                    match scheme["in"].as_str() {
+                        Some("query") => {
+                            let url = curl.get_url();
+                            let sep = if url.contains('?') { '&' } else { '?' };
+                            curl.set_url(&format!("{}{}{}={{{{api_key}}}}", url, sep, key));
+                        }
+                        Some("cookie") => curl.add_cookie(format!("{}={{{{api_key}}}}", key)),
+                        _ => curl.add_headers(format!("{}: {{{{api_key}}}}", key)),
+                    }
+                }
+                _ => self.ignored.push(format!(
+                    "{}: {} security scheme '{}'",
+                    self.label, kind, name
+                )),
+            }
+        }
+    }
+
+    fn use_vars(&mut self, vars: &[&str]) {
+        self.variables
+            .extend(vars.iter().map(|var| var.to_string()));
+    }
+}
+
+// An example value for a schema, from its example or default when it has one
+fn sample(spec: &Value, schema: &Value, depth: usize) -> Value {
+    let schema = resolve(spec, schema);
+    if depth > MAX_SCHEMA_DEPTH {
+        return Value::Null;
+    }
+    if let Some(example) = schema.get("example").or_else(|| schema.get("default")) {
+        return example.clone();
+    }
+    if let Some(first) = schema["enum"].get(0) {
+        return first.clone();
+    }
+    if let Some(all) = schema["allOf"].as_array() {
+        let mut merged = Map::new();
+        for part in all {
+            if let Value::Object(fields) = sample(spec, part, depth + 1) {
+                merged.extend(fields);
+            }
+        }
+        return Value::Object(merged);
+    }
+    if let Some(first) = schema["oneOf"].get(0).or_else(|| schema["anyOf"].get(0)) {
+        return sample(spec, first, depth + 1);
+    }
+    // OpenAPI 3.1 allows a list of types, like ["string", "null"]
+    let kind = match &schema["type"] {
+        Value::Array(kinds) => kinds.iter().find(|k| *k != "null").and_then(Value::as_str),
+        kind => kind.as_str(),
+    };
+    match kind {
+        Some("object") | None if schema.get("properties").is_some() => Value::Object(
+            schema["properties"]
+                .as_object()
+                .into_iter()
+                .flatten()
+                .map(|(name, prop)| (name.clone(), sample(spec, prop, depth + 1)))
+                .collect(),
+        ),
+        Some("object") => json!({}),
+        Some("array") => json!([sample(spec, &schema["items"], depth + 1)]),
+        Some("integer") | Some("number") => json!(0),
+        Some("boolean") => json!(false),
+        Some("string") => match schema["format"].as_str() {
+            Some("date-time") => json!("1970-01-01T00:00:00Z"),
+            Some("date") => json!("1970-01-01"),
+            Some("email") => json!("user@example.com"),
+            Some("uuid") => json!("00000000-0000-0000-0000-000000000000"),
+            _ => json!("string"),
+        },
+        _ => Value::Null,
+    }
+}
+
+// Follows local "$ref"s like "#/components/schemas/Pet", anything else is returned as is
+fn resolve<'v>(spec: &'v Value, value: &'v Value) -> &'v Value {
+    let mut value = value;
+    for _ in 0..MAX_SCHEMA_DEPTH {
+        let Some(target) = value["$ref"].as_str().and_then(|r| r.strip_prefix('#')) else {
+            break;
+        };
+        match spec.pointer(target) {
+            Some(found) => value = found,
+            None => break,
+        }
+    }
+    value
+}
+
+// YAML allows keys that aren't strings, like the status codes under `responses`
+fn yaml_to_json(yaml: serde_yaml::Value) -> Value {
+    match yaml {
+        serde_yaml::Value::Null => Value::Null,
+        serde_yaml::Value::Bool(b) => Value::Bool(b),
+        serde_yaml::Value::Number(n) => serde_json::to_value(n).unwrap_or(Value::Null),
+        serde_yaml::Value::String(s) => Value::String(s),
+        serde_yaml::Value::Sequence(items) => {
+            Value::Array(items.into_iter().map(yaml_to_json).collect())
+        }
+        serde_yaml::Value::Mapping(map) => Value::Object(
+            map.into_iter()
+                .map(|(key, value)| {
+                    let key = match yaml_to_json(key) {
+                        Value::String(key) => key,
+                        key => key.to_string(),
+                    };
+                    (key, yaml_to_json(value))
+                })
+                .collect(),
+        ),
+        serde_yaml::Value::Tagged(tagged) => yaml_to_json(tagged.value),
+    }
+}
+
+fn list(value: &Value) -> impl Iterator<Item = &Value> {
+    value.as_array().into_iter().flatten()
+}
+
+// Strings without their quotes, everything else as JSON
+fn scalar(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        value => value.to_string(),
+    }
+}
+
+fn text(value: &Value) -> Option<String> {
+    value.as_str().map(String::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PETSTORE: &str = r#"
+openapi: 3.0.3
+info:
+  title: Petstore
+  version: 1.0.0
+servers:
+  - url: https://{region}.petstore.example.com/v1/
+    variables:
+      region:
+        default: eu
+security:
+  - bearerAuth: []
+paths:
+  /pets:
+    get:
+      summary: List pets
+      operationId: listPets
+      tags: [pets]
+      parameters:
+        - name: limit
+          in: query
+          required: true
+          schema:
+            type: integer
+            example: 10
+        - name: offset
+          in: query
+          schema:
+            type: integer
+        - $ref: '#/components/parameters/RequestId'
+      responses:
+        200:
+          description: A list of pets
+    post:
+      operationId: createPet
+      tags: [pets]
+      requestBody:
+        content:
+          application/xml:
+            schema:
+              $ref: '#/components/schemas/Pet'
+          application/json:
+            schema:
+              $ref: '#/components/schemas/Pet'
+      responses:
+        201:
+          description: Created
+  /pets/{petId}:
+    parameters:
+      - name: petId
+        in: path
+        required: true
+        schema:
+          type: string
+    delete:
+      description: Removes the pet
+      security:
+        - apiKey: []
+      responses:
+        204:
+          description: Deleted
+    options:
+      responses:
+        200:
+          description: Allowed methods
+  /pets/{petId}/photo:
+    put:
+      tags: [photos]
+      security: []
+      parameters:
+        - name: petId
+          in: path
+          required: true
+          example: 7
+      requestBody:
+        content:
+          multipart/form-data:
+            schema:
+              properties:
+                caption:
+                  type: string
+                file:
+                  type: string
+                  format: binary
+      responses:
+        200:
+          description: Uploaded
+components:
+  parameters:
+    RequestId:
+      name: X-Request-Id
+      in: header
+      required: true
+      schema:
+        type: string
+  schemas:
+    Pet:
+      type: object
+      required: [name]
+      properties:
+        id:
+          type: integer
+        name:
+          type: string
+          example: rex
+        tags:
+          type: array
+          items:
+            type: string
+        owner:
+          $ref: '#/components/schemas/Owner'
+    Owner:
+      allOf:
+        - properties:
+            email:
+              type: string
+              format: email
+        - properties:
+            status:
+              type: string
+              enum: [active, away]
+  securitySchemes:
+    bearerAuth:
+      type: http
+      scheme: bearer
+    apiKey:
+      type: apiKey
+      in: header
+      name: X-Api-Key
+"#;
+
+    #[test]
+    fn test_parse_openapi_spec() {
+        let mut import = parse_openapi_spec(PETSTORE).unwrap();
+        assert_eq!(import.name, "Petstore");
+        assert_eq!(
+            import.folders,
+            vec![vec![String::from("pets")], vec![String::from("photos")]]
+        );
+        assert_eq!(
+            import.variables,
+            vec![
+                (
+                    String::from("base_url"),
+                    String::from("https://eu.petstore.example.com/v1")
+                ),
+                (String::from("token"), String::new()),
+                (String::from("api_key"), String::new()),
+            ]
+        );
+        assert_eq!(
+            import.ignored,
+            vec![String::from("OPTIONS /pets/{petId}: unsupported method")]
+        );
+        let names: Vec<&str> = import.requests.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "List pets",
+                "createPet",
+                "DELETE /pets/{petId}",
+                "PUT /pets/{petId}/photo"
+            ]
+        );
+
+        let list = &mut import.requests[0];
+        assert_eq!(list.folders, vec![String::from("pets")]);
+        // only the required query parameters are added
+        assert_eq!(list.curl.get_url(), "{{base_url}}/pets?limit=10");
+        assert_eq!(
+            list.curl.get_headers(),
+            vec![String::from("X-Request-Id: {{X-Request-Id}}")]
+        );
+        assert_eq!(
+            list.curl.get_auth(),
+            &AuthKind::Bearer(String::from("{{token}}"))
+        );
+
+        let create = &mut import.requests[1];
+        assert_eq!(
+            create.curl.get_headers(),
+            vec![String::from("Content-Type: application/json")]
+        );
+        let body: Value = serde_json::from_str(&create.curl.get_request_body().unwrap()).unwrap();
+        assert_eq!(
+            body,
+            json!({
+                "id": 0,
+                "name": "rex",
+                "tags": ["string"],
+                "owner": {"email": "user@example.com", "status": "active"}
+            })
+        );
+
+        let delete = &mut import.requests[2];
+        assert_eq!(delete.description.as_deref(), Some("Removes the pet"));
+        assert_eq!(delete.curl.get_url(), "{{base_url}}/pets/{{petId}}");
+        assert_eq!(
+            delete.curl.get_headers(),
+            vec![String::from("X-Api-Key: {{api_key}}")]
+        );
+        assert_eq!(delete.curl.get_auth(), &AuthKind::None);
+
+        let photo = &mut import.requests[3];
+        assert_eq!(photo.curl.get_url(), "{{base_url}}/pets/7/photo");
+        assert_eq!(photo.curl.get_auth(), &AuthKind::None);
+        assert_eq!(
+            photo.curl.get_form_parts(),
+            vec![
+                String::from("caption=string"),
+                String::from("file=@{{file}}")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_swagger_spec() {
+        let spec = json!({
+            "swagger": "2.0",
+            "info": {"title": "Legacy"},
+            "host": "api.example.com",
+            "basePath": "/v2",
+            "schemes": ["http"],
+            "securityDefinitions": {"basic": {"type": "basic"}},
+            "paths": {
+                "/users": {
+                    "post": {
+                        "summary": "Create user",
+                        "security": [{"basic": []}],
+                        "parameters": [{
+                            "name": "user",
+                            "in": "body",
+                            "schema": {"$ref": "#/definitions/User"}
+                        }]
+                    }
+                },
+                "/login": {
+                    "post": {
+                        "consumes": ["application/x-www-form-urlencoded"],
+                        "parameters": [
+                            {"name": "user name", "in": "formData", "type": "string", "example": "a&b"},
+                            {"name": "remember", "in": "formData", "type": "boolean", "default": true}
+                        ]
+                    }
+                }
+            },
+            "definitions": {"User": {"properties": {"name": {"type": "string"}}}}
+        });
+        let mut import = parse_openapi_spec(&spec.to_string()).unwrap();
+        assert_eq!(import.variables[0].1, "http://api.example.com/v2");
+        // serde_json keeps the paths sorted
+        let login = &mut import.requests[0];
+        assert_eq!(
+            login.curl.get_request_body().as_deref(),
+            Some("remember=true&user%20name=a%26b")
+        );
+        let user = &mut import.requests[1];
+        assert_eq!(user.name, "Create user");
+        assert_eq!(
+            user.curl.get_request_body().as_deref(),
+            Some("{\"name\":\"string\"}")
+        );
+        assert_eq!(
+            user.curl.get_auth(),
+            &AuthKind::Basic(String::from("{{username}}:{{password}}"))
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid_spec() {
+        assert!(parse_openapi_spec("{\"info\": {}}").is_err());
+        assert!(parse_openapi_spec("openapi: 3.0.0\ninfo: {}").is_err());
+        assert!(parse_openapi_spec(": not yaml [").is_err());
+    }
+}
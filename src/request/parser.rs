@@ -0,0 +1,630 @@
+/*
+* Parser for raw curl command lines, e.g. the one-liners found in API docs
+* or copied from the browser's dev tools ("Copy as cURL").
+ */
+use std::collections::VecDeque;
+
+use super::command::{CmdOpts, CurlOpts};
+use super::curl::{AuthKind, Curl};
+use crate::display::AppOptions;
+
+/// The result of importing a curl command: the request itself, the options
+/// to display in the TUI, and anything we could not translate.
+#[derive(Debug)]
+pub struct CurlImport<'a> {
+    pub curl: Curl<'a>,
+    pub method: String,
+    pub opts: Vec<AppOptions>,
+    pub ignored: Vec<String>,
+}
+
+// Flags that don't change what request is sent, so we can safely drop them
+const NO_OP_FLAGS: [&str; 8] = [
+    "-s",
+    "--silent",
+    "-S",
+    "--show-error",
+    "--compressed",
+    "--no-progress-meter",
+    "-N",
+    "--no-buffer",
+];
+
+// Options we don't support that take a value, it's reported along with the flag
+const VALUE_FLAGS: [&str; 49] = [
+    "--connect-timeout",
+    "-m",
+    "--max-time",
+    "--retry",
+    "--retry-delay",
+    "--retry-max-time",
+    "-w",
+    "--write-out",
+    "-E",
+    "--cert",
+    "--cert-type",
+    "--key",
+    "--key-type",
+    "--pass",
+    "-r",
+    "--range",
+    "-c",
+    "--cookie-jar",
+    "-D",
+    "--dump-header",
+    "-K",
+    "--config",
+    "--resolve",
+    "--connect-to",
+    "--interface",
+    "--limit-rate",
+    "-Y",
+    "--speed-limit",
+    "-y",
+    "--speed-time",
+    "-U",
+    "--proxy-user",
+    "--noproxy",
+    "--proxy-header",
+    "--preproxy",
+    "--socks5",
+    "--socks5-hostname",
+    "--ciphers",
+    "--tls-max",
+    "--local-port",
+    "--max-filesize",
+    "-z",
+    "--time-cond",
+    "-C",
+    "--continue-at",
+    "--form-string",
+    "--trace",
+    "--trace-ascii",
+    "--stderr",
+];
+
+// Short flags that never take an argument, so they can be bundled like `-sSL`
+const SHORT_SWITCHES: &str = "vLifsSIGpkN#q";
+
+/// Splits a command line into arguments the way a POSIX shell would,
+/// honoring single/double quotes, `$'...'` quoting, escapes and line continuations.
+pub fn split_command_line(input: &str) -> Result<Vec<String>, String> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut in_arg = false;
+    let mut chars = input.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => {
+                in_arg = true;
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(ch) => current.push(ch),
+                        None => return Err(String::from("Unterminated single quote")),
+                    }
+                }
+            }
+            '$' if chars.peek() == Some(&'\'') => {
+                chars.next();
+                in_arg = true;
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some('\\') => match chars.next() {
+                            Some('n') => current.push('\n'),
+                            Some('t') => current.push('\t'),
+                            Some('r') => current.push('\r'),
+                            Some(ch) => current.push(ch),
+                            None => return Err(String::from("Unterminated $' quote")),
+                        },
+                        Some(ch) => current.push(ch),
+                        None => return Err(String::from("Unterminated $' quote")),
+                    }
+                }
+            }
+            '"' => {
+                in_arg = true;
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some('\n') => {}
+                            Some(ch @ ('"' | '\\' | '$' | '`')) => current.push(ch),
+                            Some(ch) => {
+                                current.push('\\');
+                                current.push(ch);
+                            }
+                            None => return Err(String::from("Unterminated double quote")),
+                        },
+                        Some(ch) => current.push(ch),
+                        None => return Err(String::from("Unterminated double quote")),
+                    }
+                }
+            }
+            '\\' => match chars.next() {
+                // line continuation
+                Some('\n') => {}
+                Some('\r') if chars.peek() == Some(&'\n') => {
+                    chars.next();
+                }
+                Some(ch) => {
+                    in_arg = true;
+                    current.push(ch);
+                }
+                None => {}
+            },
+            ch if ch.is_whitespace() => {
+                if in_arg {
+                    args.push(std::mem::take(&mut current));
+                    in_arg = false;
+                }
+            }
+            ch => {
+                in_arg = true;
+                current.push(ch);
+            }
+        }
+    }
+    if in_arg {
+        args.push(current);
+    }
+    Ok(args)
+}
+
+/// Quotes an argument so a POSIX shell reads it back as a single word,
+/// the inverse of `split_command_line`.
+pub fn quote_arg(arg: &str) -> String {
+    let is_safe = |c: char| c.is_ascii_alphanumeric() || "-_./:=@%+,".contains(c);
+    if !arg.is_empty() && arg.chars().all(is_safe) {
+        String::from(arg)
+    } else {
+        // a single quote can't appear inside single quotes, so close, escape it and reopen
+        format!("'{}'", arg.replace('\'', "'\\''"))
+    }
+}
+
+// Which auth scheme the -u credentials are used with
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum AuthScheme {
+    Basic,
+    Digest,
+    Ntlm,
+    Negotiate,
+    AwsSigv4,
+}
+
+/// Parses a curl command line into a populated `Curl` and the matching `AppOptions`.
+pub fn parse_curl_command(input: &str) -> Result<CurlImport<'static>, String> {
+    let mut args: VecDeque<String> = split_command_line(input)?.into();
+    if args.front().is_some_and(|first| first == "curl") {
+        args.pop_front();
+    }
+
+    let mut method: Option<String> = None;
+    let mut url: Option<String> = None;
+    let mut opts: Vec<AppOptions> = Vec::new();
+    let mut ignored: Vec<String> = Vec::new();
+    let mut body: Vec<String> = Vec::new();
+    let mut cookies: Vec<String> = Vec::new();
+    let mut user: Option<String> = None;
+    let mut proxy: Option<String> = None;
+    let mut form: Vec<String> = Vec::new();
+    let mut bearer: Option<String> = None;
+    let mut scheme = AuthScheme::Basic;
+    let mut head = false;
+    let mut get = false;
+
+    while let Some(arg) = args.pop_front() {
+        // expand `-sSL` into `-s -S -L` and `--flag=value` / `-XPOST` into flag + value
+        if let Some(expanded) = expand_arg(&arg) {
+            expanded.into_iter().rev().for_each(|a| args.push_front(a));
+            continue;
+        }
+        let mut value = |flag: &str| {
+            args.pop_front()
+                .ok_or_else(|| format!("Error: {} requires a value", flag))
+        };
+        match arg.as_str() {
+            "-X" | "--request" => method = Some(value(&arg)?.to_uppercase()),
+            "--url" => url = Some(value(&arg)?),
+            "-H" | "--header" => {
+                let header = value(&arg)?;
+                // `Name;` is how curl sends a header with an empty value
+                let empty = header
+                    .strip_suffix(';')
+                    .filter(|name| !name.is_empty() && !name.contains(char::is_whitespace));
+                let Some((key, val)) = header.split_once(':').or(empty.map(|name| (name, "")))
+                else {
+                    return Err(format!("Error: invalid header \"{}\"", header));
+                };
+                let token = val.trim().strip_prefix("Bearer ");
+                if key.trim().eq_ignore_ascii_case("authorization") && token.is_some() {
+                    bearer = token.map(|t| t.trim().to_string());
+                } else {
+                    opts.push(AppOptions::Headers(header.clone()));
+                }
+            }
+            "-d" | "--data" | "--data-raw" | "--data-binary" | "--data-ascii" => {
+                body.push(value(&arg)?)
+            }
+            "--data-urlencode" => body.push(url_encode_data(&value(&arg)?)),
+            "--json" => {
+                body.push(value(&arg)?);
+                opts.push(AppOptions::Headers(String::from(
+                    "Content-Type: application/json",
+                )));
+                opts.push(AppOptions::Headers(String::from(
+                    "Accept: application/json",
+                )));
+            }
+            "-u" | "--user" => user = Some(value(&arg)?),
+            "--basic" => scheme = AuthScheme::Basic,
+            "--digest" => scheme = AuthScheme::Digest,
+            "--ntlm" => scheme = AuthScheme::Ntlm,
+            "--negotiate" => scheme = AuthScheme::Negotiate,
+            // CuTE uses the same flag for "any auth" and unrestricted auth
+            "--anyauth" | "--any-auth" => opts.push(AppOptions::UnrestrictedAuth),
+            "--aws-sigv4" => {
+                value(&arg)?;
+                scheme = AuthScheme::AwsSigv4;
+            }
+            "--oauth2-bearer" => bearer = Some(value(&arg)?),
+            "-b" | "--cookie" => cookies.push(value(&arg)?),
+            "-L" | "--location" => opts.push(AppOptions::FollowRedirects),
+            "--max-redirs" => {
+                let max = value(&arg)?;
+                let max = max
+                    .parse::<usize>()
+                    .map_err(|_| format!("Error: invalid --max-redirs \"{}\"", max))?;
+                opts.push(AppOptions::MaxRedirects(max));
+            }
+            "--unix-socket" => opts.push(AppOptions::UnixSocket(value(&arg)?)),
+            "-x" | "--proxy" => proxy = Some(value(&arg)?),
+            "-F" | "--form" => form.push(value(&arg)?),
+            "-T" | "--upload-file" => opts.push(AppOptions::UploadFile(value(&arg)?)),
+            "-o" | "--output" => opts.push(AppOptions::Outfile(value(&arg)?)),
+            "-v" | "--verbose" => opts.push(AppOptions::Verbose),
+            "-i" | "--include" => opts.push(AppOptions::EnableHeaders),
+            "-e" | "--referer" => opts.push(AppOptions::Referrer(value(&arg)?)),
+            "-A" | "--user-agent" => opts.push(AppOptions::UserAgent(value(&arg)?)),
+            "--capath" | "--cacert" => opts.push(AppOptions::CaPath(value(&arg)?)),
+            "--certinfo" => opts.push(AppOptions::CertInfo),
+            "-f" | "--fail" => opts.push(AppOptions::FailOnError),
+            "-#" | "--progress-bar" => opts.push(AppOptions::ProgressBar),
+            "-p" | "--proxytunnel" | "--proxy-tunnel" => opts.push(AppOptions::ProxyTunnel),
+            "--glob" => opts.push(AppOptions::MatchWildcard),
+            "--location-trusted" => {
+                opts.push(AppOptions::FollowRedirects);
+                opts.push(AppOptions::UnrestrictedAuth);
+            }
+            "--keepalive" => opts.push(AppOptions::TcpKeepAlive),
+            "--keepalive-time" => {
+                value(&arg)?;
+                opts.push(AppOptions::TcpKeepAlive);
+            }
+            "-I" | "--head" => head = true,
+            "-G" | "--get" => get = true,
+            flag if NO_OP_FLAGS.contains(&flag) => {}
+            flag if VALUE_FLAGS.contains(&flag) => {
+                let value = value(flag)?;
+                ignored.push(format!("{} {}", flag, quote_arg(&value)));
+            }
+            flag if flag.starts_with('-') && flag.len() > 1 => ignored.push(flag.to_string()),
+            _ => {
+                if url.is_some() {
+                    return Err(format!("Error: unexpected argument \"{}\"", arg));
+                }
+                url = Some(arg);
+            }
+        }
+    }
+
+    let Some(url) = url else {
+        return Err(String::from("Error: no URL found in the curl command"));
+    };
+    let mut url = url;
+    if get && !body.is_empty() {
+        // -G sends the data as a query string
+        let sep = if url.contains('?') { '&' } else { '?' };
+        url = format!("{}{}{}", url, sep, body.join("&"));
+        body.clear();
+    }
+    let method = method.unwrap_or_else(|| {
+        if head {
+            String::from("HEAD")
+        } else if (!body.is_empty() && !get) || !form.is_empty() {
+            String::from("POST")
+        } else if opts.iter().any(|o| matches!(o, AppOptions::UploadFile(_))) {
+            String::from("PUT")
+        } else {
+            String::from("GET")
+        }
+    });
+    opts.insert(0, AppOptions::URL(url));
+    if !body.is_empty() {
+        opts.push(AppOptions::RequestBody(body.join("&")));
+    }
+    if !cookies.is_empty() {
+        opts.push(AppOptions::Cookie(cookies.join("; ")));
+    }
+
+    let auth = match (bearer, user.clone(), scheme) {
+        (Some(token), _, _) => AuthKind::Bearer(token),
+        (None, _, AuthScheme::Ntlm) => AuthKind::Ntlm,
+        (None, _, AuthScheme::Negotiate) => AuthKind::Spnego,
+        (None, _, AuthScheme::AwsSigv4) => AuthKind::AwsSigv4,
+        (None, Some(login), AuthScheme::Digest) => AuthKind::Digest(login),
+        (None, Some(login), _) => AuthKind::Basic(login),
+        (None, None, _) => AuthKind::None,
+    };
+
+    let mut curl = Curl::new();
+    curl.set_method(method.clone());
+    opts.iter().for_each(|opt| apply_option(&mut curl, opt));
+    if let Some(ref proxy) = proxy {
+        curl.set_proxy(proxy);
+    }
+    form.iter().for_each(|part| curl.add_form_part(part));
+    match auth {
+        AuthKind::None => {}
+        AuthKind::Basic(ref login) | AuthKind::Bearer(ref login) | AuthKind::Digest(ref login) => {
+            opts.push(AppOptions::Auth(login.clone()));
+        }
+        ref other => opts.push(AppOptions::Auth(other.to_string())),
+    }
+    match auth {
+        // the login goes with the --ntlm flag, AuthKind::Ntlm has no room for it
+        AuthKind::Ntlm => curl.set_ntlm_auth(user),
+        auth => curl.set_auth(auth),
+    }
+    Ok(CurlImport {
+        curl,
+        method,
+        opts,
+        ignored,
+    })
+}
+
+// Returns the separate arguments a bundled argument stands for, None if it's a plain argument
+fn expand_arg(arg: &str) -> Option<Vec<String>> {
+    if let Some(stripped) = arg.strip_prefix("--") {
+        let (flag, value) = stripped.split_once('=')?;
+        return Some(vec![format!("--{}", flag), value.to_string()]);
+    }
+    if arg.len() <= 2 || !arg.starts_with('-') {
+        return None;
+    }
+    let flags: Vec<char> = arg.chars().skip(1).collect();
+    // -XPOST, -H'Key: Value', -sSXPOST: switches, then a flag with its value attached
+    let split = flags
+        .iter()
+        .position(|c| !SHORT_SWITCHES.contains(*c))
+        .unwrap_or(flags.len());
+    let mut expanded: Vec<String> = flags
+        .iter()
+        .take((split + 1).min(flags.len()))
+        .map(|c| format!("-{}", c))
+        .collect();
+    if split + 1 < flags.len() {
+        expanded.push(flags[split + 1..].iter().collect());
+    }
+    Some(expanded)
+}
+
+// Mirrors App::add_app_option, but on a bare Curl so the import doesn't need an App
+fn apply_option(curl: &mut Curl, opt: &AppOptions) {
+    match opt {
+        AppOptions::URL(url) => curl.set_url(url),
+        AppOptions::Headers(header) => curl.add_headers(header.clone()),
+        AppOptions::RequestBody(body) => curl.set_request_body(body),
+        AppOptions::Cookie(cookie) => curl.add_cookie(cookie.clone()),
+        AppOptions::UnixSocket(socket) => curl.set_unix_socket(socket),
+        AppOptions::UploadFile(file) => curl.set_upload_file(file),
+        AppOptions::Outfile(file) => curl.set_outfile(file),
+        AppOptions::Referrer(referrer) => curl.set_referrer(referrer),
+        AppOptions::UserAgent(ua) => curl.set_user_agent(ua),
+        AppOptions::CaPath(path) => curl.set_ca_path(path),
+        AppOptions::MaxRedirects(max) => curl.set_max_redirects(*max),
+        AppOptions::Verbose => curl.set_verbose(true),
+        AppOptions::EnableHeaders => curl.enable_response_headers(true),
+        AppOptions::FollowRedirects => curl.set_follow_redirects(true),
+        AppOptions::UnrestrictedAuth => curl.set_unrestricted_auth(true),
+        AppOptions::CertInfo => curl.set_cert_info(true),
+        AppOptions::FailOnError => curl.set_fail_on_error(true),
+        AppOptions::ProgressBar => curl.enable_progress_bar(true),
+        AppOptions::ProxyTunnel => curl.set_proxy_tunnel(true),
+        AppOptions::MatchWildcard => curl.match_wildcard(true),
+        AppOptions::TcpKeepAlive => curl.set_tcp_keepalive(true),
+        _ => {}
+    }
+}
+
+// --data-urlencode: "name=content" encodes only the content, otherwise encode everything
+fn url_encode_data(data: &str) -> String {
+    match data.split_once('=') {
+        Some((name, content)) if !name.is_empty() => {
+            format!("{}={}", name, percent_encode(content))
+        }
+        Some((_, content)) => percent_encode(content),
+        None => percent_encode(data),
+    }
+}
+
+pub fn percent_encode(data: &str) -> String {
+    data.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::request::curl::Method;
+
+    #[test]
+    fn test_split_command_line() {
+        let args = split_command_line(
+            "curl -H 'Content-Type: application/json' \\\n --data \"{\\\"a\\\": 1}\" $'x\\ty' plain\\ arg",
+        )
+        .unwrap();
+        assert_eq!(
+            args,
+            vec![
+                "curl",
+                "-H",
+                "Content-Type: application/json",
+                "--data",
+                "{\"a\": 1}",
+                "x\ty",
+                "plain arg"
+            ]
+        );
+        assert!(split_command_line("curl 'unterminated").is_err());
+    }
+
+    #[test]
+    fn test_parse_post_with_headers_and_body() {
+        let import = parse_curl_command(
+            "curl -X POST https://example.com/api -H 'Content-Type: application/json' -d '{\"key\": \"value\"}'",
+        )
+        .unwrap();
+        assert_eq!(import.method, "POST");
+        assert_eq!(import.curl.get_method(), Some(&Method::Post));
+        assert_eq!(import.curl.get_url(), "https://example.com/api");
+        assert_eq!(
+            import.curl.get_headers(),
+            vec![String::from("Content-Type: application/json")]
+        );
+        assert_eq!(
+            import.curl.get_request_body(),
+            Some(String::from("{\"key\": \"value\"}"))
+        );
+        assert!(import
+            .opts
+            .contains(&AppOptions::URL(String::from("https://example.com/api"))));
+        assert!(import.ignored.is_empty());
+    }
+
+    #[test]
+    fn test_parse_implied_methods() {
+        let import = parse_curl_command("curl https://example.com --data a=b").unwrap();
+        assert_eq!(import.method, "POST");
+        let import = parse_curl_command("curl -I https://example.com").unwrap();
+        assert_eq!(import.method, "HEAD");
+        let import = parse_curl_command("curl -G https://example.com?x=1 -d a=b").unwrap();
+        assert_eq!(import.method, "GET");
+        assert_eq!(import.curl.get_url(), "https://example.com?x=1&a=b");
+        let import = parse_curl_command("curl -T file.txt https://example.com").unwrap();
+        assert_eq!(import.method, "PUT");
+        assert_eq!(import.curl.get_upload_file(), Some("file.txt"));
+    }
+
+    #[test]
+    fn test_parse_auth() {
+        let import = parse_curl_command("curl -u user:pass --ntlm https://example.com").unwrap();
+        assert_eq!(import.curl.get_auth(), &AuthKind::Ntlm);
+        assert_eq!(
+            import.curl.get_ntlm_login(),
+            Some(String::from("user:pass"))
+        );
+        assert!(import.ignored.is_empty());
+        let mut curl = import.curl;
+        assert!(curl.get_command_string().contains("--ntlm -u user:pass"));
+        let import = parse_curl_command("curl --ntlm https://example.com").unwrap();
+        assert_eq!(import.curl.get_ntlm_login(), None);
+        let import = parse_curl_command("curl -u user:pass https://example.com").unwrap();
+        assert_eq!(
+            import.curl.get_auth(),
+            &AuthKind::Basic(String::from("user:pass"))
+        );
+        let import =
+            parse_curl_command("curl --digest --user user:pass https://example.com").unwrap();
+        assert_eq!(
+            import.curl.get_auth(),
+            &AuthKind::Digest(String::from("user:pass"))
+        );
+        let import =
+            parse_curl_command("curl -H 'Authorization: Bearer abc123' https://example.com")
+                .unwrap();
+        assert_eq!(
+            import.curl.get_auth(),
+            &AuthKind::Bearer(String::from("abc123"))
+        );
+        assert!(import.curl.get_headers().is_empty());
+        assert!(import
+            .opts
+            .contains(&AppOptions::Auth(String::from("abc123"))));
+    }
+
+    #[test]
+    fn test_parse_flags() {
+        let import = parse_curl_command(
+            "curl -sSL --max-redirs=3 -b 'a=1' --cookie b=2 --unix-socket /var/run/docker.sock -vi -XGET http://localhost/containers/json -k",
+        )
+        .unwrap();
+        assert_eq!(import.method, "GET");
+        assert!(import.curl.has_unix_socket());
+        for opt in [
+            AppOptions::FollowRedirects,
+            AppOptions::MaxRedirects(3),
+            AppOptions::Cookie(String::from("a=1; b=2")),
+            AppOptions::UnixSocket(String::from("/var/run/docker.sock")),
+            AppOptions::Verbose,
+            AppOptions::EnableHeaders,
+        ] {
+            assert!(import.opts.contains(&opt), "missing {:?}", opt);
+        }
+        assert_eq!(import.ignored, vec![String::from("-k")]);
+    }
+
+    #[test]
+    fn test_parse_unsupported_values() {
+        let import = parse_curl_command("curl https://x.com/api --connect-timeout 10").unwrap();
+        assert_eq!(import.curl.get_url(), "https://x.com/api");
+        assert_eq!(import.ignored, vec![String::from("--connect-timeout 10")]);
+        let import = parse_curl_command("curl -m 10 https://x.com/api -w '%{http_code}'").unwrap();
+        assert_eq!(import.curl.get_url(), "https://x.com/api");
+        assert_eq!(
+            import.ignored,
+            vec![String::from("-m 10"), String::from("-w '%{http_code}'")]
+        );
+        let mut import = parse_curl_command("curl -k https://x.com -x http://proxy:8080").unwrap();
+        assert_eq!(import.ignored, vec![String::from("-k")]);
+        assert!(import
+            .curl
+            .get_command_string()
+            .contains("-x http://proxy:8080"));
+
+        let import =
+            parse_curl_command("curl https://x.com/upload -F title=cat -F file=@cat.png").unwrap();
+        assert_eq!(import.method, "POST");
+        assert_eq!(
+            import.curl.get_form_parts(),
+            vec![String::from("title=cat"), String::from("file=@cat.png")]
+        );
+        let import = parse_curl_command("curl https://x.com -H 'X-Empty;'").unwrap();
+        assert_eq!(import.curl.get_headers(), vec![String::from("X-Empty;")]);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(parse_curl_command("curl -X POST").is_err());
+        assert!(parse_curl_command("curl https://example.com -H").is_err());
+        assert!(parse_curl_command("curl https://example.com -H novalue").is_err());
+        assert!(parse_curl_command("curl https://example.com -H 'two words;'").is_err());
+        assert!(parse_curl_command("curl https://example.com --connect-timeout").is_err());
+        assert!(parse_curl_command("curl https://a.com https://b.com").is_err());
+    }
+
+    #[test]
+    fn test_data_urlencode() {
+        assert_eq!(url_encode_data("name=a b&c"), "name=a%20b%26c");
+        assert_eq!(url_encode_data("=a b"), "a%20b");
+    }
+}
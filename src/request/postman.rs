@@ -0,0 +1,503 @@
+/*
+* Importer for Postman v2.0/v2.1 collection exports. Folders become collections,
+* requests become saved commands, and Postman's {{variables}} are kept as they are
+* so they can be filled in from a CuTE environment.
+ */
+use serde_json::{json, Value};
+
+use super::command::{CmdOpts, CurlOpts};
+use super::curl::{AuthKind, Curl};
+use super::importer::{CollectionImport, ImportedRequest};
+use super::parser::percent_encode;
+
+pub fn parse_postman_collection(input: &str) -> Result<CollectionImport<'static>, String> {
+    let collection: Value = serde_json::from_str(input)
+        .map_err(|e| format!("Error: invalid Postman collection: {}", e))?;
+    let Some(items) = collection.get("item").and_then(Value::as_array) else {
+        return Err(String::from(
+            "Error: not a Postman v2 collection, export it as Collection v2.1",
+        ));
+    };
+    let name = text(&collection["info"]["name"]).unwrap_or(String::from("Postman"));
+    let mut import = CollectionImport::new(&name);
+    // collection variables become the collection's environment
+    import.variables = enabled_pairs(&collection["variable"]);
+    import_items(items, &[], collection.get("auth"), &mut import);
+    Ok(import)
+}
+
+fn import_items(
+    items: &[Value],
+    folders: &[String],
+    auth: Option<&Value>,
+    import: &mut CollectionImport,
+) {
+    for item in items {
+        let name = text(&item["name"]).unwrap_or(String::from("Untitled"));
+        let mut path = folders.to_vec();
+        path.push(name.clone());
+        // folders and requests can set their own auth, otherwise they inherit it
+        let auth = item.get("auth").or(auth);
+        if let Some(children) = item.get("item").and_then(Value::as_array) {
+            import.add_folder(&path);
+            import_items(children, &path, auth, import);
+            continue;
+        }
+        let label = path.join("/");
+        let Some(request) = item.get("request") else {
+            import.ignored.push(format!("{}: no request", label));
+            continue;
+        };
+        if has_scripts(item) {
+            import
+                .ignored
+                .push(format!("{}: pre-request and test scripts", label));
+        }
+        match import_request(request, request.get("auth").or(auth)) {
+            Ok((curl, ignored)) => {
+                import.requests.push(ImportedRequest {
+                    name,
+                    description: text(&request["description"])
+                        .or_else(|| text(&request["description"]["content"]))
+                        .filter(|description| !description.is_empty()),
+                    folders: folders.to_vec(),
+                    curl,
+                });
+                ignored
+                    .into_iter()
+                    .for_each(|reason| import.ignored.push(format!("{}: {}", label, reason)));
+            }
+            Err(reason) => import.ignored.push(format!("{}: {}", label, reason)),
+        }
+    }
+}
+
+// Err is the reason the whole request was skipped, the Vec lists the parts we left out
+fn import_request(
+    request: &Value,
+    auth: Option<&Value>,
+) -> Result<(Curl<'static>, Vec<String>), String> {
+    let mut ignored = Vec::new();
+    // a request can be just its URL
+    let url = match request {
+        Value::String(url) => Some(url.clone()),
+        request => import_url(&request["url"]),
+    };
+    let url = url
+        .filter(|url| !url.is_empty())
+        .ok_or(String::from("no URL"))?;
+    let method = text(&request["method"])
+        .unwrap_or(String::from("GET"))
+        .to_uppercase();
+    if !["GET", "POST", "PUT", "PATCH", "DELETE", "HEAD"].contains(&method.as_str()) {
+        return Err(format!("unsupported method {}", method));
+    }
+
+    let mut curl = Curl::new();
+    curl.set_method(method);
+    let mut url = url;
+    let headers = enabled_pairs(&request["header"]);
+    let has_content_type = headers
+        .iter()
+        .any(|(key, _)| key.eq_ignore_ascii_case("content-type"));
+    headers
+        .iter()
+        .for_each(|(key, value)| curl.add_headers(format!("{}: {}", key, value)));
+
+    match auth.and_then(import_auth) {
+        Some(Ok(Auth::Kind(kind))) => curl.set_auth(kind),
+        Some(Ok(Auth::Header(header))) => curl.add_headers(header),
+        Some(Ok(Auth::Query(param))) => {
+            let sep = if url.contains('?') { '&' } else { '?' };
+            url = format!("{}{}{}", url, sep, param);
+        }
+        Some(Err(reason)) => ignored.push(reason),
+        None => {}
+    }
+    curl.set_url(&url);
+
+    let body = &request["body"];
+    match text(&body["mode"]).as_deref() {
+        Some("raw") => {
+            let raw = text(&body["raw"]).unwrap_or_default();
+            if !raw.is_empty() {
+                curl.set_request_body(&raw);
+            }
+            let language = text(&body["options"]["raw"]["language"]);
+            if language.as_deref() == Some("json") && !has_content_type {
+                curl.add_headers(String::from("Content-Type: application/json"));
+            }
+        }
+        Some("urlencoded") => {
+            let data = enabled_pairs(&body["urlencoded"])
+                .iter()
+                .map(|(key, value)| format!("{}={}", percent_encode(key), percent_encode(value)))
+                .collect::<Vec<String>>()
+                .join("&");
+            if !data.is_empty() {
+                curl.set_request_body(&data);
+            }
+        }
+        Some("formdata") => {
+            for part in enabled(&body["formdata"]) {
+                let key = text(&part["key"]).unwrap_or_default();
+                match text(&part["type"]).as_deref() {
+                    Some("file") => match text(&part["src"]) {
+                        Some(src) => curl.add_form_part(&format!("{}=@{}", key, src)),
+                        None => ignored.push(format!("form file \"{}\"", key)),
+                    },
+                    _ => curl.add_form_part(&format!(
+                        "{}={}",
+                        key,
+                        text(&part["value"]).unwrap_or_default()
+                    )),
+                }
+            }
+        }
+        Some("graphql") => {
+            let graphql = &body["graphql"];
+            // variables are stored as a JSON string
+            let variables = text(&graphql["variables"])
+                .and_then(|vars| serde_json::from_str::<Value>(&vars).ok())
+                .unwrap_or(json!({}));
+            let query = json!({ "query": text(&graphql["query"]), "variables": variables });
+            curl.set_request_body(&query.to_string());
+            if !has_content_type {
+                curl.add_headers(String::from("Content-Type: application/json"));
+            }
+        }
+        Some(mode) => ignored.push(format!("{} body", mode)),
+        None => {}
+    }
+    Ok((curl, ignored))
+}
+
+// Postman stores the URL both as text and split into its parts, the text is what the user typed
+fn import_url(url: &Value) -> Option<String> {
+    if let Some(url) = text(url).or_else(|| text(&url["raw"])) {
+        return Some(url);
+    }
+    let host = match &url["host"] {
+        Value::Array(parts) => parts.iter().filter_map(text).collect::<Vec<_>>().join("."),
+        host => text(host)?,
+    };
+    let path = match &url["path"] {
+        Value::Array(parts) => parts.iter().filter_map(text).collect::<Vec<_>>().join("/"),
+        path => text(path).unwrap_or_default(),
+    };
+    let mut full = match text(&url["protocol"]) {
+        Some(protocol) => format!("{}://{}", protocol, host),
+        None => host,
+    };
+    if !path.is_empty() {
+        full = format!("{}/{}", full, path.trim_start_matches('/'));
+    }
+    let query = enabled_pairs(&url["query"])
+        .iter()
+        .map(|(key, value)| format!("{}={}", key, value))
+        .collect::<Vec<String>>();
+    if !query.is_empty() {
+        full = format!("{}?{}", full, query.join("&"));
+    }
+    Some(full)
+}
+
+enum Auth {
+    Kind(AuthKind),
+    Header(String),
+    Query(String),
+}
+
+// None for no auth, Err names the auth type we can't translate
+fn import_auth(auth: &Value) -> Option<Result<Auth, String>> {
+    let kind = text(&auth["type"])?;
+    // v2.1 stores the settings as [{"key": .., "value": ..}], v2.0 as an object
+    let settings = &auth[&kind];
+    let setting = |name: &str| match settings {
+        Value::Array(settings) => settings
+            .iter()
+            .find(|setting| setting["key"] == name)
+            .and_then(|setting| text(&setting["value"])),
+        settings => text(&settings[name]),
+    };
+    let login = || {
+        format!(
+            "{}:{}",
+            setting("username").unwrap_or_default(),
+            setting("password").unwrap_or_default()
+        )
+    };
+    match kind.as_str() {
+        "noauth" => None,
+        "basic" => Some(Ok(Auth::Kind(AuthKind::Basic(login())))),
+        "digest" => Some(Ok(Auth::Kind(AuthKind::Digest(login())))),
+        "bearer" => Some(Ok(Auth::Kind(AuthKind::Bearer(
+            setting("token").unwrap_or_default(),
+        )))),
+        "apikey" => {
+            let key = setting("key").unwrap_or_default();
+            let value = setting("value").unwrap_or_default();
+            match setting("in").as_deref() {
+                Some("query") => Some(Ok(Auth::Query(format!("{}={}", key, value)))),
+                _ => Some(Ok(Auth::Header(format!("{}: {}", key, value)))),
+            }
+        }
+        other => Some(Err(format!("{} auth", other))),
+    }
+}
+
+fn has_scripts(item: &Value) -> bool {
+    item["event"].as_array().is_some_and(|events| {
+        events.iter().any(|event| match &event["script"]["exec"] {
+            Value::Array(lines) => lines
+                .iter()
+                .filter_map(Value::as_str)
+                .any(|l| !l.is_empty()),
+            exec => exec.as_str().is_some_and(|exec| !exec.is_empty()),
+        })
+    })
+}
+
+// The entries of a key/value list that aren't switched off in Postman
+fn enabled(list: &Value) -> Vec<&Value> {
+    list.as_array()
+        .map(|entries| {
+            entries
+                .iter()
+                .filter(|entry| entry["disabled"] != true)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn enabled_pairs(list: &Value) -> Vec<(String, String)> {
+    match list {
+        // headers can also be exported as a "Key: Value" block
+        Value::String(block) => block
+            .lines()
+            .filter_map(|line| line.split_once(':'))
+            .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+            .collect(),
+        list => enabled(list)
+            .iter()
+            .filter_map(|entry| {
+                let key = text(&entry["key"])?;
+                Some((key, text(&entry["value"]).unwrap_or_default()))
+            })
+            .collect(),
+    }
+}
+
+fn text(value: &Value) -> Option<String> {
+    value.as_str().map(String::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::db::DB;
+    use crate::database::tree::collection_commands;
+
+    const COLLECTION: &str = r#"{
+        "info": {
+            "name": "Petstore",
+            "schema": "https://schema.getpostman.com/json/collection/v2.1.0/collection.json"
+        },
+        "variable": [
+            {"key": "base_url", "value": "https://petstore.example.com"},
+            {"key": "debug", "value": "1", "disabled": true}
+        ],
+        "auth": {"type": "bearer", "bearer": [{"key": "token", "value": "{{token}}", "type": "string"}]},
+        "item": [
+            {
+                "name": "Pets",
+                "item": [
+                    {
+                        "name": "List pets",
+                        "request": {
+                            "method": "GET",
+                            "header": [
+                                {"key": "Accept", "value": "application/json"},
+                                {"key": "X-Debug", "value": "1", "disabled": true}
+                            ],
+                            "url": {
+                                "raw": "{{base_url}}/pets?limit=10",
+                                "host": ["{{base_url}}"],
+                                "path": ["pets"],
+                                "query": [{"key": "limit", "value": "10"}]
+                            },
+                            "description": "All the pets"
+                        }
+                    },
+                    {
+                        "name": "Create pet",
+                        "event": [{"listen": "test", "script": {"exec": ["pm.test('ok')"]}}],
+                        "request": {
+                            "method": "POST",
+                            "url": "{{base_url}}/pets",
+                            "body": {
+                                "mode": "raw",
+                                "raw": "{\"name\": \"rex\"}",
+                                "options": {"raw": {"language": "json"}}
+                            }
+                        }
+                    },
+                    {"name": "Empty folder", "item": []}
+                ]
+            },
+            {
+                "name": "Login",
+                "request": {
+                    "method": "POST",
+                    "auth": {"type": "basic", "basic": [
+                        {"key": "username", "value": "admin"},
+                        {"key": "password", "value": "s3cret"}
+                    ]},
+                    "url": {"protocol": "https", "host": ["api", "example", "com"], "path": ["login"]},
+                    "body": {"mode": "urlencoded", "urlencoded": [
+                        {"key": "remember me", "value": "yes&no"},
+                        {"key": "skip", "value": "1", "disabled": true}
+                    ]}
+                }
+            },
+            {
+                "name": "Upload",
+                "request": {
+                    "method": "PUT",
+                    "auth": {"type": "oauth2"},
+                    "url": "https://api.example.com/upload",
+                    "body": {"mode": "formdata", "formdata": [
+                        {"key": "title", "value": "cat", "type": "text"},
+                        {"key": "photo", "src": "/tmp/cat.png", "type": "file"}
+                    ]}
+                }
+            },
+            {"name": "Options", "request": {"method": "OPTIONS", "url": "https://api.example.com"}}
+        ]
+    }"#;
+
+    #[test]
+    fn test_parse_postman_collection() {
+        let mut import = parse_postman_collection(COLLECTION).unwrap();
+        assert_eq!(import.name, "Petstore");
+        assert_eq!(
+            import.variables,
+            vec![(
+                String::from("base_url"),
+                String::from("https://petstore.example.com")
+            )]
+        );
+        assert_eq!(
+            import.folders,
+            vec![
+                vec![String::from("Pets")],
+                vec![String::from("Pets"), String::from("Empty folder")]
+            ]
+        );
+        assert_eq!(import.requests.len(), 4);
+        assert_eq!(
+            import.ignored,
+            vec![
+                String::from("Pets/Create pet: pre-request and test scripts"),
+                String::from("Upload: oauth2 auth"),
+                String::from("Options: unsupported method OPTIONS"),
+            ]
+        );
+
+        let list = &mut import.requests[0];
+        assert_eq!(list.folders, vec![String::from("Pets")]);
+        assert_eq!(list.description.as_deref(), Some("All the pets"));
+        assert_eq!(list.curl.get_url(), "{{base_url}}/pets?limit=10");
+        assert_eq!(
+            list.curl.get_headers(),
+            vec![String::from("Accept: application/json")]
+        );
+        // the collection's auth is inherited
+        assert_eq!(
+            list.curl.get_auth(),
+            &AuthKind::Bearer(String::from("{{token}}"))
+        );
+
+        let create = &mut import.requests[1];
+        assert_eq!(
+            create.curl.get_request_body().as_deref(),
+            Some("{\"name\": \"rex\"}")
+        );
+        assert_eq!(
+            create.curl.get_headers(),
+            vec![String::from("Content-Type: application/json")]
+        );
+
+        let login = &mut import.requests[2];
+        assert_eq!(login.curl.get_url(), "https://api.example.com/login");
+        assert_eq!(
+            login.curl.get_auth(),
+            &AuthKind::Basic(String::from("admin:s3cret"))
+        );
+        assert_eq!(
+            login.curl.get_request_body().as_deref(),
+            Some("remember%20me=yes%26no")
+        );
+
+        let upload = &mut import.requests[3];
+        assert_eq!(upload.curl.get_auth(), &AuthKind::None);
+        assert_eq!(
+            upload.curl.get_form_parts(),
+            vec![
+                String::from("title=cat"),
+                String::from("photo=@/tmp/cat.png")
+            ]
+        );
+        assert!(upload
+            .curl
+            .get_command_string()
+            .starts_with("curl -X PUT https://api.example.com/upload -F title=cat"));
+    }
+
+    #[test]
+    fn test_import_auth() {
+        let header = json!({"type": "apikey", "apikey": {"key": "X-Api-Key", "value": "abc"}});
+        assert!(matches!(
+            import_auth(&header),
+            Some(Ok(Auth::Header(header))) if header == "X-Api-Key: abc"
+        ));
+        let query = json!({"type": "apikey", "apikey": [
+            {"key": "key", "value": "api_key"},
+            {"key": "value", "value": "abc"},
+            {"key": "in", "value": "query"}
+        ]});
+        assert!(matches!(
+            import_auth(&query),
+            Some(Ok(Auth::Query(param))) if param == "api_key=abc"
+        ));
+        assert!(import_auth(&json!({"type": "noauth"})).is_none());
+    }
+
+    #[test]
+    fn test_save_postman_import() {
+        let db = DB::new_in_memory().unwrap();
+        let mut import = parse_postman_collection(COLLECTION).unwrap();
+        assert_eq!(import.save(&db).unwrap(), 4);
+
+        let collections = db.get_collections().unwrap();
+        let names: Vec<&str> = collections.iter().map(|c| c.get_name()).collect();
+        assert_eq!(names, vec!["Empty folder", "Pets", "Petstore"]);
+        let commands = db.get_commands().unwrap();
+        let root = collections.iter().find(|c| c.get_name() == "Petstore");
+        let labels: Vec<String> =
+            collection_commands(&collections, &commands, root.map(|c| c.get_id()))
+                .iter()
+                .map(|cmd| cmd.get_label().to_string())
+                .collect();
+        assert_eq!(labels, vec!["List pets", "Create pet", "Login", "Upload"]);
+        let saved = commands.iter().find(|cmd| cmd.get_label() == "Upload");
+        let curl: Curl = serde_json::from_str(saved.unwrap().get_curl_json()).unwrap();
+        assert_eq!(curl.get_form_parts().len(), 2);
+    }
+
+    #[test]
+    fn test_parse_invalid_collection() {
+        assert!(parse_postman_collection("not json").is_err());
+        assert!(parse_postman_collection("{\"info\": {}, \"requests\": []}").is_err());
+    }
+}
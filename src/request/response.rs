@@ -1,60 +1,175 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
 
-#[derive(Debug, Serialize, Deserialize)]
+// The status line and headers of a single response. A request that follows redirects,
+// or gets a `100 Continue`, receives one of these per response before the final one.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HeaderBlock {
+    pub version: String,
+    pub status: u16,
+    pub reason: String,
+    // In the order they were received, duplicates included
+    pub headers: Vec<(String, String)>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Response {
     pub status: u16,
-    pub headers: HashMap<String, String>,
+    pub reason: String,
+    pub version: String,
+    // The headers of the final response, in the order they were received, duplicates included
+    pub headers: Vec<(String, String)>,
+    // Every header block we received, the last one is the final response
+    pub hops: Vec<HeaderBlock>,
     pub body: String,
 }
 
+// "HTTP/1.1 200 OK" -> ("HTTP/1.1", 200, "OK"), HTTP/2 has no reason phrase
+fn parse_status_line(line: &str) -> Option<(String, u16, String)> {
+    let mut parts = line.trim().splitn(3, ' ');
+    let version = parts.next().filter(|v| v.starts_with("HTTP/"))?;
+    let status = parts.next()?.parse::<u16>().ok()?;
+    let reason = parts.next().unwrap_or_default().trim();
+    Some((version.to_string(), status, reason.to_string()))
+}
+
 impl Response {
+    // Builds the response from the raw header lines handed to us by libcurl and the body
+    pub fn from_parts(raw_headers: &str, body: String) -> Result<Response, &'static str> {
+        let mut hops: Vec<HeaderBlock> = Vec::new();
+        for line in raw_headers.lines() {
+            if let Some((version, status, reason)) = parse_status_line(line) {
+                hops.push(HeaderBlock {
+                    version,
+                    status,
+                    reason,
+                    headers: Vec::new(),
+                });
+            } else if let (Some(block), Some((key, value))) =
+                (hops.last_mut(), line.split_once(':'))
+            {
+                block
+                    .headers
+                    .push((key.trim().to_string(), value.trim().to_string()));
+            }
+        }
+        let last = hops.last().cloned().ok_or("No response headers received")?;
+        Ok(Response {
+            status: last.status,
+            reason: last.reason,
+            version: last.version,
+            headers: last.headers,
+            hops,
+            body,
+        })
+    }
+
+    // Parses a response where the headers were written into the body (curl -i)
     pub fn from_raw_string(response_str: &str) -> Result<Response, &'static str> {
-        // Split the response into lines
         let lines: Vec<&str> = response_str.lines().collect();
-
-        // Ensure there's at least one line in the response
         if lines.is_empty() {
             return Err("Empty response");
         }
-
-        // Parse the first line to extract the status code
-        let first_line = lines[0].trim();
-        let status_code = first_line
-            .split_whitespace()
-            .nth(1)
-            .and_then(|status| status.parse::<u16>().ok())
-            .ok_or("Invalid status code")?;
-
-        // HashMap for the headers
-        let mut headers = HashMap::new();
-        let mut body = String::new();
-
-        // Iterate through the remaining lines to extract headers and body
-        let mut parsing_body = false;
-        for line in &lines[1..] {
-            if parsing_body {
-                body.push_str(line);
-                body.push('\n');
-            } else if line.is_empty() {
-                // An empty line separates headers from the body
-                parsing_body = true;
-            } else {
-                // Parse headers in key-value format (will happen first)
-                if let Some((key, value)) = line.split_once(':') {
-                    headers.insert(key.trim().to_string(), value.trim().to_string());
-                }
+        // the body starts after the empty line following the last header block
+        let mut raw_headers = Vec::new();
+        let mut body_start = lines.len();
+        let mut i = 0;
+        while i < lines.len() {
+            if parse_status_line(lines[i]).is_none() {
+                break;
+            }
+            while i < lines.len() && !lines[i].trim().is_empty() {
+                raw_headers.push(lines[i]);
+                i += 1;
             }
+            i += 1;
+            body_start = i.min(lines.len());
         }
+        if raw_headers.is_empty() {
+            return Err("Invalid status code");
+        }
+        let mut body = lines[body_start..].join("\n");
+        if !body.is_empty() {
+            body.push('\n');
+        }
+        Response::from_parts(&raw_headers.join("\n"), body)
+    }
 
-        Ok(Response {
-            status: status_code,
-            headers,
-            body,
-        })
+    // The first value of a header of the final response, names are case insensitive
+    pub fn get_header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    // Every value of a header, e.g. multiple Set-Cookie headers
+    pub fn get_header_values(&self, name: &str) -> Vec<&str> {
+        self.headers
+            .iter()
+            .filter(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+            .collect()
+    }
+
+    // Every header block as it was received, separated by an empty line
+    pub fn get_headers(&self) -> String {
+        self.hops
+            .iter()
+            .map(|block| {
+                let status_line = format!("{} {} {}", block.version, block.status, block.reason);
+                let mut lines = vec![status_line.trim_end().to_string()];
+                lines.extend(
+                    block
+                        .headers
+                        .iter()
+                        .map(|(key, value)| format!("{}: {}", key, value)),
+                );
+                lines.join("\n")
+            })
+            .collect::<Vec<String>>()
+            .join("\n\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REDIRECTED: &str = "HTTP/1.1 301 Moved Permanently\r\nLocation: /new\r\nContent-Length: 0\r\n\r\nHTTP/2 200\r\ncontent-type: application/json\r\nset-cookie: a=1\r\nset-cookie: b=2\r\n\r\n";
+
+    #[test]
+    fn test_from_parts() {
+        let response = Response::from_parts(REDIRECTED, String::from("{}")).unwrap();
+        assert_eq!(response.status, 200);
+        assert_eq!(response.version, "HTTP/2");
+        assert_eq!(response.reason, "");
+        assert_eq!(response.hops.len(), 2);
+        assert_eq!(response.hops[0].status, 301);
+        assert_eq!(response.hops[0].reason, "Moved Permanently");
+        assert_eq!(
+            response.hops[0].headers[0],
+            (String::from("Location"), String::from("/new"))
+        );
+        assert_eq!(
+            response.get_header("Content-Type"),
+            Some("application/json")
+        );
+        assert_eq!(response.get_header_values("Set-Cookie"), vec!["a=1", "b=2"]);
+        assert_eq!(response.body, "{}");
+        assert_eq!(
+            response.get_headers(),
+            "HTTP/1.1 301 Moved Permanently\nLocation: /new\nContent-Length: 0\n\nHTTP/2 200\ncontent-type: application/json\nset-cookie: a=1\nset-cookie: b=2"
+        );
+        assert!(Response::from_parts("", String::new()).is_err());
     }
 
-    pub fn get_headers(&self) -> serde_json::Value {
-        serde_json::to_value(&self.headers).unwrap()
+    #[test]
+    fn test_from_raw_string() {
+        let raw = format!("{}{{\"key\": \"value\"}}", REDIRECTED);
+        let response = Response::from_raw_string(&raw).unwrap();
+        assert_eq!(response.status, 200);
+        assert_eq!(response.hops.len(), 2);
+        assert_eq!(response.body, "{\"key\": \"value\"}\n");
+        assert!(Response::from_raw_string("{\"key\": \"value\"}").is_err());
     }
 }
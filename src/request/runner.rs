@@ -0,0 +1,230 @@
+/*
+* Test Runner - executes saved commands one after another and checks their
+* assertions against each response, for quick API smoke tests.
+ */
+use std::fmt::Display;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use super::assertion::Assertion;
+use super::command::CmdOpts;
+use super::curl::Curl;
+use super::environment::expand_vars;
+use crate::database::db::SavedCommand;
+use crate::display::menuopts::LOCKED_ERROR;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TestResult {
+    pub id: i32,
+    pub label: String,
+    pub method: String,
+    pub url: String,
+    // None if the request failed before we got a response
+    pub status: Option<u16>,
+    pub duration: Duration,
+    // size of the response body in bytes
+    pub size: usize,
+    // Every assertion along with the reason it failed, if it did
+    pub checks: Vec<(Assertion, Result<(), String>)>,
+    // The request itself failed, or got an error status with no assertions to check
+    pub error: Option<String>,
+}
+
+impl TestResult {
+    pub fn passed(&self) -> bool {
+        self.error.is_none() && self.checks.iter().all(|(_, result)| result.is_ok())
+    }
+
+    // Why the test failed, one message per failed check
+    pub fn failures(&self) -> Vec<String> {
+        self.error
+            .iter()
+            .cloned()
+            .chain(
+                self.checks
+                    .iter()
+                    .filter_map(|(_, result)| result.clone().err()),
+            )
+            .collect()
+    }
+}
+
+impl Display for TestResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}  {} {}  {}  {}ms",
+            if self.passed() { "PASS" } else { "FAIL" },
+            self.method,
+            self.label,
+            self.status
+                .map(|status| status.to_string())
+                .unwrap_or(String::from("---")),
+            self.duration.as_millis()
+        )
+    }
+}
+
+// Executes a single saved command and checks its assertions
+pub fn run_command(
+    saved: &SavedCommand,
+    env_vars: &[(String, String)],
+    cancel: &Arc<AtomicBool>,
+) -> TestResult {
+    let mut result = TestResult {
+        id: saved.get_id(),
+        label: saved.get_label().to_string(),
+        method: String::from("GET"),
+        url: String::new(),
+        status: None,
+        duration: Duration::ZERO,
+        size: 0,
+        checks: Vec::new(),
+        error: None,
+    };
+    if saved.is_locked() {
+        result.error = Some(String::from(LOCKED_ERROR));
+        return result;
+    }
+    // deserializing re-applies the stored options to the curl handle
+    let mut curl: Curl = match serde_json::from_str(saved.get_curl_json()) {
+        Ok(curl) => curl,
+        Err(e) => {
+            result.error = Some(format!("invalid saved command: {}", e));
+            return result;
+        }
+    };
+    if let Some(method) = curl.get_method() {
+        result.method = method.to_string();
+    }
+    result.url = expand_vars(&curl.get_url(), env_vars);
+    curl.set_env_vars(env_vars.to_vec());
+    curl.set_cancel_flag(cancel.clone());
+    let started = Instant::now();
+    let executed = curl.execute(None);
+    result.duration = started.elapsed();
+    if let Err(e) = executed {
+        result.error = Some(e);
+        return result;
+    }
+    let Some(response) = curl.get_http_response() else {
+        result.error = Some(String::from("no response received"));
+        return result;
+    };
+    result.status = Some(response.status);
+    result.size = response.body.len();
+    result.checks = saved
+        .get_assertions()
+        .iter()
+        .map(|assertion| {
+            (
+                assertion.clone(),
+                assertion.check(response, result.duration),
+            )
+        })
+        .collect();
+    // without assertions, a test only fails when the server returns an error
+    if result.checks.is_empty() && response.status >= 400 {
+        result.error = Some(format!("got status {}", response.status));
+    }
+    result
+}
+
+// Runs the commands in order, stopping early if the run is cancelled
+pub fn run_tests(
+    commands: &[SavedCommand],
+    env_vars: &[(String, String)],
+    cancel: &Arc<AtomicBool>,
+) -> Vec<TestResult> {
+    let mut results = Vec::new();
+    for saved in commands {
+        if cancel.load(Ordering::Relaxed) {
+            break;
+        }
+        results.push(run_command(saved, env_vars, cancel));
+    }
+    results
+}
+
+// "3 requests, 2 passed, 1 failed"
+pub fn summary(results: &[TestResult]) -> String {
+    let passed = results.iter().filter(|result| result.passed()).count();
+    format!(
+        "{} requests, {} passed, {} failed",
+        results.len(),
+        passed,
+        results.len() - passed
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::db::DB;
+
+    fn save_get(db: &DB, url: &str) {
+        let mut curl = Curl::new();
+        curl.set_get_method();
+        curl.set_url(url);
+        db.add_command(
+            &curl.get_command_string(),
+            serde_json::to_string(&curl).unwrap(),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_run_tests() {
+        let mut server = mockito::Server::new();
+        let _users = server
+            .mock("GET", "/users")
+            .with_status(200)
+            .with_header("Content-Type", "application/json")
+            .with_body("{\"users\": [{\"id\": 1}]}")
+            .create();
+        let _missing = server.mock("GET", "/missing").with_status(404).create();
+        let db = DB::new_in_memory().unwrap();
+        save_get(&db, "{{base_url}}/users");
+        save_get(&db, &format!("{}/missing", server.url()));
+        save_get(&db, &format!("{}/missing", server.url()));
+        let assertions = [
+            "status == 200",
+            "header Content-Type == application/json",
+            "json $.users[0].id == 1",
+        ]
+        .map(|text| text.parse::<Assertion>().unwrap());
+        db.set_command_assertions(1, &assertions).unwrap();
+        db.set_command_assertions(3, &[Assertion::Status(404)])
+            .unwrap();
+
+        let vars = vec![(String::from("base_url"), server.url())];
+        let cancel = Arc::new(AtomicBool::new(false));
+        let results = run_tests(&db.get_commands().unwrap(), &vars, &cancel);
+        assert_eq!(results.len(), 3);
+        assert!(results[0].passed(), "{:?}", results[0].failures());
+        assert_eq!(results[0].url, format!("{}/users", server.url()));
+        assert_eq!(results[0].checks.len(), 3);
+        // no assertions, so the 404 fails it
+        assert!(!results[1].passed());
+        assert_eq!(results[1].failures(), vec![String::from("got status 404")]);
+        // the 404 was expected here
+        assert!(results[2].passed());
+        assert_eq!(summary(&results), "3 requests, 2 passed, 1 failed");
+        assert!(results[1].to_string().starts_with("FAIL  GET "));
+
+        cancel.store(true, Ordering::Relaxed);
+        assert!(run_tests(&db.get_commands().unwrap(), &vars, &cancel).is_empty());
+    }
+
+    #[test]
+    fn test_run_command_failed() {
+        let db = DB::new_in_memory().unwrap();
+        save_get(&db, "http://127.0.0.1:1");
+        let cancel = Arc::new(AtomicBool::new(false));
+        let result = run_command(&db.get_commands().unwrap()[0], &[], &cancel);
+        assert!(!result.passed());
+        assert_eq!(result.status, None);
+        assert_eq!(result.failures().len(), 1);
+    }
+}
@@ -0,0 +1,527 @@
+/*
+* Code snippets - Renders a built request as client code in other languages,
+* so it can be handed off to developers.
+ */
+use std::fmt::{Display, Formatter};
+
+use super::command::CmdOpts;
+use super::curl::{AuthKind, Curl};
+use super::parser::quote_arg;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SnippetLang {
+    Reqwest,
+    PythonRequests,
+    JsFetch,
+    Httpie,
+    Libcurl,
+}
+
+// In the same order as the snippet menu
+pub const SNIPPET_LANGS: [SnippetLang; 5] = [
+    SnippetLang::Reqwest,
+    SnippetLang::PythonRequests,
+    SnippetLang::JsFetch,
+    SnippetLang::Httpie,
+    SnippetLang::Libcurl,
+];
+
+impl Display for SnippetLang {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SnippetLang::Reqwest => write!(f, "Rust (reqwest)"),
+            SnippetLang::PythonRequests => write!(f, "Python (requests)"),
+            SnippetLang::JsFetch => write!(f, "JavaScript (fetch)"),
+            SnippetLang::Httpie => write!(f, "HTTPie"),
+            SnippetLang::Libcurl => write!(f, "C (libcurl)"),
+        }
+    }
+}
+
+impl SnippetLang {
+    pub fn from_index(index: usize) -> Option<Self> {
+        SNIPPET_LANGS.get(index).copied()
+    }
+
+    // The default file extension when writing the snippet to a file
+    pub fn extension(&self) -> &'static str {
+        match self {
+            SnippetLang::Reqwest => "rs",
+            SnippetLang::PythonRequests => "py",
+            SnippetLang::JsFetch => "js",
+            SnippetLang::Httpie => "sh",
+            SnippetLang::Libcurl => "c",
+        }
+    }
+}
+
+// The parts of a request every snippet is built from
+struct SnippetRequest {
+    method: String,
+    url: String,
+    headers: Vec<(String, String)>,
+    body: Option<String>,
+    auth: AuthKind,
+}
+
+impl SnippetRequest {
+    fn from_curl(curl: &Curl) -> Self {
+        let body = curl.get_request_body().filter(|body| !body.is_empty());
+        let method = match curl.get_method() {
+            Some(method) => method.to_string(),
+            None if body.is_some() => String::from("POST"),
+            None => String::from("GET"),
+        };
+        let mut headers: Vec<(String, String)> = curl
+            .get_headers()
+            .iter()
+            .filter_map(|h| h.split_once(':'))
+            .map(|(key, val)| (key.trim().to_string(), val.trim().to_string()))
+            .collect();
+        // bearer auth is just a header in every language
+        let auth = match curl.get_auth() {
+            AuthKind::Bearer(token) => {
+                headers.push((String::from("Authorization"), format!("Bearer {}", token)));
+                AuthKind::None
+            }
+            auth => auth.clone(),
+        };
+        SnippetRequest {
+            method,
+            url: curl.get_url(),
+            headers,
+            body,
+            auth,
+        }
+    }
+
+    // The auth scheme, for the comment we leave when a language has no support for it
+    fn auth_name(&self) -> &'static str {
+        match self.auth {
+            AuthKind::Digest(_) => "Digest",
+            AuthKind::Ntlm => "NTLM",
+            AuthKind::Spnego => "SPNEGO",
+            AuthKind::AwsSigv4 => "AWS SigV4",
+            _ => "This",
+        }
+    }
+}
+
+/// Renders the request as code in the given language.
+pub fn generate_snippet(curl: &Curl, lang: SnippetLang) -> String {
+    let req = SnippetRequest::from_curl(curl);
+    match lang {
+        SnippetLang::Reqwest => reqwest_snippet(&req),
+        SnippetLang::PythonRequests => python_snippet(&req),
+        SnippetLang::JsFetch => fetch_snippet(&req),
+        SnippetLang::Httpie => httpie_snippet(&req),
+        SnippetLang::Libcurl => libcurl_snippet(&req),
+    }
+}
+
+fn split_login(login: &str) -> (String, Option<String>) {
+    match login.split_once(':') {
+        Some((user, pass)) => (user.to_string(), Some(pass.to_string())),
+        None => (login.to_string(), None),
+    }
+}
+
+// Debug formatting of a str is a valid Rust string literal
+fn rust_str(s: &str) -> String {
+    format!("{:?}", s)
+}
+
+// A JSON string is a valid string literal in both Python and JavaScript
+fn json_str(s: &str) -> String {
+    serde_json::to_string(s).unwrap_or_default()
+}
+
+fn c_str(s: &str) -> String {
+    let mut escaped = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            // octal, so a following digit can't be read as part of the escape
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\{:03o}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+fn reqwest_snippet(req: &SnippetRequest) -> String {
+    let mut lines = vec![
+        String::from("use reqwest::blocking::Client;"),
+        String::new(),
+        String::from("fn main() -> Result<(), Box<dyn std::error::Error>> {"),
+        String::from("    let client = Client::new();"),
+        String::from("    let response = client"),
+        format!(
+            "        .{}({})",
+            req.method.to_lowercase(),
+            rust_str(&req.url)
+        ),
+    ];
+    for (key, val) in &req.headers {
+        lines.push(format!(
+            "        .header({}, {})",
+            rust_str(key),
+            rust_str(val)
+        ));
+    }
+    match req.auth {
+        AuthKind::None => {}
+        AuthKind::Basic(ref login) => {
+            let (user, pass) = split_login(login);
+            let pass = pass.map_or(String::from("None::<&str>"), |p| {
+                format!("Some({})", rust_str(&p))
+            });
+            lines.push(format!(
+                "        .basic_auth({}, {})",
+                rust_str(&user),
+                pass
+            ));
+        }
+        _ => lines.push(format!(
+            "        // {} authentication is not supported by reqwest",
+            req.auth_name()
+        )),
+    }
+    if let Some(ref body) = req.body {
+        lines.push(format!("        .body({})", rust_str(body)));
+    }
+    lines.push(String::from("        .send()?;"));
+    lines.push(String::from("    println!(\"{}\", response.text()?);"));
+    lines.push(String::from("    Ok(())"));
+    lines.push(String::from("}"));
+    lines.join("\n")
+}
+
+fn python_snippet(req: &SnippetRequest) -> String {
+    let mut imports = vec![String::from("import requests")];
+    let mut lines = vec![format!("url = {}", json_str(&req.url))];
+    let mut args = vec![String::from("url")];
+    if !req.headers.is_empty() {
+        lines.push(String::from("headers = {"));
+        for (key, val) in &req.headers {
+            lines.push(format!("    {}: {},", json_str(key), json_str(val)));
+        }
+        lines.push(String::from("}"));
+        args.push(String::from("headers=headers"));
+    }
+    if let Some(ref body) = req.body {
+        lines.push(format!("data = {}", json_str(body)));
+        args.push(String::from("data=data"));
+    }
+    match req.auth {
+        AuthKind::None => {}
+        AuthKind::Basic(ref login) => {
+            let (user, pass) = split_login(login);
+            args.push(format!(
+                "auth=({}, {})",
+                json_str(&user),
+                json_str(&pass.unwrap_or_default())
+            ));
+        }
+        AuthKind::Digest(ref login) => {
+            let (user, pass) = split_login(login);
+            imports.push(String::from("from requests.auth import HTTPDigestAuth"));
+            args.push(format!(
+                "auth=HTTPDigestAuth({}, {})",
+                json_str(&user),
+                json_str(&pass.unwrap_or_default())
+            ));
+        }
+        _ => lines.push(format!(
+            "# {} authentication needs an auth plugin for requests",
+            req.auth_name()
+        )),
+    }
+    format!(
+        "{}\n\n{}\n\nresponse = requests.{}({})\nprint(response.text)",
+        imports.join("\n"),
+        lines.join("\n"),
+        req.method.to_lowercase(),
+        args.join(", ")
+    )
+}
+
+fn fetch_snippet(req: &SnippetRequest) -> String {
+    let mut lines = vec![
+        format!("const response = await fetch({}, {{", json_str(&req.url)),
+        format!("  method: {},", json_str(&req.method)),
+    ];
+    let mut headers: Vec<String> = req
+        .headers
+        .iter()
+        .map(|(key, val)| format!("    {}: {},", json_str(key), json_str(val)))
+        .collect();
+    match req.auth {
+        AuthKind::None => {}
+        AuthKind::Basic(ref login) => headers.push(format!(
+            "    \"Authorization\": \"Basic \" + btoa({}),",
+            json_str(login)
+        )),
+        _ => lines.push(format!(
+            "  // {} authentication is not supported by fetch",
+            req.auth_name()
+        )),
+    }
+    if !headers.is_empty() {
+        lines.push(String::from("  headers: {"));
+        lines.extend(headers);
+        lines.push(String::from("  },"));
+    }
+    if let Some(ref body) = req.body {
+        lines.push(format!("  body: {},", json_str(body)));
+    }
+    lines.push(String::from("});"));
+    lines.push(String::from("console.log(await response.text());"));
+    lines.join("\n")
+}
+
+fn httpie_snippet(req: &SnippetRequest) -> String {
+    let mut args = vec![
+        String::from("http"),
+        req.method.clone(),
+        quote_arg(&req.url),
+    ];
+    for (key, val) in &req.headers {
+        args.push(quote_arg(&format!("{}:{}", key, val)));
+    }
+    match req.auth {
+        AuthKind::None => {}
+        AuthKind::Basic(ref login) => {
+            args.push(String::from("-a"));
+            args.push(quote_arg(login));
+        }
+        AuthKind::Digest(ref login) => {
+            args.extend([String::from("-A"), String::from("digest")]);
+            args.push(String::from("-a"));
+            args.push(quote_arg(login));
+        }
+        _ => {}
+    }
+    if let Some(ref body) = req.body {
+        args.push(String::from("--raw"));
+        args.push(quote_arg(body));
+    }
+    let cmd = args.join(" ");
+    match req.auth {
+        AuthKind::Ntlm | AuthKind::Spnego | AuthKind::AwsSigv4 => format!(
+            "# {} authentication needs an HTTPie auth plugin\n{}",
+            req.auth_name(),
+            cmd
+        ),
+        _ => cmd,
+    }
+}
+
+// Follows the layout of `curl --libcurl`
+fn libcurl_snippet(req: &SnippetRequest) -> String {
+    let mut lines = vec![
+        String::from("#include <curl/curl.h>"),
+        String::new(),
+        String::from("int main(void)"),
+        String::from("{"),
+        String::from("  CURLcode ret;"),
+        String::from("  CURL *hnd;"),
+    ];
+    let has_headers = !req.headers.is_empty();
+    if has_headers {
+        lines.push(String::from("  struct curl_slist *slist1;"));
+        lines.push(String::new());
+        lines.push(String::from("  slist1 = NULL;"));
+        for (key, val) in &req.headers {
+            lines.push(format!(
+                "  slist1 = curl_slist_append(slist1, {});",
+                c_str(&format!("{}: {}", key, val))
+            ));
+        }
+    }
+    lines.push(String::new());
+    lines.push(String::from("  hnd = curl_easy_init();"));
+    let setopt = |opt: &str, val: String| format!("  curl_easy_setopt(hnd, {}, {});", opt, val);
+    lines.push(setopt("CURLOPT_URL", c_str(&req.url)));
+    if let Some(ref body) = req.body {
+        lines.push(setopt("CURLOPT_POSTFIELDS", c_str(body)));
+        lines.push(setopt(
+            "CURLOPT_POSTFIELDSIZE_LARGE",
+            format!("(curl_off_t){}", body.len()),
+        ));
+    }
+    if has_headers {
+        lines.push(setopt("CURLOPT_HTTPHEADER", String::from("slist1")));
+    }
+    match req.auth {
+        AuthKind::None | AuthKind::Bearer(_) => {}
+        AuthKind::Basic(ref login) => {
+            lines.push(setopt("CURLOPT_USERPWD", c_str(login)));
+            lines.push(setopt(
+                "CURLOPT_HTTPAUTH",
+                String::from("(long)CURLAUTH_BASIC"),
+            ));
+        }
+        AuthKind::Digest(ref login) => {
+            lines.push(setopt("CURLOPT_USERPWD", c_str(login)));
+            lines.push(setopt(
+                "CURLOPT_HTTPAUTH",
+                String::from("(long)CURLAUTH_DIGEST"),
+            ));
+        }
+        AuthKind::Ntlm => {
+            lines.push(setopt(
+                "CURLOPT_HTTPAUTH",
+                String::from("(long)CURLAUTH_NTLM"),
+            ));
+        }
+        AuthKind::Spnego => {
+            lines.push(setopt("CURLOPT_USERPWD", c_str(":")));
+            lines.push(setopt(
+                "CURLOPT_HTTPAUTH",
+                String::from("(long)CURLAUTH_NEGOTIATE"),
+            ));
+        }
+        AuthKind::AwsSigv4 => {
+            lines.push(setopt("CURLOPT_AWS_SIGV4", c_str("aws:amz")));
+        }
+    }
+    match req.method.as_str() {
+        "GET" => {}
+        "HEAD" => lines.push(setopt("CURLOPT_NOBODY", String::from("1L"))),
+        "POST" if req.body.is_some() => {}
+        method => lines.push(setopt("CURLOPT_CUSTOMREQUEST", c_str(method))),
+    }
+    lines.push(String::new());
+    lines.push(String::from("  ret = curl_easy_perform(hnd);"));
+    lines.push(String::new());
+    lines.push(String::from("  curl_easy_cleanup(hnd);"));
+    lines.push(String::from("  hnd = NULL;"));
+    if has_headers {
+        lines.push(String::from("  curl_slist_free_all(slist1);"));
+        lines.push(String::from("  slist1 = NULL;"));
+    }
+    lines.push(String::new());
+    lines.push(String::from("  return (int)ret;"));
+    lines.push(String::from("}"));
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::request::command::CurlOpts;
+
+    fn post_request() -> Curl<'static> {
+        let mut curl = Curl::new();
+        curl.set_post_method();
+        curl.set_url("https://example.com/api?q=\"x\"");
+        curl.add_headers(String::from("Content-Type: application/json"));
+        curl.set_request_body("{\"key\": \"it's\"}");
+        curl.set_auth(AuthKind::Basic(String::from("user:pass")));
+        curl
+    }
+
+    #[test]
+    fn test_reqwest_snippet() {
+        let snippet = generate_snippet(&post_request(), SnippetLang::Reqwest);
+        assert_eq!(
+            snippet,
+            r#"use reqwest::blocking::Client;
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let client = Client::new();
+    let response = client
+        .post("https://example.com/api?q=\"x\"")
+        .header("Content-Type", "application/json")
+        .basic_auth("user", Some("pass"))
+        .body("{\"key\": \"it's\"}")
+        .send()?;
+    println!("{}", response.text()?);
+    Ok(())
+}"#
+        );
+    }
+
+    #[test]
+    fn test_python_snippet() {
+        let snippet = generate_snippet(&post_request(), SnippetLang::PythonRequests);
+        assert_eq!(
+            snippet,
+            r#"import requests
+
+url = "https://example.com/api?q=\"x\""
+headers = {
+    "Content-Type": "application/json",
+}
+data = "{\"key\": \"it's\"}"
+
+response = requests.post(url, headers=headers, data=data, auth=("user", "pass"))
+print(response.text)"#
+        );
+        let mut curl = Curl::new();
+        curl.set_url("https://example.com");
+        curl.set_auth(AuthKind::Digest(String::from("user:pass")));
+        let snippet = generate_snippet(&curl, SnippetLang::PythonRequests);
+        assert!(snippet.contains("from requests.auth import HTTPDigestAuth"));
+        assert!(snippet.contains("requests.get(url, auth=HTTPDigestAuth(\"user\", \"pass\"))"));
+    }
+
+    #[test]
+    fn test_fetch_snippet() {
+        let snippet = generate_snippet(&post_request(), SnippetLang::JsFetch);
+        assert_eq!(
+            snippet,
+            r#"const response = await fetch("https://example.com/api?q=\"x\"", {
+  method: "POST",
+  headers: {
+    "Content-Type": "application/json",
+    "Authorization": "Basic " + btoa("user:pass"),
+  },
+  body: "{\"key\": \"it's\"}",
+});
+console.log(await response.text());"#
+        );
+    }
+
+    #[test]
+    fn test_httpie_snippet() {
+        let snippet = generate_snippet(&post_request(), SnippetLang::Httpie);
+        assert_eq!(
+            snippet,
+            r#"http POST 'https://example.com/api?q="x"' Content-Type:application/json -a user:pass --raw '{"key": "it'\''s"}'"#
+        );
+        let mut curl = Curl::new();
+        curl.set_url("https://example.com");
+        curl.set_bearer_auth(String::from("abc123"));
+        assert_eq!(
+            generate_snippet(&curl, SnippetLang::Httpie),
+            "http GET https://example.com 'Authorization:Bearer abc123'"
+        );
+    }
+
+    #[test]
+    fn test_libcurl_snippet() {
+        let mut curl = Curl::new();
+        curl.set_put_method();
+        curl.set_url("https://example.com/api?q=\"x\"");
+        curl.add_headers(String::from("Content-Type: application/json"));
+        curl.set_request_body("a\tb\u{1}1");
+        curl.set_auth(AuthKind::Basic(String::from("user:pass")));
+        let snippet = generate_snippet(&curl, SnippetLang::Libcurl);
+        assert!(snippet
+            .contains("slist1 = curl_slist_append(slist1, \"Content-Type: application/json\");"));
+        assert!(snippet.contains(
+            "curl_easy_setopt(hnd, CURLOPT_URL, \"https://example.com/api?q=\\\"x\\\"\");"
+        ));
+        assert!(snippet.contains("curl_easy_setopt(hnd, CURLOPT_POSTFIELDS, \"a\\tb\\0011\");"));
+        assert!(snippet.contains("curl_easy_setopt(hnd, CURLOPT_USERPWD, \"user:pass\");"));
+        assert!(snippet.contains("curl_easy_setopt(hnd, CURLOPT_CUSTOMREQUEST, \"PUT\");"));
+        assert!(snippet.ends_with("  return (int)ret;\n}"));
+    }
+}
@@ -1,15 +1,32 @@
+use std::io::Read;
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
 use crate::database::db::DB;
+use crate::display::menuopts::CANCELLED_ERROR;
 
 use super::command::CmdOpts;
+use super::curl::Curl;
+use super::environment::expand_vars;
+use super::parser::quote_arg;
+
+// How often we check whether the download finished or was cancelled
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
 
-#[derive(Debug, Eq, Clone, PartialEq)]
+#[derive(Debug, Clone)]
 pub struct Wget {
     cmd: Vec<String>,
     rec_level: Option<usize>,
-    auth: Option<String>,
+    // user and password
+    auth: Option<(String, String)>,
     url: String,
     output: String,
     response: Option<String>,
+    cancel: Arc<AtomicBool>,
+    env_vars: Vec<(String, String)>,
 }
 
 impl CmdOpts for Wget {
@@ -19,25 +36,31 @@ impl CmdOpts for Wget {
 
     fn execute(&mut self, _db: Option<&mut Box<DB>>) -> Result<(), String> {
         self.build_command();
-        let command = std::process::Command::new("sh")
-            .arg("-c")
-            .args([self.cmd.join(" ")]) // Rest Of The Command Arguments
-            .output()
-            .expect("failed to execute process");
-        if command.status.success() {
-            self.response = Some(String::from_utf8(command.stdout).unwrap());
+        // no shell in between, so nothing in a variable's value can run as a command
+        let mut child = Command::new(&self.cmd[0])
+            .args(&self.cmd[1..])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| e.to_string())?;
+        // drain the pipes while we wait, so wget never blocks on a full pipe
+        let stdout = read_pipe(child.stdout.take());
+        let stderr = read_pipe(child.stderr.take());
+        let status = self.wait_or_cancel(&mut child)?;
+        let stdout = stdout.join().unwrap_or_default();
+        let stderr = stderr.join().unwrap_or_default();
+        if status.success() {
+            self.response = Some(String::from_utf8_lossy(&stdout).to_string());
             Ok(())
         } else {
-            Err(String::from_utf8(command.stderr).unwrap())
+            Err(String::from_utf8_lossy(&stderr).to_string())
         }
     }
 
 
     fn add_basic_auth(&mut self, info: &str) {
-        let mut split = info.split(':');
-        let usr = split.next().unwrap();
-        let pwd = split.next().unwrap();
-        self.auth = Some(format!("--user={} --password={}", usr, pwd));
+        let (usr, pwd) = info.split_once(':').unwrap_or((info, ""));
+        self.auth = Some((usr.to_string(), pwd.to_string()));
     }
 
 
@@ -71,22 +94,42 @@ impl CmdOpts for Wget {
     fn get_command_string(&mut self) -> String {
         let mut cmdstr = vec![String::from("wget")];
         if self.has_url() {
-            cmdstr.push(self.url.clone());
+            cmdstr.push(quote_arg(&self.url));
         }
         if self.has_rec() {
             cmdstr.push(format!("-r {}", self.rec_level.unwrap()));
         }
-        if let Some(ref auth) = self.auth {
-            cmdstr.push(auth.clone());
+        if let Some((ref usr, ref pwd)) = self.auth {
+            cmdstr.push(format!("--user={}", quote_arg(usr)));
+            cmdstr.push(format!("--password={}", quote_arg(pwd)));
         }
         if self.has_output() {
-            cmdstr.push(format!("-O {}", self.output));
+            cmdstr.push(format!("-O {}", quote_arg(&self.output)));
         }
-        return cmdstr.join(" ").trim().to_string();
+        cmdstr.join(" ").trim().to_string()
     }
     fn has_auth(&self) -> bool {
         self.auth.is_some()
     }
+    fn as_curl(&self) -> Option<&Curl<'_>> {
+        None
+    }
+    fn set_env_vars(&mut self, vars: Vec<(String, String)>) {
+        self.env_vars = vars;
+    }
+    fn set_cancel_flag(&mut self, cancel: Arc<AtomicBool>) {
+        self.cancel = cancel;
+    }
+}
+
+fn read_pipe<R: Read + Send + 'static>(pipe: Option<R>) -> JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut buf = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut buf);
+        }
+        buf
+    })
 }
 
 impl Default for Wget {
@@ -104,6 +147,24 @@ impl Wget {
             auth: None,
             output: String::new(),
             response: None,
+            cancel: Arc::new(AtomicBool::new(false)),
+            env_vars: Vec::new(),
+        }
+    }
+
+    // Waits for the download to finish, killing it if it gets cancelled first
+    fn wait_or_cancel(&self, child: &mut Child) -> Result<std::process::ExitStatus, String> {
+        loop {
+            if self.cancel.load(Ordering::Relaxed) {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(String::from(CANCELLED_ERROR));
+            }
+            match child.try_wait() {
+                Ok(Some(status)) => return Ok(status),
+                Ok(None) => thread::sleep(POLL_INTERVAL),
+                Err(e) => return Err(e.to_string()),
+            }
         }
     }
 
@@ -120,25 +181,30 @@ impl Wget {
         self.rec_level.is_some()
     }
 
-    // This just builds a vector of strings for executing in command.arg(str)
+    // This just builds a vector of strings for executing in command.arg(str), one argument each.
+    // The url goes last after `--`, so a value starting with `-` can't be read as an option
 
     pub fn build_command(&mut self) {
-        self.cmd.push(self.url.clone());
-        if self.has_rec() {
-            self.cmd.push("--depth".to_string());
-            if self.has_rec() {
-                self.cmd.push(self.rec_level.unwrap().to_string());
-            }
+        self.cmd = vec![String::from("wget")];
+        if let Some(level) = self.rec_level {
+            self.cmd.push(String::from("-r"));
+            self.cmd.push(String::from("-l"));
+            self.cmd.push(level.to_string());
         }
-        if let Some(ref auth) = self.auth {
-            self.cmd.push(auth.clone());
+        if let Some((ref usr, ref pwd)) = self.auth {
+            self.cmd.push(format!("--user={}", usr));
+            self.cmd.push(format!("--password={}", pwd));
         }
         if self.has_output() {
-            self.cmd.push(format!("-O {}", self.output));
+            self.cmd.push(String::from("-O"));
+            self.cmd.push(expand_vars(&self.output, &self.env_vars));
         }
+        self.cmd.push(String::from("--"));
+        self.cmd.push(expand_vars(&self.url, &self.env_vars));
     }
 }
 
+#[cfg(test)]
 mod tests {
     use super::*;
     use mockito::ServerGuard;
@@ -157,7 +223,7 @@ mod tests {
     #[test]
     fn test_new_wget() {
         let wget = Wget::new();
-        assert_eq!("wget", wget.cmd.get(0).unwrap());
+        assert_eq!("wget", wget.cmd.first().unwrap());
         assert_eq!("", wget.url);
         assert_eq!("", wget.output);
     }
@@ -225,4 +291,64 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_build_command_keeps_values_whole() {
+        let mut wget = Wget::new();
+        wget.set_url("{{base_url}}/file");
+        wget.set_outfile("{{out}}");
+        wget.set_env_vars(vec![
+            (
+                String::from("base_url"),
+                String::from("http://x.com; rm -rf ~"),
+            ),
+            (String::from("out"), String::from("a file $(id)")),
+        ]);
+        wget.build_command();
+        // building again starts over
+        wget.build_command();
+        assert_eq!(
+            wget.cmd,
+            vec![
+                "wget",
+                "-O",
+                "a file $(id)",
+                "--",
+                "http://x.com; rm -rf ~/file"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_build_command_url_not_an_option() {
+        let mut wget = Wget::new();
+        wget.set_url("{{target}}");
+        wget.set_rec_download_level(2);
+        wget.set_env_vars(vec![(
+            String::from("target"),
+            String::from("--output-document=/tmp/.bashrc"),
+        )]);
+        wget.build_command();
+        assert_eq!(
+            wget.cmd,
+            vec![
+                "wget",
+                "-r",
+                "-l",
+                "2",
+                "--",
+                "--output-document=/tmp/.bashrc"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_execute_cancelled() {
+        let mut wget = Wget::new();
+        wget.set_url("http://127.0.0.1:9");
+        wget.set_outfile("cancelled_output");
+        wget.set_cancel_flag(Arc::new(AtomicBool::new(true)));
+        assert_eq!(wget.execute(None), Err(String::from(CANCELLED_ERROR)));
+        let _ = std::fs::remove_file("cancelled_output");
+    }
+
 }
@@ -63,6 +63,7 @@ pub fn handle_authentication_screen<B: Backend>(app: &mut App, frame: &mut Frame
                     "Alert: NTLM Auth Enabled",
                 )));
             }
+            6 => app.goto_screen(Screen::KeyPicker),
             _ => {}
         }
     }
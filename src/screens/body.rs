@@ -0,0 +1,159 @@
+use super::default_rect;
+use super::render::render_header_paragraph;
+use crate::app::App;
+use crate::display::menuopts::{
+    FILTER_INPUT_TITLE, SEARCH_INPUT_TITLE, SEARCH_REGEX_TITLE, VIEW_BODY_PARAGRAPH,
+    VIEW_BODY_TITLE, VIEW_HEADERS_PARAGRAPH, VIEW_HEADERS_TITLE,
+};
+use crate::display::viewer::ViewInput;
+use crate::screens::screen::Screen;
+use tui::backend::Backend;
+use tui::layout::{Constraint, Direction, Layout, Rect};
+use tui::style::{Color, Modifier, Style};
+use tui::text::{Line, Span};
+use tui::widgets::{Block, Borders, Clear, Paragraph};
+use tui::Frame;
+
+pub fn handle_view_body_screen<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>) {
+    frame.render_widget(
+        render_header_paragraph(VIEW_BODY_PARAGRAPH, VIEW_BODY_TITLE, app.config.get_style()),
+        frame.size(),
+    );
+    if app.viewer.is_none() {
+        app.viewer = Some(app.get_body_viewer());
+    }
+    let name = match app.raw_body {
+        true => "Response Body (raw)",
+        false => "Response Body (pretty)",
+    };
+    render_viewer(app, frame, name);
+}
+
+pub fn handle_view_headers_screen<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>) {
+    frame.render_widget(
+        render_header_paragraph(
+            VIEW_HEADERS_PARAGRAPH,
+            VIEW_HEADERS_TITLE,
+            app.config.get_style(),
+        ),
+        frame.size(),
+    );
+    if app.viewer.is_none() {
+        app.viewer = Some(app.get_headers_viewer());
+    }
+    render_viewer(app, frame, "Response Headers");
+}
+
+fn render_viewer<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>, name: &str) {
+    let mut area = default_rect(frame.size());
+    let filtered = app.current_screen == Screen::ViewBody && !app.body_filter.trim().is_empty();
+    // the filter or search gets a line below the body while it's typed, an applied filter stays
+    if app.view_input.is_some() || filtered {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(3), Constraint::Length(3)])
+            .split(area);
+        area = chunks[0];
+        render_view_input(app, frame, chunks[1]);
+    }
+    let gutter_style = Style::default()
+        .fg(app.config.get_outline_color())
+        .bg(app.config.get_bg_color());
+    let viewer = app.viewer.as_mut().unwrap();
+    // the borders take a row at the top and bottom
+    viewer.set_height(area.height.saturating_sub(2) as usize);
+    let visible = viewer.visible_lines();
+    let width = viewer.line_count().to_string().len();
+    let lines: Vec<Line> = visible
+        .iter()
+        .skip(viewer.get_scroll())
+        .take(area.height as usize)
+        .map(|line| {
+            let mut spans = vec![Span::styled(
+                format!("{:>width$} ", line + 1, width = width),
+                gutter_style,
+            )];
+            let mut body = viewer.get_line(*line);
+            if *line == viewer.get_cursor() {
+                body.patch_style(Style::default().add_modifier(Modifier::REVERSED));
+            }
+            spans.extend(body.spans);
+            Line::from(spans)
+        })
+        .collect();
+    let mut title = String::from(name);
+    if filtered {
+        title.push_str(&format!(" | {}", app.body_filter.trim()));
+    }
+    title.push_str(&format!(
+        " [{}/{}]",
+        viewer.get_cursor() + 1,
+        viewer.line_count()
+    ));
+    if let Some(search) = viewer.get_search() {
+        title.push_str(&match search.current_match() {
+            Some(current) => format!(
+                " [{}: {}/{} matches]",
+                search.get_query(),
+                current,
+                search.match_count()
+            ),
+            None => format!(" [{}: no matches]", search.get_query()),
+        });
+    }
+    let paragraph = Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .style(app.config.get_style());
+    frame.render_widget(Clear, area);
+    frame.render_widget(paragraph, area);
+}
+
+fn render_view_input<B: Backend>(app: &App, frame: &mut Frame<'_, B>, area: Rect) {
+    let search_error = app
+        .viewer
+        .as_ref()
+        .and_then(|viewer| viewer.get_search_error());
+    let (prompt, title, error) = match app.view_input {
+        Some(ViewInput::Search { forward, regex }) => (
+            if forward { "/" } else { "?" },
+            if regex {
+                SEARCH_REGEX_TITLE
+            } else {
+                SEARCH_INPUT_TITLE
+            },
+            search_error,
+        ),
+        _ => ("", FILTER_INPUT_TITLE, app.filter_error.as_deref()),
+    };
+    let editing = app.view_input.is_some();
+    let value = match editing {
+        true => app.input.value(),
+        false => app.body_filter.as_str(),
+    };
+    // keep 2 for borders and 1 for cursor
+    let width = area.width.max(3 + prompt.len() as u16) - 3 - prompt.len() as u16;
+    let scroll = app.input.visual_scroll(width as usize);
+    // what's still being typed is often invalid, the last result stays until it's fixed
+    let block = match error {
+        Some(e) => Block::default()
+            .borders(Borders::ALL)
+            .title(e)
+            .title_style(app.config.get_style_error()),
+        None => Block::default().borders(Borders::ALL).title(title),
+    };
+    let input = Paragraph::new(format!("{}{}", prompt, value))
+        .style(match editing {
+            true => app.config.get_style().fg(Color::LightBlue),
+            false => app.config.get_style(),
+        })
+        .scroll((0, scroll as u16))
+        .block(block);
+    frame.render_widget(Clear, area);
+    frame.render_widget(input, area);
+    if editing {
+        frame.set_cursor(
+            area.x + ((app.input.visual_cursor()).max(scroll) - scroll + prompt.len()) as u16 + 1,
+            area.y + 1,
+        );
+    }
+}
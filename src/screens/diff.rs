@@ -0,0 +1,80 @@
+use super::default_rect;
+use super::render::render_header_paragraph;
+use crate::app::App;
+use crate::display::menuopts::{DIFF_PARAGRAPH, DIFF_TITLE};
+use crate::request::diff::{Change, DiffRow};
+use tui::backend::Backend;
+use tui::layout::{Constraint, Direction, Layout};
+use tui::style::{Color, Style};
+use tui::text::Line;
+use tui::widgets::{Block, Borders, Clear, Paragraph};
+use tui::Frame;
+
+pub fn handle_diff_screen<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>) {
+    frame.render_widget(
+        render_header_paragraph(DIFF_PARAGRAPH, DIFF_TITLE, app.config.get_style()),
+        frame.size(),
+    );
+    let style = app.config.get_style();
+    let Some(viewer) = app.diff.as_mut() else {
+        return;
+    };
+    let area = default_rect(frame.size());
+    let panes = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(area);
+    // the borders take a row at the top and bottom
+    viewer.set_height(area.height.saturating_sub(2) as usize);
+    let (left, right) = viewer.get_labels();
+    let summary = match viewer.change_count() {
+        0 => String::from("identical"),
+        count => format!("{} changed lines", count),
+    };
+    let titles = [
+        format!("{} diff | {}", viewer.get_mode(), left),
+        format!("{} [{}]", right, summary),
+    ];
+    for (side, pane) in panes.iter().enumerate() {
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(titles[side].as_str());
+        let paragraph = match viewer.get_error() {
+            Some(e) => Paragraph::new(e).style(app.config.get_style_error()),
+            None => Paragraph::new(
+                viewer
+                    .get_rows()
+                    .iter()
+                    .skip(viewer.get_scroll())
+                    .take(area.height as usize)
+                    .map(|row| diff_line(row, side == 0, style))
+                    .collect::<Vec<Line>>(),
+            )
+            .style(style),
+        };
+        frame.render_widget(Clear, *pane);
+        frame.render_widget(paragraph.block(block), *pane);
+    }
+}
+
+// Removed lines are red on the left, added ones green on the right and changed ones yellow on both
+fn diff_line(row: &DiffRow, left: bool, style: Style) -> Line<'static> {
+    let line = match left {
+        true => row.left.as_ref(),
+        false => row.right.as_ref(),
+    };
+    let Some(line) = line else {
+        return Line::styled("", style.fg(Color::DarkGray));
+    };
+    let (marker, color) = match (row.change, left) {
+        (Change::Same, _) => (" ", None),
+        (Change::Changed, _) => ("~", Some(Color::Yellow)),
+        (_, true) => ("-", Some(Color::Red)),
+        (_, false) => ("+", Some(Color::Green)),
+    };
+    let style = match color {
+        Some(color) => style.fg(color),
+        None => style,
+    };
+    Line::styled(format!("{} {}", marker, line), style)
+}
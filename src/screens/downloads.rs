@@ -34,16 +34,7 @@ pub fn handle_downloads_screen<B: Backend>(app: &mut App, frame: &mut Frame<'_,
             app.selected = None;
             // Execute command
         }
-        Some(3) => match app.execute_command() {
-            Ok(_) => {
-                let response = app.command.as_ref().unwrap().get_response();
-                app.response = Some(response.clone());
-                app.goto_screen(Screen::Response(response));
-            }
-            Err(e) => {
-                app.goto_screen(Screen::Error(e));
-            }
-        },
+        Some(3) => app.spawn_command(),
         _ => {}
     };
 }
@@ -0,0 +1,56 @@
+use super::render::handle_screen_defaults;
+use crate::app::App;
+use crate::display::inputopt::InputOpt;
+use crate::screens::screen::Screen;
+use tui::backend::Backend;
+use tui::Frame;
+use tui_input::Input;
+
+pub fn handle_environments_screen<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>) {
+    handle_screen_defaults(app, frame);
+    // if we select an environment, open options
+    if let Some(num) = app.selected {
+        app.goto_screen(Screen::EnvMenu(num));
+    }
+}
+
+pub fn handle_env_menu_screen<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>, index: usize) {
+    handle_screen_defaults(app, frame);
+    let Some(env) = app
+        .get_environments()
+        .unwrap_or_default()
+        .get(index)
+        .cloned()
+    else {
+        app.go_back_screen();
+        return;
+    };
+    match app.selected {
+        // Switch to this environment
+        Some(0) => match app.switch_environment(env.get_id()) {
+            Ok(_) => app.goto_screen(Screen::Environments),
+            Err(e) => app.goto_screen(Screen::Error(e.to_string())),
+        },
+        // Edit variables
+        Some(1) => app.goto_screen(Screen::EnvVars(env.get_id())),
+        // Delete
+        Some(2) => match app.delete_environment(env.get_id()) {
+            Ok(_) => app.goto_screen(Screen::Environments),
+            Err(e) => app.goto_screen(Screen::Error(e.to_string())),
+        },
+        // Cancel
+        Some(3) => app.go_back_screen(),
+        _ => {}
+    }
+}
+
+pub fn handle_env_vars_screen<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>, id: i32) {
+    handle_screen_defaults(app, frame);
+    // selecting a variable lets you edit it, starting from its current value
+    if let Some(num) = app.selected {
+        if let Some((key, value)) = app.db.get_env_vars(id).unwrap_or_default().get(num) {
+            app.input = Input::new(format!("{}={}", key, value));
+        }
+        app.goto_screen(Screen::InputMenu(InputOpt::EnvVar(id)));
+    }
+}
@@ -0,0 +1,39 @@
+use super::render::render_header_paragraph;
+use crate::app::App;
+use crate::display::menuopts::{DEFAULT_MENU_PARAGRAPH, EXECUTING_TITLE, SPINNER_FRAMES};
+use crate::screens::default_rect;
+use tui::backend::Backend;
+use tui::layout::Alignment;
+use tui::text::Text;
+use tui::widgets::{Block, Borders, Paragraph, Wrap};
+use tui::Frame;
+
+pub fn handle_executing_screen<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>) {
+    frame.render_widget(
+        render_header_paragraph(
+            DEFAULT_MENU_PARAGRAPH,
+            EXECUTING_TITLE,
+            app.config.get_style(),
+        ),
+        frame.size(),
+    );
+    let Some(executing) = app.executing.as_ref() else {
+        return;
+    };
+    // the spinner moves once per tick
+    let frame_idx = (executing.elapsed.as_millis() / 250) as usize % SPINNER_FRAMES.len();
+    let text = format!(
+        "{}\n\n{} {:.1}s\n\nPress Esc or Ctrl-X to cancel",
+        executing.url,
+        SPINNER_FRAMES[frame_idx],
+        executing.elapsed.as_secs_f64()
+    );
+    frame.render_widget(
+        Paragraph::new(Text::from(text))
+            .block(Block::default().borders(Borders::ALL).title("Executing"))
+            .style(app.config.get_style())
+            .alignment(Alignment::Center)
+            .wrap(Wrap { trim: true }),
+        default_rect(frame.size()),
+    );
+}
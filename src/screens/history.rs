@@ -0,0 +1,60 @@
+use super::render::handle_screen_defaults;
+use crate::app::App;
+use crate::screens::screen::Screen;
+use tui::backend::Backend;
+use tui::Frame;
+
+pub fn handle_history_screen<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>, query: &str) {
+    handle_screen_defaults(app, frame);
+    // the list is filtered by the query, so look the entry up the same way
+    if let Some(num) = app.selected {
+        match app.get_history(query).unwrap_or_default().get(num) {
+            Some(entry) => app.goto_screen(Screen::HistoryMenu(entry.get_id())),
+            None => app.selected = None,
+        }
+    }
+}
+
+pub fn handle_history_menu_screen<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>, id: i32) {
+    handle_screen_defaults(app, frame);
+    match app.selected {
+        // Execute again
+        Some(0) => {
+            if let Err(e) = app.rerun_history_entry(id) {
+                app.goto_screen(Screen::Error(e));
+            }
+        }
+        // Save as a command
+        Some(1) => match app.save_history_entry(id) {
+            Ok(_) => app.goto_screen(Screen::Success),
+            Err(e) => app.goto_screen(Screen::Error(e)),
+        },
+        // Compare with another response
+        Some(2) => match app.compare_history_entry(id) {
+            Ok(_) => app.goto_screen(Screen::DiffPicker),
+            Err(e) => app.goto_screen(Screen::Error(e)),
+        },
+        // Delete
+        Some(3) => match app.delete_history_entry(id) {
+            Ok(_) => app.go_back_screen(),
+            Err(e) => app.goto_screen(Screen::Error(e.to_string())),
+        },
+        // Cancel
+        Some(4) => app.go_back_screen(),
+        _ => {}
+    }
+}
+
+// The other response to compare with the one picked first
+pub fn handle_diff_picker_screen<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>) {
+    handle_screen_defaults(app, frame);
+    if let Some(num) = app.selected {
+        match app.get_history("").unwrap_or_default().get(num) {
+            Some(entry) => match app.diff_with_history_entry(entry) {
+                Ok(_) => app.goto_screen(Screen::Diff),
+                Err(e) => app.goto_screen(Screen::Error(e)),
+            },
+            None => app.selected = None,
+        }
+    }
+}
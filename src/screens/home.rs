@@ -1,5 +1,6 @@
 use super::render::handle_screen_defaults;
 use crate::app::App;
+use crate::display::inputopt::InputOpt;
 use crate::request::command::Cmd;
 use crate::request::wget::Wget;
 use crate::screens::screen::Screen;
@@ -22,6 +23,10 @@ pub fn handle_home_screen<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>) {
             }
             2 => app.goto_screen(Screen::SavedKeys),
             3 => app.goto_screen(Screen::SavedCommands),
+            4 => app.goto_screen(Screen::ImportCurl),
+            5 => app.goto_screen(Screen::Environments),
+            6 => app.goto_screen(Screen::History(String::new())),
+            7 => app.goto_screen(Screen::InputMenu(InputOpt::OpenHttpFile)),
             _ => {}
         }
     }
@@ -0,0 +1,93 @@
+use super::default_rect;
+use super::render::handle_screen_defaults;
+use crate::app::App;
+use crate::display::inputopt::InputOpt;
+use crate::display::menuopts::HTTP_REQUEST_MENU_OPTIONS;
+use crate::request::httpfile::escape_body;
+use crate::screens::screen::Screen;
+use tui::backend::Backend;
+use tui::prelude::{Constraint, Direction, Layout};
+use tui::text::Text;
+use tui::widgets::{Block, Borders, Paragraph};
+use tui::Frame;
+use tui_input::Input;
+
+pub fn handle_http_file_screen<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>) {
+    handle_screen_defaults(app, frame);
+    if let Some(num) = app.selected {
+        if num < app.get_http_requests().len() {
+            app.goto_screen(Screen::HttpRequestMenu(num));
+        } else {
+            app.selected = None;
+        }
+    }
+}
+
+pub fn handle_http_request_menu_screen<B: Backend>(
+    app: &mut App,
+    frame: &mut Frame<'_, B>,
+    index: usize,
+) {
+    let request = match app.get_http_request(index) {
+        Ok(request) => request,
+        Err(e) => {
+            app.goto_screen(Screen::Error(e));
+            return;
+        }
+    };
+    handle_screen_defaults(app, frame);
+    // Preview the request as it's written in the file, below the menu options
+    let preview = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(
+            [
+                Constraint::Length(HTTP_REQUEST_MENU_OPTIONS.len() as u16 * 2 + 1),
+                Constraint::Min(1),
+            ]
+            .as_ref(),
+        )
+        .split(default_rect(frame.size()))[1];
+    frame.render_widget(
+        Paragraph::new(Text::from(request.to_text()))
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(request.get_label().to_string()),
+            )
+            .style(app.config.get_style()),
+        preview,
+    );
+    match app.selected {
+        // Execute
+        Some(0) => {
+            if let Err(e) = app.execute_http_request(index) {
+                app.goto_screen(Screen::Error(e));
+            }
+        }
+        // Edit the request line
+        Some(1) => {
+            app.input = Input::new(format!("{} {}", request.method, request.url));
+            app.goto_screen(Screen::InputMenu(InputOpt::HttpRequestLine(index)));
+        }
+        // Add or change a header
+        Some(2) => app.goto_screen(Screen::InputMenu(InputOpt::HttpHeader(index))),
+        // Edit the body
+        Some(3) => {
+            app.input = Input::new(escape_body(request.body.as_deref().unwrap_or_default()));
+            app.goto_screen(Screen::InputMenu(InputOpt::HttpBody(index)));
+        }
+        // Rename
+        Some(4) => {
+            app.input = Input::new(request.name.clone());
+            app.goto_screen(Screen::InputMenu(InputOpt::HttpRequestName(index)));
+        }
+        // Save as a command
+        Some(5) => match app.save_http_request(index) {
+            Ok(_) => app.goto_screen(Screen::Success),
+            Err(e) => app.goto_screen(Screen::Error(e)),
+        },
+        // Cancel
+        Some(6) => app.goto_screen(Screen::HttpFile),
+        _ => {}
+    }
+}
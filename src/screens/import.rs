@@ -0,0 +1,106 @@
+use super::default_rect;
+use super::render::handle_screen_defaults;
+use crate::app::App;
+use crate::display::inputopt::InputOpt;
+use crate::display::menuopts::{BUNDLE_IMPORT_MENU_OPTIONS, CLIPBOARD_EMPTY_ERROR};
+use crate::request::importer::ImportFormat;
+use crate::screens::screen::Screen;
+use tui::backend::Backend;
+use tui::prelude::{Constraint, Direction, Layout};
+use tui::text::Text;
+use tui::widgets::{Block, Borders, Paragraph, Wrap};
+use tui::Frame;
+
+pub fn handle_import_screen<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>) {
+    handle_screen_defaults(app, frame);
+    match app.selected {
+        // Import from clipboard
+        Some(0) => match app.paste_from_clipboard() {
+            Ok(text) if !text.trim().is_empty() => import_curl_command(app, &text),
+            Ok(_) => app.goto_screen(Screen::Error(String::from(CLIPBOARD_EMPTY_ERROR))),
+            Err(e) => app.goto_screen(Screen::Error(e)),
+        },
+        // Type or paste the command
+        Some(1) => app.goto_screen(Screen::InputMenu(InputOpt::ImportCurl)),
+        // Postman collection file
+        Some(2) => app.goto_screen(Screen::InputMenu(InputOpt::ImportCollection(
+            ImportFormat::Postman,
+        ))),
+        // OpenAPI or Swagger spec
+        Some(3) => app.goto_screen(Screen::InputMenu(InputOpt::ImportCollection(
+            ImportFormat::OpenApi,
+        ))),
+        // HAR file from a browser's dev tools
+        Some(4) => app.goto_screen(Screen::InputMenu(InputOpt::ImportCollection(
+            ImportFormat::Har,
+        ))),
+        // Bundle of saved commands, keys and environments
+        Some(5) => app.goto_screen(Screen::InputMenu(InputOpt::ImportBundle)),
+        Some(6) => app.goto_screen(Screen::InputMenu(InputOpt::ExportBundle)),
+        // Cancel
+        Some(7) => app.goto_screen(Screen::Home),
+        _ => {}
+    }
+}
+
+pub fn handle_import_bundle_screen<B: Backend>(
+    app: &mut App,
+    frame: &mut Frame<'_, B>,
+    preview: &str,
+) {
+    handle_screen_defaults(app, frame);
+    let area = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(
+            [
+                Constraint::Length(BUNDLE_IMPORT_MENU_OPTIONS.len() as u16 * 2 + 1),
+                Constraint::Min(1),
+            ]
+            .as_ref(),
+        )
+        .split(default_rect(frame.size()))[1];
+    frame.render_widget(
+        Paragraph::new(Text::from(preview))
+            .block(Block::default().borders(Borders::ALL).title("Preview"))
+            .style(app.config.get_style())
+            .wrap(Wrap { trim: false }),
+        area,
+    );
+    match app.selected {
+        // Import
+        Some(0) => match app.import_bundle() {
+            Ok(summary) => app.goto_screen(Screen::Error(format!("Alert: Imported {}", summary))),
+            Err(e) => app.goto_screen(Screen::Error(e)),
+        },
+        // Cancel
+        Some(1) => {
+            app.bundle = None;
+            app.goto_screen(Screen::ImportCurl);
+        }
+        _ => {}
+    }
+}
+
+// Shared by the clipboard import and the input screen
+pub fn import_curl_command(app: &mut App, cmd: &str) {
+    match app.import_curl_command(cmd) {
+        Ok(ignored) if ignored.is_empty() => app.goto_screen(Screen::RequestMenu(String::new())),
+        Ok(ignored) => app.goto_screen(Screen::RequestMenu(format!(
+            "Alert: Command imported, these options are not supported and were ignored: {}",
+            ignored.join(" ")
+        ))),
+        Err(e) => app.goto_screen(Screen::Error(e)),
+    }
+}
+
+pub fn import_collection(app: &mut App, format: ImportFormat, path: &str) {
+    match app.import_collection(format, path) {
+        Ok((_, ignored)) if ignored.is_empty() => app.goto_screen(Screen::SavedCommands),
+        Ok((count, ignored)) => app.goto_screen(Screen::Error(format!(
+            "Alert: Imported {} commands, these items could not be translated: {}",
+            count,
+            ignored.join(", ")
+        ))),
+        Err(e) => app.goto_screen(Screen::Error(e)),
+    }
+}
@@ -1,13 +1,24 @@
 use crate::app::App;
 use crate::display::menuopts::{
-    CERT_ERROR, HEADER_ERROR, INPUT_OPT_AUTH_ANY, INPUT_OPT_AUTH_BASIC, INPUT_OPT_AUTH_BEARER,
-    INPUT_OPT_BASIC, INPUT_OPT_HEADERS, INPUT_OPT_REC_DOWNLOAD, PARSE_INT_ERROR, SOCKET_ERROR,
-    UPLOAD_FILEPATH_ERROR,
+    CERT_ERROR, HEADER_ERROR, HTTP_REQUEST_LINE_ERROR, INPUT_OPT_ASSERTION, INPUT_OPT_AUTH_ANY,
+    INPUT_OPT_AUTH_BASIC, INPUT_OPT_AUTH_BEARER, INPUT_OPT_BASIC, INPUT_OPT_COLLECTION_NAME,
+    INPUT_OPT_COMMAND_DESCRIPTION, INPUT_OPT_COMMAND_NAME, INPUT_OPT_CONFIRM_PASSPHRASE,
+    INPUT_OPT_ENV_NAME, INPUT_OPT_ENV_VAR, INPUT_OPT_EXPORT_BUNDLE, INPUT_OPT_EXPORT_HAR,
+    INPUT_OPT_HEADERS, INPUT_OPT_HISTORY_SEARCH, INPUT_OPT_HTTP_BODY, INPUT_OPT_HTTP_HEADER,
+    INPUT_OPT_HTTP_NAME, INPUT_OPT_HTTP_REQUEST_LINE, INPUT_OPT_IMPORT_BUNDLE,
+    INPUT_OPT_IMPORT_CURL, INPUT_OPT_IMPORT_HAR, INPUT_OPT_IMPORT_OPENAPI,
+    INPUT_OPT_IMPORT_POSTMAN, INPUT_OPT_KEY_EXPIRY, INPUT_OPT_KEY_HOST, INPUT_OPT_KEY_LABEL,
+    INPUT_OPT_KEY_TYPE, INPUT_OPT_NEW_PASSPHRASE, INPUT_OPT_OPEN_HTTP_FILE, INPUT_OPT_REC_DOWNLOAD,
+    INPUT_OPT_SNIPPET_FILE, INPUT_OPT_UNLOCK, PARSE_INT_ERROR, PASSPHRASE_MISMATCH_ERROR,
+    SOCKET_ERROR, UPLOAD_FILEPATH_ERROR,
 };
 use crate::display::AppOptions;
 use crate::request::command::CmdType;
 use crate::request::curl::AuthKind;
+use crate::request::httpfile::{parse_request_line, unescape_body};
+use crate::request::importer::ImportFormat;
 use crate::screens::auth::AuthType;
+use crate::screens::import::{import_collection, import_curl_command};
 use crate::screens::Screen;
 use crate::{app::InputMode, display::inputopt::InputOpt};
 use std::path::Path;
@@ -32,6 +43,35 @@ pub fn get_input_prompt(opt: InputOpt) -> Text<'static> {
         InputOpt::RequestBody => Text::from("Enter a body for your request and press Enter"),
         InputOpt::Headers => Text::from(Line::from(INPUT_OPT_HEADERS)),
         InputOpt::RecursiveDownload => Text::from(INPUT_OPT_REC_DOWNLOAD),
+        InputOpt::ImportCurl => Text::from(INPUT_OPT_IMPORT_CURL),
+        InputOpt::ImportCollection(ImportFormat::Postman) => Text::from(INPUT_OPT_IMPORT_POSTMAN),
+        InputOpt::ImportCollection(ImportFormat::OpenApi) => Text::from(INPUT_OPT_IMPORT_OPENAPI),
+        InputOpt::ImportCollection(ImportFormat::Har) => Text::from(INPUT_OPT_IMPORT_HAR),
+        InputOpt::ExportHar(_) => Text::from(INPUT_OPT_EXPORT_HAR),
+        InputOpt::ImportBundle => Text::from(INPUT_OPT_IMPORT_BUNDLE),
+        InputOpt::ExportBundle => Text::from(INPUT_OPT_EXPORT_BUNDLE),
+        InputOpt::SnippetFile(_) => Text::from(INPUT_OPT_SNIPPET_FILE),
+        InputOpt::EnvName => Text::from(INPUT_OPT_ENV_NAME),
+        InputOpt::EnvVar(_) => Text::from(INPUT_OPT_ENV_VAR),
+        InputOpt::HistorySearch => Text::from(INPUT_OPT_HISTORY_SEARCH),
+        InputOpt::NewCollection(_) | InputOpt::RenameCollection(_) => {
+            Text::from(INPUT_OPT_COLLECTION_NAME)
+        }
+        InputOpt::RenameCommand(_) => Text::from(INPUT_OPT_COMMAND_NAME),
+        InputOpt::CommandDescription(_) => Text::from(INPUT_OPT_COMMAND_DESCRIPTION),
+        InputOpt::KeyLabel(_) => Text::from(INPUT_OPT_KEY_LABEL),
+        InputOpt::KeyHost(_) => Text::from(INPUT_OPT_KEY_HOST),
+        InputOpt::KeyType(_) => Text::from(INPUT_OPT_KEY_TYPE),
+        InputOpt::KeyExpiry(_) => Text::from(INPUT_OPT_KEY_EXPIRY),
+        InputOpt::Assertion(_, _) => Text::from(INPUT_OPT_ASSERTION),
+        InputOpt::OpenHttpFile => Text::from(INPUT_OPT_OPEN_HTTP_FILE),
+        InputOpt::HttpRequestLine(_) => Text::from(INPUT_OPT_HTTP_REQUEST_LINE),
+        InputOpt::HttpHeader(_) => Text::from(INPUT_OPT_HTTP_HEADER),
+        InputOpt::HttpBody(_) => Text::from(INPUT_OPT_HTTP_BODY),
+        InputOpt::HttpRequestName(_) => Text::from(INPUT_OPT_HTTP_NAME),
+        InputOpt::Unlock => Text::from(INPUT_OPT_UNLOCK),
+        InputOpt::NewPassphrase => Text::from(INPUT_OPT_NEW_PASSPHRASE),
+        InputOpt::ConfirmPassphrase(_) => Text::from(INPUT_OPT_CONFIRM_PASSPHRASE),
         InputOpt::Auth(auth) => match auth {
             AuthType::Basic => Text::from(INPUT_OPT_AUTH_BASIC),
             AuthType::Bearer => Text::from(INPUT_OPT_AUTH_BEARER),
@@ -85,7 +125,14 @@ pub fn handle_default_input_screen<B: Backend>(
 
     let width = chunks[0].width.max(3) - 3; // keep 2 for borders and 1 for cursor
     let scroll = app.input.visual_scroll(width as usize);
-    let input = Paragraph::new(app.input.value())
+    // never show a passphrase on screen
+    let value = match opt {
+        InputOpt::Unlock | InputOpt::NewPassphrase | InputOpt::ConfirmPassphrase(_) => {
+            "*".repeat(app.input.value().chars().count())
+        }
+        _ => app.input.value().to_string(),
+    };
+    let input = Paragraph::new(value)
         .style(match app.input_mode {
             InputMode::Normal => Style::default(),
             InputMode::Editing => Style::default().fg(Color::LightBlue),
@@ -224,10 +271,147 @@ pub fn parse_input(message: String, opt: InputOpt, app: &mut App) {
         InputOpt::Auth(auth) => {
             parse_auth(auth, app, &message);
         }
+        InputOpt::ImportCurl => import_curl_command(app, &message),
+        InputOpt::ImportCollection(format) => import_collection(app, format, &message),
+        InputOpt::ImportBundle => match app.preview_bundle(&message) {
+            Ok(preview) => app.goto_screen(Screen::ImportBundle(preview)),
+            Err(e) => app.goto_screen(Screen::Error(e)),
+        },
+        InputOpt::ExportBundle => match app.export_bundle(&message) {
+            Ok(path) => app.goto_screen(Screen::Error(format!(
+                "Alert: Exported your saved commands, keys and environments to {}",
+                path
+            ))),
+            Err(e) => app.goto_screen(Screen::Error(e)),
+        },
+        InputOpt::ExportHar(export) => match app.export_har(&export, &message) {
+            Ok((count, path)) => app.goto_screen(Screen::Error(format!(
+                "Alert: Exported {} requests to {}",
+                count, path
+            ))),
+            Err(e) => app.goto_screen(Screen::Error(e)),
+        },
+        InputOpt::SnippetFile(lang) => match app.write_snippet(lang, &message) {
+            Ok(_) => app.goto_screen(Screen::Success),
+            Err(e) => app.goto_screen(Screen::Error(e)),
+        },
+        InputOpt::EnvName => match app.add_environment(&message) {
+            Ok(_) => app.goto_screen(Screen::Environments),
+            Err(e) => app.goto_screen(Screen::Error(e)),
+        },
+        InputOpt::EnvVar(id) => match app.set_env_var(id, &message) {
+            Ok(_) => app.goto_screen(Screen::EnvVars(id)),
+            Err(e) => app.goto_screen(Screen::Error(e)),
+        },
+        InputOpt::HistorySearch => app.goto_screen(Screen::History(message.trim().to_string())),
+        InputOpt::NewCollection(parent) => match app.add_collection(&message, parent) {
+            Ok(_) => app.goto_screen(Screen::SavedCommands),
+            Err(e) => app.goto_screen(Screen::Error(e)),
+        },
+        InputOpt::RenameCollection(id) => match app.rename_collection(id, &message) {
+            Ok(_) => app.goto_screen(Screen::SavedCommands),
+            Err(e) => app.goto_screen(Screen::Error(e)),
+        },
+        InputOpt::RenameCommand(id) => match app.rename_saved_command(id, &message) {
+            Ok(_) => app.goto_screen(Screen::SavedCommands),
+            Err(e) => app.goto_screen(Screen::Error(e)),
+        },
+        InputOpt::Assertion(id, index) => match app.set_assertion(id, index, &message) {
+            Ok(_) => app.goto_screen(Screen::Assertions(id)),
+            Err(e) => app.goto_screen(Screen::Error(e)),
+        },
+        InputOpt::CommandDescription(id) => match app.set_saved_command_description(id, &message) {
+            Ok(_) => app.goto_screen(Screen::SavedCommands),
+            Err(e) => app.goto_screen(Screen::Error(e)),
+        },
+        InputOpt::KeyLabel(id) => match app.set_key_label(id, &message) {
+            Ok(_) => app.goto_screen(Screen::SavedKeys),
+            Err(e) => app.goto_screen(Screen::Error(e)),
+        },
+        InputOpt::KeyHost(id) => match app.set_key_host(id, &message) {
+            Ok(_) => app.goto_screen(Screen::SavedKeys),
+            Err(e) => app.goto_screen(Screen::Error(e)),
+        },
+        InputOpt::KeyType(id) => match app.set_key_kind(id, &message) {
+            Ok(_) => app.goto_screen(Screen::SavedKeys),
+            Err(e) => app.goto_screen(Screen::Error(e)),
+        },
+        InputOpt::KeyExpiry(id) => match app.set_key_expiry(id, &message) {
+            Ok(_) => app.goto_screen(Screen::SavedKeys),
+            Err(e) => app.goto_screen(Screen::Error(e)),
+        },
+        InputOpt::Unlock => match app.unlock(&message) {
+            // back to wherever we were asked for it
+            Ok(_) => app.go_back_screen(),
+            Err(e) => app.goto_screen(Screen::Error(e)),
+        },
+        InputOpt::NewPassphrase if message.is_empty() => match app.remove_passphrase() {
+            Ok(_) => app.goto_screen(Screen::Error(String::from(
+                "Alert: The passphrase was removed, your keys and saved auth are stored in plaintext",
+            ))),
+            Err(e) => app.goto_screen(Screen::Error(e)),
+        },
+        InputOpt::NewPassphrase => {
+            app.goto_screen(Screen::InputMenu(InputOpt::ConfirmPassphrase(message)))
+        }
+        InputOpt::ConfirmPassphrase(passphrase) => {
+            if passphrase != message {
+                app.goto_screen(Screen::Error(String::from(PASSPHRASE_MISMATCH_ERROR)));
+                return;
+            }
+            match app.set_passphrase(&message) {
+                Ok(_) => app.goto_screen(Screen::Error(String::from(
+                    "Alert: Your keys and saved auth are encrypted, CuTE will ask for the passphrase when it starts",
+                ))),
+                Err(e) => app.goto_screen(Screen::Error(e)),
+            }
+        }
+        InputOpt::OpenHttpFile => match app.open_http_file(&message) {
+            Ok(_) => app.goto_screen(Screen::HttpFile),
+            Err(e) => app.goto_screen(Screen::Error(e)),
+        },
+        InputOpt::HttpRequestLine(index) => {
+            let result = app.edit_http_request(index, |request| {
+                let (method, url, version) = parse_request_line(&message);
+                if url.is_empty() {
+                    return Err(String::from(HTTP_REQUEST_LINE_ERROR));
+                }
+                (request.method, request.url) = (method, url);
+                request.version = version.or(request.version.take());
+                Ok(())
+            });
+            show_http_edit_result(app, index, result);
+        }
+        InputOpt::HttpHeader(index) => {
+            let result = app.edit_http_request(index, |request| request.set_header(&message));
+            show_http_edit_result(app, index, result);
+        }
+        InputOpt::HttpBody(index) => {
+            let result = app.edit_http_request(index, |request| {
+                request.body = Some(unescape_body(message.trim())).filter(|body| !body.is_empty());
+                Ok(())
+            });
+            show_http_edit_result(app, index, result);
+        }
+        InputOpt::HttpRequestName(index) => {
+            let result = app.edit_http_request(index, |request| {
+                request.name = message.trim().to_string();
+                Ok(())
+            });
+            show_http_edit_result(app, index, result);
+        }
         _ => {}
     }
 }
 
+// Edits are written to the file right away, then we go back to the request
+fn show_http_edit_result(app: &mut App, index: usize, result: Result<(), String>) {
+    match result {
+        Ok(_) => app.goto_screen(Screen::HttpRequestMenu(index)),
+        Err(e) => app.goto_screen(Screen::Error(e)),
+    }
+}
+
 pub fn render_input_with_prompt<B: Backend>(frame: &mut Frame<'_, B>, prompt: Text) {
     // Render the input with the provided prompt
     let chunks = Layout::default()
@@ -1,3 +1,4 @@
+#[allow(clippy::module_inception)]
 pub mod input;
 
 pub mod request_body_input;
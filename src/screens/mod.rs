@@ -15,6 +15,12 @@ pub mod request;
 // Response Screen
 pub mod response;
 
+// Response Body Viewer Screen
+pub mod body;
+
+// Response Diff Screen
+pub mod diff;
+
 pub mod more_flags;
 
 // All Input Type Screens
@@ -23,6 +29,27 @@ pub mod input;
 // Auth Screen
 pub mod auth;
 
+// Import cURL Command Screen
+pub mod import;
+
+// Code Snippet Screens
+pub mod snippet;
+
+// Running Command Screen
+pub mod executing;
+
+// Environment Screens
+pub mod environments;
+
+// Request History Screens
+pub mod history;
+
+// Assertion And Test Run Screens
+pub mod tests;
+
+// .http Request File Screens
+pub mod http_file;
+
 pub mod render;
 
 pub mod saved_keys;
@@ -1,28 +1,48 @@
 use crate::display::inputopt::InputOpt;
 use crate::display::menuopts::{
     API_KEY_PARAGRAPH, API_KEY_TITLE, AUTH_MENU_TITLE, DEFAULT_MENU_PARAGRAPH, DEFAULT_MENU_TITLE,
-    DOWNLOAD_MENU_TITLE, ERROR_MENU_TITLE, INPUT_MENU_TITLE, SAVED_COMMANDS_TITLE,
-    SUCCESS_MENU_TITLE, VIEW_BODY_TITLE,
+    DIFF_PICKER_PARAGRAPH, DIFF_TITLE, DOWNLOAD_MENU_TITLE, ENVIRONMENTS_PARAGRAPH,
+    ENVIRONMENTS_TITLE, ERROR_MENU_TITLE, HISTORY_PARAGRAPH, HISTORY_TITLE, HTTP_FILE_PARAGRAPH,
+    HTTP_FILE_TITLE, IMPORT_MENU_TITLE, INPUT_MENU_TITLE, SAVED_COMMANDS_TITLE, SNIPPET_MENU_TITLE,
+    SUCCESS_MENU_TITLE, TEST_RESULTS_TITLE, VIEW_BODY_TITLE,
 };
 use crate::display::AppOptions;
 use crate::screens::input::input::handle_default_input_screen;
 
 use super::auth::handle_authentication_screen;
+use super::body::{handle_view_body_screen, handle_view_headers_screen};
+use super::diff::handle_diff_screen;
 use super::downloads::handle_downloads_screen;
+use super::environments::{
+    handle_env_menu_screen, handle_env_vars_screen, handle_environments_screen,
+};
+use super::executing::handle_executing_screen;
+use super::history::{
+    handle_diff_picker_screen, handle_history_menu_screen, handle_history_screen,
+};
 use super::home::handle_home_screen;
+use super::http_file::{handle_http_file_screen, handle_http_request_menu_screen};
+use super::import::{handle_import_bundle_screen, handle_import_screen};
 use super::input::request_body_input::handle_req_body_input_screen;
 use super::method::handle_method_select_screen;
 use super::more_flags::handle_more_flags_screen;
 use super::request::handle_request_menu_screen;
 use super::response::handle_response_screen;
-use super::saved_commands::{handle_alert_menu, handle_saved_commands_screen};
-use super::saved_keys::{handle_key_menu, handle_saved_keys_screen};
+use super::saved_commands::{
+    handle_alert_menu, handle_collection_menu_screen, handle_move_to_screen,
+    handle_saved_commands_screen,
+};
+use super::saved_keys::{
+    handle_key_menu, handle_key_picker_screen, handle_saved_keys_screen, saved_key_items,
+};
+use super::snippet::{handle_snippet_export_screen, handle_snippets_screen};
+use super::tests::{handle_assertions_screen, handle_test_results_screen};
 use crate::screens::error::handle_error_screen;
 use crate::{app::App, display::menuopts::SAVED_COMMANDS_PARAGRAPH};
 use tui::style::Stylize;
 use tui::text::Line;
-use tui::widgets::ListState;
 use tui::widgets::{Block, Borders};
+use tui::widgets::{ListItem, ListState};
 use tui::{
     backend::Backend,
     layout::Alignment,
@@ -32,7 +52,7 @@ use tui::{
     Frame,
 };
 
-use super::{centered_rect, default_rect, small_rect, Screen};
+use super::{centered_rect, small_rect, Screen};
 
 /// Renders the user interface widgets.
 pub fn render<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>) {
@@ -57,6 +77,11 @@ pub fn render<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>) {
         let area = small_rect(frame.size());
         let opts = app.opts.clone();
         let display_opts = handle_display_options(&opts);
+        // let the user know which environment their {{variables}} come from
+        let title = match app.get_active_environment_name() {
+            Some(name) => format!("-Request Options- [{}]", name),
+            None => String::from("-Request Options-"),
+        };
         frame.render_widget(
             Paragraph::new(display_opts)
                 .block(
@@ -64,7 +89,7 @@ pub fn render<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>) {
                         .borders(Borders::ALL)
                         .border_type(BorderType::Double)
                         .title_style(Style::new().bold().italic())
-                        .title("-Request Options-"),
+                        .title(title),
                 )
                 .fg(app.config.get_fg_color())
                 .bg(app.config.get_bg_color())
@@ -93,28 +118,104 @@ pub fn render<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>) {
 
 pub fn handle_screen_defaults<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>) {
     let mut items: Option<Vec<String>> = None;
+    // lists that style their own items
+    let mut styled_items: Option<Vec<ListItem>> = None;
     match app.current_screen {
         Screen::SavedKeys => {
+            styled_items = Some(saved_key_items(&app.get_saved_keys().unwrap_or_default()));
+        }
+        Screen::KeyPicker => {
+            styled_items = Some(saved_key_items(&app.get_key_choices()));
+        }
+        Screen::Environments => {
             items = Some(
-                app.get_saved_keys()
-                    .unwrap()
-                    .into_iter()
-                    .map(|x| format!("{:?}", x))
+                app.get_environments()
+                    .unwrap_or_default()
+                    .iter()
+                    .map(|env| env.to_string())
+                    .collect::<Vec<String>>(),
+            );
+        }
+        Screen::EnvVars(id) => {
+            items = Some(
+                app.db
+                    .get_env_vars(id)
+                    .unwrap_or_default()
+                    .iter()
+                    .map(|(key, value)| format!("{} = {}", key, value))
+                    .collect::<Vec<String>>(),
+            );
+        }
+        Screen::DiffPicker => {
+            items = Some(
+                app.get_history("")
+                    .unwrap_or_default()
+                    .iter()
+                    .map(|entry| entry.to_string())
+                    .collect::<Vec<String>>(),
+            );
+        }
+        Screen::History(ref query) => {
+            items = Some(
+                app.get_history(query)
+                    .unwrap_or_default()
+                    .iter()
+                    .map(|entry| entry.to_string())
                     .collect::<Vec<String>>(),
             );
         }
         Screen::SavedCommands => {
             items = Some(
-                app.get_saved_commands()
-                    .unwrap()
+                app.get_command_tree()
+                    .iter()
+                    .map(|row| row.get_label())
+                    .collect::<Vec<String>>(),
+            );
+        }
+        Screen::Assertions(id) => {
+            items = Some(
+                app.get_assertions(id)
+                    .iter()
+                    .map(|assertion| assertion.to_string())
+                    .collect::<Vec<String>>(),
+            );
+        }
+        // failed requests list the reasons under them
+        Screen::TestResults => {
+            items = Some(
+                app.test_results
+                    .iter()
+                    .map(|result| {
+                        std::iter::once(result.to_string())
+                            .chain(result.failures().iter().map(|f| format!("      {}", f)))
+                            .collect::<Vec<String>>()
+                            .join("\n")
+                    })
+                    .collect::<Vec<String>>(),
+            );
+        }
+        Screen::HttpFile => {
+            items = Some(
+                app.get_http_requests()
+                    .iter()
+                    .map(|request| request.to_string())
+                    .collect::<Vec<String>>(),
+            );
+        }
+        Screen::MoveTo(item) => {
+            items = Some(
+                app.get_move_targets(item)
                     .into_iter()
-                    .map(|x| format!("{:?}", x))
+                    .map(|(_, path)| path)
                     .collect::<Vec<String>>(),
             );
         }
         _ => {}
     }
-    let menu_options = app.current_screen.get_list(items);
+    let menu_options = match styled_items {
+        Some(styled_items) => app.current_screen.get_styled_list(styled_items),
+        None => app.current_screen.get_list(items),
+    };
     let area = centered_rect(70, 60, frame.size());
 
     let mut state = ListState::with_selected(ListState::default(), Some(app.cursor));
@@ -124,7 +225,12 @@ pub fn handle_screen_defaults<B: Backend>(app: &mut App, frame: &mut Frame<'_, B
     frame.render_stateful_widget(menu_options, area, &mut state);
     let (paragraph, title) = match app.current_screen {
         Screen::Home => (&DEFAULT_MENU_PARAGRAPH, &DEFAULT_MENU_TITLE),
-        Screen::SavedCommands => (&SAVED_COMMANDS_PARAGRAPH, &SAVED_COMMANDS_TITLE),
+        Screen::SavedCommands | Screen::CollectionMenu(_) | Screen::MoveTo(_) => {
+            (&SAVED_COMMANDS_PARAGRAPH, &SAVED_COMMANDS_TITLE)
+        }
+        Screen::Assertions(_) | Screen::TestResults => {
+            (&DEFAULT_MENU_PARAGRAPH, &TEST_RESULTS_TITLE)
+        }
         Screen::Response(_) => (&DEFAULT_MENU_PARAGRAPH, &DEFAULT_MENU_TITLE),
         Screen::InputMenu(_) => (&DEFAULT_MENU_PARAGRAPH, &INPUT_MENU_TITLE),
         Screen::Authentication => (&DEFAULT_MENU_PARAGRAPH, &AUTH_MENU_TITLE),
@@ -133,7 +239,20 @@ pub fn handle_screen_defaults<B: Backend>(app: &mut App, frame: &mut Frame<'_, B
         Screen::ViewBody => (&DEFAULT_MENU_PARAGRAPH, &VIEW_BODY_TITLE),
         Screen::Downloads(_) => (&DEFAULT_MENU_PARAGRAPH, &DOWNLOAD_MENU_TITLE),
         Screen::SavedKeys => (&API_KEY_PARAGRAPH, &API_KEY_TITLE),
+        Screen::KeyPicker => (&DEFAULT_MENU_PARAGRAPH, &AUTH_MENU_TITLE),
         Screen::HeaderAddRemove => (&DEFAULT_MENU_PARAGRAPH, &DEFAULT_MENU_TITLE),
+        Screen::ImportCurl | Screen::ImportBundle(_) => {
+            (&DEFAULT_MENU_PARAGRAPH, &IMPORT_MENU_TITLE)
+        }
+        Screen::CodeSnippets | Screen::SnippetExport(_) => {
+            (&DEFAULT_MENU_PARAGRAPH, &SNIPPET_MENU_TITLE)
+        }
+        Screen::Environments | Screen::EnvMenu(_) | Screen::EnvVars(_) => {
+            (&ENVIRONMENTS_PARAGRAPH, &ENVIRONMENTS_TITLE)
+        }
+        Screen::History(_) | Screen::HistoryMenu(_) => (&HISTORY_PARAGRAPH, &HISTORY_TITLE),
+        Screen::DiffPicker => (&DIFF_PICKER_PARAGRAPH, &DIFF_TITLE),
+        Screen::HttpFile | Screen::HttpRequestMenu(_) => (&HTTP_FILE_PARAGRAPH, &HTTP_FILE_TITLE),
         _ => (&DEFAULT_MENU_PARAGRAPH, &DEFAULT_MENU_TITLE),
     };
     frame.render_widget(
@@ -146,14 +265,8 @@ pub fn handle_screen<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>, screen
     match screen {
         Screen::Home => handle_home_screen(app, frame),
         Screen::Method => handle_method_select_screen(app, frame),
-        Screen::ViewBody => {
-            let area = default_rect(frame.size());
-            let response = app.response.clone().unwrap();
-            let paragraph = Paragraph::new(Text::from(response.as_str()))
-                .style(app.config.get_style())
-                .alignment(Alignment::Center);
-            frame.render_widget(paragraph, area);
-        }
+        Screen::ViewBody => handle_view_body_screen(app, frame),
+        Screen::ViewHeaders => handle_view_headers_screen(app, frame),
         Screen::Downloads(e) => {
             if is_prompt(&e) {
                 handle_downloads_screen(app, frame, &e);
@@ -183,7 +296,7 @@ pub fn handle_screen<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>, screen
         // RESPONSE SCREEN ******************************************************
         Screen::Response(resp) => {
             app.set_response(resp.clone());
-            handle_response_screen(app, frame, resp.to_string());
+            handle_response_screen(app, frame);
         }
         Screen::SavedCommands => {
             handle_saved_commands_screen(app, frame);
@@ -202,6 +315,25 @@ pub fn handle_screen<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>, screen
         }
         Screen::RequestBodyInput => handle_req_body_input_screen(app, frame, InputOpt::RequestBody),
         Screen::KeysMenu(cmd) => handle_key_menu(app, frame, cmd),
+        Screen::KeyPicker => handle_key_picker_screen(app, frame),
+        Screen::ImportCurl => handle_import_screen(app, frame),
+        Screen::ImportBundle(preview) => handle_import_bundle_screen(app, frame, &preview),
+        Screen::CodeSnippets => handle_snippets_screen(app, frame),
+        Screen::SnippetExport(lang) => handle_snippet_export_screen(app, frame, lang),
+        Screen::Executing => handle_executing_screen(app, frame),
+        Screen::Environments => handle_environments_screen(app, frame),
+        Screen::EnvMenu(index) => handle_env_menu_screen(app, frame, index),
+        Screen::EnvVars(id) => handle_env_vars_screen(app, frame, id),
+        Screen::History(query) => handle_history_screen(app, frame, &query),
+        Screen::HistoryMenu(id) => handle_history_menu_screen(app, frame, id),
+        Screen::DiffPicker => handle_diff_picker_screen(app, frame),
+        Screen::Diff => handle_diff_screen(app, frame),
+        Screen::CollectionMenu(id) => handle_collection_menu_screen(app, frame, id),
+        Screen::MoveTo(item) => handle_move_to_screen(app, frame, item),
+        Screen::Assertions(id) => handle_assertions_screen(app, frame, id),
+        Screen::TestResults => handle_test_results_screen(app, frame),
+        Screen::HttpFile => handle_http_file_screen(app, frame),
+        Screen::HttpRequestMenu(index) => handle_http_request_menu_screen(app, frame, index),
         _ => {}
     }
 }
@@ -210,7 +342,7 @@ fn is_prompt(e: &str) -> bool {
     e.to_lowercase().contains("error") || e.to_lowercase().contains("alert")
 }
 
-fn handle_display_options(opts: &[AppOptions]) -> Vec<Line> {
+fn handle_display_options(opts: &[AppOptions]) -> Vec<Line<'_>> {
     opts.iter()
         .map(|x| Line::from(x.get_value()))
         .collect::<Vec<Line>>()
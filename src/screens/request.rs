@@ -51,16 +51,7 @@ pub fn handle_request_menu_screen<B: Backend>(
                 app.goto_screen(Screen::RequestMenu(String::from(VALID_COMMAND_ERROR)));
                 return;
             }
-            match app.execute_command() {
-                Ok(()) => {
-                    let response = app.command.as_mut().unwrap().get_response();
-                    app.response = Some(response.clone());
-                    app.goto_screen(Screen::Response(response));
-                }
-                Err(e) => {
-                    app.goto_screen(Screen::Error(e.to_string()));
-                }
-            }
+            app.spawn_command();
         }
         // more options
         Some(11) => app.goto_screen(Screen::MoreFlags),
@@ -2,14 +2,12 @@ use super::{default_rect, small_alert_box};
 use crate::app::App;
 use crate::display::inputopt::InputOpt;
 
-use crate::request::response::Response;
 use crate::screens::screen::Screen;
 use tui::backend::Backend;
-use tui::text::Text;
-use tui::widgets::{ListState, Paragraph};
+use tui::widgets::ListState;
 use tui::Frame;
 
-pub fn handle_response_screen<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>, resp: String) {
+pub fn handle_response_screen<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>) {
     let area = default_rect(small_alert_box(frame.size()));
     let new_list = app.current_screen.get_list(None);
     let mut state = ListState::with_selected(ListState::default(), Some(app.cursor));
@@ -27,22 +25,10 @@ pub fn handle_response_screen<B: Backend>(app: &mut App, frame: &mut Frame<'_, B
                 app.goto_screen(Screen::InputMenu(InputOpt::Execute));
             }
             // View response headers
-            1 => {
-                let area_2 = small_alert_box(frame.size());
-                // Check for response error here
-                let response = match Response::from_raw_string(resp.as_str()) {
-                    Ok(resp) => resp,
-                    Err(e) => {
-                        // Hit the error screen.
-                        app.goto_screen(Screen::Error(String::from(e)));
-                        return;
-                    }
-                };
-                let headers = response.get_headers();
-                let paragraph = Paragraph::new(Text::from(headers.to_string()));
-                frame.render_widget(paragraph, area_2);
-                //app.goto_screen(Screen::SavedCommands);
-            }
+            1 => match app.get_response_headers() {
+                Ok(_) => app.goto_screen(Screen::ViewHeaders),
+                Err(e) => app.goto_screen(Screen::Error(e)),
+            },
             // View response body
             2 => {
                 app.goto_screen(Screen::ViewBody);
@@ -70,7 +56,16 @@ pub fn handle_response_screen<B: Backend>(app: &mut App, frame: &mut Frame<'_, B
                     app.goto_screen(Screen::Error("Failed to copy to clipboard".to_string()));
                 }
             }
+            // Generate code snippet
             4 => {
+                app.goto_screen(Screen::CodeSnippets);
+            }
+            // Compare with a response from history
+            5 => match app.compare_current_response() {
+                Ok(_) => app.goto_screen(Screen::DiffPicker),
+                Err(e) => app.goto_screen(Screen::Error(e)),
+            },
+            6 => {
                 // Return To Home
                 app.remove_all_app_options();
                 app.goto_screen(Screen::Home);
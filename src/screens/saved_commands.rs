@@ -1,18 +1,79 @@
 use super::render::handle_screen_defaults;
+use super::screen::MoveItem;
 use super::Screen;
 use crate::app::App;
+use crate::database::tree::TreeRow;
+use crate::display::inputopt::InputOpt;
 use crate::display::menuopts::CMD_MENU_OPTIONS;
+use crate::request::har::HarExport;
 use tui::backend::Backend;
 use tui::prelude::{Constraint, Direction, Layout, Margin};
 use tui::style::{Color, Modifier, Style};
 use tui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph};
 use tui::Frame;
+use tui_input::Input;
 
 pub fn handle_saved_commands_screen<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>) {
     handle_screen_defaults(app, frame);
-    // if we select a command, open options
-    if let Some(cmd) = app.selected {
-        app.goto_screen(Screen::CmdMenu(cmd));
+    // selecting a collection opens or closes it, selecting a command opens options
+    if let Some(num) = app.selected {
+        match app.get_command_tree().get(num) {
+            Some(TreeRow::Collection { id, .. }) => app.toggle_collection(*id),
+            Some(TreeRow::Command { index, .. }) => app.goto_screen(Screen::CmdMenu(*index)),
+            None => app.selected = None,
+        }
+    }
+}
+
+pub fn handle_collection_menu_screen<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>, id: i32) {
+    handle_screen_defaults(app, frame);
+    let Some(collection) = app
+        .get_collections()
+        .unwrap_or_default()
+        .into_iter()
+        .find(|c| c.get_id() == id)
+    else {
+        app.go_back_screen();
+        return;
+    };
+    match app.selected {
+        // Run every command inside as a test
+        Some(0) => app.spawn_tests(Some(id)),
+        // New collection inside
+        Some(1) => app.goto_screen(Screen::InputMenu(InputOpt::NewCollection(Some(id)))),
+        // Rename
+        Some(2) => {
+            app.input = Input::new(collection.get_name().to_string());
+            app.goto_screen(Screen::InputMenu(InputOpt::RenameCollection(id)));
+        }
+        // Move
+        Some(3) => app.goto_screen(Screen::MoveTo(MoveItem::Collection(id))),
+        // Delete
+        Some(4) => match app.delete_collection(id) {
+            Ok(_) => app.goto_screen(Screen::SavedCommands),
+            Err(e) => app.goto_screen(Screen::Error(e.to_string())),
+        },
+        // Export every command inside as HAR
+        Some(5) => app.goto_screen(Screen::InputMenu(InputOpt::ExportHar(HarExport::Commands(
+            Some(id),
+        )))),
+        // Cancel
+        Some(6) => app.goto_screen(Screen::SavedCommands),
+        _ => {}
+    }
+}
+
+pub fn handle_move_to_screen<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>, item: MoveItem) {
+    handle_screen_defaults(app, frame);
+    if let Some(num) = app.selected {
+        let Some((target, _)) = app.get_move_targets(item).get(num).cloned() else {
+            app.selected = None;
+            return;
+        };
+        match app.move_item(item, target) {
+            Ok(_) => app.goto_screen(Screen::SavedCommands),
+            Err(e) => app.goto_screen(Screen::Error(e)),
+        }
     }
 }
 
@@ -70,7 +131,6 @@ pub fn handle_alert_menu<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>, cm
         // execute saved command
         Some(0) => {
             app.execute_saved_command(cmd);
-            app.goto_screen(Screen::Response(app.response.clone().unwrap()));
         }
         // delete item
         Some(1) => {
@@ -87,8 +147,31 @@ pub fn handle_alert_menu<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>, cm
             }
             app.goto_screen(Screen::Success);
         }
+        // generate code snippet
+        Some(3) => match app.load_saved_command(cmd) {
+            Ok(_) => app.goto_screen(Screen::CodeSnippets),
+            Err(e) => app.goto_screen(Screen::Error(e)),
+        },
+        // rename, starting from the current name
+        Some(4) => {
+            app.input = Input::new(selected.get_name().unwrap_or_default().to_string());
+            app.goto_screen(Screen::InputMenu(InputOpt::RenameCommand(
+                selected.get_id(),
+            )));
+        }
+        // edit description
+        Some(5) => {
+            app.input = Input::new(selected.get_description().unwrap_or_default().to_string());
+            app.goto_screen(Screen::InputMenu(InputOpt::CommandDescription(
+                selected.get_id(),
+            )));
+        }
+        // move to a collection
+        Some(6) => app.goto_screen(Screen::MoveTo(MoveItem::Command(selected.get_id()))),
+        // edit assertions
+        Some(7) => app.goto_screen(Screen::Assertions(selected.get_id())),
         // cancel
-        Some(3) => {
+        Some(8) => {
             app.goto_screen(Screen::SavedCommands);
         }
         _ => {}
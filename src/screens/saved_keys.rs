@@ -1,12 +1,16 @@
 use super::render::handle_screen_defaults;
+use super::screen::determine_line_size;
 use super::{centered_rect, small_alert_box, Screen};
 use crate::app::App;
+use crate::database::db::SavedKey;
+use crate::display::inputopt::InputOpt;
 use crate::display::menuopts::KEY_MENU_OPTIONS;
 use tui::backend::Backend;
 use tui::prelude::{Constraint, Direction, Layout, Margin};
 use tui::style::{Color, Modifier, Style};
 use tui::widgets::{Block, BorderType, Borders, List, ListItem, ListState, Paragraph};
 use tui::Frame;
+use tui_input::Input;
 
 pub fn handle_saved_keys_screen<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>) {
     handle_screen_defaults(app, frame);
@@ -72,7 +76,7 @@ pub fn handle_key_menu<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>, cmd:
         .split(alert_box)[1];
     let show_cmds = app.get_saved_keys().unwrap();
     let selected = show_cmds.get(cmd).unwrap().clone();
-    let paragraph = Paragraph::new(format!("{:?}", selected))
+    let paragraph = Paragraph::new(key_details(&selected))
         .block(Block::default().borders(Borders::ALL).title("Selected Key"))
         .alignment(tui::layout::Alignment::Center);
     frame.render_widget(paragraph, cmd_str);
@@ -81,9 +85,7 @@ pub fn handle_key_menu<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>, cmd:
     match app.selected {
         // Add a new key
         Some(0) => {
-            app.goto_screen(Screen::InputMenu(
-                crate::display::inputopt::InputOpt::ApiKey,
-            ));
+            app.goto_screen(Screen::InputMenu(InputOpt::ApiKey));
         }
         // delete item
         Some(1) => {
@@ -98,10 +100,87 @@ pub fn handle_key_menu<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>, cmd:
             }
             app.goto_screen(Screen::Success);
         }
-        // cancel
+        // edit label, starting from the current one
         Some(3) => {
+            app.input = Input::new(selected.get_label_text().unwrap_or_default().to_string());
+            app.goto_screen(Screen::InputMenu(InputOpt::KeyLabel(selected.get_id())));
+        }
+        // edit host
+        Some(4) => {
+            app.input = Input::new(selected.get_host().unwrap_or_default().to_string());
+            app.goto_screen(Screen::InputMenu(InputOpt::KeyHost(selected.get_id())));
+        }
+        // change type
+        Some(5) => {
+            app.input = Input::new(selected.get_kind().to_string());
+            app.goto_screen(Screen::InputMenu(InputOpt::KeyType(selected.get_id())));
+        }
+        // set expiry date
+        Some(6) => {
+            app.input = Input::new(selected.get_expires().unwrap_or_default().to_string());
+            app.goto_screen(Screen::InputMenu(InputOpt::KeyExpiry(selected.get_id())));
+        }
+        // cancel
+        Some(7) => {
             app.goto_screen(Screen::SavedKeys);
         }
         _ => {}
     }
 }
+
+// The keys for the request's host, choosing one uses it for the auth
+pub fn handle_key_picker_screen<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>) {
+    handle_screen_defaults(app, frame);
+    let keys = app.get_key_choices();
+    if keys.is_empty() {
+        let paragraph = Paragraph::new(
+            "No saved keys for this host. Add one, or change the host of a key, from the API Keys menu",
+        )
+        .style(Style::default());
+        frame.render_widget(paragraph, small_alert_box(frame.size()));
+    }
+    if let Some(key) = app.selected.and_then(|index| keys.get(index)) {
+        match app.use_saved_key(key) {
+            Ok(_) => app.goto_screen(Screen::RequestMenu(format!(
+                "Alert: Using the saved key {}",
+                key.get_label()
+            ))),
+            Err(e) => app.goto_screen(Screen::Error(e)),
+        }
+    }
+}
+
+// Everything about a key except the key itself
+fn key_details(key: &SavedKey) -> String {
+    let expires = match key.get_expires() {
+        Some(date) if key.is_expired() => format!("{} (expired)", date),
+        Some(date) if key.expires_soon() => format!("{} (soon)", date),
+        Some(date) => date.to_string(),
+        None => String::from("never"),
+    };
+    format!(
+        "Label: {}\nType: {}\nKey: {}\nHost: {}\nCreated: {}\nExpires: {}",
+        key.get_label(),
+        key.get_kind(),
+        key.get_masked_key(),
+        key.get_host().unwrap_or("any"),
+        key.get_created().unwrap_or("unknown"),
+        expires
+    )
+}
+
+// The saved keys list, with expired keys in red and the ones about to expire in yellow
+pub fn saved_key_items(keys: &[SavedKey]) -> Vec<ListItem<'static>> {
+    keys.iter()
+        .map(|key| {
+            let item = ListItem::new(format!("{}{}", key, determine_line_size(keys.len())));
+            if key.is_expired() {
+                item.style(Style::default().fg(Color::Red))
+            } else if key.expires_soon() {
+                item.style(Style::default().fg(Color::Yellow))
+            } else {
+                item
+            }
+        })
+        .collect()
+}
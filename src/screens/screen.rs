@@ -6,13 +6,24 @@ use std::fmt::{Display, Formatter};
 
 use crate::display::inputopt::InputOpt;
 use crate::display::menuopts::{
-    AUTHENTICATION_MENU_OPTIONS, CMD_MENU_OPTIONS, DOWNLOAD_MENU_OPTIONS, KEY_MENU_OPTIONS,
-    MAIN_MENU_OPTIONS, METHOD_MENU_OPTIONS, MORE_FLAGS_MENU, NEWLINE, OPTION_PADDING_MAX,
-    OPTION_PADDING_MID, OPTION_PADDING_MIN, REQUEST_MENU_OPTIONS, RESPONSE_MENU_OPTIONS,
+    AUTHENTICATION_MENU_OPTIONS, BUNDLE_IMPORT_MENU_OPTIONS, CMD_MENU_OPTIONS,
+    COLLECTION_MENU_OPTIONS, DOWNLOAD_MENU_OPTIONS, ENV_MENU_OPTIONS, HISTORY_MENU_OPTIONS,
+    HTTP_REQUEST_MENU_OPTIONS, IMPORT_MENU_OPTIONS, KEY_MENU_OPTIONS, MAIN_MENU_OPTIONS,
+    METHOD_MENU_OPTIONS, MORE_FLAGS_MENU, NEWLINE, OPTION_PADDING_MAX, OPTION_PADDING_MID,
+    OPTION_PADDING_MIN, REQUEST_MENU_OPTIONS, RESPONSE_MENU_OPTIONS, SNIPPET_EXPORT_OPTIONS,
+    SNIPPET_MENU_OPTIONS,
 };
+use crate::request::snippet::SnippetLang;
 use tui::style::{Color, Modifier, Style};
 use tui::widgets::{Block, Borders, List, ListItem};
 
+// What is being moved on the MoveTo screen, by database id
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum MoveItem {
+    Command(i32),
+    Collection(i32),
+}
+
 #[derive(Debug, Default, PartialEq, Clone)]
 pub enum Screen {
     #[default]
@@ -29,10 +40,33 @@ pub enum Screen {
     SavedCommands,
     Error(String),
     ViewBody,
+    ViewHeaders,
     MoreFlags,
     CmdMenu(usize),
     KeysMenu(usize),
     RequestBodyInput,
+    ImportCurl,
+    CodeSnippets,
+    SnippetExport(SnippetLang),
+    Executing,
+    Environments,
+    EnvMenu(usize),
+    EnvVars(i32),
+    History(String),
+    HistoryMenu(i32),
+    CollectionMenu(i32),
+    MoveTo(MoveItem),
+    Assertions(i32),
+    TestResults,
+    HttpFile,
+    HttpRequestMenu(usize),
+    // the preview of the bundle, shown below the options
+    ImportBundle(String),
+    // saved keys to use for the auth of the request
+    KeyPicker,
+    // history entries to compare with the response picked first
+    DiffPicker,
+    Diff,
 }
 
 impl Display for Screen {
@@ -51,10 +85,31 @@ impl Display for Screen {
             Screen::SavedCommands => "My Saved Commands",
             Screen::Error(_) => "Error",
             Screen::ViewBody => "ViewBody",
+            Screen::ViewHeaders => "ViewHeaders",
             Screen::MoreFlags => "MoreFlags",
             Screen::CmdMenu(_) => "CmdMenu",
             Screen::KeysMenu(_) => "KeysMenu",
             Screen::RequestBodyInput => "RequestBodyInput",
+            Screen::ImportCurl => "Import",
+            Screen::CodeSnippets => "Code Snippets",
+            Screen::SnippetExport(_) => "Export Snippet",
+            Screen::Executing => "Executing",
+            Screen::Environments => "Environments (press 'a' to add)",
+            Screen::EnvMenu(_) => "Environment",
+            Screen::EnvVars(_) => "Variables (press 'a' to add, Enter to edit)",
+            Screen::History(query) if query.is_empty() => "History (press / to search)",
+            Screen::History(_) => "History (search results)",
+            Screen::HistoryMenu(_) => "History Entry",
+            Screen::CollectionMenu(_) => "Collection",
+            Screen::MoveTo(_) => "Move to",
+            Screen::Assertions(_) => "Assertions (press 'a' to add, Enter to edit)",
+            Screen::TestResults => "Test Results (Enter to edit assertions)",
+            Screen::HttpFile => ".http File (press 'r' to reload)",
+            Screen::HttpRequestMenu(_) => ".http Request",
+            Screen::ImportBundle(_) => "Import Bundle",
+            Screen::KeyPicker => "Use a Saved Key",
+            Screen::DiffPicker => "Compare with",
+            Screen::Diff => "Diff",
         };
         write!(f, "{}", screen)
     }
@@ -72,61 +127,61 @@ impl<'a> Screen {
         match &self {
             Screen::Home => {
                 let len = MAIN_MENU_OPTIONS.len();
-                return MAIN_MENU_OPTIONS
+                MAIN_MENU_OPTIONS
                     .iter()
                     .map(|x| format!("{}{}", x, determine_line_size(len)))
                     .map(|i| ListItem::new(i.clone()))
-                    .collect();
+                    .collect()
             }
             Screen::Method => {
                 let len = METHOD_MENU_OPTIONS.len();
-                return METHOD_MENU_OPTIONS
+                METHOD_MENU_OPTIONS
                     .iter()
                     .map(|x| format!("{}{}", x, determine_line_size(len)))
                     .map(|i| ListItem::new(i.clone()))
-                    .collect();
+                    .collect()
             }
             Screen::HeaderAddRemove => {
                 let len = METHOD_MENU_OPTIONS.len();
-                return METHOD_MENU_OPTIONS
+                METHOD_MENU_OPTIONS
                     .iter()
                     .map(|x| format!("{}{}", x, determine_line_size(len)))
                     .map(|i| ListItem::new(i.clone()))
-                    .collect();
+                    .collect()
             }
             Screen::RequestMenu(_) => {
                 let len = REQUEST_MENU_OPTIONS.len();
-                return REQUEST_MENU_OPTIONS
+                REQUEST_MENU_OPTIONS
                     .iter()
                     .map(|x| format!("{}{}", x, determine_line_size(len)))
                     .map(|i| ListItem::new(i.clone()))
-                    .collect();
+                    .collect()
             }
             Screen::SavedCommands => {
                 let len = REQUEST_MENU_OPTIONS.len();
-                return items
+                items
                     .unwrap_or(vec!["No Saved Commands".to_string()])
                     .iter()
                     .map(|c| ListItem::new(format!("{}{}", c, determine_line_size(len))))
-                    .collect();
+                    .collect()
             }
             Screen::Response(_) => {
-                return RESPONSE_MENU_OPTIONS
+                RESPONSE_MENU_OPTIONS
                     .iter()
                     .map(|x| format!("{}{}", x, OPTION_PADDING_MID))
                     .map(|i| ListItem::new(i.clone()))
-                    .collect();
+                    .collect()
             }
             Screen::InputMenu(_) => {
                 vec![ListItem::new("Input Menu").style(Style::default().fg(Color::Green))]
             }
             Screen::Authentication => {
                 let len = AUTHENTICATION_MENU_OPTIONS.len();
-                return AUTHENTICATION_MENU_OPTIONS
+                AUTHENTICATION_MENU_OPTIONS
                     .iter()
                     .map(|x| format!("{}{}", x, determine_line_size(len)))
                     .map(|i| ListItem::new(i.clone()))
-                    .collect();
+                    .collect()
             }
             Screen::Success => {
                 vec![ListItem::new("Success!").style(Style::default().fg(Color::Green))]
@@ -137,6 +192,12 @@ impl<'a> Screen {
             Screen::ViewBody => {
                 vec![ListItem::new("View Body").style(Style::default().fg(Color::Green))]
             }
+            Screen::ViewHeaders => {
+                vec![ListItem::new("View Headers").style(Style::default().fg(Color::Green))]
+            }
+            Screen::Diff => {
+                vec![ListItem::new("Diff").style(Style::default().fg(Color::Green))]
+            }
             Screen::RequestBodyInput => {
                 vec![ListItem::new("Request Body Input").style(Style::default().fg(Color::Green))]
             }
@@ -146,42 +207,103 @@ impl<'a> Screen {
                 .collect(),
             Screen::Downloads(_) => {
                 let len = DOWNLOAD_MENU_OPTIONS.len();
-                return DOWNLOAD_MENU_OPTIONS
+                DOWNLOAD_MENU_OPTIONS
                     .iter()
                     .map(|x| format!("{}{}", x, determine_line_size(len)))
                     .map(|i| ListItem::new(i.clone()))
-                    .collect();
+                    .collect()
             }
             Screen::SavedKeys => {
-                let mut len = 0;
-                if items.is_some() {
-                    len = items.as_ref().unwrap().len();
-                }
-                return items
+                let len = items.as_ref().map_or(0, |items| items.len());
+                items
                     .unwrap_or(vec!["No Saved Commands".to_string()])
                     .iter()
                     .map(|c| ListItem::new(format!("{}{}", c, determine_line_size(len))))
-                    .collect();
+                    .collect()
             }
             Screen::KeysMenu(_) => KEY_MENU_OPTIONS
                 .iter()
                 .map(|i| ListItem::new(format!("{}{}", i, NEWLINE)))
                 .collect(),
+            Screen::ImportCurl => {
+                let len = IMPORT_MENU_OPTIONS.len();
+                IMPORT_MENU_OPTIONS
+                    .iter()
+                    .map(|x| format!("{}{}", x, determine_line_size(len)))
+                    .map(|i| ListItem::new(i.clone()))
+                    .collect()
+            }
+            Screen::CodeSnippets => {
+                let len = SNIPPET_MENU_OPTIONS.len();
+                SNIPPET_MENU_OPTIONS
+                    .iter()
+                    .map(|x| format!("{}{}", x, determine_line_size(len)))
+                    .map(|i| ListItem::new(i.clone()))
+                    .collect()
+            }
+            // the snippet preview is rendered below these
+            Screen::SnippetExport(_) => SNIPPET_EXPORT_OPTIONS
+                .iter()
+                .map(|i| ListItem::new(format!("{}{}", i, NEWLINE)))
+                .collect(),
+            Screen::Environments
+            | Screen::EnvVars(_)
+            | Screen::History(_)
+            | Screen::MoveTo(_)
+            | Screen::Assertions(_)
+            | Screen::TestResults
+            | Screen::HttpFile
+            | Screen::KeyPicker
+            | Screen::DiffPicker => {
+                let len = items.as_ref().map_or(0, |items| items.len());
+                items
+                    .unwrap_or_default()
+                    .iter()
+                    .map(|c| ListItem::new(format!("{}{}", c, determine_line_size(len))))
+                    .collect()
+            }
+            Screen::EnvMenu(_) => ENV_MENU_OPTIONS
+                .iter()
+                .map(|i| ListItem::new(format!("{}{}", i, NEWLINE)))
+                .collect(),
+            Screen::HistoryMenu(_) => HISTORY_MENU_OPTIONS
+                .iter()
+                .map(|i| ListItem::new(format!("{}{}", i, NEWLINE)))
+                .collect(),
+            Screen::CollectionMenu(_) => COLLECTION_MENU_OPTIONS
+                .iter()
+                .map(|i| ListItem::new(format!("{}{}", i, NEWLINE)))
+                .collect(),
+            // the request is previewed below these
+            Screen::HttpRequestMenu(_) => HTTP_REQUEST_MENU_OPTIONS
+                .iter()
+                .map(|i| ListItem::new(format!("{}{}", i, NEWLINE)))
+                .collect(),
+            Screen::ImportBundle(_) => BUNDLE_IMPORT_MENU_OPTIONS
+                .iter()
+                .map(|i| ListItem::new(format!("{}{}", i, NEWLINE)))
+                .collect(),
+            // nothing to select while a command is running
+            Screen::Executing => Vec::new(),
             Screen::MoreFlags => {
                 let len = MORE_FLAGS_MENU.len();
-                return MORE_FLAGS_MENU
+                MORE_FLAGS_MENU
                     .iter()
                     .map(|i| {
                         ListItem::new(format!("{}{}", i, determine_line_size(len)))
                             .style(Style::default().fg(Color::Red))
                     })
-                    .collect();
+                    .collect()
             }
         }
     }
 
-    pub fn get_list(&self, items: Option<Vec<String>>) -> List {
-        List::new(self.get_opts(items))
+    pub fn get_list(&self, items: Option<Vec<String>>) -> List<'_> {
+        self.get_styled_list(self.get_opts(items))
+    }
+
+    pub fn get_styled_list<'b>(&self, items: Vec<ListItem<'b>>) -> List<'b> {
+        List::new(items)
             .block(
                 Block::default()
                     .title(self.to_string().clone())
@@ -0,0 +1,72 @@
+use super::render::handle_screen_defaults;
+use crate::app::App;
+use crate::display::inputopt::InputOpt;
+use crate::display::menuopts::SNIPPET_EXPORT_OPTIONS;
+use crate::request::snippet::SnippetLang;
+use crate::screens::screen::Screen;
+use tui::backend::Backend;
+use tui::prelude::{Constraint, Direction, Layout};
+use tui::text::Text;
+use tui::widgets::{Block, Borders, Paragraph};
+use tui::Frame;
+
+use super::default_rect;
+
+pub fn handle_snippets_screen<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>) {
+    handle_screen_defaults(app, frame);
+    if let Some(num) = app.selected {
+        match SnippetLang::from_index(num) {
+            Some(lang) => app.goto_screen(Screen::SnippetExport(lang)),
+            // Cancel
+            None => app.go_back_screen(),
+        }
+    }
+}
+
+pub fn handle_snippet_export_screen<B: Backend>(
+    app: &mut App,
+    frame: &mut Frame<'_, B>,
+    lang: SnippetLang,
+) {
+    let snippet = match app.get_snippet(lang) {
+        Ok(snippet) => snippet,
+        Err(e) => {
+            app.goto_screen(Screen::Error(e));
+            return;
+        }
+    };
+    handle_screen_defaults(app, frame);
+    // Preview the snippet below the menu options
+    let preview = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(
+            [
+                Constraint::Length(SNIPPET_EXPORT_OPTIONS.len() as u16 * 2 + 1),
+                Constraint::Min(1),
+            ]
+            .as_ref(),
+        )
+        .split(default_rect(frame.size()))[1];
+    frame.render_widget(
+        Paragraph::new(Text::from(snippet.as_str()))
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(lang.to_string()),
+            )
+            .style(app.config.get_style()),
+        preview,
+    );
+    match app.selected {
+        // Copy to clipboard
+        Some(0) => match app.copy_to_clipboard(&snippet) {
+            Ok(_) => app.goto_screen(Screen::Success),
+            Err(e) => app.goto_screen(Screen::Error(e)),
+        },
+        // Save to a file
+        Some(1) => app.goto_screen(Screen::InputMenu(InputOpt::SnippetFile(lang))),
+        // Cancel
+        Some(2) => app.go_back_screen(),
+        _ => {}
+    }
+}
@@ -0,0 +1,29 @@
+use super::render::handle_screen_defaults;
+use crate::app::App;
+use crate::display::inputopt::InputOpt;
+use crate::screens::screen::Screen;
+use tui::backend::Backend;
+use tui::Frame;
+use tui_input::Input;
+
+pub fn handle_assertions_screen<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>, id: i32) {
+    handle_screen_defaults(app, frame);
+    // selecting an assertion lets you edit it, starting from its current text
+    if let Some(num) = app.selected {
+        if let Some(assertion) = app.get_assertions(id).get(num) {
+            app.input = Input::new(assertion.to_string());
+        }
+        app.goto_screen(Screen::InputMenu(InputOpt::Assertion(id, Some(num))));
+    }
+}
+
+pub fn handle_test_results_screen<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>) {
+    handle_screen_defaults(app, frame);
+    // jump to the assertions of the selected request to fix them up
+    if let Some(num) = app.selected {
+        match app.test_results.get(num) {
+            Some(result) => app.goto_screen(Screen::Assertions(result.id)),
+            None => app.selected = None,
+        }
+    }
+}